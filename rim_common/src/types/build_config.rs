@@ -24,6 +24,12 @@ pub struct BuildConfig {
     #[serde(flatten)]
     overridable: OverridableConfig,
     pub cargo: CargoConfig,
+    /// Whether the toolkit manifests downloaded from server must be signed,
+    /// check [`manifest_public_key`](BuildConfig::manifest_public_key).
+    #[serde(default)]
+    pub require_signature: bool,
+    /// The `minisign` public key used to verify the signature of toolkit manifests.
+    pub manifest_public_key: Option<String>,
     pub locale: HashMap<String, LocaleMap>,
     #[serde(rename = "override")]
     overrides: HashMap<String, OverridableConfig>,
@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use url::Url;
 
+use super::Proxy;
 use crate::setter;
 
 #[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Clone)]
@@ -56,15 +57,20 @@ impl ToolInfo {
         None
     }
 
-    /// Convert package source form [`Url`](ToolSource::Url) to [`Path`](ToolSource::Path).
+    /// Convert package source form [`Url`](ToolSource::Url) to [`Path`](ToolSource::Path),
+    /// with an optional SHA-256 checksum of the file at `path`, falling back to
+    /// the checksum of the `Url` source.
     ///
     /// Do nothing if current tool's source is not a `Url` type.
-    pub fn url_to_path<P: Into<PathBuf>>(&mut self, path: P) {
+    pub fn url_to_path<P: Into<PathBuf>>(&mut self, path: P, sha256: Option<String>) {
         if let Self::Complex(details) = self {
             let Some(ToolSource::Url {
                 version,
                 url: _,
                 filename: _,
+                strip_components,
+                size,
+                sha256: url_sha256,
             }) = &details.source
             else {
                 return;
@@ -73,6 +79,9 @@ impl ToolInfo {
             details.source = Some(ToolSource::Path {
                 version: version.clone(),
                 path: path.into(),
+                strip_components: *strip_components,
+                size: *size,
+                sha256: sha256.or_else(|| url_sha256.clone()),
             });
         }
     }
@@ -111,6 +120,8 @@ impl ToolInfo {
     /// 1. Uses `git` url as source without a `tag`.
     /// 2. Uses `path` or `url` as source without `version`.
     /// 3. Uses `restricted` source without specifying a `version`.
+    /// 4. Uses `GitHub` release as source without a `tag`, or with `latest` tag.
+    /// 5. Uses `npm` or `pip` package as source without a `version`.
     pub fn version(&self) -> Option<&str> {
         match self {
             Self::Basic(ver) => Some(ver),
@@ -121,8 +132,13 @@ impl ToolInfo {
                 match source {
                     ToolSource::Git { tag, .. } => tag.as_deref(),
                     ToolSource::Version { version } => Some(version),
+                    ToolSource::GitHubRelease { tag, .. } => {
+                        tag.as_deref().filter(|t| *t != LATEST_TAG)
+                    }
                     ToolSource::Path { version, .. }
                     | ToolSource::Url { version, .. }
+                    | ToolSource::Npm { version, .. }
+                    | ToolSource::Pip { version, .. }
                     | ToolSource::Restricted { version, .. } => version.as_deref(),
                 }
             }
@@ -209,6 +225,36 @@ impl ToolInfo {
             .unwrap_or_default()
     }
 
+    /// Get the proxy settings specific to this tool.
+    pub fn proxy(&self) -> Option<&Proxy> {
+        self.details().and_then(|det| det.proxy.as_ref())
+    }
+
+    /// Get the command to run after installing this tool.
+    pub fn post_install(&self) -> Option<&str> {
+        self.details().and_then(|det| det.post_install.as_deref())
+    }
+
+    /// Get the command to run before uninstalling this tool.
+    pub fn pre_uninstall(&self) -> Option<&str> {
+        self.details().and_then(|det| det.pre_uninstall.as_deref())
+    }
+
+    /// Get the uninstall script of a [`Script`](ToolKind::Script) tool.
+    pub fn uninstall_script(&self) -> Option<&str> {
+        self.details()
+            .and_then(|det| det.uninstall_script.as_deref())
+    }
+
+    /// Return `true` if this tool has any hook command, or is installed by a script,
+    /// which needs to be allowed the same way as hooks.
+    pub fn has_hooks(&self) -> bool {
+        self.post_install().is_some()
+            || self.pre_uninstall().is_some()
+            || self.kind() == Some(ToolKind::Script)
+            || self.uninstall_script().is_some()
+    }
+
     /// Get a designated filename for `Url` source.
     pub fn filename(&self) -> Option<&str> {
         if let Some(det) = self.details() {
@@ -218,8 +264,43 @@ impl ToolInfo {
         }
         None
     }
+
+    /// Get the number of leading path components to strip when extracting
+    /// the package of a `Url` or `Path` source.
+    pub fn strip_components(&self) -> Option<usize> {
+        match &self.details()?.source {
+            Some(ToolSource::Url {
+                strip_components, ..
+            })
+            | Some(ToolSource::Path {
+                strip_components, ..
+            }) => *strip_components,
+            _ => None,
+        }
+    }
+
+    /// Get the package size (in bytes) of a `Url` or `Path` source, if specified.
+    pub fn size(&self) -> Option<u64> {
+        match &self.details()?.source {
+            Some(ToolSource::Url { size, .. }) | Some(ToolSource::Path { size, .. }) => *size,
+            _ => None,
+        }
+    }
+
+    /// Get the expected SHA-256 checksum of the package of a `Url` or `Path` source, if specified.
+    pub fn sha256(&self) -> Option<&str> {
+        match &self.details()?.source {
+            Some(ToolSource::Url { sha256, .. }) | Some(ToolSource::Path { sha256, .. }) => {
+                sha256.as_deref()
+            }
+            _ => None,
+        }
+    }
 }
 
+/// The tag referring to the latest release of a [`GitHubRelease`](ToolSource::GitHubRelease).
+pub const LATEST_TAG: &str = "latest";
+
 fn is_false(val: &bool) -> bool {
     !val
 }
@@ -253,6 +334,26 @@ pub struct ToolInfoDetails {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     /// A list of tools that this package conflicts with.
     pub conflicts: Vec<String>,
+    /// Proxy settings for downloading this tool only,
+    /// which overrides the `[proxy]` section of the manifest.
+    pub proxy: Option<Proxy>,
+    /// A shell command to run after this tool is installed,
+    /// only runs when hooks are explicitly allowed by the user.
+    ///
+    /// ```toml
+    /// my_tool = { url = "https://example.com/my_tool.zip", post-install = "bin/my_tool --init" }
+    /// ```
+    pub post_install: Option<String>,
+    /// A shell command to run before this tool is uninstalled,
+    /// only runs when hooks are explicitly allowed by the user.
+    pub pre_uninstall: Option<String>,
+    /// Path to the uninstall script of a [`Script`](ToolKind::Script) tool, relative to
+    /// its install directory, which is usually generated by the install script.
+    ///
+    /// ```toml
+    /// my_tool = { url = "https://example.com/install.sh", kind = "script", uninstall-script = "uninstall.sh" }
+    /// ```
+    pub uninstall_script: Option<String>,
 }
 
 impl ToolInfoDetails {
@@ -291,10 +392,71 @@ pub enum ToolSource {
         version: Option<String>,
         url: Url,
         filename: Option<String>,
+        /// Drop this many leading components from the path of every entry
+        /// when extracting the package, just like `tar --strip-components`.
+        strip_components: Option<usize>,
+        /// The size of the package in bytes, which is shown to users
+        /// before they decide what to install.
+        size: Option<u64>,
+        /// The expected SHA-256 checksum of the package file,
+        /// which will be verified after downloading it.
+        sha256: Option<String>,
     },
     Path {
         version: Option<String>,
         path: PathBuf,
+        /// Same as the `strip_components` of [`Url`](ToolSource::Url) source.
+        strip_components: Option<usize>,
+        /// Same as the `size` of [`Url`](ToolSource::Url) source.
+        size: Option<u64>,
+        /// Same as the `sha256` of [`Url`](ToolSource::Url) source,
+        /// but it will be verified before installing the package.
+        sha256: Option<String>,
+    },
+    /// A tool published as an asset of a GitHub release.
+    ///
+    /// The concrete download URL is resolved before installation by querying the
+    /// GitHub releases API. `asset-pattern` is the name of the asset to download,
+    /// which may contain `{target}` and `{version}` placeholders, where `{version}` is the
+    /// release tag without the leading `v`. If `tag` is missing or is `latest`,
+    /// the latest release will be used.
+    ///
+    /// ```toml
+    /// ripgrep = { repo = "BurntSushi/ripgrep", tag = "14.1.0", asset-pattern = "ripgrep-{version}-{target}.tar.gz" }
+    /// ```
+    #[serde(rename_all = "kebab-case")]
+    GitHubRelease {
+        repo: String,
+        tag: Option<String>,
+        asset_pattern: String,
+    },
+    /// A tool published as an npm package, which requires a Node.js runtime
+    /// (with `npm` or `pnpm`) to install.
+    ///
+    /// If `version` is missing, the latest version will be installed.
+    ///
+    /// ```toml
+    /// prettier = { package = "prettier", version = "3.3.3" }
+    /// ```
+    Npm {
+        package: String,
+        version: Option<String>,
+    },
+    /// A tool published as a Python package, which will be installed into an isolated
+    /// virtual environment using a Python 3 interpreter on user's machine.
+    ///
+    /// `version` could be an exact version or a version specifier such as `>=1.0`,
+    /// if it is missing, the latest version will be installed.
+    ///
+    /// ```toml
+    /// black = { pip = "black", version = "24.4.2", extras = ["jupyter"] }
+    /// ```
+    Pip {
+        #[serde(rename = "pip")]
+        package: String,
+        version: Option<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        extras: Vec<String>,
     },
     Version {
         #[serde(alias = "ver")]
@@ -324,6 +486,9 @@ pub enum ToolKind {
     DirWithBin,
     /// Installer type, which need to be executed to install a certain tool.
     Installer,
+    /// An install script (`.sh`, `.bash`, `.ps1` or `.py`), which is executed with
+    /// the tool's install directory as its argument, only when hooks are allowed.
+    Script,
     /// Pre-built executable files.
     /// i.e.:
     /// ```text
@@ -331,8 +496,17 @@ pub enum ToolKind {
     /// ├─── cargo-some_binary.exe
     /// ```
     Executables,
+    /// A single `.AppImage` file, which is only supported on Linux.
+    AppImage,
+    /// A Linux distribution package (`.deb` or `.rpm`), which is installed
+    /// using the system package manager.
+    DistroPackage,
     /// We have a custom "script" for how to deal with such directory.
     Custom,
+    /// An npm package, which is installed into its own prefix using `npm` or `pnpm`.
+    NpmPackage,
+    /// A Python package, which is installed into its own virtual environment using `pip`.
+    PipPackage,
     /// Plugin file, such as `.vsix` files for Visual Studio.
     Plugin,
     // `Cargo` just don't make any sense
@@ -1,8 +1,12 @@
-use super::{TomlParser, ToolMap};
+use super::{TomlParser, ToolMap, ToolSource};
 use crate::{setter, utils};
+use anyhow::{bail, Context};
 use indexmap::{IndexMap, IndexSet};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
 use url::Url;
 
 #[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Default, Clone)]
@@ -20,6 +24,8 @@ pub struct ToolkitManifest {
     pub tools: Tools,
     /// Proxy settings that used for download.
     pub proxy: Option<Proxy>,
+    /// Additional configurations of the installation process.
+    pub config: Option<ManifestConfig>,
     /// Path to the manifest file.
     #[serde(skip)]
     pub path: Option<PathBuf>,
@@ -31,15 +37,202 @@ pub struct ToolkitManifest {
 impl TomlParser for ToolkitManifest {
     const FILENAME: &'static str = "toolset-manifest.toml";
 
-    fn load<P: AsRef<std::path::Path>>(path: P) -> anyhow::Result<Self> {
-        let raw = utils::read_to_string("manifest", &path)?;
-        let mut temp_manifest = Self::from_str(&raw)?;
+    /// Deserialize a manifest from either TOML or JSON string, check [`parse_manifest_table`].
+    fn from_str(from: &str) -> anyhow::Result<Self> {
+        Ok(toml::Value::Table(parse_manifest_table(from, None)?).try_into()?)
+    }
+
+    /// Load a manifest from the given path, resolving any other manifest listed
+    /// in its top-level `include` array.
+    ///
+    /// Included manifests are merged into the including one, where values of
+    /// the including manifest take precedence, and tables (such as `tools.target`)
+    /// are combined. Relative include paths are resolved against the directory of
+    /// the including manifest.
+    fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let merged = load_with_includes(path.as_ref(), &mut vec![])?;
+        let mut temp_manifest: Self = toml::Value::Table(merged).try_into()?;
         temp_manifest.path = Some(path.as_ref().to_path_buf());
         Ok(temp_manifest)
     }
 }
 
+/// The top-level key for listing other manifests to include.
+const INCLUDE_KEY: &str = "include";
+
+/// Load the raw manifest table at `path` with all of its includes merged in.
+///
+/// `chain` holds the manifests that are currently being loaded,
+/// which is used to detect cyclic includes.
+fn load_with_includes(path: &Path, chain: &mut Vec<PathBuf>) -> anyhow::Result<toml::Table> {
+    let full_path = utils::to_normalized_absolute_path(path, None)?;
+    if chain.contains(&full_path) {
+        let cycle = chain
+            .iter()
+            .chain(std::iter::once(&full_path))
+            .map(|p| format!("'{}'", p.display()))
+            .collect::<Vec<_>>()
+            .join(" -> ");
+        bail!("cyclic include detected in toolkit manifest: {cycle}");
+    }
+
+    let raw = utils::read_to_string("manifest", &full_path)?;
+    let mut table = parse_manifest_table(&raw, Some(&full_path))
+        .with_context(|| format!("unable to parse manifest '{}'", full_path.display()))?;
+    let Some(includes) = table.remove(INCLUDE_KEY) else {
+        return Ok(table);
+    };
+    let includes: Vec<PathBuf> = includes.try_into().with_context(|| {
+        format!(
+            "invalid '{INCLUDE_KEY}' in manifest '{}', expecting a list of paths",
+            full_path.display()
+        )
+    })?;
+
+    let parent_dir = full_path.parent().unwrap_or(Path::new("."));
+    chain.push(full_path.clone());
+    let mut merged = toml::Table::new();
+    for include in includes {
+        let include_path = utils::to_normalized_absolute_path(&include, Some(parent_dir))?;
+        let included = load_with_includes(&include_path, chain)?;
+        merge_table(&mut merged, included, &[]);
+    }
+    chain.pop();
+
+    merge_table(&mut merged, table, &[]);
+    Ok(merged)
+}
+
+/// Load the `[tools]` table of a drop-in manifest, which must be the only thing in it.
+fn load_drop_in(path: &Path) -> anyhow::Result<Tools> {
+    let raw = utils::read_to_string("drop-in manifest", path)?;
+    let mut table = parse_manifest_table(&raw, Some(path))?;
+    if let Some(key) = table.keys().find(|key| *key != "tools") {
+        bail!("drop-in manifests can only contain the `tools` table, found `{key}`");
+    }
+    let Some(tools) = table.remove("tools") else {
+        return Ok(Tools::default());
+    };
+    Ok(tools.try_into()?)
+}
+
+/// Parse the raw content of a manifest as a table, the content can be written in either
+/// TOML or JSON, where JSON is detected by the `.json` extension of `path`,
+/// or by the leading `{` of the content.
+fn parse_manifest_table(raw: &str, path: Option<&Path>) -> anyhow::Result<toml::Table> {
+    // files saved by some editors on Windows starts with a BOM
+    let raw = raw.strip_prefix('\u{feff}').unwrap_or(raw);
+    let content = raw.trim_start();
+    if content.starts_with('<') {
+        bail!(
+            "expecting a TOML or JSON manifest but got what looks like an HTML page, \
+            please make sure the manifest URL is correct"
+        );
+    }
+
+    let is_json = content.starts_with('{')
+        || path
+            .and_then(Path::extension)
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    if is_json {
+        let mut value: serde_json::Value = serde_json::from_str(raw)?;
+        // TOML does not have `null`, treat them as missing values instead
+        remove_json_nulls(&mut value);
+        Ok(serde_json::from_value(value)?)
+    } else {
+        Ok(toml::from_str(raw)?)
+    }
+}
+
+fn remove_json_nulls(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            map.retain(|_, v| !v.is_null());
+            map.values_mut().for_each(remove_json_nulls);
+        }
+        serde_json::Value::Array(list) => list.iter_mut().for_each(remove_json_nulls),
+        _ => (),
+    }
+}
+
+/// Merge `overlay` into `base`, values in `overlay` take precedence
+/// except for tables, which are merged recursively.
+///
+/// `path` is the list of keys leading to `base`, tools under `tools.target.<target>`
+/// are always replaced as a whole instead of being merged, otherwise we might end up
+/// having a tool with a mixture of different sources.
+fn merge_table(base: &mut toml::Table, overlay: toml::Table, path: &[&str]) {
+    let is_tool_map = matches!(path, ["tools", "target", _]);
+    for (key, value) in overlay {
+        match value {
+            toml::Value::Table(table) if !is_tool_map => {
+                if let Some(toml::Value::Table(base_table)) = base.get_mut(&key) {
+                    let mut child_path = path.to_vec();
+                    child_path.push(&key);
+                    merge_table(base_table, table, &child_path);
+                } else {
+                    base.insert(key, toml::Value::Table(table));
+                }
+            }
+            value => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// The name of the directory that holds drop-in manifests,
+/// check [`ToolkitManifest::merge_drop_ins`].
+pub const DROP_IN_DIR: &str = "components.d";
+
 impl ToolkitManifest {
+    /// Merge every drop-in manifest (`*.toml` or `*.json` files) under `dir` into this
+    /// manifest, which allows adding tools without modifying the manifest itself.
+    ///
+    /// A drop-in manifest can only contain a `[tools]` table, so it is not able to
+    /// change the toolchain or any other configuration. Drop-ins are merged in the
+    /// alphabetical order of their file names, with the following precedence:
+    ///
+    /// - Tools in `tools.target` and `tools.descriptions` of a later merged drop-in
+    ///   replace the ones with the same name, either from the manifest or earlier drop-ins.
+    /// - Tools in `tools.group` are appended to the existing groups.
+    ///
+    /// Note that relative paths in drop-ins are resolved against the manifest instead of
+    /// the drop-in, so it is better to use absolute paths or URLs for tool sources.
+    ///
+    /// Nothing will be merged if `dir` does not exist.
+    pub fn merge_drop_ins(&mut self, dir: &Path) -> anyhow::Result<()> {
+        if !dir.is_dir() {
+            return Ok(());
+        }
+        let mut drop_ins = utils::walk_dir(dir, false)?
+            .into_iter()
+            .filter(|path| {
+                path.is_file()
+                    && utils::extension_str(path).is_some_and(|ext| {
+                        ext.eq_ignore_ascii_case("toml") || ext.eq_ignore_ascii_case("json")
+                    })
+            })
+            .collect::<Vec<_>>();
+        drop_ins.sort();
+
+        for path in drop_ins {
+            let tools = load_drop_in(&path)
+                .with_context(|| format!("invalid drop-in manifest '{}'", path.display()))?;
+            self.tools.merge(tools);
+        }
+        Ok(())
+    }
+
+    /// Serialize the manifest into a JSON string.
+    ///
+    /// Note that manifests are still written as TOML by default, check [`TomlParser::to_toml`].
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        // serialize to TOML value first, so that the missing values are omitted instead of `null`
+        let value = toml::Value::try_from(self)?;
+        Ok(serde_json::to_string_pretty(&value)?)
+    }
+
     /// Get a list of all optional components in rust toolchain.
     pub fn optional_toolchain_components(&self) -> &[String] {
         self.rust.optional_components.as_slice()
@@ -50,6 +243,41 @@ impl ToolkitManifest {
         self.tools.descriptions.get(tool).map(|s| s.as_str())
     }
 
+    /// Get the maximum download speed (bytes per second) set in `[config]` section.
+    pub fn max_download_speed(&self) -> Option<u64> {
+        self.config.as_ref().and_then(|c| c.max_download_speed)
+    }
+
+    /// Get the fallback download mirrors set in `[config]` section.
+    pub fn mirrors(&self) -> &[Url] {
+        self.config
+            .as_ref()
+            .map(|c| c.mirrors.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Get the policy of opening the editor after installation set in `[config]` section,
+    /// default to [`OpenEditorPolicy::Never`].
+    ///
+    /// This takes precedence over the `--yes` option, meaning that `never` stops the editor
+    /// from opening even without `--yes`, and `always` opens it even with `--yes`.
+    /// Only when the policy is `ask` will the `--yes` option skip the prompt (and not open it).
+    pub fn open_editor_after_install(&self) -> OpenEditorPolicy {
+        self.config
+            .as_ref()
+            .and_then(|c| c.open_editor_after_install)
+            .unwrap_or_default()
+    }
+
+    /// Whether to create shortcuts for the installed tools, as set in `[config]` section,
+    /// default to `true`.
+    pub fn create_shortcuts(&self) -> bool {
+        self.config
+            .as_ref()
+            .and_then(|c| c.create_shortcuts)
+            .unwrap_or(true)
+    }
+
     /// Get the group name of a certain tool.
     pub fn group_name(&self, tool: &str) -> Option<&str> {
         self.tools
@@ -58,7 +286,268 @@ impl ToolkitManifest {
             .find_map(|(group, tools)| tools.contains(tool).then_some(group.as_str()))
     }
 
+    /// Get the targets that have any tools or `rustup` binaries configured in this manifest.
+    ///
+    /// Only target triples are included, `tools.target` keys of glob patterns and `all`
+    /// are not, check [`Tools::for_target`] to see if a target has tools through them.
+    pub fn available_targets(&self) -> IndexSet<&str> {
+        self.tools
+            .target
+            .keys()
+            .filter(|key| *key != ALL_TARGETS && !is_target_pattern(key))
+            .chain(self.rust.rustup.keys())
+            .map(String::as_str)
+            .collect()
+    }
+
     setter!(offline(self.is_offline, bool));
+
+    /// Check the URLs in this manifest, returning an error pointing at the first invalid one.
+    ///
+    /// This checks for:
+    /// - `config.mirrors` and the `url` of tools, which must be absolute `http(s)`
+    ///   or `file` URLs, check [`validate_server_url`](utils::validate_server_url).
+    /// - `proxy.http` and `proxy.https`, which must be `http(s)` or `socks5(h)` URLs.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let invalid = |field: &str, reason: anyhow::Error| {
+            anyhow::anyhow!("invalid `{field}` in toolkit manifest: {reason}")
+        };
+
+        for (idx, mirror) in self.mirrors().iter().enumerate() {
+            utils::validate_server_url(mirror)
+                .map_err(|e| invalid(&format!("config.mirrors[{idx}]"), e))?;
+        }
+        if let Some(proxy) = &self.proxy {
+            for (field, url) in [("proxy.http", &proxy.http), ("proxy.https", &proxy.https)] {
+                let Some(url) = url else { continue };
+                if !matches!(url.scheme(), "http" | "https" | "socks5" | "socks5h") {
+                    return Err(invalid(
+                        field,
+                        anyhow::anyhow!(
+                            "'{url}' is not a proxy URL, expecting `http`, `https`, \
+                            `socks5` or `socks5h` scheme"
+                        ),
+                    ));
+                }
+            }
+        }
+        for (target, tools) in &self.tools.target {
+            for (name, info) in tools.iter() {
+                let Some(ToolSource::Url { url, .. }) =
+                    info.details().and_then(|d| d.source.as_ref())
+                else {
+                    continue;
+                };
+                utils::validate_server_url(url)
+                    .map_err(|e| invalid(&format!("tools.target.{target}.{name}.url"), e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check this manifest for common mistakes, and return a list of diagnostics.
+    ///
+    /// This checks for:
+    /// - Group members that are not defined in any target.
+    /// - `requires`, `conflicts` and `obsoletes` referencing unknown tools.
+    /// - `restricted` tools having neither `default` nor `source`.
+    /// - Duplicated identifiers in the same target.
+    pub fn lint(&self) -> Vec<ManifestDiagnostic> {
+        let mut diagnostics = vec![];
+
+        for (group, members) in &self.tools.group {
+            for member in members {
+                let is_defined = self
+                    .tools
+                    .target
+                    .values()
+                    .any(|tools| tools_contain(tools, member));
+                if !is_defined {
+                    diagnostics.push(ManifestDiagnostic::new(
+                        None,
+                        DiagnosticKind::UnknownGroupMember {
+                            group: group.clone(),
+                            tool: member.clone(),
+                        },
+                    ));
+                }
+            }
+        }
+
+        for (target, tools) in &self.tools.target {
+            let mut identifiers: IndexMap<&str, &str> = IndexMap::new();
+
+            for (name, info) in tools.deref() {
+                let references = [
+                    (Relation::Requires, info.dependencies()),
+                    (Relation::Conflicts, info.conflicts()),
+                    (Relation::Obsoletes, info.obsoletes()),
+                ];
+                for (relation, names) in references {
+                    for reference in names.iter().filter(|r| !tools_contain(tools, r)) {
+                        diagnostics.push(ManifestDiagnostic::new(
+                            Some(target),
+                            DiagnosticKind::UnknownReference {
+                                tool: name.clone(),
+                                relation,
+                                reference: reference.clone(),
+                            },
+                        ));
+                    }
+                }
+
+                if let Some(ToolSource::Restricted {
+                    default: None,
+                    source: None,
+                    ..
+                }) = info.details().and_then(|d| d.source.as_ref())
+                {
+                    diagnostics.push(ManifestDiagnostic::new(
+                        Some(target),
+                        DiagnosticKind::RestrictedWithoutSource { tool: name.clone() },
+                    ));
+                }
+
+                if let Some(id) = info.identifier() {
+                    if let Some(first) = identifiers.insert(id, name) {
+                        diagnostics.push(ManifestDiagnostic::new(
+                            Some(target),
+                            DiagnosticKind::DuplicatedIdentifier {
+                                identifier: id.to_string(),
+                                tools: [first.to_string(), name.clone()],
+                            },
+                        ));
+                    }
+                }
+            }
+        }
+
+        diagnostics
+    }
+}
+
+/// Check if a tool with the given name or identifier exists in the map.
+fn tools_contain(tools: &ToolMap, name: &str) -> bool {
+    tools.contains_key(name) || tools.iter().any(|(id, _)| id == name)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Relation {
+    Requires,
+    Conflicts,
+    Obsoletes,
+}
+
+impl std::fmt::Display for Relation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Requires => "requires",
+            Self::Conflicts => "conflicts",
+            Self::Obsoletes => "obsoletes",
+        };
+        f.write_str(s)
+    }
+}
+
+/// The kind of problem found by [`ToolkitManifest::lint`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum DiagnosticKind {
+    /// A tool listed in `[tools.group]` is not defined in any target.
+    UnknownGroupMember { group: String, tool: String },
+    /// A tool references another tool that is not defined in the same target.
+    UnknownReference {
+        tool: String,
+        relation: Relation,
+        reference: String,
+    },
+    /// A `restricted` tool that has neither `default` nor `source`,
+    /// meaning that it cannot be installed without user input.
+    RestrictedWithoutSource { tool: String },
+    /// The same identifier was used by multiple tools in the same target.
+    DuplicatedIdentifier {
+        identifier: String,
+        tools: [String; 2],
+    },
+}
+
+impl std::fmt::Display for DiagnosticKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownGroupMember { group, tool } => write!(
+                f,
+                "tool '{tool}' in group '{group}' is not defined in any target"
+            ),
+            Self::UnknownReference {
+                tool,
+                relation,
+                reference,
+            } => write!(
+                f,
+                "tool '{tool}' {relation} '{reference}', which is not defined in the same target"
+            ),
+            Self::RestrictedWithoutSource { tool } => write!(
+                f,
+                "restricted tool '{tool}' has neither `default` nor `source` specified"
+            ),
+            Self::DuplicatedIdentifier { identifier, tools } => write!(
+                f,
+                "identifier '{identifier}' is used by both '{}' and '{}'",
+                tools[0], tools[1]
+            ),
+        }
+    }
+}
+
+/// A single problem found in a toolkit manifest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ManifestDiagnostic {
+    pub severity: Severity,
+    /// The target that this problem was found in, `None` if it's not target specific.
+    pub target: Option<String>,
+    #[serde(flatten)]
+    pub kind: DiagnosticKind,
+}
+
+impl ManifestDiagnostic {
+    fn new(target: Option<&str>, kind: DiagnosticKind) -> Self {
+        // A tool may conflict with or obsolete tools from other toolkits,
+        // thus unknown references of them are not necessarily mistakes.
+        let severity = match &kind {
+            DiagnosticKind::UnknownReference {
+                relation: Relation::Conflicts | Relation::Obsoletes,
+                ..
+            } => Severity::Warning,
+            _ => Severity::Error,
+        };
+        Self {
+            severity,
+            target: target.map(ToString::to_string),
+            kind,
+        }
+    }
+}
+
+impl std::fmt::Display for ManifestDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        if let Some(target) = &self.target {
+            write!(f, "{severity}: [{target}] {}", self.kind)
+        } else {
+            write!(f, "{severity}: {}", self.kind)
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, PartialEq, Eq, Default, Clone)]
@@ -187,6 +676,8 @@ pub struct Tools {
     /// Note that not all tools will have a group.
     #[serde(default)]
     group: IndexMap<String, IndexSet<String>>,
+    /// Tools of each target, keyed by a target triple, a glob pattern of target triples
+    /// (such as `*-pc-windows-*`), or `all` for tools that are available in every target.
     #[serde(default)]
     pub target: IndexMap<String, ToolMap>,
 }
@@ -201,6 +692,144 @@ impl Tools {
             ..Default::default()
         }
     }
+
+    /// Get the tools that are available in `triple`, which are the tools under its exact key
+    /// in `tools.target`, the keys with glob patterns matching it (such as `*-pc-windows-*`),
+    /// and the `all` key.
+    ///
+    /// If a tool (by its identifier) appears under multiple keys, the one under the exact key
+    /// takes precedence over the ones under glob patterns, which then take precedence over
+    /// the one under `all`. Glob patterns are checked in the order of them being defined.
+    ///
+    /// Return `None` if there are no available tools in `triple`.
+    pub fn for_target(&self, triple: &str) -> Option<Cow<'_, ToolMap>> {
+        let exact = self.target.get(triple);
+        let globs = self
+            .target
+            .iter()
+            .filter(|(key, _)| is_target_pattern(key) && Self::key_matches(key, triple))
+            .map(|(_, tools)| tools);
+        let all = self.target.get(ALL_TARGETS);
+
+        let mut matched = exact.into_iter().chain(globs).chain(all);
+        let first = matched.next()?;
+        let rest = matched.collect::<Vec<_>>();
+        if rest.is_empty() {
+            return Some(Cow::Borrowed(first));
+        }
+
+        let mut seen = HashSet::new();
+        let mut merged = ToolMap::new();
+        for tools in [first].into_iter().chain(rest) {
+            for (name, info) in &**tools {
+                let identifier = info.identifier().unwrap_or(name);
+                if merged.contains_key(name) || !seen.insert(identifier) {
+                    continue;
+                }
+                merged.insert(name.clone(), info.clone());
+            }
+        }
+        Some(Cow::Owned(merged))
+    }
+
+    /// Check whether the tools under a `tools.target` `key` apply to `triple`, which is when
+    /// the key is the triple itself, a glob pattern matching it, or `all`.
+    pub fn key_matches(key: &str, triple: &str) -> bool {
+        key == triple
+            || key == ALL_TARGETS
+            || (is_target_pattern(key)
+                && glob::Pattern::new(key).is_ok_and(|pattern| pattern.matches(triple)))
+    }
+
+    /// Merge `other` into `self`, check [`ToolkitManifest::merge_drop_ins`] for precedence.
+    fn merge(&mut self, other: Tools) {
+        self.descriptions.extend(other.descriptions);
+        for (group, tools) in other.group {
+            self.group.entry(group).or_default().extend(tools);
+        }
+        for (target, mut tools) in other.target {
+            self.target
+                .entry(target)
+                .or_default()
+                .extend(tools.drain(..));
+        }
+    }
+}
+
+/// The key of `tools.target` for tools that are available in every target.
+pub const ALL_TARGETS: &str = "all";
+
+/// Check whether a `tools.target` key is a glob pattern (such as `x86_64-*-linux-gnu`).
+fn is_target_pattern(key: &str) -> bool {
+    key.contains(['*', '?', '['])
+}
+
+/// Additional configurations of the installation process.
+#[derive(Deserialize, Default, Serialize, PartialEq, Eq, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct ManifestConfig {
+    /// The maximum download speed in bytes per second.
+    pub max_download_speed: Option<u64>,
+    /// Value of `net.git-fetch-with-cli` in cargo's `config.toml`.
+    pub cargo_git_fetch_with_cli: Option<bool>,
+    /// Value of `net.retry` in cargo's `config.toml`.
+    pub cargo_net_retry: Option<u32>,
+    /// Value of `http.check-revoke` in cargo's `config.toml`.
+    pub cargo_check_revoke: Option<bool>,
+    /// Token to authenticate with the cargo registry, which will be written to
+    /// cargo's `credentials.toml`.
+    ///
+    /// This is a secret, so it's never written back when the manifest is saved
+    /// (into the installation directory), nor shown in debug output.
+    #[serde(skip_serializing)]
+    pub cargo_registry_token: Option<String>,
+    /// Value of `registries.<name>.credential-provider` in cargo's `config.toml`,
+    /// such as `cargo:token-from-stdout <command>`.
+    pub cargo_registry_credential_process: Option<String>,
+    /// Whether to open the editor (trying the demo project) after a successful installation.
+    pub open_editor_after_install: Option<OpenEditorPolicy>,
+    /// Fallback servers to download packages from when the original one is unavailable,
+    /// the origin of each download url will be replaced while preserving the path.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<Url>,
+    /// Whether to create desktop or application menu shortcuts for the installed tools.
+    pub create_shortcuts: Option<bool>,
+}
+
+impl std::fmt::Debug for ManifestConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ManifestConfig")
+            .field("max_download_speed", &self.max_download_speed)
+            .field("cargo_git_fetch_with_cli", &self.cargo_git_fetch_with_cli)
+            .field("cargo_net_retry", &self.cargo_net_retry)
+            .field("cargo_check_revoke", &self.cargo_check_revoke)
+            .field(
+                "cargo_registry_token",
+                &self.cargo_registry_token.as_ref().map(|_| "<redacted>"),
+            )
+            .field(
+                "cargo_registry_credential_process",
+                &self.cargo_registry_credential_process,
+            )
+            .field("open_editor_after_install", &self.open_editor_after_install)
+            .field("mirrors", &self.mirrors)
+            .field("create_shortcuts", &self.create_shortcuts)
+            .finish()
+    }
+}
+
+/// Policy of opening the editor after installation, check
+/// [`ToolkitManifest::open_editor_after_install`] for more details.
+#[derive(Debug, Deserialize, Default, Serialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum OpenEditorPolicy {
+    /// Always open the editor without asking.
+    Always,
+    /// Ask the user before opening the editor.
+    Ask,
+    /// Never open the editor, and never ask.
+    #[default]
+    Never,
 }
 
 /// The proxy for download
@@ -235,7 +864,7 @@ impl TryFrom<&Proxy> for reqwest::Proxy {
 
 #[cfg(test)]
 mod tests {
-    use crate::types::{ToolInfo, ToolInfoDetails, ToolSource};
+    use crate::types::{ToolInfo, ToolInfoDetails, ToolKind, ToolSource};
 
     use super::*;
 
@@ -253,6 +882,9 @@ mod tests {
                 version: $version.map(ToString::to_string),
                 url: $url_str.parse().unwrap(),
                 filename: None,
+                strip_components: None,
+                size: None,
+                sha256: None,
             }))
         };
         ($git:literal, $branch:expr, $tag:expr, $rev:expr) => {
@@ -267,6 +899,9 @@ mod tests {
             complex_tool(ToolInfoDetails::new().with_source(ToolSource::Path {
                 path: $path,
                 version: $version.map(ToString::to_string),
+                strip_components: None,
+                size: None,
+                sha256: None,
             }))
         };
     }
@@ -592,6 +1227,82 @@ no-proxy = "localhost,some.domain.com"
         );
     }
 
+    #[test]
+    fn tool_with_proxy() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-pc-windows-msvc]
+t1 = { url = "https://internal.example.com/t1.zip", proxy = { https = "http://proxy.internal:8080" } }
+t2 = { url = "https://example.com/t2.zip" }
+"#;
+        let manifest = ToolkitManifest::from_str(input).unwrap();
+        let tools = manifest.tools.target.get("x86_64-pc-windows-msvc").unwrap();
+        assert_eq!(
+            tools["t1"].proxy(),
+            Some(&Proxy {
+                https: Some(Url::parse("http://proxy.internal:8080").unwrap()),
+                ..Default::default()
+            })
+        );
+        assert!(tools["t2"].proxy().is_none());
+
+        let roundtrip = ToolkitManifest::from_str(&manifest.to_toml().unwrap()).unwrap();
+        assert_eq!(roundtrip, manifest);
+    }
+
+    #[test]
+    fn with_config() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+[config]
+max-download-speed = 1048576
+cargo-git-fetch-with-cli = true
+cargo-net-retry = 5
+cargo-registry-credential-process = "cargo:token"
+open-editor-after-install = "ask"
+mirrors = ["https://mirror.example.com/rim/"]
+create-shortcuts = false
+"#;
+        let expected = ToolkitManifest::from_str(input).unwrap();
+        assert_eq!(expected.max_download_speed(), Some(1048576));
+        let config = expected.config.as_ref().unwrap();
+        assert_eq!(config.cargo_git_fetch_with_cli, Some(true));
+        assert_eq!(config.cargo_net_retry, Some(5));
+        assert_eq!(config.cargo_check_revoke, None);
+        assert_eq!(config.cargo_registry_token, None);
+        assert_eq!(
+            config.cargo_registry_credential_process.as_deref(),
+            Some("cargo:token")
+        );
+        assert_eq!(expected.open_editor_after_install(), OpenEditorPolicy::Ask);
+        assert_eq!(
+            expected.mirrors(),
+            [Url::parse("https://mirror.example.com/rim/").unwrap()]
+        );
+        assert!(!expected.create_shortcuts());
+
+        // the registry token is a secret that should never be persisted nor logged
+        let input = "[rust]\nversion = \"1.0.0\"\n[config]\ncargo-registry-token = \"s3cr3t\"";
+        let with_token = ToolkitManifest::from_str(input).unwrap();
+        let config = with_token.config.as_ref().unwrap();
+        assert_eq!(config.cargo_registry_token.as_deref(), Some("s3cr3t"));
+        assert!(!with_token.to_toml().unwrap().contains("s3cr3t"));
+        assert!(!format!("{with_token:?}").contains("s3cr3t"));
+
+        let input = "[rust]\nversion = \"1.0.0\"";
+        let expected = ToolkitManifest::from_str(input).unwrap();
+        assert_eq!(expected.max_download_speed(), None);
+        assert!(expected.mirrors().is_empty());
+        assert!(expected.create_shortcuts());
+        assert_eq!(
+            expected.open_editor_after_install(),
+            OpenEditorPolicy::Never
+        );
+    }
+
     #[test]
     fn with_product_info() {
         let input = r#"
@@ -713,6 +1424,358 @@ tool_b = { default = "https://example.com/installer.exe", restricted = true }
         );
     }
 
+    #[test]
+    fn github_release_source() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-pc-windows-msvc]
+tool_a = { repo = "owner/tool_a", tag = "v0.1.0", asset-pattern = "tool_a-{version}-{target}.zip" }
+tool_b = { repo = "owner/tool_b", asset-pattern = "tool_b-{target}.zip" }
+"#;
+
+        let expected = ToolkitManifest::from_str(input).unwrap();
+        let (_, tool) = expected.tools.target.iter().next().unwrap();
+        let tool_a = &tool["tool_a"];
+        assert_eq!(
+            tool_a.details().unwrap().source,
+            Some(ToolSource::GitHubRelease {
+                repo: "owner/tool_a".into(),
+                tag: Some("v0.1.0".into()),
+                asset_pattern: "tool_a-{version}-{target}.zip".into(),
+            })
+        );
+        assert_eq!(tool_a.version(), Some("v0.1.0"));
+        assert!(!tool_a.is_cargo_tool());
+        assert_eq!(tool["tool_b"].version(), None);
+    }
+
+    #[test]
+    fn npm_source() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-pc-windows-msvc]
+tool_a = { package = "eslint", version = "9.0.0" }
+tool_b = { package = "prettier" }
+"#;
+
+        let expected = ToolkitManifest::from_str(input).unwrap();
+        let (_, tool) = expected.tools.target.iter().next().unwrap();
+        let tool_a = &tool["tool_a"];
+        assert_eq!(
+            tool_a.details().unwrap().source,
+            Some(ToolSource::Npm {
+                package: "eslint".into(),
+                version: Some("9.0.0".into()),
+            })
+        );
+        assert_eq!(tool_a.version(), Some("9.0.0"));
+        assert!(!tool_a.is_cargo_tool());
+        assert_eq!(tool["tool_b"].version(), None);
+
+        // make sure it can be serialized back without losing information
+        let roundtrip = ToolkitManifest::from_str(&expected.to_toml().unwrap()).unwrap();
+        let (_, rt_tool) = roundtrip.tools.target.iter().next().unwrap();
+        assert_eq!(
+            rt_tool["tool_b"].details().unwrap().source,
+            Some(ToolSource::Npm {
+                package: "prettier".into(),
+                version: None,
+            })
+        );
+        assert_eq!(rt_tool["tool_a"], tool["tool_a"]);
+    }
+
+    #[test]
+    fn pip_source() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-pc-windows-msvc]
+tool_a = { pip = "black", version = "24.4.2", extras = ["jupyter"] }
+tool_b = { pip = "ruff" }
+"#;
+
+        let expected = ToolkitManifest::from_str(input).unwrap();
+        let (_, tool) = expected.tools.target.iter().next().unwrap();
+        let tool_a = &tool["tool_a"];
+        assert_eq!(
+            tool_a.details().unwrap().source,
+            Some(ToolSource::Pip {
+                package: "black".into(),
+                version: Some("24.4.2".into()),
+                extras: vec!["jupyter".into()],
+            })
+        );
+        assert_eq!(tool_a.version(), Some("24.4.2"));
+        assert!(!tool_a.is_cargo_tool());
+        assert_eq!(tool["tool_b"].version(), None);
+
+        // make sure it can be serialized back without losing information
+        let roundtrip = ToolkitManifest::from_str(&expected.to_toml().unwrap()).unwrap();
+        let (_, rt_tool) = roundtrip.tools.target.iter().next().unwrap();
+        assert_eq!(
+            rt_tool["tool_b"].details().unwrap().source,
+            Some(ToolSource::Pip {
+                package: "ruff".into(),
+                version: None,
+                extras: vec![],
+            })
+        );
+        assert_eq!(rt_tool["tool_a"], tool["tool_a"]);
+    }
+
+    #[test]
+    fn load_with_includes() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = r#"
+name = "base"
+[rust]
+version = "1.0.0"
+components = ["clippy"]
+
+[tools.target.x86_64-pc-windows-msvc]
+tool_a = "0.1.0"
+tool_b = { url = "https://example.com/tool_b.zip" }
+"#;
+        let kit = r#"
+include = ["common/base.toml"]
+name = "kit"
+[rust]
+version = "1.1.0"
+
+[tools.target.x86_64-pc-windows-msvc]
+tool_b = { path = "tool_b.zip" }
+tool_c = "0.2.0"
+"#;
+        utils::ensure_dir(dir.path().join("common")).unwrap();
+        utils::write_file(dir.path().join("common").join("base.toml"), base, false).unwrap();
+        utils::write_file(dir.path().join("kit.toml"), kit, false).unwrap();
+
+        let manifest = ToolkitManifest::load(dir.path().join("kit.toml")).unwrap();
+        assert_eq!(manifest.name.as_deref(), Some("kit"));
+        assert_eq!(manifest.rust.channel, "1.1.0");
+        assert_eq!(manifest.rust.components, ["clippy"]);
+
+        let tools = &manifest.tools.target["x86_64-pc-windows-msvc"];
+        assert_eq!(
+            tools.keys().collect::<Vec<_>>(),
+            ["tool_a", "tool_b", "tool_c"]
+        );
+        assert_eq!(
+            tools["tool_b"],
+            ToolInfo::new_detailed(ToolInfoDetails::new().with_source(ToolSource::Path {
+                version: None,
+                path: "tool_b.zip".into(),
+                strip_components: None,
+                size: None,
+                sha256: None,
+            }))
+        );
+    }
+
+    #[test]
+    fn merge_drop_in_tools() {
+        let base = r#"
+[rust]
+version = "1.0.0"
+components = ["clippy"]
+
+[tools.descriptions]
+tool_a = "A"
+
+[tools.group]
+Group = ["tool_a"]
+
+[tools.target.x86_64-pc-windows-msvc]
+tool_a = "0.1.0"
+tool_b = "0.1.0"
+"#;
+        let drop_in = r#"
+[tools.descriptions]
+internal = "Internal tool"
+
+[tools.group]
+Group = ["internal"]
+
+[tools.target.x86_64-pc-windows-msvc]
+tool_b = { url = "https://example.com/tool_b.zip" }
+internal = { url = "https://example.com/internal.zip" }
+"#;
+        let dir = tempfile::tempdir().unwrap();
+        utils::write_file(dir.path().join("10-internal.toml"), drop_in, false).unwrap();
+        utils::write_file(dir.path().join("README.md"), "ignored", false).unwrap();
+
+        let mut manifest = ToolkitManifest::from_str(base).unwrap();
+        manifest.merge_drop_ins(dir.path()).unwrap();
+        assert_eq!(manifest.rust.components, ["clippy"]);
+        assert_eq!(manifest.get_tool_description("tool_a"), Some("A"));
+        assert_eq!(
+            manifest.get_tool_description("internal"),
+            Some("Internal tool")
+        );
+        assert_eq!(
+            manifest.tools.group["Group"].iter().collect::<Vec<_>>(),
+            ["tool_a", "internal"]
+        );
+        let tools = &manifest.tools.target["x86_64-pc-windows-msvc"];
+        assert_eq!(
+            tools.keys().collect::<Vec<_>>(),
+            ["tool_a", "tool_b", "internal"]
+        );
+        assert_eq!(tools["tool_a"], ToolInfo::Basic("0.1.0".into()));
+        assert!(matches!(
+            tools["tool_b"].details().and_then(|d| d.source.as_ref()),
+            Some(ToolSource::Url { .. })
+        ));
+
+        // drop-ins cannot override the toolchain
+        utils::write_file(
+            dir.path().join("20-rust.toml"),
+            "[rust]\nversion = \"2.0.0\"",
+            false,
+        )
+        .unwrap();
+        let err = manifest.merge_drop_ins(dir.path()).unwrap_err();
+        assert!(format!("{err:#}").contains("can only contain the `tools` table"));
+    }
+
+    #[test]
+    fn load_json_manifest() {
+        let toml_input = r#"
+name = "kit"
+[rust]
+version = "1.0.0"
+components = ["clippy"]
+
+[tools.target.x86_64-pc-windows-msvc]
+tool_a = "0.1.0"
+tool_b = { url = "https://example.com/tool_b.zip" }
+
+[config]
+cargo-net-retry = 5
+"#;
+        let json_input = r#"{
+  "name": "kit",
+  "edition": null,
+  "rust": { "version": "1.0.0", "components": ["clippy"] },
+  "tools": {
+    "target": {
+      "x86_64-pc-windows-msvc": {
+        "tool_a": "0.1.0",
+        "tool_b": { "url": "https://example.com/tool_b.zip" }
+      }
+    }
+  },
+  "config": { "cargo-net-retry": 5 }
+}"#;
+        let from_toml = ToolkitManifest::from_str(toml_input).unwrap();
+        assert_eq!(ToolkitManifest::from_str(json_input).unwrap(), from_toml);
+
+        // detected by extension, even with a leading BOM
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("kit.json");
+        utils::write_file(&path, &format!("\u{feff}{json_input}"), false).unwrap();
+        let mut from_json = ToolkitManifest::load(&path).unwrap();
+        from_json.path = None;
+        assert_eq!(from_json, from_toml);
+
+        let json = from_toml.to_json().unwrap();
+        assert_eq!(ToolkitManifest::from_str(&json).unwrap(), from_toml);
+    }
+
+    #[test]
+    fn reject_html_manifest() {
+        let err = ToolkitManifest::from_str("\n<!DOCTYPE html>\n<html></html>").unwrap_err();
+        assert!(err.to_string().contains("HTML"));
+    }
+
+    #[test]
+    fn cyclic_includes() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = r#"
+include = ["b.toml"]
+[rust]
+version = "1.0.0"
+"#;
+        utils::write_file(dir.path().join("a.toml"), a, false).unwrap();
+        utils::write_file(dir.path().join("b.toml"), "include = [\"a.toml\"]", false).unwrap();
+
+        let err = ToolkitManifest::load(dir.path().join("a.toml")).unwrap_err();
+        assert!(err.to_string().contains("cyclic include"));
+    }
+
+    #[test]
+    fn lint_manifest() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[tools.group]
+Group = ["tool_a", "tool_x"]
+
+[tools.target.x86_64-pc-windows-msvc]
+tool_a = { version = "0.1.0", requires = ["tool_b", "tool_y"], obsoletes = ["tool_z"] }
+tool_b = { version = "0.1.0", identifier = "b" }
+tool_c = { version = "0.1.0", identifier = "b", requires = ["b"] }
+tool_d = { restricted = true }
+"#;
+
+        let manifest = ToolkitManifest::from_str(input).unwrap();
+        let target = Some("x86_64-pc-windows-msvc".to_string());
+        let diagnostics = manifest.lint();
+        assert_eq!(
+            diagnostics,
+            vec![
+                ManifestDiagnostic {
+                    severity: Severity::Error,
+                    target: None,
+                    kind: DiagnosticKind::UnknownGroupMember {
+                        group: "Group".into(),
+                        tool: "tool_x".into()
+                    }
+                },
+                ManifestDiagnostic {
+                    severity: Severity::Error,
+                    target: target.clone(),
+                    kind: DiagnosticKind::UnknownReference {
+                        tool: "tool_a".into(),
+                        relation: Relation::Requires,
+                        reference: "tool_y".into()
+                    }
+                },
+                ManifestDiagnostic {
+                    severity: Severity::Warning,
+                    target: target.clone(),
+                    kind: DiagnosticKind::UnknownReference {
+                        tool: "tool_a".into(),
+                        relation: Relation::Obsoletes,
+                        reference: "tool_z".into()
+                    }
+                },
+                ManifestDiagnostic {
+                    severity: Severity::Error,
+                    target: target.clone(),
+                    kind: DiagnosticKind::DuplicatedIdentifier {
+                        identifier: "b".into(),
+                        tools: ["tool_b".into(), "tool_c".into()]
+                    }
+                },
+                ManifestDiagnostic {
+                    severity: Severity::Error,
+                    target,
+                    kind: DiagnosticKind::RestrictedWithoutSource {
+                        tool: "tool_d".into()
+                    }
+                },
+            ]
+        );
+    }
+
     #[test]
     fn tool_dependency_control() {
         let input = r#"
@@ -733,6 +1796,165 @@ tool_d = "0.1.0""#;
         assert_eq!(tool_a.conflicts, ["tool_d".to_string()]);
     }
 
+    #[test]
+    fn manifest_available_targets() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[rust.rustup]
+x86_64-pc-windows-msvc = "packages/x86_64-pc-windows-msvc/rustup-init.exe"
+aarch64-unknown-linux-gnu = "packages/aarch64-unknown-linux-gnu/rustup-init"
+
+[tools.target.x86_64-pc-windows-msvc]
+t1 = "0.1.0"
+[tools.target.x86_64-unknown-linux-gnu]
+t1 = "0.1.0"
+[tools.target."*-apple-darwin"]
+t1 = "0.1.0"
+[tools.target.all]
+t2 = "0.1.0"
+"#;
+
+        let expected = ToolkitManifest::from_str(input).unwrap();
+        let targets = expected.available_targets();
+        assert_eq!(
+            targets.into_iter().collect::<Vec<_>>(),
+            [
+                "x86_64-pc-windows-msvc",
+                "x86_64-unknown-linux-gnu",
+                "aarch64-unknown-linux-gnu"
+            ]
+        );
+    }
+
+    #[test]
+    fn target_key_matches() {
+        let triple = "x86_64-unknown-linux-gnu";
+        assert!(Tools::key_matches(triple, triple));
+        assert!(Tools::key_matches("all", triple));
+        assert!(Tools::key_matches("x86_64-*-linux-*", triple));
+        assert!(Tools::key_matches("*", triple));
+        assert!(!Tools::key_matches("*-pc-windows-*", triple));
+        assert!(!Tools::key_matches("aarch64-unknown-linux-gnu", triple));
+    }
+
+    #[test]
+    fn with_hook_commands() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-pc-windows-msvc]
+tool_a = { url = "https://example.com/a.zip", post-install = "bin/a --init", pre-uninstall = "bin/a --reset" }
+tool_b = { url = "https://example.com/b.zip" }
+"#;
+
+        let expected = ToolkitManifest::from_str(input).unwrap();
+        let (_, tool) = expected.tools.target.iter().next().unwrap();
+        assert_eq!(tool["tool_a"].post_install(), Some("bin/a --init"));
+        assert_eq!(tool["tool_a"].pre_uninstall(), Some("bin/a --reset"));
+        assert!(tool["tool_a"].has_hooks());
+        assert!(!tool["tool_b"].has_hooks());
+    }
+
+    #[test]
+    fn with_script_tool() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-unknown-linux-gnu]
+tool_a = { url = "https://example.com/install.sh", kind = "script", uninstall-script = "uninstall.sh" }
+"#;
+
+        let expected = ToolkitManifest::from_str(input).unwrap();
+        let (_, tool) = expected.tools.target.iter().next().unwrap();
+        assert_eq!(tool["tool_a"].kind(), Some(ToolKind::Script));
+        assert_eq!(tool["tool_a"].uninstall_script(), Some("uninstall.sh"));
+        assert!(tool["tool_a"].has_hooks());
+    }
+
+    #[test]
+    fn with_strip_components() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-pc-windows-msvc]
+tool_a = { url = "https://example.com/a.zip", strip_components = 2 }
+tool_b = { path = "b.tar.gz", strip_components = 1 }
+tool_c = { url = "https://example.com/c.zip" }
+"#;
+
+        let expected = ToolkitManifest::from_str(input).unwrap();
+        let (_, tool) = expected.tools.target.iter().next().unwrap();
+        assert_eq!(tool["tool_a"].strip_components(), Some(2));
+        assert_eq!(tool["tool_b"].strip_components(), Some(1));
+        assert_eq!(tool["tool_c"].strip_components(), None);
+    }
+
+    #[test]
+    fn with_package_size() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-pc-windows-msvc]
+tool_a = { url = "https://example.com/a.zip", size = 1024 }
+tool_b = { path = "b.tar.gz", size = 2048 }
+tool_c = { url = "https://example.com/c.zip" }
+tool_d = "1.0.0"
+"#;
+
+        let expected = ToolkitManifest::from_str(input).unwrap();
+        let (_, tool) = expected.tools.target.iter().next().unwrap();
+        assert_eq!(tool["tool_a"].size(), Some(1024));
+        assert_eq!(tool["tool_b"].size(), Some(2048));
+        assert_eq!(tool["tool_c"].size(), None);
+        assert_eq!(tool["tool_d"].size(), None);
+    }
+
+    #[test]
+    fn with_package_sha256() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-pc-windows-msvc]
+tool_a = { path = "tools/a.zip", sha256 = "abc123" }
+tool_b = { path = "tools/b.zip" }
+tool_c = { url = "https://example.com/c.zip", sha256 = "def456" }
+tool_d = "1.0.0"
+"#;
+
+        let manifest = ToolkitManifest::from_str(input).unwrap();
+        let tools = &manifest.tools.target["x86_64-pc-windows-msvc"];
+        assert_eq!(tools["tool_a"].sha256(), Some("abc123"));
+        assert_eq!(tools["tool_b"].sha256(), None);
+        assert_eq!(tools["tool_c"].sha256(), Some("def456"));
+        assert_eq!(tools["tool_d"].sha256(), None);
+
+        // the checksum of url source is kept when converting it to path source,
+        // unless a checksum of the local file is provided.
+        let mut tool_c = tools["tool_c"].clone();
+        tool_c.url_to_path("tools/c.zip", None);
+        assert!(matches!(
+            tool_c.details().and_then(|d| d.source.as_ref()),
+            Some(ToolSource::Path { .. })
+        ));
+        assert_eq!(tool_c.sha256(), Some("def456"));
+        let mut tool_c = tools["tool_c"].clone();
+        tool_c.url_to_path("tools/c.zip", Some("789abc".into()));
+        assert_eq!(tool_c.sha256(), Some("789abc"));
+
+        // the checksum should survive a round trip
+        let serialized = manifest.to_toml().unwrap();
+        assert!(serialized.contains("sha256 = \"abc123\""));
+        let deserialized = ToolkitManifest::from_str(&serialized).unwrap();
+        assert_eq!(deserialized.tools, manifest.tools);
+    }
+
     #[test]
     fn rust_profile_backward_compatible() {
         let input = r#"
@@ -754,4 +1976,65 @@ description = "Everything provided by official Rust-lang"
             Some("Everything provided by official Rust-lang")
         );
     }
+
+    #[test]
+    fn validate_urls() {
+        let valid = r#"
+[rust]
+version = "1.0.0"
+
+[config]
+mirrors = ["https://mirror.example.com/dist"]
+
+[tools.target.x86_64-unknown-linux-gnu]
+a = { version = "0.1.0", url = "https://example.com/a.zip" }
+b = { version = "0.1.0", url = "file:///path/to/b.zip" }
+"#;
+        ToolkitManifest::from_str(valid)
+            .unwrap()
+            .validate()
+            .unwrap();
+
+        // the host is parsed as scheme when it's missing
+        let bad_mirror = r#"
+[rust]
+version = "1.0.0"
+
+[config]
+mirrors = ["mirror.example.com:8080/dist"]
+"#;
+        let err = ToolkitManifest::from_str(bad_mirror)
+            .unwrap()
+            .validate()
+            .unwrap_err();
+        assert!(err.to_string().contains("`config.mirrors[0]`"));
+
+        let bad_tool = r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-unknown-linux-gnu]
+a = { version = "0.1.0", url = "example.com:80/a.zip" }
+"#;
+        let err = ToolkitManifest::from_str(bad_tool)
+            .unwrap()
+            .validate()
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("`tools.target.x86_64-unknown-linux-gnu.a.url`"));
+
+        let bad_proxy = r#"
+[rust]
+version = "1.0.0"
+
+[proxy]
+https = "ftp://proxy.example.com"
+"#;
+        let err = ToolkitManifest::from_str(bad_proxy)
+            .unwrap()
+            .validate()
+            .unwrap_err();
+        assert!(err.to_string().contains("`proxy.https`"));
+    }
 }
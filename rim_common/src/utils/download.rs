@@ -1,10 +1,16 @@
 use std::cmp::min;
+use std::fmt;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
 use std::path::Path;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, bail, Context, Result};
 use indicatif::ProgressBar;
-use reqwest::{header, Client};
+use percent_encoding::percent_decode_str;
+use reqwest::{header, Client, StatusCode};
+use serde::{Deserialize, Serialize};
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 use url::Url;
@@ -13,11 +19,82 @@ use super::progress_bar::{CliProgress, Style};
 use crate::types::Proxy as CrateProxy;
 use crate::{build_config, setter};
 
+/// The default number of attempts to make before giving up a download.
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+/// The delay before the first retry, which gets doubled after each failed attempt.
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+/// The longest delay between two attempts, including the one asked by server.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Whether network access is disabled, check [`set_offline`].
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Disable network access, so that only local files (`file://` URLs) can be
+/// read or downloaded, any other URL will be refused with an error.
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+/// Return `true` if network access was disabled by [`set_offline`].
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// Return an error if `url` requires network access while it was disabled by [`set_offline`].
+pub fn ensure_network_allowed(url: &Url) -> Result<()> {
+    check_network_access(url, is_offline())
+}
+
+fn check_network_access(url: &Url, offline: bool) -> Result<()> {
+    if offline && url.scheme() != "file" {
+        bail!(t!("offline_network_disabled", url = url));
+    }
+    Ok(())
+}
+
 fn default_proxy() -> reqwest::Proxy {
     reqwest::Proxy::custom(|url| env_proxy::for_url(url).to_url())
         .no_proxy(reqwest::NoProxy::from_env())
 }
 
+/// Validators of a previously fetched content, which can be sent with a conditional
+/// request so that the server can skip sending it again if it was not modified.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CacheValidators {
+    /// Value of the `ETag` response header.
+    pub etag: Option<String>,
+    /// Value of the `Last-Modified` response header.
+    pub last_modified: Option<String>,
+}
+
+impl CacheValidators {
+    fn from_headers(headers: &header::HeaderMap) -> Self {
+        let get = |name| {
+            headers
+                .get(name)
+                .and_then(|val| val.to_str().ok())
+                .map(ToOwned::to_owned)
+        };
+        Self {
+            etag: get(header::ETAG),
+            last_modified: get(header::LAST_MODIFIED),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+/// Text content retrieved by [`DownloadOpt::read_if_modified`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum ConditionalText {
+    /// The content was not modified since it was fetched with the given validators.
+    NotModified,
+    /// The content was (re)sent by the server, along with its new validators.
+    Modified(String, CacheValidators),
+}
+
 #[derive(Debug)]
 pub struct DownloadOpt<T: Sized> {
     /// The verbose name of the file to download.
@@ -30,6 +107,21 @@ pub struct DownloadOpt<T: Sized> {
     pub proxy: Option<CrateProxy>,
     /// Whether or not to resuming previous download.
     resume: bool,
+    /// Whether or not to send the current locale as `Accept-Language` header,
+    /// allowing the server to respond with localized content.
+    localized: bool,
+    /// The maximum download speed in bytes per second, `None` means unlimited.
+    rate_limit: Option<u64>,
+    /// How many times to try downloading a file before giving up,
+    /// only network errors and certain server errors will be retried.
+    max_attempts: u32,
+    /// The delay before the first retry.
+    retry_base_delay: Duration,
+    /// Fallback servers to download from when the original url failed,
+    /// check [`mirrored_url`] for how the url gets rewritten.
+    mirrors: Vec<Url>,
+    /// The expected SHA-256 checksum of the downloaded file, if known.
+    sha256: Option<String>,
 }
 
 impl DownloadOpt<ProgressBar> {
@@ -41,12 +133,37 @@ impl DownloadOpt<ProgressBar> {
             insecure: false,
             proxy: None,
             resume: false,
+            localized: false,
+            rate_limit: None,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            retry_base_delay: RETRY_BASE_DELAY,
+            mirrors: vec![],
+            sha256: None,
         }
     }
 
     setter!(with_proxy(self.proxy, Option<CrateProxy>));
     setter!(insecure(self.insecure, bool));
     setter!(resume(self.resume, bool));
+    setter!(localized(self.localized, bool));
+    setter!(with_rate_limit(self.rate_limit, Option<u64>));
+    setter!(with_max_attempts(self.max_attempts, u32));
+    setter!(with_mirrors(self.mirrors, Vec<Url>));
+    setter!(with_sha256(self.sha256, Option<String>));
+
+    /// Headers that will be sent with every request.
+    fn default_headers(&self) -> Result<header::HeaderMap> {
+        let mut headers = header::HeaderMap::new();
+        if self.localized {
+            let locale = rust_i18n::locale().to_string();
+            headers.insert(
+                header::ACCEPT_LANGUAGE,
+                header::HeaderValue::from_str(&locale)
+                    .with_context(|| format!("invalid locale '{locale}'"))?,
+            );
+        }
+        Ok(headers)
+    }
 
     /// Build and return a client for download
     fn client(&self) -> Result<Client> {
@@ -62,6 +179,7 @@ impl DownloadOpt<ProgressBar> {
         };
         let client = Client::builder()
             .user_agent(user_agent)
+            .default_headers(self.default_headers()?)
             .connect_timeout(Duration::from_secs(30))
             .danger_accept_invalid_certs(self.insecure)
             .proxy(proxy)
@@ -87,6 +205,7 @@ impl DownloadOpt<ProgressBar> {
             });
         }
 
+        ensure_network_allowed(url)?;
         if self.insecure {
             warn!("{}", t!("insecure_download"));
         }
@@ -106,7 +225,137 @@ impl DownloadOpt<ProgressBar> {
             );
         }
     }
+    /// Consume self, and retrieve text response from a given url, unless the server
+    /// responds with `304 Not Modified` to the given `validators`.
+    ///
+    /// Local files are read every time, as they do not have any validators.
+    pub async fn read_if_modified(
+        self,
+        url: &Url,
+        validators: &CacheValidators,
+    ) -> Result<ConditionalText> {
+        if url.scheme() == "file" {
+            let content = self.read(url).await?;
+            return Ok(ConditionalText::Modified(
+                content,
+                CacheValidators::default(),
+            ));
+        }
+
+        ensure_network_allowed(url)?;
+        if self.insecure {
+            warn!("{}", t!("insecure_download"));
+        }
+
+        let client = self.client()?;
+        self.retry(|| self.read_if_modified_(&client, url, validators))
+            .await
+    }
+
+    /// Make a single attempt to retrieve text response from a given url,
+    /// check [`read_if_modified`](DownloadOpt::read_if_modified) for details.
+    async fn read_if_modified_(
+        &self,
+        client: &Client,
+        url: &Url,
+        validators: &CacheValidators,
+    ) -> Result<ConditionalText> {
+        let mut req = client.get(url.as_ref());
+        if let Some(etag) = &validators.etag {
+            req = req.header(header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &validators.last_modified {
+            req = req.header(header::IF_MODIFIED_SINCE, last_modified);
+        }
+        let resp = req
+            .send()
+            .await
+            .with_context(|| format!("failed to receive server response from '{url}'"))?;
+        if resp.status() == StatusCode::NOT_MODIFIED {
+            return Ok(ConditionalText::NotModified);
+        }
+        if !resp.status().is_success() {
+            return Err(StatusError::new(url, &resp).into());
+        }
+        let new_validators = CacheValidators::from_headers(resp.headers());
+        Ok(ConditionalText::Modified(
+            resp.text().await?,
+            new_validators,
+        ))
+    }
+
+    /// Consume self, and get the size (in bytes) of the file located at a given url
+    /// by sending a `HEAD` request, without downloading it.
+    ///
+    /// Return `None` if the server does not tell the size.
+    pub async fn content_length(self, url: &Url) -> Result<Option<u64>> {
+        if url.scheme() == "file" {
+            let path = url
+                .to_file_path()
+                .map_err(|_| anyhow!("unable to convert to file path for url '{url}'"))?;
+            return Ok(Some(fs::metadata(&path).await?.len()));
+        }
+        ensure_network_allowed(url)?;
+
+        let resp = self
+            .client()?
+            .head(url.as_ref())
+            .send()
+            .await
+            .with_context(|| format!("failed to receive server response from '{url}'"))?;
+        if !resp.status().is_success() {
+            bail!(
+                "unable to get the size of '{url}': server responded with error {}",
+                resp.status()
+            );
+        }
+        // NB: `Response::content_length` is not reliable for `HEAD` requests,
+        // as it reports the length of the (empty) body, so read the header directly.
+        Ok(resp
+            .headers()
+            .get(header::CONTENT_LENGTH)
+            .and_then(|val| val.to_str().ok())
+            .and_then(|val| val.parse().ok()))
+    }
+
+    /// Consume self, and get the real name of the file located at a given url,
+    /// as suggested by server's `Content-Disposition` header, by sending a `HEAD` request.
+    ///
+    /// Return `None` if the server does not suggest one.
+    pub async fn remote_filename(self, url: &Url) -> Result<Option<String>> {
+        if url.scheme() == "file" {
+            return Ok(None);
+        }
+        ensure_network_allowed(url)?;
+
+        let resp = self
+            .client()?
+            .head(url.as_ref())
+            .send()
+            .await
+            .with_context(|| format!("failed to receive server response from '{url}'"))?;
+        if !resp.status().is_success() {
+            bail!(
+                "unable to get the filename of '{url}': server responded with error {}",
+                resp.status()
+            );
+        }
+        Ok(resp
+            .headers()
+            .get(header::CONTENT_DISPOSITION)
+            .and_then(|val| val.to_str().ok())
+            .and_then(filename_from_content_disposition))
+    }
+
     /// Consume self, and download from given `Url` to `Path`.
+    ///
+    /// Failed attempts caused by network errors or retryable server errors
+    /// (`408`, `429` and `5xx`) will be retried with exponential backoff,
+    /// up to `max_attempts` times in total. If it still fails, each of the `mirrors`
+    /// will be tried in order.
+    ///
+    /// The file is verified with the expected `sha256` checksum (if any) no matter
+    /// where it came from, including local files.
     pub async fn download(self, url: &Url, path: &Path) -> Result<()> {
         if url.scheme() == "file" {
             fs::copy(
@@ -115,14 +364,84 @@ impl DownloadOpt<ProgressBar> {
                 path,
             )
             .await?;
-            return Ok(());
+        } else {
+            self.download_from_server(url, path).await?;
         }
 
+        if let Some(expected) = &self.sha256 {
+            super::verify_sha256(path, expected)
+                .with_context(|| format!("'{}' from '{url}' cannot be verified", self.name))?;
+        }
+        Ok(())
+    }
+
+    /// Download from a remote `Url` to `Path`, falling back to the mirrors on failure.
+    async fn download_from_server(&self, url: &Url, path: &Path) -> Result<()> {
+        ensure_network_allowed(url)?;
         if self.insecure {
             warn!("{}", t!("insecure_download"));
         }
 
-        let helper = DownloadHelper::new(&self.client()?, url, path, self.resume).await?;
+        let client = self.client()?;
+        let mut err = match self.download_with_retry(&client, url, path).await {
+            Ok(()) => return Ok(()),
+            Err(e) => e,
+        };
+        for mirror in &self.mirrors {
+            let mirrored = mirrored_url(url, mirror)?;
+            warn!("{err:#}");
+            info!("trying to download '{}' from mirror '{mirror}'", self.name);
+            match self.download_with_retry(&client, &mirrored, path).await {
+                Ok(()) => {
+                    info!("'{}' was downloaded from mirror '{mirror}'", self.name);
+                    return Ok(());
+                }
+                Err(e) => err = e,
+            }
+        }
+        Err(err)
+    }
+
+    /// Download from given `Url` to `Path`, retrying on failures that are worth retrying.
+    async fn download_with_retry(&self, client: &Client, url: &Url, path: &Path) -> Result<()> {
+        self.retry(|| self.download_(client, url, path)).await
+    }
+
+    /// Run `attempt_fn` until it succeeds, retrying on failures that are worth retrying,
+    /// up to `max_attempts` times in total.
+    async fn retry<F, Fut, R>(&self, mut attempt_fn: F) -> Result<R>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<R>>,
+    {
+        let mut attempt = 1;
+        loop {
+            let err = match attempt_fn().await {
+                Ok(res) => return Ok(res),
+                Err(e) => e,
+            };
+            if attempt >= self.max_attempts || !is_retryable(&err) {
+                return Err(err.context(format!(
+                    "failed to download '{}' after {attempt} attempt(s)",
+                    self.name
+                )));
+            }
+
+            let delay =
+                retry_after(&err).unwrap_or_else(|| backoff_delay(self.retry_base_delay, attempt));
+            warn!(
+                "failed to download '{}': {err:#}, retrying in {:.1}s",
+                self.name,
+                delay.as_secs_f64()
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Make a single attempt to download from given `Url` to `Path`.
+    async fn download_(&self, client: &Client, url: &Url, path: &Path) -> Result<()> {
+        let helper = DownloadHelper::new(client, url, path, self.resume).await?;
         let (mut resp, mut file, mut downloaded_bytes) =
             (helper.response, helper.file, helper.downloaded_bytes);
 
@@ -135,14 +454,47 @@ impl DownloadOpt<ProgressBar> {
             Style::Bytes(total_size),
         )
         .ok();
+        let mut limiter = self.rate_limit.filter(|r| *r > 0).map(RateLimiter::new);
 
-        while let Some(chunk) = resp.chunk().await? {
+        let incomplete = |received, source| IncompleteDownload {
+            name: self.name.clone(),
+            received,
+            expected: total_size,
+            source,
+        };
+        let mut written = 0;
+        loop {
+            // the connection might be closed before everything was received,
+            // which hyper reports as an error when reading the next chunk
+            let chunk = match resp.chunk().await {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => break,
+                Err(e) if written < total_size => return Err(incomplete(written, Some(e)).into()),
+                Err(e) => return Err(e.into()),
+            };
             file.write_all(&chunk).await?;
+            written += chunk.len() as u64;
 
             downloaded_bytes = min(downloaded_bytes + chunk.len() as u64, total_size);
             if let Some(indicator) = &maybe_indicator {
                 (self.handler.update)(indicator, Some(downloaded_bytes));
             }
+            // wait before reading the next chunk, so that the progress
+            // indicator shows the throttled speed as well.
+            if let Some(limiter) = &mut limiter {
+                let delay = limiter.consume(chunk.len() as u64);
+                if !delay.is_zero() {
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+
+        file.flush().await?;
+
+        // the connection might be closed before everything was received,
+        // which should be reported before the checksum verification that is more confusing
+        if written != total_size {
+            return Err(incomplete(written, None).into());
         }
 
         if let Some(indicator) = &maybe_indicator {
@@ -163,6 +515,211 @@ impl DownloadOpt<ProgressBar> {
     }
 }
 
+/// Rewrite `url` to point to the same file on a `mirror` server, by replacing
+/// the origin of `url` with the mirror's, and prefixing its path with the mirror's path.
+///
+/// For example, `https://example.com/dist/a.tar.gz` with mirror `https://mirror.com/rim/`
+/// gets rewritten to `https://mirror.com/rim/dist/a.tar.gz`.
+fn mirrored_url(url: &Url, mirror: &Url) -> Result<Url> {
+    if mirror.cannot_be_a_base() {
+        bail!("invalid mirror '{mirror}'");
+    }
+    let mut mirrored = mirror.clone();
+    let path = format!(
+        "{}/{}",
+        mirror.path().trim_end_matches('/'),
+        url.path().trim_start_matches('/')
+    );
+    mirrored.set_path(&path);
+    mirrored.set_query(url.query());
+    Ok(mirrored)
+}
+
+/// Extract the filename from the value of a `Content-Disposition` header,
+/// the encoded `filename*` parameter (RFC 6266) is preferred over `filename`.
+///
+/// Any directory component of the filename will be stripped.
+fn filename_from_content_disposition(value: &str) -> Option<String> {
+    let mut plain = None;
+    let mut encoded = None;
+    for param in value.split(';').skip(1) {
+        let Some((key, val)) = param.split_once('=') else {
+            continue;
+        };
+        let val = val.trim();
+        match key.trim().to_ascii_lowercase().as_str() {
+            "filename" => plain = Some(val.trim_matches('"').to_string()),
+            // in the form of `charset'language'percent-encoded-name`
+            "filename*" => {
+                encoded = val
+                    .splitn(3, '\'')
+                    .nth(2)
+                    .and_then(|name| percent_decode_str(name).decode_utf8().ok())
+                    .map(|name| name.into_owned());
+            }
+            _ => (),
+        }
+    }
+
+    plain_filename(&encoded.or(plain)?)
+}
+
+/// Get the name of the file that `url` points to, which is the percent-decoded
+/// last segment of its path, return `None` if it does not look like a file.
+pub fn url_filename(url: &Url) -> Option<String> {
+    let segment = url.path_segments()?.next_back()?;
+    plain_filename(&percent_decode_str(segment).decode_utf8().ok()?)
+}
+
+/// Strip any directory component of `name`, and make sure there's something left.
+fn plain_filename(name: &str) -> Option<String> {
+    let name = name.rsplit(['/', '\\']).next()?.trim();
+    (!name.is_empty() && name != "." && name != "..").then(|| name.to_string())
+}
+
+/// An error response from server when downloading a file.
+#[derive(Debug)]
+struct StatusError {
+    url: Url,
+    status: StatusCode,
+    /// The delay requested by server via `Retry-After` header, only the number
+    /// of seconds form is supported.
+    retry_after: Option<Duration>,
+}
+
+impl StatusError {
+    fn new(url: &Url, response: &reqwest::Response) -> Self {
+        let retry_after = response
+            .headers()
+            .get(header::RETRY_AFTER)
+            .and_then(|val| val.to_str().ok())
+            .and_then(|val| val.trim().parse().ok())
+            .map(Duration::from_secs);
+        Self {
+            url: url.clone(),
+            status: response.status(),
+            retry_after,
+        }
+    }
+}
+
+impl fmt::Display for StatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "server returns error when attempting download from '{}': {}",
+            self.url, self.status
+        )
+    }
+}
+
+impl std::error::Error for StatusError {}
+
+/// The response body is shorter (or longer) than the `Content-Length` told by server.
+#[derive(Debug)]
+struct IncompleteDownload {
+    name: String,
+    received: u64,
+    expected: u64,
+    /// The error of reading the body, if the connection was closed unexpectedly.
+    source: Option<reqwest::Error>,
+}
+
+impl fmt::Display for IncompleteDownload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "download of '{}' incomplete (got {} of {} bytes)",
+            self.name, self.received, self.expected
+        )
+    }
+}
+
+impl std::error::Error for IncompleteDownload {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source.as_ref().map(|e| e as _)
+    }
+}
+
+/// Check whether a failed download attempt is worth retrying, which is
+/// either a network error, a temporary server error or an incomplete download.
+fn is_retryable(err: &anyhow::Error) -> bool {
+    for cause in err.chain() {
+        if let Some(e) = cause.downcast_ref::<StatusError>() {
+            return e.status == StatusCode::REQUEST_TIMEOUT
+                || e.status == StatusCode::TOO_MANY_REQUESTS
+                || e.status.is_server_error();
+        }
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            return e.is_connect() || e.is_timeout() || e.is_request() || e.is_body();
+        }
+        if cause.is::<IncompleteDownload>() {
+            return true;
+        }
+    }
+    false
+}
+
+/// The delay requested by server before the next attempt, only honored for `429` responses.
+fn retry_after(err: &anyhow::Error) -> Option<Duration> {
+    err.chain()
+        .find_map(|cause| cause.downcast_ref::<StatusError>())
+        .filter(|e| e.status == StatusCode::TOO_MANY_REQUESTS)
+        .and_then(|e| e.retry_after)
+        .map(|delay| delay.min(MAX_RETRY_DELAY))
+}
+
+/// Compute the delay before the next attempt, which is doubled after each `attempt`,
+/// with a random jitter so that clients don't retry all at once.
+fn backoff_delay(base: Duration, attempt: u32) -> Duration {
+    let exp = base
+        .saturating_mul(2_u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(MAX_RETRY_DELAY);
+    // `RandomState` is randomly seeded, which is good enough for jittering
+    let random = std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish();
+    let jitter = (random % 1000) as f64 / 1000.0;
+    exp / 2 + exp.mul_f64(jitter / 2.0)
+}
+
+/// A token bucket that limits the download speed.
+///
+/// The bucket holds at most one second worth of tokens (bytes), and gets
+/// refilled continuously at the given rate.
+struct RateLimiter {
+    /// Bytes per second.
+    rate: u64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    fn new(rate: u64) -> Self {
+        Self {
+            rate,
+            tokens: rate as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Take `bytes` amount of tokens from the bucket, and return how long should
+    /// the caller wait before continuing, in order to stay under the rate limit.
+    fn consume(&mut self, bytes: u64) -> Duration {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.last_refill = now;
+
+        let rate = self.rate as f64;
+        self.tokens = (self.tokens + elapsed * rate).min(rate) - bytes as f64;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / rate)
+        }
+    }
+}
+
 struct DownloadHelper {
     response: reqwest::Response,
     file: fs::File,
@@ -202,7 +759,7 @@ impl DownloadHelper {
 
             return Self::new_without_resume(client, url, path).await;
         } else if !status.is_success() {
-            bail!("server returns error when attempting download from '{url}': {status}");
+            return Err(StatusError::new(url, &response).into());
         }
 
         Ok(Self {
@@ -236,3 +793,310 @@ async fn get_response_(
     })?;
     Ok(resp)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::sha256_hex;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn rate_limiter_delay() {
+        let mut limiter = RateLimiter::new(1000);
+        // the bucket starts full, allowing a one second burst
+        assert!(limiter.consume(1000).is_zero());
+        // running out of tokens, need to wait for them to be refilled
+        let delay = limiter.consume(500);
+        assert!(delay > Duration::from_millis(400) && delay <= Duration::from_millis(500));
+    }
+
+    #[test]
+    fn accept_language_header() {
+        let opt = DownloadOpt::new("test", true);
+        assert!(opt.default_headers().unwrap().is_empty());
+
+        let _guard = crate::utils::lock_locale("zh-CN");
+        let opt = DownloadOpt::new("test", true).localized(true);
+        let headers = opt.default_headers().unwrap();
+        assert_eq!(headers.get(header::ACCEPT_LANGUAGE).unwrap(), "zh-CN");
+    }
+
+    /// Start a minimal HTTP server that replies the given raw `responses` in order,
+    /// one per connection (the last one will be repeated), and return its url
+    /// along with a counter of the received requests.
+    fn mock_server(responses: &'static [&'static str]) -> (Url, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/file", listener.local_addr().unwrap());
+        let counter = Arc::new(AtomicUsize::new(0));
+        let counter_clone = Arc::clone(&counter);
+
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request = vec![];
+                let mut buf = [0; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    let len = stream.read(&mut buf).unwrap();
+                    if len == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..len]);
+                }
+                let count = counter_clone.fetch_add(1, Ordering::SeqCst);
+                let response = responses[count.min(responses.len() - 1)];
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        (url.parse().unwrap(), counter)
+    }
+
+    fn mock_download_opt() -> DownloadOpt<ProgressBar> {
+        let mut opt = DownloadOpt::new("test", true).with_proxy(Some(CrateProxy {
+            no_proxy: Some("127.0.0.1".into()),
+            ..Default::default()
+        }));
+        opt.retry_base_delay = Duration::from_millis(1);
+        opt
+    }
+
+    const UNAVAILABLE: &str =
+        "HTTP/1.1 503 Service Unavailable\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+    const NOT_FOUND: &str =
+        "HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+    const OK: &str = "HTTP/1.1 200 OK\r\ncontent-length: 5\r\nconnection: close\r\n\r\nhello";
+
+    #[test]
+    fn retry_until_succeed() {
+        let (url, counter) = mock_server(&[UNAVAILABLE, UNAVAILABLE, OK]);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+
+        mock_download_opt().blocking_download(&url, &path).unwrap();
+        assert_eq!(counter.load(Ordering::SeqCst), 3);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello");
+    }
+
+    #[test]
+    fn truncated_download() {
+        const TRUNCATED: &str =
+            "HTTP/1.1 200 OK\r\ncontent-length: 10\r\nconnection: close\r\n\r\nhello";
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+
+        let (url, counter) = mock_server(&[TRUNCATED]);
+        let err = mock_download_opt()
+            .with_max_attempts(2)
+            .blocking_download(&url, &path)
+            .unwrap_err();
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+        assert!(is_retryable(&err));
+        assert!(format!("{err:#}").contains("download of 'test' incomplete (got 5 of 10 bytes)"));
+
+        // succeed once the whole file was received
+        let (url, counter) = mock_server(&[TRUNCATED, OK]);
+        mock_download_opt().blocking_download(&url, &path).unwrap();
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello");
+
+        let err = anyhow::Error::from(IncompleteDownload {
+            name: "test".into(),
+            received: 5,
+            expected: 10,
+            source: None,
+        });
+        assert!(is_retryable(&err));
+        assert_eq!(
+            err.to_string(),
+            "download of 'test' incomplete (got 5 of 10 bytes)"
+        );
+    }
+
+    #[test]
+    fn network_access_in_offline_mode() {
+        let remote = Url::parse("https://example.com/manifest.toml").unwrap();
+        let local = Url::parse("file:///path/to/manifest.toml").unwrap();
+        let _guard = crate::utils::lock_locale("en-US");
+
+        assert!(check_network_access(&remote, false).is_ok());
+        assert!(check_network_access(&local, true).is_ok());
+        let err = check_network_access(&remote, true).unwrap_err();
+        assert!(err.to_string().contains("offline mode"));
+    }
+
+    #[tokio::test]
+    async fn conditional_read() {
+        const OK_WITH_ETAG: &str =
+            "HTTP/1.1 200 OK\r\netag: \"v1\"\r\ncontent-length: 5\r\nconnection: close\r\n\r\nhello";
+        const NOT_MODIFIED: &str =
+            "HTTP/1.1 304 Not Modified\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+        let (url, counter) = mock_server(&[OK_WITH_ETAG, NOT_MODIFIED]);
+
+        let fetched = mock_download_opt()
+            .read_if_modified(&url, &CacheValidators::default())
+            .await
+            .unwrap();
+        let ConditionalText::Modified(content, validators) = fetched else {
+            panic!("expecting content to be fetched");
+        };
+        assert_eq!(content, "hello");
+        assert_eq!(validators.etag.as_deref(), Some("\"v1\""));
+        assert!(validators.last_modified.is_none());
+
+        let fetched = mock_download_opt()
+            .read_if_modified(&url, &validators)
+            .await
+            .unwrap();
+        assert_eq!(fetched, ConditionalText::NotModified);
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+
+        // temporary server errors are retried
+        let (url, counter) = mock_server(&[UNAVAILABLE, OK_WITH_ETAG]);
+        let fetched = mock_download_opt()
+            .read_if_modified(&url, &CacheValidators::default())
+            .await
+            .unwrap();
+        assert!(matches!(fetched, ConditionalText::Modified(content, _) if content == "hello"));
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn give_up_retrying() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+
+        // not retryable
+        let (url, counter) = mock_server(&[NOT_FOUND, OK]);
+        let err = mock_download_opt()
+            .blocking_download(&url, &path)
+            .unwrap_err();
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+        assert!(err.to_string().contains("after 1 attempt(s)"));
+
+        // running out of attempts
+        let (url, counter) = mock_server(&[UNAVAILABLE, OK]);
+        let err = mock_download_opt()
+            .with_max_attempts(1)
+            .blocking_download(&url, &path)
+            .unwrap_err();
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+        assert!(err.to_string().contains("after 1 attempt(s)"));
+    }
+
+    #[test]
+    fn exponential_backoff() {
+        let base = Duration::from_secs(1);
+        for attempt in 1..=4 {
+            let exp = base * 2_u32.pow(attempt - 1);
+            let delay = backoff_delay(base, attempt);
+            assert!(delay >= exp / 2 && delay <= exp, "{delay:?}");
+        }
+        assert!(backoff_delay(base, 100) <= MAX_RETRY_DELAY);
+    }
+
+    #[test]
+    fn parse_content_disposition() {
+        assert_eq!(
+            filename_from_content_disposition("attachment; filename=\"foo.tar.gz\"").as_deref(),
+            Some("foo.tar.gz")
+        );
+        assert_eq!(
+            filename_from_content_disposition(
+                "attachment; filename=fallback.zip; filename*=UTF-8''%E4%B8%AD%20a.zip"
+            )
+            .as_deref(),
+            Some("中 a.zip")
+        );
+        assert_eq!(
+            filename_from_content_disposition("attachment; filename=\"../../evil.exe\"").as_deref(),
+            Some("evil.exe")
+        );
+        assert_eq!(filename_from_content_disposition("inline"), None);
+        assert_eq!(
+            filename_from_content_disposition("attachment; filename=\"\""),
+            None
+        );
+    }
+
+    #[test]
+    fn filename_of_url() {
+        let filename = |url: &str| url_filename(&Url::parse(url).unwrap());
+        assert_eq!(
+            filename("https://example.com/dl/tool-1.0.tar.gz?v=1").as_deref(),
+            Some("tool-1.0.tar.gz")
+        );
+        assert_eq!(
+            filename("https://example.com/dl/my%20tool%2B.zip").as_deref(),
+            Some("my tool+.zip")
+        );
+        assert_eq!(
+            filename("https://example.com/dl/..%2Fevil.exe").as_deref(),
+            Some("evil.exe")
+        );
+        assert_eq!(filename("https://example.com/dl/"), None);
+    }
+
+    #[test]
+    fn rewrite_url_with_mirror() {
+        let url = Url::parse("https://example.com/dist/a.tar.gz?v=1").unwrap();
+        let mirror = Url::parse("http://mirror.com:8080").unwrap();
+        assert_eq!(
+            mirrored_url(&url, &mirror).unwrap().as_str(),
+            "http://mirror.com:8080/dist/a.tar.gz?v=1"
+        );
+        let mirror = Url::parse("https://mirror.com/rim/").unwrap();
+        assert_eq!(
+            mirrored_url(&url, &mirror).unwrap().as_str(),
+            "https://mirror.com/rim/dist/a.tar.gz?v=1"
+        );
+    }
+
+    #[test]
+    fn fallback_to_mirror() {
+        let (url, primary) = mock_server(&[NOT_FOUND]);
+        let (mirror, fallback) = mock_server(&[OK]);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file");
+
+        mock_download_opt()
+            .with_mirrors(vec![mirror.join("/").unwrap()])
+            .with_sha256(Some(sha256_hex("hello")))
+            .blocking_download(&url, &path)
+            .unwrap();
+        assert_eq!(primary.load(Ordering::SeqCst), 1);
+        assert_eq!(fallback.load(Ordering::SeqCst), 1);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello");
+
+        // checksum mismatch
+        let (url, _) = mock_server(&[OK]);
+        let err = mock_download_opt()
+            .with_sha256(Some(sha256_hex("world")))
+            .blocking_download(&url, &path)
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("checksum mismatch"));
+    }
+
+    #[test]
+    fn verify_local_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        std::fs::write(&src, "hello").unwrap();
+        let url = Url::from_file_path(&src).unwrap();
+        let path = dir.path().join("file");
+
+        mock_download_opt()
+            .with_sha256(Some(sha256_hex("hello")))
+            .blocking_download(&url, &path)
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "hello");
+
+        let err = mock_download_opt()
+            .with_sha256(Some(sha256_hex("world")))
+            .blocking_download(&url, &path)
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("checksum mismatch"));
+    }
+}
@@ -1,10 +1,13 @@
 use anyhow::{anyhow, bail, Result};
+use bzip2::read::BzDecoder;
 use flate2::read::GzDecoder;
+use lz4_flex::frame::FrameDecoder;
 use sevenz_rust::{Password, SevenZReader};
 use std::ffi::OsStr;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{Read, Write};
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, SyncSender};
 use xz2::read::XzDecoder;
 use zip::ZipArchive;
 
@@ -18,27 +21,45 @@ enum ExtractableKind {
     /// `7-zip` compressed files, ended with `.7z`
     SevenZ(SevenZReader<File>),
     Gz(tar::Archive<GzDecoder<File>>),
-    Xz(tar::Archive<XzDecoder<File>>),
+    Xz(tar::Archive<XzReader>),
+    Bz2(tar::Archive<BzDecoder<File>>),
+    /// Tarballs compressed as `lz4` frames, ended with `.tar.lz4`
+    Lz4(tar::Archive<FrameDecoder<File>>),
+    /// A single file compressed as `lz4` frames, ended with `.lz4`
+    Lz4File(FrameDecoder<File>),
     Zip(ZipArchive<File>),
 }
 
+/// File extensions of the supported archive formats.
+const SUPPORTED_EXTENSIONS: &[&str] = &["7z", "zip", "gz", "xz", "bz2", "lz4", "crate"];
+
 pub struct Extractable<'a> {
     path: &'a Path,
     kind: ExtractableKind,
     quiet: bool,
+    /// How many levels of nested archives to extract, check [`extract_recursive`](Extractable::extract_recursive).
+    max_depth: usize,
+    /// How many leading components to drop from the path of every entry, just like
+    /// `tar --strip-components`, entries with no more than that many components are skipped.
+    strip_components: usize,
 }
 
 impl<'a> Extractable<'a> {
     pub fn is_supported(path: &'a Path) -> bool {
-        let Ok(extensions) = file_extension(path) else {
-            return false;
-        };
-        matches!(extensions, "7z" | "zip" | "gz" | "xz" | "crate")
+        match file_extension(path) {
+            Ok(ext) => SUPPORTED_EXTENSIONS.contains(&ext),
+            // files without extension might still be archives, such as the ones
+            // downloaded from an URL that does not tell the filename.
+            Err(_) => detect_format_from_content(path).is_some(),
+        }
     }
 
     pub fn load(path: &'a Path, custom_kind: Option<&str>) -> Result<Self> {
         let ext = if let Some(custom) = custom_kind {
             custom
+        } else if let Some(detected) = detect_format_from_content(path) {
+            // trust the content over the extension, in case the file was misnamed
+            detected
         } else {
             file_extension(path)?
         };
@@ -71,9 +92,29 @@ impl<'a> Extractable<'a> {
                     "{}",
                     t!("loading_archive_info", kind = ext, path = path.display())
                 );
-                let tar_xz = XzDecoder::new(File::open(path)?);
+                let tar_xz = XzReader::open(path)?;
                 ExtractableKind::Xz(tar::Archive::new(tar_xz))
             }
+            "bz2" => {
+                info!(
+                    "{}",
+                    t!("loading_archive_info", kind = ext, path = path.display())
+                );
+                let tar_bz2 = BzDecoder::new(File::open(path)?);
+                ExtractableKind::Bz2(tar::Archive::new(tar_bz2))
+            }
+            "lz4" => {
+                info!(
+                    "{}",
+                    t!("loading_archive_info", kind = ext, path = path.display())
+                );
+                let decoder = FrameDecoder::new(File::open(path)?);
+                if is_lz4_tarball(path) {
+                    ExtractableKind::Lz4(tar::Archive::new(decoder))
+                } else {
+                    ExtractableKind::Lz4File(decoder)
+                }
+            }
             _ => bail!("'{ext}' is not a supported extractable file format"),
         };
 
@@ -81,10 +122,16 @@ impl<'a> Extractable<'a> {
             path,
             kind,
             quiet: false,
+            max_depth: 0,
+            strip_components: 0,
         })
     }
 
     setter!(quiet(self.quiet, bool));
+    setter!(max_depth(self.max_depth, usize));
+    // NB: stripping only applies to the top-level archive, not the nested ones,
+    // and it does not affect single compressed files such as `foo.lz4`.
+    setter!(strip_components(self.strip_components, usize));
 
     /// Extract current file into a specific directory.
     ///
@@ -94,6 +141,7 @@ impl<'a> Extractable<'a> {
             file_path: self.path,
             output_dir: root,
             indicator: CliProgress::new(self.quiet),
+            strip_components: self.strip_components,
         };
 
         match &mut self.kind {
@@ -101,10 +149,52 @@ impl<'a> Extractable<'a> {
             ExtractableKind::SevenZ(archive) => helper.extract_7z(archive),
             ExtractableKind::Gz(archive) => helper.extract_tar(archive),
             ExtractableKind::Xz(archive) => helper.extract_tar(archive),
+            ExtractableKind::Bz2(archive) => helper.extract_tar(archive),
+            ExtractableKind::Lz4(archive) => helper.extract_tar(archive),
+            ExtractableKind::Lz4File(decoder) => helper.extract_single_file(decoder),
+        }
+    }
+
+    /// Extract current file into a specific directory like [`extract_to`](Extractable::extract_to),
+    /// then if the only thing left behind is another supported archive, such as a `.tar.gz`
+    /// inside of a `.zip`, extract that archive in place as well.
+    ///
+    /// This repeats for at most [`max_depth`](Extractable::max_depth) levels of nested archives,
+    /// which is `0` by default, meaning nested archives will not be extracted at all.
+    pub fn extract_recursive(&mut self, root: &Path) -> Result<()> {
+        self.extract_to(root)?;
+
+        for _ in 0..self.max_depth {
+            let Some(nested) = find_solo_archive(root)? else {
+                return Ok(());
+            };
+            let Some(dest) = nested.parent().map(Path::to_path_buf) else {
+                return Ok(());
+            };
+            // move the archive out of the way, so that its content can be extracted in place
+            let tmp = dest.join(".rim-nested-archive");
+            fs::rename(&nested, &tmp)?;
+            let kind = detect_format_from_content(&tmp).or(file_extension(&nested).ok());
+            Extractable::load(&tmp, kind)?
+                .quiet(self.quiet)
+                .extract_to(&dest)?;
+            fs::remove_file(&tmp)?;
+        }
+
+        if self.max_depth > 0 && find_solo_archive(root)?.is_some() {
+            warn!(
+                "{}",
+                t!(
+                    "nested_archive_too_deep",
+                    path = self.path.display(),
+                    max = self.max_depth
+                )
+            );
         }
+        Ok(())
     }
 
-    /// Extract file into a specific root like [`extract_to`](Extractable::extract_to),
+    /// Extract file into a specific root like [`extract_recursive`](Extractable::extract_recursive),
     /// then look for **solo** nested directory and return the last one.
     ///
     /// This works similar to skipping common prefixes, except this does not
@@ -154,12 +244,31 @@ impl<'a> Extractable<'a> {
         }
 
         // first we need to extract the tarball
-        self.extract_to(root)?;
+        self.extract_recursive(root)?;
         // then find the last solo dir recursively
         inner_(root, stop)
     }
 }
 
+/// Follow the solo directories under `root` and return the path of the file at the end of it
+/// if that file is a nested archive.
+///
+/// Note that `.crate` files are not considered as nested archives, because those are meant
+/// to be installed as they are.
+fn find_solo_archive(root: &Path) -> Result<Option<PathBuf>> {
+    let mut path = root.to_path_buf();
+    while path.is_dir() {
+        match walk_dir(&path, false)?.as_slice() {
+            [entry] => path = entry.clone(),
+            _ => return Ok(None),
+        }
+    }
+    let is_archive = path.is_file()
+        && !file_extension(&path).is_ok_and(|ext| ext == "crate")
+        && Extractable::is_supported(&path);
+    Ok(is_archive.then_some(path))
+}
+
 fn file_extension(path: &Path) -> Result<&str> {
     path.extension()
         .ok_or_else(|| {
@@ -178,6 +287,52 @@ fn file_extension(path: &Path) -> Result<&str> {
         })
 }
 
+/// Guess the archive format of a file by looking at its magic bytes,
+/// and return the file extension of that format.
+fn detect_format_from_content(path: &Path) -> Option<&'static str> {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"7z\xBC\xAF\x27\x1C", "7z"),
+        (b"PK\x03\x04", "zip"),
+        (b"\x1F\x8B", "gz"),
+        (b"\xFD7zXZ\x00", "xz"),
+        (b"BZh", "bz2"),
+        (b"\x04\x22\x4D\x18", "lz4"),
+    ];
+
+    let mut header = [0_u8; 6];
+    let mut file = File::open(path).ok()?;
+    let len = file.read(&mut header).ok()?;
+    SIGNATURES
+        .iter()
+        .find_map(|(magic, ext)| header[..len].starts_with(magic).then_some(*ext))
+}
+
+/// Check whether an `lz4` compressed file contains a tarball, either by its name
+/// (such as `foo.tar.lz4`) or by the `ustar` magic in its decompressed header.
+fn is_lz4_tarball(path: &Path) -> bool {
+    let stem_is_tar = path
+        .file_stem()
+        .map(Path::new)
+        .and_then(Path::extension)
+        .is_some_and(|ext| ext == "tar");
+    if stem_is_tar {
+        return true;
+    }
+
+    let Ok(file) = File::open(path) else {
+        return false;
+    };
+    let mut header = Vec::with_capacity(262);
+    if FrameDecoder::new(file)
+        .take(262)
+        .read_to_end(&mut header)
+        .is_err()
+    {
+        return false;
+    }
+    header.get(257..262) == Some(&b"ustar"[..])
+}
+
 fn filename_matches_keyword<S: AsRef<OsStr>>(path: &Path, keyword: S) -> bool {
     if let Some(name) = path.file_name() {
         name == keyword.as_ref()
@@ -191,6 +346,7 @@ struct ExtractHelper<'a, T: Sized> {
     file_path: &'a Path,
     output_dir: &'a Path,
     indicator: CliProgress<T>,
+    strip_components: usize,
 }
 
 impl<T: Sized> ExtractHelper<'_, T> {
@@ -217,10 +373,10 @@ impl<T: Sized> ExtractHelper<'_, T> {
 
         for i in 0..zip_len {
             let mut zip_file = archive.by_index(i)?;
-            let Some(out_path) = zip_file
-                .enclosed_name()
-                .map(|path| self.output_dir.join(path))
-            else {
+            let Some(entry_path) = zip_file.enclosed_name() else {
+                bail!(self.escaping_entry_msg(Path::new(zip_file.name())));
+            };
+            let Some(out_path) = self.stripped_out_path(&entry_path) else {
                 continue;
             };
 
@@ -260,8 +416,17 @@ impl<T: Sized> ExtractHelper<'_, T> {
 
         archive.for_each_entries(|entry, reader| {
             let mut buf = [0_u8; 1024];
-            let entry_path = PathBuf::from(entry.name());
-            let out_path = self.output_dir.join(&entry_path);
+            let entry_path = Path::new(entry.name());
+            let Some(enclosed) = enclosed_path(entry_path) else {
+                return Err(sevenz_rust::Error::other(
+                    self.escaping_entry_msg(entry_path),
+                ));
+            };
+            let Some(out_path) = self.stripped_out_path(&enclosed) else {
+                // the content still needs to be read to get to the next entry
+                std::io::copy(reader, &mut std::io::sink())?;
+                return Ok(true);
+            };
 
             if entry.is_directory() {
                 ensure_dir(&out_path).map_err(|_| {
@@ -308,10 +473,605 @@ impl<T: Sized> ExtractHelper<'_, T> {
             auto_tick_duration: Some(std::time::Duration::from_millis(100)),
         })?;
 
-        archive.unpack(self.output_dir)?;
+        ensure_dir(self.output_dir)?;
+        let root = fs::canonicalize(self.output_dir)?;
+        // Unpack each entry manually instead of using `archive.unpack`,
+        // so that we can refuse the ones trying to escape the output directory.
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let entry_path = entry.path()?.to_path_buf();
+            let Some(enclosed) = enclosed_path(&entry_path) else {
+                bail!(self.escaping_entry_msg(&entry_path));
+            };
+            if self.strip_components == 0 {
+                entry.unpack_in(self.output_dir)?;
+            } else if let Some(out_path) = self.stripped_out_path(&enclosed) {
+                self.unpack_stripped(&mut entry, &entry_path, &out_path, &root)?;
+            }
+        }
 
         // Stop progress bar's progress
         self.end_progress_bar(&bar);
         Ok(())
     }
+
+    /// Unpack a tar `entry` to `out_path`, which is its path with leading components stripped.
+    ///
+    /// `Entry::unpack_in` cannot be used after stripping, so this does the same checks as it,
+    /// refusing to write through an extracted symlink that points outside of the output
+    /// directory (canonicalized as `root`), and also refuses links targeting outside of it.
+    fn unpack_stripped<R: Read>(
+        &self,
+        entry: &mut tar::Entry<'_, R>,
+        entry_path: &Path,
+        out_path: &Path,
+        root: &Path,
+    ) -> Result<()> {
+        let parent = out_path.parent().unwrap_or(self.output_dir);
+        // Check the closest existing ancestor before creating any missing directories,
+        // otherwise they could be created through a symlink pointing outside already.
+        let existing = parent
+            .ancestors()
+            .find(|p| p.symlink_metadata().is_ok())
+            .unwrap_or(self.output_dir);
+        if !fs::canonicalize(existing)?.starts_with(root) {
+            bail!(self.escaping_entry_msg(entry_path));
+        }
+        ensure_parent_dir(out_path)?;
+
+        let entry_type = entry.header().entry_type();
+        if !entry_type.is_symlink() && !entry_type.is_hard_link() {
+            entry.unpack(out_path)?;
+            return Ok(());
+        }
+
+        let Some(target) = entry.link_name()?.map(|t| t.to_path_buf()) else {
+            bail!(
+                "link entry '{}' from '{}' has no target",
+                entry_path.display(),
+                self.file_path.display()
+            );
+        };
+        if entry_type.is_hard_link() {
+            // hard link targets are paths inside of the archive, which need stripping too
+            let Some(src) = enclosed_path(&target).and_then(|t| self.stripped_out_path(&t)) else {
+                bail!(self.escaping_entry_msg(entry_path));
+            };
+            if !fs::canonicalize(&src)?.starts_with(root) {
+                bail!(self.escaping_entry_msg(entry_path));
+            }
+            fs::hard_link(&src, out_path)?;
+        } else {
+            // symlink targets are relative to the directory containing the link
+            let rel_parent = parent.strip_prefix(self.output_dir).unwrap_or(parent);
+            if enclosed_path(&rel_parent.join(&target)).is_none() {
+                bail!(self.escaping_entry_msg(entry_path));
+            }
+            entry.unpack(out_path)?;
+        }
+        Ok(())
+    }
+
+    /// Get the output path of an (enclosed) entry after stripping leading components
+    /// of it, return `None` if there's nothing left after stripping.
+    fn stripped_out_path(&self, entry_path: &Path) -> Option<PathBuf> {
+        let stripped = entry_path
+            .components()
+            .skip(self.strip_components)
+            .collect::<PathBuf>();
+        (!stripped.as_os_str().is_empty()).then(|| self.output_dir.join(stripped))
+    }
+
+    fn escaping_entry_msg(&self, entry_path: &Path) -> String {
+        format!(
+            "refusing to extract entry '{}' from '{}', because it points outside of the output directory",
+            entry_path.display(),
+            self.file_path.display()
+        )
+    }
+
+    /// Decompress a single compressed file (not an archive) into the output directory,
+    /// the decompressed file is named after the stem of the compressed one,
+    /// i.e. `foo.lz4` will be decompressed as `foo`.
+    fn extract_single_file<R: Read>(&self, reader: &mut R) -> Result<()> {
+        let Some(name) = self.file_path.file_stem() else {
+            bail!(
+                "unable to determine the decompressed file name of '{}'",
+                self.file_path.display()
+            );
+        };
+        let out_path = self.output_dir.join(name);
+
+        let bar = self.start_progress_bar(Style::Spinner {
+            auto_tick_duration: Some(std::time::Duration::from_millis(100)),
+        })?;
+
+        ensure_dir(self.output_dir)?;
+        let mut out_file = File::create(&out_path)?;
+        std::io::copy(reader, &mut out_file)?;
+
+        self.end_progress_bar(&bar);
+        Ok(())
+    }
+}
+
+/// The size of decompressed chunks sent from the background decoding thread of [`XzReader`].
+const XZ_CHUNK_SIZE: usize = 1024 * 1024;
+/// How many decompressed chunks could be buffered before the decoding thread waits.
+const XZ_BUFFERED_CHUNKS: usize = 8;
+
+/// Reader of `.xz` compressed data, which decompresses on a background thread when possible.
+///
+/// Large `.tar.xz` archives, such as the Rust toolchain components, are slow to extract
+/// because decompressing them and writing the unpacked files to disk happen one after
+/// another on a single thread. With this reader, the decompression keeps running on its
+/// own thread while the entries are being unpacked, which is usually the case for
+/// archives with a lot of files. The output is exactly the same as the single-threaded one,
+/// regardless of how many blocks (or concatenated streams) the archive has.
+///
+/// NB: Decoding the blocks of a multi-block `.xz` file in parallel requires the multi-threaded
+/// decoder of liblzma 5.4+, but the bundled liblzma of `xz2` (which is shared with `zip`,
+/// so it cannot be replaced by another binding) is older than that.
+///
+/// It falls back to single-threaded decompression if there is only one CPU available,
+/// or the background thread cannot be spawned.
+enum XzReader {
+    Single(XzDecoder<File>),
+    Threaded(ThreadedXzDecoder),
+}
+
+impl XzReader {
+    fn open(path: &Path) -> Result<Self> {
+        let multi_cpu = std::thread::available_parallelism().is_ok_and(|n| n.get() > 1);
+        if multi_cpu {
+            match ThreadedXzDecoder::spawn(File::open(path)?) {
+                Ok(decoder) => return Ok(Self::Threaded(decoder)),
+                Err(e) => debug!("unable to decompress xz on another thread, falling back: {e}"),
+            }
+        }
+        Ok(Self::Single(XzDecoder::new(File::open(path)?)))
+    }
+}
+
+impl Read for XzReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Single(decoder) => decoder.read(buf),
+            Self::Threaded(decoder) => decoder.read(buf),
+        }
+    }
+}
+
+/// Receives the chunks decompressed by a background thread, check [`XzReader`].
+struct ThreadedXzDecoder {
+    rx: Receiver<std::io::Result<Vec<u8>>>,
+    chunk: Vec<u8>,
+    pos: usize,
+}
+
+impl ThreadedXzDecoder {
+    /// Start decompressing `file` on a new thread.
+    fn spawn(file: File) -> std::io::Result<Self> {
+        let (tx, rx) = mpsc::sync_channel(XZ_BUFFERED_CHUNKS);
+        std::thread::Builder::new()
+            .name("xz-decoder".into())
+            .spawn(move || decode_xz_chunks(XzDecoder::new(file), &tx))?;
+        Ok(Self {
+            rx,
+            chunk: vec![],
+            pos: 0,
+        })
+    }
+}
+
+/// Read everything from `decoder` and send it in chunks, until it reaches the end,
+/// an error occurs, or the receiver has gone.
+fn decode_xz_chunks<R: Read>(mut decoder: R, tx: &SyncSender<std::io::Result<Vec<u8>>>) {
+    loop {
+        let mut chunk = vec![0; XZ_CHUNK_SIZE];
+        let mut filled = 0;
+        let res = loop {
+            match decoder.read(&mut chunk[filled..]) {
+                Ok(0) => break Ok(()),
+                Ok(n) => {
+                    filled += n;
+                    if filled == chunk.len() {
+                        break Ok(());
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => (),
+                Err(e) => break Err(e),
+            }
+        };
+        chunk.truncate(filled);
+        let is_end = filled < XZ_CHUNK_SIZE;
+        if filled > 0 && tx.send(Ok(chunk)).is_err() {
+            return;
+        }
+        if let Err(e) = res {
+            let _ = tx.send(Err(e));
+            return;
+        }
+        if is_end {
+            return;
+        }
+    }
+}
+
+impl Read for ThreadedXzDecoder {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.pos == self.chunk.len() {
+            match self.rx.recv() {
+                Ok(chunk) => {
+                    self.chunk = chunk?;
+                    self.pos = 0;
+                }
+                // the sender is dropped once everything has been decompressed
+                Err(_) => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.chunk.len() - self.pos);
+        buf[..n].copy_from_slice(&self.chunk[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// Normalize the path of an archive entry, return `None` if it is an absolute path,
+/// or it contains `..` components that escape the root of the archive.
+fn enclosed_path(path: &Path) -> Option<PathBuf> {
+    let mut result = PathBuf::new();
+    for comp in path.components() {
+        match comp {
+            Component::Prefix(_) | Component::RootDir => return None,
+            Component::ParentDir => {
+                if !result.pop() {
+                    return None;
+                }
+            }
+            Component::CurDir => (),
+            Component::Normal(name) => result.push(name),
+        }
+    }
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_archive_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let cases: &[(&[u8], Option<&str>)] = &[
+            (b"BZh91AY&SY", Some("bz2")),
+            (b"\x1F\x8B\x08\x00", Some("gz")),
+            (b"\xFD7zXZ\x00\x00", Some("xz")),
+            (b"PK\x03\x04\x14\x00", Some("zip")),
+            (b"\x04\x22\x4D\x18\x64\x40", Some("lz4")),
+            (b"MZ\x90\x00", None),
+            (b"", None),
+        ];
+        for (idx, (content, expected)) in cases.iter().enumerate() {
+            let path = dir.path().join(format!("file_{idx}"));
+            std::fs::write(&path, content).unwrap();
+            assert_eq!(detect_format_from_content(&path), *expected);
+        }
+    }
+
+    #[test]
+    fn extract_lz4() {
+        use lz4_flex::frame::FrameEncoder;
+
+        let dir = tempfile::tempdir().unwrap();
+
+        // a bare `.lz4` file is decompressed as a file named after its stem
+        let bare = dir.path().join("hello.txt.lz4");
+        let mut encoder = FrameEncoder::new(File::create(&bare).unwrap());
+        encoder.write_all(b"hello lz4").unwrap();
+        encoder.finish().unwrap();
+
+        let out = dir.path().join("bare");
+        Extractable::load(&bare, None)
+            .unwrap()
+            .quiet(true)
+            .extract_to(&out)
+            .unwrap();
+        let content = std::fs::read_to_string(out.join("hello.txt")).unwrap();
+        assert_eq!(content, "hello lz4");
+
+        // a `.tar.lz4` file is unpacked as a tarball
+        let tarball = dir.path().join("archive.tar.lz4");
+        let encoder = FrameEncoder::new(File::create(&tarball).unwrap());
+        let mut builder = tar::Builder::new(encoder);
+        builder
+            .append_path_with_name(out.join("hello.txt"), "a/hello.txt")
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let out = dir.path().join("tarball");
+        Extractable::load(&tarball, None)
+            .unwrap()
+            .quiet(true)
+            .extract_to(&out)
+            .unwrap();
+        assert!(out.join("a").join("hello.txt").is_file());
+    }
+
+    #[test]
+    fn extract_xz_on_another_thread() {
+        use xz2::write::XzEncoder;
+
+        let dir = tempfile::tempdir().unwrap();
+        // big enough to be sent from the decoding thread in multiple chunks
+        let content = (0..XZ_CHUNK_SIZE * 3 + 7)
+            .map(|i| (i * 7 % 251) as u8)
+            .collect::<Vec<_>>();
+        let src = dir.path().join("big.bin");
+        fs::write(&src, &content).unwrap();
+        let tarball = dir.path().join("big.tar.xz");
+        let encoder = XzEncoder::new(File::create(&tarball).unwrap(), 6);
+        let mut builder = tar::Builder::new(encoder);
+        builder.append_path_with_name(&src, "a/big.bin").unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        // same output as the single-threaded decoder
+        let mut threaded = vec![];
+        ThreadedXzDecoder::spawn(File::open(&tarball).unwrap())
+            .unwrap()
+            .read_to_end(&mut threaded)
+            .unwrap();
+        let mut single = vec![];
+        XzDecoder::new(File::open(&tarball).unwrap())
+            .read_to_end(&mut single)
+            .unwrap();
+        assert_eq!(threaded, single);
+
+        let out = dir.path().join("out");
+        Extractable::load(&tarball, None)
+            .unwrap()
+            .quiet(true)
+            .extract_to(&out)
+            .unwrap();
+        assert_eq!(fs::read(out.join("a").join("big.bin")).unwrap(), content);
+    }
+
+    #[test]
+    fn extract_nested_archive() {
+        use zip::write::SimpleFileOptions;
+
+        let dir = tempfile::tempdir().unwrap();
+        let tarball = dir.path().join("inner.tar.gz");
+        let encoder = flate2::write::GzEncoder::new(
+            File::create(&tarball).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut builder = tar::Builder::new(encoder);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(5);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "tool/bin/hello", &b"hello"[..])
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let zipped = dir.path().join("outer.zip");
+        let mut writer = zip::ZipWriter::new(File::create(&zipped).unwrap());
+        writer
+            .start_file("inner.tar.gz", SimpleFileOptions::default())
+            .unwrap();
+        writer.write_all(&fs::read(&tarball).unwrap()).unwrap();
+        writer.finish().unwrap();
+
+        // nested archives are left as they are by default
+        let out = dir.path().join("default");
+        Extractable::load(&zipped, None)
+            .unwrap()
+            .quiet(true)
+            .extract_recursive(&out)
+            .unwrap();
+        assert!(out.join("inner.tar.gz").is_file());
+
+        let out = dir.path().join("nested");
+        let bin_dir = Extractable::load(&zipped, None)
+            .unwrap()
+            .quiet(true)
+            .max_depth(2)
+            .extract_then_skip_solo_dir(&out, Some("bin"))
+            .unwrap();
+        assert_eq!(bin_dir, out.join("tool"));
+        assert!(!out.join("inner.tar.gz").exists());
+        assert_eq!(
+            fs::read_to_string(bin_dir.join("bin").join("hello")).unwrap(),
+            "hello"
+        );
+    }
+
+    #[test]
+    fn extract_with_strip_components() {
+        use zip::write::SimpleFileOptions;
+
+        const ENTRIES: &[&str] = &[
+            "pkg-1.0/inner/bin/hello",
+            "pkg-1.0/inner/README",
+            "pkg-1.0/x",
+        ];
+        let dir = tempfile::tempdir().unwrap();
+
+        let tarball = dir.path().join("pkg.tar.gz");
+        let encoder = flate2::write::GzEncoder::new(
+            File::create(&tarball).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut builder = tar::Builder::new(encoder);
+        for entry in ENTRIES {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(5);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, entry, &b"hello"[..])
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let zipped = dir.path().join("pkg.zip");
+        let mut writer = zip::ZipWriter::new(File::create(&zipped).unwrap());
+        for entry in ENTRIES {
+            writer
+                .start_file(*entry, SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(b"hello").unwrap();
+        }
+        writer.finish().unwrap();
+
+        for archive in [tarball, zipped] {
+            let out = dir.path().join("out");
+            Extractable::load(&archive, None)
+                .unwrap()
+                .quiet(true)
+                .strip_components(2)
+                .extract_to(&out)
+                .unwrap();
+            assert!(out.join("bin").join("hello").is_file());
+            assert!(out.join("README").is_file());
+            // entries that are not deep enough are skipped
+            assert!(!out.join("x").exists());
+            assert!(!out.join("pkg-1.0").exists());
+            fs::remove_dir_all(&out).unwrap();
+        }
+    }
+
+    #[test]
+    fn refuse_escaping_entries() {
+        assert_eq!(
+            enclosed_path(Path::new("./a/../b/c")),
+            Some(PathBuf::from("b/c"))
+        );
+        assert_eq!(enclosed_path(Path::new("a/../../evil")), None);
+        assert_eq!(enclosed_path(Path::new("../evil")), None);
+        assert_eq!(enclosed_path(Path::new("/etc/evil")), None);
+
+        let dir = tempfile::tempdir().unwrap();
+        let tarball = dir.path().join("evil.tar.gz");
+        let encoder = flate2::write::GzEncoder::new(
+            File::create(&tarball).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut builder = tar::Builder::new(encoder);
+        // `tar::Builder` refuses to write `..` paths, so write the header manually.
+        let mut header = tar::Header::new_gnu();
+        header.as_gnu_mut().unwrap().name[..7].copy_from_slice(b"../evil");
+        header.set_size(4);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append(&header, &b"evil"[..]).unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let out = dir.path().join("out");
+        let res = Extractable::load(&tarball, None)
+            .unwrap()
+            .quiet(true)
+            .extract_to(&out);
+        assert!(res.is_err());
+        assert!(!dir.path().join("evil").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn refuse_escaping_links_when_stripping() {
+        use tar::EntryType::{Link, Regular, Symlink};
+
+        let dir = tempfile::tempdir().unwrap();
+        let outside = dir.path().join("outside");
+        fs::create_dir(&outside).unwrap();
+        fs::write(outside.join("secret"), "secret").unwrap();
+
+        // entries are `(type, path, link target)`, regular files contain `evil`
+        let make_tarball = |name: &str, entries: &[(tar::EntryType, &str, &str)]| {
+            let tarball = dir.path().join(name);
+            let encoder = flate2::write::GzEncoder::new(
+                File::create(&tarball).unwrap(),
+                flate2::Compression::default(),
+            );
+            let mut builder = tar::Builder::new(encoder);
+            for (kind, path, target) in entries {
+                let mut header = tar::Header::new_gnu();
+                header.set_entry_type(*kind);
+                header.set_mode(0o644);
+                if *kind == Regular {
+                    header.set_size(4);
+                    header.set_cksum();
+                    builder
+                        .append_data(&mut header, path, &b"evil"[..])
+                        .unwrap();
+                } else {
+                    header.set_size(0);
+                    builder.append_link(&mut header, path, target).unwrap();
+                }
+            }
+            builder.into_inner().unwrap().finish().unwrap();
+            tarball
+        };
+        let extract = |tarball: &Path, out: &Path| {
+            Extractable::load(tarball, None)
+                .unwrap()
+                .quiet(true)
+                .strip_components(1)
+                .extract_to(out)
+        };
+
+        let outside_str = outside.to_str().unwrap();
+        let cases = [
+            // writing through a symlink pointing outside
+            &[
+                (Symlink, "pkg/link", "../outside"),
+                (Regular, "pkg/link/evil", ""),
+            ][..],
+            &[
+                (Symlink, "pkg/link", outside_str),
+                (Regular, "pkg/link/evil", ""),
+            ],
+            // hard link to a file outside of the stripped output
+            &[(Link, "pkg/hard", "../outside/secret")],
+            &[(Link, "pkg/hard", "outside/secret")],
+        ];
+        for (idx, entries) in cases.into_iter().enumerate() {
+            let tarball = make_tarball(&format!("evil{idx}.tar.gz"), entries);
+            let out = dir.path().join(format!("out{idx}"));
+            assert!(extract(&tarball, &out).is_err(), "case {idx}");
+            assert!(!outside.join("evil").exists());
+            assert!(!out.join("hard").exists());
+        }
+
+        // writing through a symlink pointing outside, that already exists in the output
+        let out = dir.path().join("existing");
+        fs::create_dir(&out).unwrap();
+        std::os::unix::fs::symlink(&outside, out.join("link")).unwrap();
+        let tarball = make_tarball("existing.tar.gz", &[(Regular, "pkg/link/evil", "")]);
+        assert!(extract(&tarball, &out).is_err());
+        assert!(!outside.join("evil").exists());
+        // no directory should be created through it either
+        let tarball = make_tarball("nested.tar.gz", &[(Regular, "pkg/link/sub/evil", "")]);
+        assert!(extract(&tarball, &out).is_err());
+        assert!(!outside.join("sub").exists());
+
+        // links inside of the output directory are fine
+        let tarball = make_tarball(
+            "inside.tar.gz",
+            &[
+                (Regular, "pkg/file", ""),
+                (Symlink, "pkg/bin/link", "../file"),
+                (Link, "pkg/hard", "pkg/file"),
+            ],
+        );
+        let out = dir.path().join("inside");
+        extract(&tarball, &out).unwrap();
+        assert_eq!(
+            fs::read_to_string(out.join("bin").join("link")).unwrap(),
+            "evil"
+        );
+        assert_eq!(fs::read_to_string(out.join("hard")).unwrap(), "evil");
+    }
 }
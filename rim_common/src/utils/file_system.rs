@@ -1,9 +1,11 @@
 use anyhow::bail;
 use anyhow::{anyhow, Context, Result};
+use sha2::{Digest, Sha256};
 use std::env;
 use std::fs;
 use std::io::Write;
 use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
 use tempfile::NamedTempFile;
 
@@ -109,6 +111,28 @@ pub fn write_file<P: AsRef<Path>>(path: P, content: &str, append: bool) -> Resul
     Ok(())
 }
 
+/// Write `content` to a file atomically, by writing it into a temporary file next to it
+/// first, then replacing the original file with it.
+///
+/// This makes sure the file at `path` will never be left half-written
+/// if the program was killed during writing.
+pub fn write_file_atomic<P: AsRef<Path>>(path: P, content: &str) -> Result<()> {
+    let path = path.as_ref();
+    let filename = path
+        .file_name()
+        .with_context(|| format!("'{}' is not a valid file path", path.display()))?;
+    let mut tmp_name = std::ffi::OsString::from(".");
+    tmp_name.push(filename);
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    write_file(&tmp_path, content, false)?;
+    fs::rename(&tmp_path, path).with_context(|| {
+        _ = fs::remove_file(&tmp_path);
+        format!("unable to write file '{}'", path.display())
+    })
+}
+
 pub fn write_bytes<P: AsRef<Path>>(path: P, content: &[u8], append: bool) -> Result<()> {
     let mut options = fs::OpenOptions::new();
     if append {
@@ -125,7 +149,10 @@ pub fn write_bytes<P: AsRef<Path>>(path: P, content: &[u8], append: bool) -> Res
 /// An [`fs::copy`] wrapper that only copies a file if:
 ///
 /// - `to` does not exist yet.
-/// - `to` exists but have different modified date.
+/// - `to` exists but have different modified date or size.
+///
+/// The permissions (such as the executable bit on unix) and modified date of `from`
+/// will be carried over to `to`.
 ///
 /// Will attempt to create parent directory if not exists.
 pub fn copy_file<P, Q>(from: P, to: Q) -> Result<()>
@@ -133,12 +160,15 @@ where
     P: AsRef<Path>,
     Q: AsRef<Path>,
 {
+    let src_meta = fs::metadata(&from)
+        .with_context(|| format!("unable to read metadata of '{}'", from.as_ref().display()))?;
     // Make sure no redundant work is done
-    if let (Ok(src_modify_time), Ok(dest_modify_time)) = (
-        fs::metadata(&from).and_then(|m| m.modified()),
-        fs::metadata(&to).and_then(|m| m.modified()),
-    ) {
-        if src_modify_time == dest_modify_time {
+    if let Ok(dest_meta) = fs::metadata(&to) {
+        let same_modify_time = matches!(
+            (src_meta.modified(), dest_meta.modified()),
+            (Ok(src_time), Ok(dest_time)) if src_time == dest_time
+        );
+        if same_modify_time && src_meta.len() == dest_meta.len() {
             return Ok(());
         }
     }
@@ -151,9 +181,55 @@ where
             to.as_ref().display()
         )
     })?;
+    preserve_metadata(&src_meta, to.as_ref()).with_context(|| {
+        format!(
+            "could not preserve the metadata of '{}'",
+            to.as_ref().display()
+        )
+    })
+}
+
+/// Apply the modified date and permissions of a source file to its copy.
+///
+/// The copy might already be read-only (as `fs::copy` copies the permissions too),
+/// so the modified date is set by path, without opening the file for writing.
+fn preserve_metadata(src_meta: &fs::Metadata, dest: &Path) -> Result<()> {
+    let modify_time = filetime::FileTime::from_last_modification_time(src_meta);
+    filetime::set_file_mtime(dest, modify_time)?;
+    fs::set_permissions(dest, src_meta.permissions())?;
     Ok(())
 }
 
+/// Calculate the SHA-256 checksum of a file, and return it as a hex string.
+pub fn calculate_sha256<P: AsRef<Path>>(path: P) -> Result<String> {
+    let mut file = fs::File::open(path.as_ref()).with_context(|| {
+        format!(
+            "unable to open '{}' to calculate its checksum",
+            path.as_ref().display()
+        )
+    })?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Make sure the SHA-256 checksum of the file at `path` is the `expected` hex string.
+pub fn verify_sha256<P: AsRef<Path>>(path: P, expected: &str) -> Result<()> {
+    let actual = calculate_sha256(path.as_ref())?;
+    if !actual.eq_ignore_ascii_case(expected.trim()) {
+        bail!(
+            "checksum mismatch for '{}': expected '{expected}', got '{actual}'",
+            path.as_ref().display()
+        );
+    }
+    Ok(())
+}
+
+/// Calculate the SHA-256 checksum of some bytes, and return it as a hex string.
+pub fn sha256_hex<B: AsRef<[u8]>>(bytes: B) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
 /// Copy file or directory into a directory, and return the full path after copying.
 pub fn copy_into<P, Q>(from: P, to: Q) -> Result<PathBuf>
 where
@@ -213,6 +289,70 @@ where
     Ok(())
 }
 
+/// The maximum number of threads to use in [`copy_as_parallel`].
+const MAX_COPY_THREADS: usize = 8;
+
+/// Similar to [`copy_as`], but copying the files of a directory using
+/// a bounded number of threads, which is faster for large directory trees.
+pub fn copy_as_parallel<P, Q>(from: P, to: Q) -> Result<()>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    // Create the directory structure first, then collect the files to copy.
+    fn collect_files_(src: &Path, dest: &Path, files: &mut Vec<(PathBuf, PathBuf)>) -> Result<()> {
+        ensure_dir(dest)?;
+        for maybe_entry in src.read_dir()? {
+            let entry = maybe_entry?;
+            let src = entry.path();
+            let dest = dest.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                collect_files_(&src, &dest, files)?;
+            } else {
+                files.push((src, dest));
+            }
+        }
+        Ok(())
+    }
+
+    let (from, to) = (from.as_ref(), to.as_ref());
+    if !from.is_dir() {
+        return copy_as(from, to);
+    }
+
+    let mut files = vec![];
+    collect_files_(from, to, &mut files).with_context(|| {
+        format!(
+            "could not copy directory '{}' to '{}'",
+            from.display(),
+            to.display()
+        )
+    })?;
+
+    let workers = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .clamp(1, MAX_COPY_THREADS);
+    let next = AtomicUsize::new(0);
+    std::thread::scope(|scope| {
+        let handles = (0..workers)
+            .map(|_| {
+                scope.spawn(|| -> Result<()> {
+                    while let Some((src, dest)) = files.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        copy_file(src, dest)?;
+                    }
+                    Ok(())
+                })
+            })
+            .collect::<Vec<_>>();
+        handles.into_iter().try_for_each(|handle| {
+            handle
+                .join()
+                .map_err(|_| anyhow!("thread panicked when copying '{}'", from.display()))?
+        })
+    })
+}
+
 /// Set file permissions (executable)
 /// rwxr-xr-x: 0o755
 #[cfg(not(windows))]
@@ -260,7 +400,148 @@ pub fn is_executable<P: AsRef<Path>>(path: P) -> bool {
     path.as_ref().is_file() && is_executable_ext
 }
 
+/// The kind of link that was created by [`create_link`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    Symbolic,
+    Hard,
+    /// Directory junction, which is only available on Windows.
+    Junction,
+}
+
+/// Create a link at `link` pointing to `original`.
+///
+/// This tries creating a symbolic link first, if that fails, it falls back to:
+/// - a hard link, if `original` is a file.
+/// - a directory junction (Windows only), if `original` is a directory, because creating
+///   symbolic links requires elevated privileges on Windows unless developer mode is on,
+///   and directories cannot be hard linked.
+pub fn create_link<P: AsRef<Path>, Q: AsRef<Path>>(original: P, link: Q) -> Result<LinkKind> {
+    let (original, link) = (original.as_ref(), link.as_ref());
+    ensure_parent_dir(link)?;
+    if let Err(e) = symlink_(original, link) {
+        debug!(
+            "unable to create symbolic link '{}' for '{}': {e}, falling back",
+            link.display(),
+            original.display()
+        );
+    } else {
+        return Ok(LinkKind::Symbolic);
+    }
+
+    if original.is_dir() {
+        #[cfg(windows)]
+        return create_junction_(original, link).map(|_| LinkKind::Junction);
+        #[cfg(not(windows))]
+        bail!(
+            "unable to create link '{}' for directory '{}'",
+            link.display(),
+            original.display()
+        );
+    }
+    fs::hard_link(original, link).with_context(|| {
+        format!(
+            "unable to create link '{}' for '{}'",
+            link.display(),
+            original.display()
+        )
+    })?;
+    Ok(LinkKind::Hard)
+}
+
+#[cfg(unix)]
+fn symlink_(original: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+#[cfg(windows)]
+fn symlink_(original: &Path, link: &Path) -> std::io::Result<()> {
+    if original.is_dir() {
+        std::os::windows::fs::symlink_dir(original, link)
+    } else {
+        std::os::windows::fs::symlink_file(original, link)
+    }
+}
+
+#[cfg(windows)]
+fn create_junction_(original: &Path, link: &Path) -> Result<()> {
+    // junctions can only point to absolute paths
+    let original = to_normalized_absolute_path(original, None)?;
+    // `mklink` is a builtin command of `cmd`, thus cannot be executed directly
+    crate::run!("cmd", "/C", "mklink", "/J", link, original)
+}
+
+/// The result of checking a path against a link target, see [`is_link_of`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkStatus {
+    /// The path is a link of the target.
+    Linked,
+    /// The path does not exist, or it is not a link of the target.
+    Unlinked,
+    /// The path is a symbolic link that cannot be resolved, either because
+    /// it is dangling or because it forms a cycle.
+    Broken,
+}
+
+/// Check whether `path` is a link of `target`.
+///
+/// Returns [`LinkStatus::Linked`] if `path` is a symbolic link (or a directory junction on Windows)
+/// that resolves to `target`, or a hard link sharing the same file with `target`.
+/// Symbolic links that are dangling or cyclic are reported as [`LinkStatus::Broken`],
+/// so they can be cleaned up instead of being mistaken for a missing path.
+pub fn is_link_of<P: AsRef<Path>, Q: AsRef<Path>>(path: P, target: Q) -> LinkStatus {
+    let (path, target) = (path.as_ref(), target.as_ref());
+    let Ok(meta) = fs::symlink_metadata(path) else {
+        return LinkStatus::Unlinked;
+    };
+
+    // NB: directory junctions are reported as symlinks on Windows as well
+    if meta.file_type().is_symlink() {
+        let resolved = match fs::canonicalize(path) {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                debug!("unable to resolve link '{}': {e}", path.display());
+                return LinkStatus::Broken;
+            }
+        };
+        return match fs::canonicalize(target) {
+            Ok(target) if target == resolved => LinkStatus::Linked,
+            _ => LinkStatus::Unlinked,
+        };
+    }
+    if is_same_file_(path, target) {
+        LinkStatus::Linked
+    } else {
+        LinkStatus::Unlinked
+    }
+}
+
+#[cfg(unix)]
+fn is_same_file_(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+#[cfg(windows)]
+fn is_same_file_(a: &Path, b: &Path) -> bool {
+    // Getting the file index requires an open handle on Windows, which is not
+    // stable in std yet, so we compare the content instead. This is fine for
+    // proxies such as the ones created by `rustup`, as they are identical copies anyway.
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(ma), Ok(mb)) if ma.is_file() && mb.is_file() && ma.len() == mb.len() => {
+            matches!((fs::read(a), fs::read(b)), (Ok(ca), Ok(cb)) if ca == cb)
+        }
+        _ => false,
+    }
+}
+
 /// Delete a file or directory (recursively) from disk.
+///
+/// Broken symbolic links, which are neither files nor directories, are removed as well.
 pub fn remove<P: AsRef<Path>>(src: P) -> Result<()> {
     if src.as_ref().is_file() {
         fs::remove_file(&src)
@@ -268,6 +549,36 @@ pub fn remove<P: AsRef<Path>>(src: P) -> Result<()> {
     } else if src.as_ref().is_dir() {
         fs::remove_dir_all(&src)
             .with_context(|| format!("unable to remove directory '{}'", src.as_ref().display()))?;
+    } else if fs::symlink_metadata(&src).is_ok_and(|meta| meta.file_type().is_symlink()) {
+        // directory links can only be removed by `remove_dir` on Windows
+        fs::remove_file(&src)
+            .or_else(|_| fs::remove_dir(&src))
+            .with_context(|| format!("unable to remove link '{}'", src.as_ref().display()))?;
+    }
+    Ok(())
+}
+
+/// Remove `path` if it is an empty directory, then do the same for its parent directories,
+/// until a non-empty one or `root` is reached.
+///
+/// `root` itself is never removed, and nothing will be removed if `path` is not inside of it.
+pub fn remove_empty_parents<P: AsRef<Path>>(path: P, root: &Path) -> Result<()> {
+    let mut current = Some(path.as_ref());
+    while let Some(dir) = current {
+        if dir == root || !dir.starts_with(root) {
+            break;
+        }
+        // the path might be removed already, continue with its parent
+        if dir.exists() {
+            let is_empty = dir.is_dir() && fs::read_dir(dir)?.next().is_none();
+            if !is_empty {
+                break;
+            }
+            debug!("removing empty directory '{}'", dir.display());
+            fs::remove_dir(dir)
+                .with_context(|| format!("unable to remove directory '{}'", dir.display()))?;
+        }
+        current = dir.parent();
     }
     Ok(())
 }
@@ -335,10 +646,257 @@ pub fn extension_str(path: &Path) -> Option<&str> {
     path.extension().and_then(|ext| ext.to_str())
 }
 
+/// Marker that will be written into generated shortcut files,
+/// so that we know which ones are safe to remove.
+const SHORTCUT_MARKER: &str = "Generated by rim";
+
+/// A shortcut (or launcher) of an application, which will be put into
+/// the application menu of current user, and optionally on the desktop.
+#[derive(Debug, Clone)]
+pub struct ApplicationShortcut<'a> {
+    /// The display name of this shortcut.
+    pub name: &'a str,
+    /// Path to the program that this shortcut points to.
+    pub path: PathBuf,
+    /// Path to an icon file, the supported format depends on the platform,
+    /// such as `.ico` on Windows, `.png` or `.svg` on Linux, and `.icns` on macOS.
+    pub icon: Option<PathBuf>,
+    /// Whether or not to put an extra shortcut on the desktop.
+    pub desktop: bool,
+}
+
+impl ApplicationShortcut<'_> {
+    /// Create shortcuts and return the paths of them.
+    pub fn create(&self) -> Result<Vec<PathBuf>> {
+        let mut created = vec![];
+        for dir in self.shortcut_dirs() {
+            ensure_dir(&dir)?;
+            created.push(self.create_shortcut_(&dir)?);
+        }
+        Ok(created)
+    }
+
+    /// Remove shortcuts that were previously created by [`create`](Self::create).
+    pub fn remove(&self) -> Result<()> {
+        for shortcut in self.created()? {
+            remove(&shortcut)?;
+        }
+        Ok(())
+    }
+
+    /// Get the paths of shortcuts that were previously created by [`create`](Self::create),
+    /// which are the ones that will be deleted by [`remove`](Self::remove).
+    pub fn created(&self) -> Result<Vec<PathBuf>> {
+        let mut created = vec![];
+        for dir in self.shortcut_dirs() {
+            created.extend(self.created_shortcut_(&dir)?);
+        }
+        Ok(created)
+    }
+
+    fn shortcut_dirs(&self) -> Vec<PathBuf> {
+        let mut dirs = vec![];
+        if let Some(app_menu) = app_menu_dir() {
+            dirs.push(app_menu);
+        }
+        if self.desktop {
+            if let Some(desktop) = dirs::desktop_dir() {
+                dirs.push(desktop);
+            }
+        }
+        dirs
+    }
+
+    #[cfg(windows)]
+    fn create_shortcut_(&self, dir: &Path) -> Result<PathBuf> {
+        let shortcut_path = dir.join(format!("{}.lnk", self.name));
+        let icon_location = if let Some(icon) = &self.icon {
+            format!("$s.IconLocation='{}';", stringify_path(icon)?)
+        } else {
+            String::new()
+        };
+        let weird_powershell_cmd = format!(
+            "$s=(New-Object -COM WScript.Shell).CreateShortcut('{}');$s.TargetPath='{}';$s.Description='{SHORTCUT_MARKER}';{icon_location}$s.Save()",
+            stringify_path(&shortcut_path)?,
+            stringify_path(&self.path)?,
+        );
+        crate::run!("powershell", weird_powershell_cmd)?;
+        Ok(shortcut_path)
+    }
+
+    #[cfg(windows)]
+    fn created_shortcut_(&self, dir: &Path) -> Result<Option<PathBuf>> {
+        let shortcut_path = dir.join(format!("{}.lnk", self.name));
+        Ok(shortcut_path.is_file().then_some(shortcut_path))
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn create_shortcut_(&self, dir: &Path) -> Result<PathBuf> {
+        let icon = if let Some(icon) = &self.icon {
+            format!("Icon={}\n", stringify_path(icon)?)
+        } else {
+            String::new()
+        };
+        let content = format!(
+            "# {SHORTCUT_MARKER}\n\
+            [Desktop Entry]\n\
+            Name={}\n\
+            Exec={} %F\n\
+            {icon}\
+            Type=Application\n\
+            StartupNotify=false",
+            self.name,
+            stringify_path(&self.path)?,
+        );
+        let shortcut_path = dir.join(format!("{}.desktop", self.name));
+        write_file(&shortcut_path, &content, false)?;
+        set_exec_permission(&shortcut_path)?;
+        Ok(shortcut_path)
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn created_shortcut_(&self, dir: &Path) -> Result<Option<PathBuf>> {
+        let shortcut_path = dir.join(format!("{}.desktop", self.name));
+        if !shortcut_path.is_file() {
+            return Ok(None);
+        }
+        let content = read_to_string("application shortcut", &shortcut_path)?;
+        Ok(content.contains(SHORTCUT_MARKER).then_some(shortcut_path))
+    }
+
+    /// Create a minimal `.app` bundle that launches the program,
+    /// which can be found by `Launchpad` and `Spotlight` when put under `~/Applications`.
+    #[cfg(target_os = "macos")]
+    fn create_shortcut_(&self, dir: &Path) -> Result<PathBuf> {
+        const LAUNCHER_NAME: &str = "launcher";
+
+        let bundle = dir.join(format!("{}.app", self.name));
+        let contents_dir = bundle.join("Contents");
+        let macos_dir = contents_dir.join("MacOS");
+        ensure_dir(&macos_dir)?;
+
+        let launcher = macos_dir.join(LAUNCHER_NAME);
+        let script = format!(
+            "#!/bin/sh\n# {SHORTCUT_MARKER}\nexec \"{}\" \"$@\"",
+            stringify_path(&self.path)?
+        );
+        write_file(&launcher, &script, false)?;
+        set_exec_permission(&launcher)?;
+
+        let icon_entry = if let Some(icon) = &self.icon {
+            let resources_dir = contents_dir.join("Resources");
+            let icon_path = copy_into(icon, &resources_dir)?;
+            let icon_name = icon_path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or_default()
+                .to_string();
+            format!("    <key>CFBundleIconFile</key>\n    <string>{icon_name}</string>\n")
+        } else {
+            String::new()
+        };
+        let plist = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<!-- {SHORTCUT_MARKER} -->
+<plist version="1.0">
+<dict>
+    <key>CFBundleName</key>
+    <string>{}</string>
+    <key>CFBundleExecutable</key>
+    <string>{LAUNCHER_NAME}</string>
+    <key>CFBundlePackageType</key>
+    <string>APPL</string>
+{icon_entry}</dict>
+</plist>"#,
+            self.name,
+        );
+        write_file(contents_dir.join("Info.plist"), &plist, false)?;
+
+        Ok(bundle)
+    }
+
+    #[cfg(target_os = "macos")]
+    fn created_shortcut_(&self, dir: &Path) -> Result<Option<PathBuf>> {
+        let bundle = dir.join(format!("{}.app", self.name));
+        let plist = bundle.join("Contents").join("Info.plist");
+        if !plist.is_file() {
+            return Ok(None);
+        }
+        let content = read_to_string("application shortcut", &plist)?;
+        Ok(content.contains(SHORTCUT_MARKER).then_some(bundle))
+    }
+}
+
+/// Get the directory where the application menu entries of current user are stored.
+fn app_menu_dir() -> Option<PathBuf> {
+    cfg_if::cfg_if! {
+        if #[cfg(windows)] {
+            dirs::data_dir().map(|d| d.join(r"Microsoft\Windows\Start Menu\Programs"))
+        } else if #[cfg(target_os = "macos")] {
+            dirs::home_dir().map(|d| d.join("Applications"))
+        } else {
+            dirs::data_local_dir().map(|d| d.join("applications"))
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[cfg(unix)]
+    #[test]
+    fn copy_dir_preserves_metadata() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = tempfile::tempdir().unwrap();
+        let src = root.path().join("src");
+        let src_exe = src.join("bin").join("tool");
+        ensure_parent_dir(&src_exe).unwrap();
+        write_file(&src_exe, "#!/bin/sh", false).unwrap();
+        fs::set_permissions(&src_exe, fs::Permissions::from_mode(0o750)).unwrap();
+
+        let dest = root.path().join("dest");
+        copy_as(&src, &dest).unwrap();
+
+        let src_meta = fs::metadata(&src_exe).unwrap();
+        let dest_meta = fs::metadata(dest.join("bin").join("tool")).unwrap();
+        assert_eq!(dest_meta.permissions().mode() & 0o777, 0o750);
+        assert_eq!(dest_meta.modified().unwrap(), src_meta.modified().unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn copy_read_only_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = tempfile::tempdir().unwrap();
+        let src = root.path().join("readonly");
+        write_file(&src, "content", false).unwrap();
+        fs::set_permissions(&src, fs::Permissions::from_mode(0o444)).unwrap();
+
+        let dest = root.path().join("copied");
+        copy_as(&src, &dest).unwrap();
+
+        let src_meta = fs::metadata(&src).unwrap();
+        let dest_meta = fs::metadata(&dest).unwrap();
+        assert_eq!(dest_meta.permissions().mode() & 0o777, 0o444);
+        assert_eq!(dest_meta.modified().unwrap(), src_meta.modified().unwrap());
+    }
+
+    #[test]
+    fn sha256_checksum() {
+        const HELLO_SHA256: &str =
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        assert_eq!(sha256_hex("hello"), HELLO_SHA256);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hello.txt");
+        write_bytes(&path, b"hello", false).unwrap();
+        assert_eq!(calculate_sha256(&path).unwrap(), HELLO_SHA256);
+    }
+
     #[test]
     fn path_ambiguity() {
         let with_dots = PathBuf::from("/path/to/home/./my_app/../my_app");
@@ -354,4 +912,210 @@ mod tests {
         let without_dots_normalized = to_normalized_absolute_path(&without_dots, None).unwrap();
         assert_eq!(with_dots_normalized, without_dots_normalized);
     }
+
+    #[test]
+    fn atomic_write_replaces_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        write_file(&path, "a longer original content", false).unwrap();
+
+        write_file_atomic(&path, "new").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap().trim(), "new");
+        // the temporary file should not be left behind
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn parallel_copy_matches_serial_copy() {
+        let src = tempfile::tempdir().unwrap();
+        for i in 0..20 {
+            let sub = src
+                .path()
+                .join(format!("dir_{}", i % 3))
+                .join(format!("sub_{i}"));
+            ensure_dir(&sub).unwrap();
+            write_file(sub.join(format!("file_{i}")), &i.to_string(), false).unwrap();
+        }
+        ensure_dir(src.path().join("empty_dir")).unwrap();
+
+        let dest = tempfile::tempdir().unwrap();
+        let serial = dest.path().join("serial");
+        let parallel = dest.path().join("parallel");
+        copy_as(src.path(), &serial).unwrap();
+        copy_as_parallel(src.path(), &parallel).unwrap();
+
+        let mut serial_entries = walk_dir(&serial, true).unwrap();
+        let mut parallel_entries = walk_dir(&parallel, true).unwrap();
+        assert_eq!(serial_entries.len(), parallel_entries.len());
+        serial_entries.sort();
+        parallel_entries.sort();
+        for (s, p) in serial_entries.iter().zip(&parallel_entries) {
+            assert_eq!(s.strip_prefix(&serial), p.strip_prefix(&parallel));
+            if s.is_file() {
+                assert_eq!(fs::read(s).unwrap(), fs::read(p).unwrap());
+            } else {
+                assert!(p.is_dir());
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn link_detection() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target");
+        let other = dir.path().join("other");
+        write_file(&target, "target", false).unwrap();
+        write_file(&other, "target", false).unwrap();
+
+        let hard = dir.path().join("hard");
+        let soft = dir.path().join("soft");
+        fs::hard_link(&target, &hard).unwrap();
+        std::os::unix::fs::symlink(&target, &soft).unwrap();
+
+        assert_eq!(is_link_of(&hard, &target), LinkStatus::Linked);
+        assert_eq!(is_link_of(&soft, &target), LinkStatus::Linked);
+        assert_eq!(is_link_of(&other, &target), LinkStatus::Unlinked);
+        assert_eq!(
+            is_link_of(dir.path().join("missing"), &target),
+            LinkStatus::Unlinked
+        );
+
+        // dangling link
+        fs::remove_file(&target).unwrap();
+        assert_eq!(is_link_of(&soft, &target), LinkStatus::Broken);
+        remove(&soft).unwrap();
+        assert!(fs::symlink_metadata(&soft).is_err());
+    }
+
+    #[test]
+    fn remove_empty_parent_dirs() {
+        let root = tempfile::tempdir().unwrap();
+        let shared = root.path().join("shared");
+        let a = shared.join("a").join("nested");
+        let b = shared.join("b");
+        ensure_dir(&a).unwrap();
+        ensure_dir(&b).unwrap();
+
+        remove_empty_parents(&a, root.path()).unwrap();
+        assert!(!shared.join("a").exists());
+        assert!(b.is_dir());
+
+        remove_empty_parents(&b, root.path()).unwrap();
+        assert!(!shared.exists());
+        assert!(root.path().is_dir());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn cyclic_link_detection() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target");
+        write_file(&target, "target", false).unwrap();
+
+        let self_ref = dir.path().join("self_ref");
+        std::os::unix::fs::symlink(&self_ref, &self_ref).unwrap();
+        assert_eq!(is_link_of(&self_ref, &target), LinkStatus::Broken);
+
+        let (a, b) = (dir.path().join("a"), dir.path().join("b"));
+        std::os::unix::fs::symlink(&b, &a).unwrap();
+        std::os::unix::fs::symlink(&a, &b).unwrap();
+        assert_eq!(is_link_of(&a, &target), LinkStatus::Broken);
+
+        remove(&self_ref).unwrap();
+        assert!(fs::symlink_metadata(&self_ref).is_err());
+    }
+
+    #[test]
+    fn link_creation() {
+        let dir = tempfile::tempdir().unwrap();
+        let target_file = dir.path().join("target_file");
+        let target_dir = dir.path().join("target_dir");
+        write_file(&target_file, "target", false).unwrap();
+        ensure_dir(&target_dir).unwrap();
+        write_file(target_dir.join("inner"), "inner", false).unwrap();
+
+        let file_link = dir.path().join("links").join("file");
+        create_link(&target_file, &file_link).unwrap();
+        assert_eq!(is_link_of(&file_link, &target_file), LinkStatus::Linked);
+
+        let dir_link = dir.path().join("links").join("dir");
+        create_link(&target_dir, &dir_link).unwrap();
+        assert_eq!(is_link_of(&dir_link, &target_dir), LinkStatus::Linked);
+        assert!(dir_link.join("inner").is_file());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn junction_creation() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target");
+        ensure_dir(&target).unwrap();
+        write_file(target.join("inner"), "inner", false).unwrap();
+
+        let junction = dir.path().join("junction");
+        create_junction_(&target, &junction).unwrap();
+        assert_eq!(is_link_of(&junction, &target), LinkStatus::Linked);
+        assert!(junction.join("inner").is_file());
+    }
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[test]
+    fn shortcut_creation() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let shortcut = ApplicationShortcut {
+            name: "my app",
+            path: PathBuf::from("/path/to/my_app"),
+            icon: Some(PathBuf::from("/path/to/icon.png")),
+            desktop: false,
+        };
+
+        let created = shortcut.create_shortcut_(temp_dir.path()).unwrap();
+        assert_eq!(created, temp_dir.path().join("my app.desktop"));
+        let content = fs::read_to_string(&created).unwrap();
+        assert!(content.contains("Name=my app\n"));
+        assert!(content.contains("Exec=/path/to/my_app %F\n"));
+        assert!(content.contains("Icon=/path/to/icon.png\n"));
+
+        let to_remove = shortcut.created_shortcut_(temp_dir.path()).unwrap();
+        assert_eq!(to_remove.as_ref(), Some(&created));
+        remove(&created).unwrap();
+        assert!(!created.exists());
+        assert!(shortcut
+            .created_shortcut_(temp_dir.path())
+            .unwrap()
+            .is_none());
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn shortcut_creation() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let icon = temp_dir.path().join("icon.icns");
+        write_bytes(&icon, &[], false).unwrap();
+        let shortcut = ApplicationShortcut {
+            name: "my app",
+            path: PathBuf::from("/path/to/my_app"),
+            icon: Some(icon),
+            desktop: false,
+        };
+
+        let created = shortcut.create_shortcut_(temp_dir.path()).unwrap();
+        assert_eq!(created, temp_dir.path().join("my app.app"));
+        let launcher = fs::read_to_string(created.join("Contents/MacOS/launcher")).unwrap();
+        assert!(launcher.contains("exec \"/path/to/my_app\""));
+        let plist = fs::read_to_string(created.join("Contents/Info.plist")).unwrap();
+        assert!(plist.contains("<string>my app</string>"));
+        assert!(plist.contains("<string>icon.icns</string>"));
+        assert!(created.join("Contents/Resources/icon.icns").is_file());
+
+        let to_remove = shortcut.created_shortcut_(temp_dir.path()).unwrap();
+        assert_eq!(to_remove.as_ref(), Some(&created));
+        remove(&created).unwrap();
+        assert!(!created.exists());
+        assert!(shortcut
+            .created_shortcut_(temp_dir.path())
+            .unwrap()
+            .is_none());
+    }
 }
@@ -2,14 +2,20 @@ use anyhow::Result;
 use chrono::Local;
 use fern::colors::{Color, ColoredLevelConfig};
 use log::LevelFilter;
-use std::io;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::sync::mpsc::Sender;
 use std::sync::OnceLock;
 
 use super::file_system::{ensure_dir, parent_dir_of_cur_exe};
+use super::progress_event::{send_progress_event, ProgressEvent};
 
 static LOGGER_SET: OnceLock<bool> = OnceLock::new();
+/// The maximum size (in bytes) of a log file before rotating to a new one.
+const MAX_LOG_FILE_SIZE: u64 = 10 * 1024 * 1024;
+/// The maximum number of log files to keep, older ones will be removed when rotating.
+const MAX_LOG_FILES: usize = 10;
 
 #[derive(Debug)]
 pub struct Logger {
@@ -64,30 +70,38 @@ impl Logger {
     ///   detailed log messages including `debug!`.
     /// - If [`quiet`](Logger::quiet) was called with `true`, this will not output any message
     ///   on `stdout`, but will still output them into log file.
+    ///
+    /// Messages are always written into a log file under [`log_dir`] at `debug` level,
+    /// regardless of the above options.
     pub fn setup(self) -> Result<()> {
+        // create the log file first, so that nothing would be missing from it
+        let log_file = RotatingLogFile::new(log_dir()?, &log_file_prefix())?;
         let mut dispatch = fern::Dispatch::new().level(LevelFilter::Trace);
         let filter_log_for_output = move |md: &log::Metadata| -> bool {
             md.level() <= self.level && md.level() != LevelFilter::Trace
         };
 
-        // log to standard output (colored info label)
+        // log to standard output (colored info label, unless color is disabled)
+        let colored = !super::no_color();
         let stdout = fern::Dispatch::new()
             .filter(filter_log_for_output)
-            .format(|out, msg, rec| {
-                out.finish(format_args!(
-                    "{}: {msg}",
+            .format(move |out, msg, rec| {
+                let level = if colored {
                     ColoredLevelConfig::new()
                         .info(Color::BrightBlue)
                         .debug(Color::Magenta)
                         .color(rec.level())
                         .to_string()
-                        .to_lowercase(),
-                ));
+                } else {
+                    rec.level().to_string()
+                };
+                out.finish(format_args!("{}: {msg}", level.to_lowercase()));
             })
             .chain(io::stdout());
         dispatch = dispatch.chain(stdout);
         // log to file (detailed trace with timestamp)
         let file_config = fern::Dispatch::new()
+            .level(LevelFilter::Debug)
             .format(|out, msg, rec| {
                 out.finish(format_args!(
                     "[{} {} {}] {msg}",
@@ -96,7 +110,7 @@ impl Logger {
                     rec.level(),
                 ))
             })
-            .chain(fern::log_file(log_file_path()?)?);
+            .chain(Box::new(log_file) as Box<dyn Write + Send>);
         dispatch = dispatch.chain(file_config);
         // log to custom channel if available (regular style)
         if let Some(sender) = self.output_sender {
@@ -112,6 +126,17 @@ impl Logger {
             dispatch = dispatch.chain(custom);
         }
 
+        // send as progress events, which is a no-op if there's no progress sender
+        let events = fern::Dispatch::new()
+            .filter(filter_log_for_output)
+            .chain(fern::Output::call(|rec| {
+                send_progress_event(ProgressEvent::Log {
+                    level: rec.level().to_string().to_lowercase(),
+                    message: rec.args().to_string(),
+                });
+            }));
+        dispatch = dispatch.chain(events);
+
         if dispatch.apply().is_ok() {
             LOGGER_SET.set(true).unwrap_or_else(|_| {
                 unreachable!("logger setup will fail before reaching this point")
@@ -121,31 +146,154 @@ impl Logger {
     }
 }
 
-static LOG_FILE_PATH: OnceLock<PathBuf> = OnceLock::new();
-/// Get the path to log file to write.
+static LOG_DIR: OnceLock<PathBuf> = OnceLock::new();
+/// Get the directory to write log files in.
 ///
-/// We put the log directory besides current binary, so that it should be easier for users to find them.
+/// We put the log directory besides current binary (which is the install dir for manager),
+/// so that it should be easier for users to find them.
 /// If for some reason the path to current binary cannot be found, we'll assume the user is running this
 /// binary in their current working dir, and create a log dir there.
 ///
-/// Note: the log file might not exists.
-///
 /// # Error
 ///
-/// Because this will attempt to create a directory named `log` to place the actual log file,
+/// Because this will attempt to create a directory named `log` to place the actual log files,
 /// this function might fail if it cannot be created.
-pub fn log_file_path() -> Result<&'static Path> {
-    let mut log_dir = parent_dir_of_cur_exe().unwrap_or(PathBuf::from("."));
-    log_dir.push("log");
-    ensure_dir(&log_dir)?;
+pub fn log_dir() -> Result<&'static Path> {
+    let log_dir = LOG_DIR.get_or_init(|| {
+        parent_dir_of_cur_exe()
+            .unwrap_or(PathBuf::from("."))
+            .join("log")
+    });
+    ensure_dir(log_dir)?;
+    Ok(log_dir)
+}
+
+fn log_file_prefix() -> String {
+    super::lowercase_program_name().unwrap_or(env!("CARGO_PKG_NAME").to_string())
+}
+
+/// A log file writer that switches to a new file named `<prefix>-<timestamp>.log`
+/// once the current one exceeds a size limit, and removes the oldest files
+/// so that only a limited number of them are kept.
+struct RotatingLogFile {
+    dir: PathBuf,
+    prefix: String,
+    file: File,
+    size: u64,
+    max_size: u64,
+    max_files: usize,
+}
+
+impl RotatingLogFile {
+    fn new(dir: &Path, prefix: &str) -> io::Result<Self> {
+        Self::with_limits(dir, prefix, MAX_LOG_FILE_SIZE, MAX_LOG_FILES)
+    }
+
+    fn with_limits(dir: &Path, prefix: &str, max_size: u64, max_files: usize) -> io::Result<Self> {
+        let this = Self {
+            dir: dir.to_path_buf(),
+            prefix: prefix.to_string(),
+            file: Self::open_new(dir, prefix)?,
+            size: 0,
+            max_size,
+            max_files,
+        };
+        this.remove_old_files()?;
+        Ok(this)
+    }
+
+    fn open_new(dir: &Path, prefix: &str) -> io::Result<File> {
+        let timestamp = Local::now().format("%Y%m%d-%H%M%S-%3f");
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(format!("{prefix}-{timestamp}.log")))
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        self.file = Self::open_new(&self.dir, &self.prefix)?;
+        self.size = 0;
+        self.remove_old_files()
+    }
+
+    /// Remove the oldest log files, keeping at most `max_files` of them.
+    fn remove_old_files(&self) -> io::Result<()> {
+        let mut files = fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                path.is_file()
+                    && path.extension().is_some_and(|ext| ext == "log")
+                    && path.file_name().is_some_and(|name| {
+                        name.to_string_lossy()
+                            .starts_with(&format!("{}-", self.prefix))
+                    })
+            })
+            .collect::<Vec<_>>();
+        if files.len() <= self.max_files {
+            return Ok(());
+        }
+        // timestamps in file names are sortable, the oldest ones come first
+        files.sort();
+        for path in &files[..files.len() - self.max_files] {
+            // the file might still be opened by another running instance
+            _ = fs::remove_file(path);
+        }
+        Ok(())
+    }
+}
 
-    let bin_name = super::lowercase_program_name().unwrap_or(env!("CARGO_PKG_NAME").to_string());
+impl Write for RotatingLogFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.size > 0 && self.size + buf.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
 
-    Ok(LOG_FILE_PATH
-        .get_or_init(|| log_dir.join(format!("{bin_name}-{}.log", Local::now().date_naive()))))
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
 }
 
 /// Return `true` if the logger was already initialized.
 pub fn logger_is_set() -> bool {
     LOGGER_SET.get().copied().unwrap_or_default()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn rotate_log_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut file = RotatingLogFile::with_limits(dir.path(), "test", 10, 2).unwrap();
+        let count_files = || fs::read_dir(dir.path()).unwrap().count();
+
+        file.write_all(b"12345").unwrap();
+        file.write_all(b"12345").unwrap();
+        assert_eq!(count_files(), 1);
+
+        // make sure the timestamps of the rotated files are different
+        std::thread::sleep(Duration::from_millis(5));
+        file.write_all(b"1").unwrap();
+        assert_eq!(count_files(), 2);
+
+        std::thread::sleep(Duration::from_millis(5));
+        file.write_all(b"1234567890").unwrap();
+        assert_eq!(count_files(), 2);
+        file.flush().unwrap();
+
+        // the oldest file was removed, and the remaining ones are the latest two
+        let mut contents = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| fs::read_to_string(e.unwrap().path()).unwrap())
+            .collect::<Vec<_>>();
+        contents.sort();
+        assert_eq!(contents, ["1", "1234567890"]);
+    }
+}
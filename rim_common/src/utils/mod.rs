@@ -6,20 +6,28 @@ mod file_system;
 mod log;
 mod process;
 mod progress_bar;
+mod progress_event;
 
 use cfg_if::cfg_if;
 // Re-exports
-pub use download::DownloadOpt;
+pub use download::{
+    ensure_network_allowed, is_offline, set_offline, url_filename, CacheValidators,
+    ConditionalText, DownloadOpt,
+};
 pub use extraction::Extractable;
 pub use file_system::*;
 pub use log::*;
 pub use process::*;
 pub use progress_bar::*;
+pub use progress_event::{progress_channel, set_progress_sender, ProgressEvent};
 
 use std::{
     ffi::OsStr,
     path::{Path, PathBuf},
-    sync::{LazyLock, Mutex},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        LazyLock, Mutex,
+    },
     time::Duration,
 };
 
@@ -27,6 +35,7 @@ use anyhow::Result;
 use url::Url;
 
 static CURRENT_LOCALE: LazyLock<Mutex<String>> = LazyLock::new(|| Mutex::new(String::new()));
+static NO_COLOR: AtomicBool = AtomicBool::new(false);
 
 /// Insert a `.exe` postfix to given input.
 ///
@@ -139,6 +148,37 @@ pub fn url_join<S: AsRef<str>>(root: &Url, s: S) -> Result<Url> {
     Ok(result)
 }
 
+/// Check whether `url` is usable as a download server, which must be an absolute
+/// `http(s)` or `file` URL.
+///
+/// Something like `mirror.example.com:8080/rust` is a valid URL with the scheme
+/// `mirror.example.com`, which would otherwise fail with a confusing error when downloading.
+pub fn validate_server_url(url: &Url) -> Result<()> {
+    match url.scheme() {
+        "http" | "https" if url.has_host() => Ok(()),
+        "file" => Ok(()),
+        _ => anyhow::bail!("'{url}' is not an absolute `http(s)` or `file` URL"),
+    }
+}
+
+/// Check whether `index` is a valid cargo registry index URL, which can be an `http(s)`,
+/// `file`, `ssh` or `git` URL, or a `sparse+http(s)` URL for sparse registries.
+pub fn validate_registry_url(index: &str) -> Result<()> {
+    let (sparse, raw) = match index.strip_prefix("sparse+") {
+        Some(rest) => (true, rest),
+        None => (false, index),
+    };
+    let valid = Url::parse(raw).is_ok_and(|url| match url.scheme() {
+        "http" | "https" => url.has_host(),
+        "file" | "ssh" | "git" => !sparse,
+        _ => false,
+    });
+    if !valid {
+        anyhow::bail!("'{index}' is not a valid cargo registry index URL");
+    }
+    Ok(())
+}
+
 pub fn path_to_str(path: &Path) -> Result<&str> {
     path.to_str().ok_or_else(|| {
         anyhow::anyhow!(
@@ -188,6 +228,11 @@ pub fn to_string_lossy<S: AsRef<OsStr>>(s: S) -> String {
     s.as_ref().to_string_lossy().to_string()
 }
 
+/// Format a size in bytes into a human readable string, such as `1.50 MiB`.
+pub fn format_size(bytes: u64) -> String {
+    indicatif::HumanBytes(bytes).to_string()
+}
+
 /// Allowing the i18n framework to use the current system locale.
 pub fn use_current_locale() {
     let locale = sys_locale::get_locale().unwrap_or_else(|| "en".to_string());
@@ -211,12 +256,76 @@ pub fn set_locale(loc: &str) {
     *CURRENT_LOCALE.lock().unwrap() = loc.to_string();
 }
 
+/// Env var to override the display language, such as `en-US`.
+pub const RIM_LOCALE: &str = "RIM_LOCALE";
+
+/// Use the `requested` locale, or the one set by [`RIM_LOCALE`] env var if not specified,
+/// otherwise use the current system locale.
+///
+/// # Error
+/// If the requested locale is not available, this will fallback to the system locale
+/// and return an error, which should be reported as a warning after the logger was set up.
+pub fn use_requested_locale(requested: Option<&str>) -> Result<()> {
+    let requested = requested
+        .map(str::to_string)
+        .or_else(|| std::env::var(RIM_LOCALE).ok().filter(|s| !s.is_empty()));
+    let Some(requested) = requested else {
+        use_current_locale();
+        return Ok(());
+    };
+
+    if let Some(locale) = supported_locale(&requested) {
+        set_locale(locale);
+        Ok(())
+    } else {
+        use_current_locale();
+        anyhow::bail!(t!(
+            "unsupported_locale",
+            locale = requested,
+            available = LOCALES.join(", ")
+        ))
+    }
+}
+
+/// Find the available locale matching `requested`, ignoring ASCII case.
+fn supported_locale(requested: &str) -> Option<&'static str> {
+    LOCALES
+        .iter()
+        .find(|l| l.eq_ignore_ascii_case(requested))
+        .copied()
+}
+
+/// Lock the global locale and switch it to `locale` for as long as the guard lives,
+/// so tests depending on the language of messages won't race with each other.
+#[cfg(test)]
+pub(crate) fn lock_locale(locale: &str) -> std::sync::MutexGuard<'static, ()> {
+    static LOCALE_LOCK: Mutex<()> = Mutex::new(());
+    let guard = LOCALE_LOCK
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    set_locale(locale);
+    guard
+}
+
 /// Get the configured locale string from `configuration.toml`
 pub fn build_cfg_locale(key: &str) -> &str {
     let cur_locale = &*CURRENT_LOCALE.lock().unwrap();
     crate::cfg_locale!(cur_locale, key)
 }
 
+/// Disable ANSI styling of the CLI output, such as colored log labels and progress bars.
+pub fn set_no_color(no_color: bool) {
+    NO_COLOR.store(no_color, Ordering::Relaxed);
+}
+
+/// Return `true` if the CLI output should not be styled, either because it was disabled
+/// by [`set_no_color`], or the `NO_COLOR` environment variable is set to a non-empty value.
+///
+/// Check <https://no-color.org> for more details.
+pub fn no_color() -> bool {
+    NO_COLOR.load(Ordering::Relaxed) || std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty())
+}
+
 /// Waits until `duration` has elapsed.
 ///
 /// Note: Use this in `async` context rather than [`std::thread::sleep`].
@@ -234,16 +343,89 @@ pub fn has_desktop_environment() -> bool {
             // assuming MacOS has DE as well, although it might not always true,
             true
         } else {
-            // Linux desktop typically have one of these env set
-            ["DESKTOP_SESSION", "XDG_CURRENT_DESKTOP", "WAYLAND_DISPLAY"].into_iter()
-                .any(|env| std::env::var_os(env).is_some())
+            is_desktop_session(|key| std::env::var(key).ok())
         }
     }
 }
 
+/// Decide whether a (Unix) session has desktop environment, base on the
+/// environment variables retrieved by `get_env`.
+///
+/// A session is considered graphical if it has a Wayland or X11 display (including
+/// the forwarded ones over SSH), or its `XDG_SESSION_TYPE` says so.
+/// Otherwise, SSH sessions are treated as headless, while local sessions
+/// fallback to checking the name of desktop.
+#[cfg_attr(any(windows, target_os = "macos"), allow(dead_code))]
+fn is_desktop_session<F>(get_env: F) -> bool
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let is_set = |key: &str| get_env(key).is_some_and(|val| !val.trim().is_empty());
+
+    if is_set("WAYLAND_DISPLAY") || is_set("DISPLAY") {
+        return true;
+    }
+    match get_env("XDG_SESSION_TYPE").as_deref().map(str::trim) {
+        Some("wayland" | "x11" | "mir") => return true,
+        Some("tty") => return false,
+        _ => (),
+    }
+    if ["SSH_CONNECTION", "SSH_CLIENT", "SSH_TTY"]
+        .into_iter()
+        .any(is_set)
+    {
+        return false;
+    }
+    ["DESKTOP_SESSION", "XDG_CURRENT_DESKTOP"]
+        .into_iter()
+        .any(is_set)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::is_root_dir;
+    use super::{
+        is_desktop_session, is_root_dir, lock_locale, supported_locale, use_requested_locale,
+        validate_registry_url, validate_server_url, CURRENT_LOCALE,
+    };
+    use std::collections::HashMap;
+
+    fn desktop_with_env(vars: &[(&str, &str)]) -> bool {
+        let env: HashMap<&str, &str> = vars.iter().copied().collect();
+        is_desktop_session(|key| env.get(key).map(|val| val.to_string()))
+    }
+
+    #[test]
+    fn detect_desktop_session() {
+        // local sessions
+        assert!(desktop_with_env(&[("WAYLAND_DISPLAY", "wayland-0")]));
+        assert!(desktop_with_env(&[("DISPLAY", ":0")]));
+        assert!(desktop_with_env(&[("XDG_SESSION_TYPE", "wayland")]));
+        assert!(desktop_with_env(&[("XDG_CURRENT_DESKTOP", "GNOME")]));
+        assert!(!desktop_with_env(&[]));
+        assert!(!desktop_with_env(&[("DISPLAY", "")]));
+        assert!(!desktop_with_env(&[
+            ("XDG_SESSION_TYPE", "tty"),
+            ("XDG_CURRENT_DESKTOP", "GNOME")
+        ]));
+
+        // remote sessions
+        let ssh = ("SSH_CONNECTION", "10.0.0.2 51234 10.0.0.1 22");
+        assert!(!desktop_with_env(&[ssh]));
+        assert!(!desktop_with_env(&[ssh, ("DESKTOP_SESSION", "ubuntu")]));
+        assert!(desktop_with_env(&[ssh, ("DISPLAY", "localhost:10.0")]));
+    }
+
+    #[test]
+    fn requested_locale() {
+        assert_eq!(supported_locale("EN-us"), Some("en-US"));
+        assert_eq!(supported_locale("zh-cn"), Some("zh-CN"));
+        assert_eq!(supported_locale("xx-YY"), None);
+
+        let _guard = lock_locale("zh-CN");
+        use_requested_locale(Some("EN-us")).unwrap();
+        assert_eq!(*CURRENT_LOCALE.lock().unwrap(), "en-US");
+        assert!(use_requested_locale(Some("xx-YY")).is_err());
+    }
 
     #[test]
     fn root_dirs() {
@@ -259,4 +441,31 @@ mod tests {
             assert!(is_root_dir("C:\\\\"));
         }
     }
+
+    #[test]
+    fn server_url_validation() {
+        let valid = [
+            "https://example.com",
+            "http://127.0.0.1:8080/rustup",
+            "file:///path/to/dist",
+        ];
+        for url in valid {
+            assert!(validate_server_url(&url.parse().unwrap()).is_ok(), "{url}");
+        }
+        // missing scheme, where the host is treated as scheme
+        let invalid = ["mirror.example.com:8080/rust", "ftp://example.com"];
+        for url in invalid {
+            assert!(validate_server_url(&url.parse().unwrap()).is_err(), "{url}");
+        }
+    }
+
+    #[test]
+    fn registry_url_validation() {
+        assert!(validate_registry_url("sparse+https://example.com/index/").is_ok());
+        assert!(validate_registry_url("https://github.com/rust-lang/crates.io-index").is_ok());
+        assert!(validate_registry_url("ssh://git@example.com/index.git").is_ok());
+        assert!(validate_registry_url("sparse+ssh://git@example.com/index.git").is_err());
+        assert!(validate_registry_url("example.com/index").is_err());
+        assert!(validate_registry_url("sparse+").is_err());
+    }
 }
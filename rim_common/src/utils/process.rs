@@ -97,7 +97,32 @@ pub fn execute(cmd: Command) -> Result<()> {
 ///   otherwise this will ignore execution error and return the error code wrapped in `Ok`.
 /// - When `log_output` is `true`,
 ///   this will redirect the command output using [`os_pipe`] and log them using [`log`] interface.
-pub fn execute_command(mut cmd: Command, expect_success: bool, log_output: bool) -> Result<i32> {
+pub fn execute_command(cmd: Command, expect_success: bool, log_output: bool) -> Result<i32> {
+    if log_output {
+        run_command(
+            cmd,
+            expect_success,
+            Some(&mut log_output_line as &mut dyn FnMut(&str)),
+        )
+    } else {
+        run_command(cmd, expect_success, None)
+    }
+}
+
+/// Execute a command to finish, passing each line of its output (both `stdout` and `stderr`)
+/// to `handler` instead of logging it.
+///
+/// This is useful to keep track of the progress of a long running command,
+/// the output can still be logged in `handler` using [`log_output_line`].
+pub fn execute_with_output_handler<F: FnMut(&str)>(cmd: Command, mut handler: F) -> Result<()> {
+    run_command(cmd, true, Some(&mut handler as &mut dyn FnMut(&str))).map(|_| ())
+}
+
+fn run_command(
+    mut cmd: Command,
+    expect_success: bool,
+    output_handler: Option<&mut dyn FnMut(&str)>,
+) -> Result<i32> {
     // ensure the program can be executed.
     // NB: sometime the program might only be a name not a path,
     // therefore we need to trace back to its path and set exec bit if it has none.
@@ -110,7 +135,7 @@ pub fn execute_command(mut cmd: Command, expect_success: bool, log_output: bool)
     };
     set_exec_permission(program_full_path)?;
 
-    let (mut child, cmd_content) = if log_output {
+    let (mut child, cmd_content) = if let Some(handler) = output_handler {
         let (mut reader, stdout) = os_pipe::pipe()?;
         let stderr = stdout.try_clone()?;
 
@@ -118,7 +143,7 @@ pub fn execute_command(mut cmd: Command, expect_success: bool, log_output: bool)
 
         // NB: to prevent deadlock, `cmd` must be dropped before reading from `reader`
         let cmd_content = cmd_to_string(cmd);
-        output_to_log(&mut reader);
+        read_lines(&mut reader, handler);
 
         (child, cmd_content)
     } else {
@@ -158,20 +183,25 @@ fn get_ret_code(status: &ExitStatus) -> i32 {
     }
 }
 
-/// Log the command output
-fn output_to_log<R: io::Read>(from: &mut R) {
+/// Read the command output line by line and pass each line to `handler`.
+fn read_lines<R: io::Read>(from: &mut R, handler: &mut dyn FnMut(&str)) {
     let reader = BufReader::new(from);
     for line in reader.lines().map_while(Result::ok) {
-        // prevent double 'info|warn|error:' labels, although this might be a dumb way to do it
-        if let Some(info) = line.strip_prefix("info: ") {
-            info!("{info}");
-        } else if let Some(warn) = line.strip_prefix("warn: ") {
-            warn!("{warn}");
-        } else if let Some(error) = line.strip_prefix("error: ") {
-            error!("{error}");
-        } else if !line.is_empty() {
-            info!("{line}");
-        }
+        handler(&line);
+    }
+}
+
+/// Log a single line of command output.
+pub fn log_output_line(line: &str) {
+    // prevent double 'info|warn|error:' labels, although this might be a dumb way to do it
+    if let Some(info) = line.strip_prefix("info: ") {
+        info!("{info}");
+    } else if let Some(warn) = line.strip_prefix("warn: ") {
+        warn!("{warn}");
+    } else if let Some(error) = line.strip_prefix("error: ") {
+        error!("{error}");
+    } else if !line.is_empty() {
+        info!("{line}");
     }
 }
 
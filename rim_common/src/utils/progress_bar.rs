@@ -1,12 +1,17 @@
 //! Progress bar indicator for commandline user interface.
 
 use std::{
+    collections::VecDeque,
+    fmt::Write,
     sync::{Arc, Mutex},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use anyhow::Result;
-use indicatif::{ProgressBar as CliProgressBar, ProgressState, ProgressStyle};
+use indicatif::style::ProgressTracker;
+use indicatif::{ProgressBar as CliProgressBar, ProgressDrawTarget, ProgressState, ProgressStyle};
+
+use super::progress_event::{self, ProgressEvent};
 
 struct ProgressPos(Mutex<f32>);
 
@@ -48,15 +53,25 @@ impl<'a> Progress<'a> {
         self
     }
 
+    /// Get the current position of progress bar.
+    pub fn pos(&self) -> f32 {
+        self.pos.load()
+    }
+
     /// Update the position of progress bar by increment a certain value.
     ///
     /// If a value given is `None`, this will increase the position by the whole `len`,
     /// otherwise it will increase the desired value instead.
+    ///
+    /// The new position is also sent as a [`ProgressEvent::Master`] event
+    /// if there's a progress sender.
     // FIXME: split `inc(None)` to a new function, such as `inc_len`, cuz this is kinda confusing.
     pub fn inc(&self, value: Option<f32>) -> Result<()> {
         let delta = value.unwrap_or(self.len);
         self.pos.add(delta);
-        (self.pos_callback)(self.pos.load())?;
+        let pos = self.pos.load();
+        (self.pos_callback)(pos)?;
+        progress_event::send_progress_event(ProgressEvent::Master { percent: pos });
         Ok(())
     }
 }
@@ -96,23 +111,100 @@ impl Style {
     }
 }
 
+/// Estimates the remaining time of a progress base on the throughput
+/// of the last [`WINDOW`](RollingEta::WINDOW), so that it reacts to
+/// the changes of download speed instead of averaging the whole progress.
+#[derive(Debug, Clone, Default)]
+struct RollingEta {
+    /// Recently recorded `(time, position)` pairs, oldest first.
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl RollingEta {
+    /// Only the samples recorded within this duration are used to compute throughput.
+    const WINDOW: Duration = Duration::from_secs(5);
+
+    fn record(&mut self, pos: u64, now: Instant) {
+        // position went backwards, meaning the progress was restarted
+        if self.samples.back().is_some_and(|(_, last)| pos < *last) {
+            self.samples.clear();
+        }
+        self.samples.push_back((now, pos));
+        // always keep at least two samples to be able to compute throughput
+        while self.samples.len() > 2
+            && self
+                .samples
+                .front()
+                .is_some_and(|(t, _)| now.duration_since(*t) > Self::WINDOW)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Number of units (bytes, items) per second, `None` if unknown.
+    fn throughput(&self) -> Option<f64> {
+        let ((start, first), (end, last)) = (self.samples.front()?, self.samples.back()?);
+        let secs = end.duration_since(*start).as_secs_f64();
+        let rate = last.saturating_sub(*first) as f64 / secs;
+        (rate.is_finite() && rate > 0.0).then_some(rate)
+    }
+
+    /// Estimated remaining time to reach `len` from `pos`, `None` if unknown.
+    fn estimate(&self, pos: u64, len: Option<u64>) -> Option<Duration> {
+        let remaining = len?.saturating_sub(pos);
+        let rate = self.throughput()?;
+        Some(Duration::from_secs_f64(remaining as f64 / rate))
+    }
+}
+
+impl ProgressTracker for RollingEta {
+    fn clone_box(&self) -> Box<dyn ProgressTracker> {
+        Box::new(self.clone())
+    }
+
+    fn tick(&mut self, state: &ProgressState, now: Instant) {
+        self.record(state.pos(), now);
+    }
+
+    fn reset(&mut self, _state: &ProgressState, _now: Instant) {
+        self.samples.clear();
+    }
+
+    fn write(&self, state: &ProgressState, w: &mut dyn Write) {
+        write!(w, "{}", format_eta(self.estimate(state.pos(), state.len())))
+            .expect("unable to display progress bar");
+    }
+}
+
+/// Format an ETA in a compact form, such as `42.0s`, `3m05s`, `1h02m`,
+/// or `--` if it is unknown.
+fn format_eta(eta: Option<Duration>) -> String {
+    let Some(eta) = eta else {
+        return "--".into();
+    };
+    let secs = eta.as_secs();
+    match secs {
+        0..60 => format!("{:.1}s", eta.as_secs_f64()),
+        60..3600 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
 // TODO: Mark this with cfg(feature = "cli")
 impl CliProgress<CliProgressBar> {
     /// Create a new progress bar for CLI to indicate download progress.
     ///
     /// When `hidden` is set to `true`, no progress bar will be shown.
+    /// When color is disabled (check [`no_color`](super::no_color)), the progress
+    /// will be printed as plain percentage lines instead.
+    /// When there's a progress sender (check [`set_progress_sender`](super::set_progress_sender)),
+    /// the progress will be sent as [`ProgressEvent`]s instead, regardless of `hidden`.
     pub fn new(hidden: bool) -> Self {
         fn start(msg: String, style: Style) -> Result<CliProgressBar> {
             let apply_custom_style = |pb: &CliProgressBar, pattern: &str| -> Result<()> {
                 pb.set_style(
                     ProgressStyle::with_template(pattern)?
-                        .with_key(
-                            "eta",
-                            |state: &ProgressState, w: &mut dyn std::fmt::Write| {
-                                write!(w, "{:.1}s", state.eta().as_secs_f64())
-                                    .expect("unable to display progress bar")
-                            },
-                        )
+                        .with_key("eta", RollingEta::default())
                         .progress_chars("#>-"),
                 );
                 Ok(())
@@ -142,12 +234,16 @@ impl CliProgress<CliProgressBar> {
             pb.finish_with_message(msg);
         }
 
-        if hidden {
+        if progress_event::has_progress_sender() {
+            Self::channel()
+        } else if hidden {
             CliProgress {
                 start: |_: String, _: Style| Ok(CliProgressBar::hidden()),
                 update: |_: &CliProgressBar, _: Option<u64>| {},
                 stop: |_: &CliProgressBar, _: String| {},
             }
+        } else if super::no_color() {
+            Self::plain()
         } else {
             CliProgress {
                 start,
@@ -158,6 +254,86 @@ impl CliProgress<CliProgressBar> {
     }
 }
 
+impl CliProgress<CliProgressBar> {
+    /// Create a progress indicator that prints the progress as plain text lines,
+    /// such as `50%`, which is suitable for non-TTY output like CI logs.
+    fn plain() -> Self {
+        /// Only print the percentage in steps of this value to avoid flooding the output.
+        const STEP: u64 = 10;
+
+        fn start(msg: String, style: Style) -> Result<CliProgressBar> {
+            println!("{msg}");
+            let len = match style {
+                Style::Bytes(total) | Style::Len(total) => Some(total),
+                Style::Spinner { .. } => None,
+            };
+            // the bar is never drawn, it's only used to keep track of the position
+            Ok(CliProgressBar::with_draw_target(
+                len,
+                ProgressDrawTarget::hidden(),
+            ))
+        }
+        fn update(pb: &CliProgressBar, pos: Option<u64>) {
+            let (Some(pos), Some(len)) = (pos, pb.length()) else {
+                return;
+            };
+            let percent = |p: u64| p.min(len) * 100 / len.max(1);
+            let (prev, cur) = (percent(pb.position()), percent(pos));
+            pb.set_position(pos);
+            if cur / STEP > prev / STEP {
+                println!("{cur}%");
+            }
+        }
+        fn stop(_pb: &CliProgressBar, msg: String) {
+            println!("{msg}");
+        }
+
+        CliProgress {
+            start,
+            update,
+            stop,
+        }
+    }
+}
+
+impl CliProgress<CliProgressBar> {
+    /// Create a progress indicator that sends [`ProgressEvent`]s to the progress sender.
+    fn channel() -> Self {
+        fn start(msg: String, style: Style) -> Result<CliProgressBar> {
+            let len = match style {
+                Style::Bytes(total) | Style::Len(total) => Some(total),
+                Style::Spinner { .. } => None,
+            };
+            progress_event::send_progress_event(ProgressEvent::SubStart { message: msg, len });
+            // the bar is never drawn, it's only used to keep track of the position
+            Ok(CliProgressBar::with_draw_target(
+                len,
+                ProgressDrawTarget::hidden(),
+            ))
+        }
+        fn update(pb: &CliProgressBar, pos: Option<u64>) {
+            let len = pb.length();
+            if let (Some(p), Some(l)) = (pos, len) {
+                let prev = pb.position();
+                pb.set_position(p);
+                if !progress_event::percent_changed(prev, p, l) {
+                    return;
+                }
+            }
+            progress_event::send_progress_event(ProgressEvent::SubUpdate { pos, len });
+        }
+        fn stop(_pb: &CliProgressBar, msg: String) {
+            progress_event::send_progress_event(ProgressEvent::SubFinish { message: msg });
+        }
+
+        CliProgress {
+            start,
+            update,
+            stop,
+        }
+    }
+}
+
 impl Default for CliProgress<CliProgressBar> {
     fn default() -> Self {
         Self::new(false)
@@ -166,7 +342,7 @@ impl Default for CliProgress<CliProgressBar> {
 
 #[cfg(test)]
 mod tests {
-    use super::ProgressPos;
+    use super::*;
 
     #[test]
     fn progress_pos_add() {
@@ -179,4 +355,36 @@ mod tests {
         orig.add(10.0);
         assert_eq!(orig.load(), 13.0);
     }
+
+    #[test]
+    fn rolling_eta() {
+        let start = Instant::now();
+        let mut eta = RollingEta::default();
+        assert_eq!(eta.estimate(0, Some(100)), None);
+
+        eta.record(0, start);
+        assert_eq!(eta.estimate(0, Some(100)), None);
+        eta.record(10, start + Duration::from_secs(1));
+        assert_eq!(eta.estimate(10, Some(100)), Some(Duration::from_secs(9)));
+        // length unknown
+        assert_eq!(eta.estimate(10, None), None);
+
+        // old samples are dropped, only the recent throughput counts
+        eta.record(20, start + Duration::from_secs(10));
+        eta.record(60, start + Duration::from_secs(11));
+        assert_eq!(eta.estimate(60, Some(100)), Some(Duration::from_secs(1)));
+
+        // stalled
+        eta.record(60, start + Duration::from_secs(20));
+        eta.record(60, start + Duration::from_secs(21));
+        assert_eq!(eta.estimate(60, Some(100)), None);
+    }
+
+    #[test]
+    fn eta_formatting() {
+        assert_eq!(format_eta(None), "--");
+        assert_eq!(format_eta(Some(Duration::from_millis(4200))), "4.2s");
+        assert_eq!(format_eta(Some(Duration::from_secs(185))), "3m05s");
+        assert_eq!(format_eta(Some(Duration::from_secs(3720))), "1h02m");
+    }
 }
@@ -0,0 +1,101 @@
+//! Structured progress events for embedders, which allows building a richer progress UI
+//! without parsing the log messages.
+//!
+//! Once a sender was set by [`set_progress_sender`] (or [`progress_channel`]), the overall
+//! progress reported by [`Progress`](super::Progress), the sub-progress of downloading and
+//! extracting reported by [`CliProgress`](super::CliProgress), and the log messages will all be
+//! sent as [`ProgressEvent`]s.
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+static EVENT_SENDER: Mutex<Option<Sender<ProgressEvent>>> = Mutex::new(None);
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ProgressEvent {
+    /// The overall progress of the current installation or uninstallation,
+    /// from `0.0` to `100.0`.
+    Master { percent: f32 },
+    /// A sub-task started, such as downloading or extracting a package,
+    /// `len` is `None` if the task only ticks without a known length.
+    SubStart { message: String, len: Option<u64> },
+    /// The position of current sub-task was updated, `pos` is `None` if it only ticked.
+    SubUpdate { pos: Option<u64>, len: Option<u64> },
+    /// The current sub-task finished.
+    SubFinish { message: String },
+    /// A log message, with a lowercased level such as `info` or `warn`.
+    Log { level: String, message: String },
+}
+
+/// Send the progress events to `sender`, or stop sending them if it's `None`.
+///
+/// Note that the progress bars will no longer be drawn on terminal once a sender is set.
+pub fn set_progress_sender(sender: Option<Sender<ProgressEvent>>) {
+    *EVENT_SENDER.lock().unwrap() = sender;
+}
+
+/// Create a channel and start sending the progress events to it,
+/// returning the receiver end of it.
+pub fn progress_channel() -> Receiver<ProgressEvent> {
+    let (tx, rx) = mpsc::channel();
+    set_progress_sender(Some(tx));
+    rx
+}
+
+/// Check whether there is a sender to send progress events to.
+pub(crate) fn has_progress_sender() -> bool {
+    EVENT_SENDER.lock().unwrap().is_some()
+}
+
+/// Send an event if there is a progress sender, the sender will be dropped
+/// if its receiver is gone.
+pub(crate) fn send_progress_event(event: ProgressEvent) {
+    let mut guard = EVENT_SENDER.lock().unwrap();
+    if let Some(sender) = guard.as_ref() {
+        if sender.send(event).is_err() {
+            *guard = None;
+        }
+    }
+}
+
+/// Whether the integer percentage changed when moving from `prev` to `cur`,
+/// which is used to avoid flooding the channel with tiny updates.
+pub(crate) fn percent_changed(prev: u64, cur: u64, len: u64) -> bool {
+    let percent = |p: u64| p.min(len) * 100 / len.max(1);
+    percent(prev) != percent(cur)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialize_progress_events() {
+        let event = ProgressEvent::Master { percent: 50.0 };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"type":"master","percent":50.0}"#
+        );
+        let event = ProgressEvent::SubStart {
+            message: "downloading 'a'".into(),
+            len: Some(1024),
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"type":"subStart","message":"downloading 'a'","len":1024}"#
+        );
+    }
+
+    #[test]
+    fn sub_update_throttling() {
+        assert!(!percent_changed(0, 5, 1000));
+        assert!(percent_changed(5, 10, 1000));
+        assert!(!percent_changed(1000, 2000, 1000));
+        assert!(percent_changed(0, 1, 1));
+        // zero length
+        assert!(!percent_changed(0, 1, 0));
+    }
+}
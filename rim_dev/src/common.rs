@@ -7,6 +7,7 @@ use std::sync::OnceLock;
 use std::time::Duration;
 
 use anyhow::{anyhow, bail, Result};
+pub(crate) use rim_common::utils::calculate_sha256;
 use rim_common::utils::{copy_as, walk_dir};
 
 fn rim_gui_dir() -> &'static Path {
@@ -97,6 +98,36 @@ where
     Ok(())
 }
 
+pub fn compress_gz<S, D>(src: S, dest: D) -> Result<()>
+where
+    S: AsRef<Path>,
+    D: AsRef<Path>,
+{
+    use flate2::write::GzEncoder;
+
+    let tar_file = fs::File::create(dest)?;
+    let encoding = GzEncoder::new(tar_file, flate2::Compression::default());
+    let mut tar = tar::Builder::new(encoding);
+
+    let name = src.as_ref().file_name().unwrap_or(OsStr::new("/"));
+    if src.as_ref().is_file() {
+        tar.append_path_with_name(src.as_ref(), name)?;
+    } else {
+        tar.append_dir_all(name, src.as_ref())?;
+    }
+    tar.into_inner()?.finish()?;
+    Ok(())
+}
+
+pub fn compress_7z<S, D>(src: S, dest: D) -> Result<()>
+where
+    S: AsRef<Path>,
+    D: AsRef<Path>,
+{
+    sevenz_rust::compress_to_path(src.as_ref(), dest.as_ref())?;
+    Ok(())
+}
+
 pub fn compress_zip<S, D>(src: S, dest: D) -> Result<()>
 where
     S: AsRef<Path>,
@@ -134,12 +165,8 @@ where
     Ok(())
 }
 
-/// Download a file from `url` to local disk, do nothing if it already exists.
+/// Download a file from `url` to local disk, replacing `dest` if it already exists.
 pub fn download<P: AsRef<Path>>(url: &str, dest: P) -> Result<()> {
-    if dest.as_ref().is_file() {
-        return Ok(());
-    }
-
     println!("downloading: {url}");
     let resp = reqwest::blocking::Client::builder()
         .timeout(Duration::from_secs(180))
@@ -162,3 +189,24 @@ pub fn download<P: AsRef<Path>>(url: &str, dest: P) -> Result<()> {
     copy_as(temp_file.path(), dest)?;
     Ok(())
 }
+
+/// Get the size of the file located at `url` by sending a `HEAD` request,
+/// return `None` if it's unknown.
+pub fn remote_file_size(url: &str) -> Option<u64> {
+    let resp = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .ok()?
+        .head(url)
+        .send()
+        .ok()?;
+    if !resp.status().is_success() {
+        return None;
+    }
+    resp.headers()
+        .get(reqwest::header::CONTENT_LENGTH)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+}
@@ -1,14 +1,21 @@
 use env::consts::EXE_SUFFIX;
 use rim_common::build_config;
-use rim_common::utils::{copy_as, copy_file, ensure_dir};
+use rim_common::utils::{copy_as_parallel, copy_file, ensure_dir};
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::{env, fs};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::{env, fs, thread};
 
 use anyhow::{bail, Context, Result};
 
 use crate::common::*;
-use crate::toolkits_parser::{ReleaseMode, Toolkit, Toolkits, PACKAGE_DIR};
+use crate::toolkits_parser::{ReleaseMode, Target, Toolkit, Toolkits, PACKAGE_DIR};
+
+const CHECKSUMS_FILENAME: &str = "SHA256SUMS";
+
+/// Held while running the GUI build command, check [`DistWorker::build_binary`].
+static GUI_BUILD_LOCK: Mutex<()> = Mutex::new(());
 
 pub const DIST_HELP: &str = r#"
 Generate release binaries
@@ -23,7 +30,23 @@ Options:
     -n, --name      Specify another name of toolkit to distribute
     -b, --binary-only
                     Build binary only (net-installer), skip offline package generation
+        --no-checksums
+                    Skip generating the `SHA256SUMS` file for the distributed artifacts
+    -j, --jobs      Number of dist targets to build in parallel, defaulting to 1,
+                    note that each target other than the native one will be built
+                    in its own target directory when this is greater than 1
     -h, -help       Print this help message
+
+Environment variables:
+    RIM_SIGNTOOL    Path to `signtool`, sign the Windows installer binaries if set,
+                    which also requires one of the certificate configurations below
+    RIM_SIGN_CERT   Path to the certificate file (.pfx) for signing
+    RIM_SIGN_CERT_PASSWORD
+                    Password of the certificate file
+    RIM_SIGN_CERT_SHA1
+                    SHA1 thumbprint of a certificate in the certificate store for signing
+    RIM_SIGN_TIMESTAMP_URL
+                    URL of the RFC 3161 timestamp server
 "#;
 
 /// A dist worker has two basic jobs:
@@ -36,6 +59,8 @@ struct DistWorker<'a> {
     toolkit: &'a Toolkit,
     /// Target triple for building the installer binaries
     build_target: &'a str,
+    /// The cargo target directory to build the binaries in
+    target_dir: &'a Path,
     /// Target triples to distribute
     dist_target: &'a str,
     edition: &'a str,
@@ -45,6 +70,7 @@ impl<'a> DistWorker<'a> {
     fn new_(
         toolkit: &'a Toolkit,
         build_target: &'a str,
+        target_dir: &'a Path,
         is_cli: bool,
         edition: &'a str,
         dist_target: &'a str,
@@ -52,6 +78,7 @@ impl<'a> DistWorker<'a> {
         Self {
             toolkit,
             build_target,
+            target_dir,
             is_cli,
             edition,
             dist_target,
@@ -62,18 +89,34 @@ impl<'a> DistWorker<'a> {
         toolkit: &'a Toolkit,
         edition: &'a str,
         build_target: &'a str,
+        target_dir: &'a Path,
         dist_target: &'a str,
     ) -> Self {
-        Self::new_(toolkit, build_target, true, edition, dist_target)
+        Self::new_(
+            toolkit,
+            build_target,
+            target_dir,
+            true,
+            edition,
+            dist_target,
+        )
     }
 
     fn gui(
         toolkit: &'a Toolkit,
         edition: &'a str,
         build_target: &'a str,
+        target_dir: &'a Path,
         dist_target: &'a str,
     ) -> Self {
-        Self::new_(toolkit, build_target, false, edition, dist_target)
+        Self::new_(
+            toolkit,
+            build_target,
+            target_dir,
+            false,
+            edition,
+            dist_target,
+        )
     }
 
     /// The compiled binary name
@@ -156,12 +199,24 @@ impl<'a> DistWorker<'a> {
         let mut cmd = self.command(noweb);
         cmd.env("HOST_TRIPLE", self.dist_target);
         cmd.env("EDITION", self.edition);
+        cmd.env("CARGO_TARGET_DIR", self.target_dir);
 
-        let status = cmd.status()?;
+        let status = if self.is_cli {
+            cmd.status()?
+        } else {
+            // GUI builds also build the frontend into the same output directory,
+            // so they cannot run in parallel even with different target directories.
+            let _guard = GUI_BUILD_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            cmd.status()?
+        };
         if status.success() {
             // when not using cross compilation, we are not running `cargo build` with
             // `--target` option, therefore the release dir's path will not have a target in it.
-            let src = release_dir(self.build_target).join(self.source_binary_name());
+            let src =
+                release_dir(self.target_dir, self.build_target).join(self.source_binary_name());
+            if cfg!(windows) && self.dist_target.contains("windows") {
+                sign_binary(&src)?;
+            }
             // copy and rename the binary with vendor name
             let to = dest_dir.join(self.dest_binary_name(noweb, self.is_cli));
             copy_file(src, to)?;
@@ -195,18 +250,27 @@ impl<'a> DistWorker<'a> {
                 src_pkg_dir.display()
             );
         }
-        copy_as(&src_pkg_dir, &dist_pkg_dir)?;
+        copy_as_parallel(&src_pkg_dir, &dist_pkg_dir)?;
 
         Ok(dist_pkg_dir)
     }
 }
 
+/// A dist target to build, along with the cargo target directory to build it in.
+#[derive(Debug)]
+struct DistJob<'a> {
+    target: &'a Target,
+    target_dir: PathBuf,
+}
+
 pub fn dist(
     mode: ReleaseMode,
     binary_only: bool,
+    no_checksums: bool,
     name: Option<String>,
     build_target: String,
     mut dist_targets: Vec<String>,
+    jobs: usize,
 ) -> Result<()> {
     let edition = name.as_deref().unwrap_or(env!("EDITION"));
     let toolkits = Toolkits::load()?;
@@ -223,6 +287,7 @@ pub fn dist(
         dist_targets.push(build_target.clone());
     }
 
+    let mut dist_jobs = vec![];
     for dist_target in &dist_targets {
         let Some(supported_target) = toolkits
             .config
@@ -233,49 +298,90 @@ pub fn dist(
             println!("skipping unsupported target '{dist_target}'");
             continue;
         };
+        dist_jobs.push(DistJob {
+            target: supported_target,
+            target_dir: target_dir_for(&build_target, dist_target, jobs),
+        });
+    }
 
-        let mut workers = vec![];
-
-        let mode = if let Some(mode_override) = supported_target.release_mode() {
-            println!("overriding dist mode to '{mode_override:?}'");
+    let groups = group_by_target_dir(dist_jobs);
+    let results = run_in_parallel(&groups, jobs, |job| {
+        let dist_target = job.target.triple();
+        let mode = if let Some(mode_override) = job.target.release_mode() {
+            println!("[{dist_target}] overriding dist mode to '{mode_override:?}'");
             mode_override
         } else {
             mode
         };
+        let ctx = DistContext {
+            toolkit,
+            edition,
+            build_target: &build_target,
+            target_dir: &job.target_dir,
+            binary_only,
+            no_checksums,
+        };
+        ctx.dist(dist_target, mode)
+    });
+
+    let total = results.len();
+    let mut failed = 0;
+    println!("\ndist summary:");
+    for (target, res) in results {
+        match res {
+            Ok(()) => println!("    {target}: succeeded"),
+            Err(e) => {
+                failed += 1;
+                println!("    {target}: failed: {e:#}");
+            }
+        }
+    }
+    if failed > 0 {
+        bail!("failed to distribute {failed} of {total} targets");
+    }
+    Ok(())
+}
 
-        match mode {
-            ReleaseMode::Cli => workers.push(DistWorker::cli(
-                toolkit,
-                edition,
-                &build_target,
+/// The options shared by every dist target.
+struct DistContext<'a> {
+    toolkit: &'a Toolkit,
+    edition: &'a str,
+    build_target: &'a str,
+    target_dir: &'a Path,
+    binary_only: bool,
+    no_checksums: bool,
+}
+
+impl DistContext<'_> {
+    fn dist(&self, dist_target: &str, mode: ReleaseMode) -> Result<()> {
+        let cli = || {
+            DistWorker::cli(
+                self.toolkit,
+                self.edition,
+                self.build_target,
+                self.target_dir,
                 dist_target,
-            )),
-            ReleaseMode::Gui => workers.push(DistWorker::gui(
-                toolkit,
-                edition,
-                &build_target,
+            )
+        };
+        let gui = || {
+            DistWorker::gui(
+                self.toolkit,
+                self.edition,
+                self.build_target,
+                self.target_dir,
                 dist_target,
-            )),
-            ReleaseMode::Both => {
-                workers.push(DistWorker::cli(
-                    toolkit,
-                    edition,
-                    &build_target,
-                    dist_target,
-                ));
-                workers.push(DistWorker::gui(
-                    toolkit,
-                    edition,
-                    &build_target,
-                    dist_target,
-                ));
-            }
-        }
+            )
+        };
+        let workers = match mode {
+            ReleaseMode::Cli => vec![cli()],
+            ReleaseMode::Gui => vec![gui()],
+            ReleaseMode::Both => vec![cli(), gui()],
+        };
 
         let mut offline_dist_dir = None;
         for worker in workers {
             worker.dist_net_installer()?;
-            if !binary_only {
+            if !self.binary_only {
                 offline_dist_dir = Some(worker.dist_noweb_installer()?);
             }
         }
@@ -289,8 +395,154 @@ pub fn dist(
             compress_offline_package(&dir, dist_target)?;
             fs::remove_dir_all(&dir)?;
         }
+
+        if !self.no_checksums {
+            write_checksums(&dist_dir(dist_target)?)?;
+        }
+        Ok(())
+    }
+}
+
+/// Get the cargo target directory to build the binaries of `dist_target` in.
+///
+/// Every dist target is built with the same `build_target`, which means they would
+/// be writing to the same `<target dir>/<build target>/release` directory. Therefore when
+/// building in parallel, targets other than the native one get their own target directory,
+/// otherwise everything is built in the default one to make the most of the build cache.
+fn target_dir_for(build_target: &str, dist_target: &str, jobs: usize) -> PathBuf {
+    let default = default_target_dir();
+    if jobs <= 1 || build_target == dist_target {
+        default
+    } else {
+        default.join("dist").join(dist_target)
+    }
+}
+
+/// Group the jobs by their target directories, as the ones sharing
+/// a target directory must be built one after another.
+fn group_by_target_dir(jobs: Vec<DistJob<'_>>) -> Vec<Vec<DistJob<'_>>> {
+    let mut groups: Vec<Vec<DistJob<'_>>> = vec![];
+    for job in jobs {
+        match groups
+            .iter_mut()
+            .find(|group| group[0].target_dir == job.target_dir)
+        {
+            Some(group) => group.push(job),
+            None => groups.push(vec![job]),
+        }
+    }
+    groups
+}
+
+/// Run `f` on every job using at most `jobs` threads at the same time, where the jobs in
+/// the same group are run sequentially, then return the result of each job's target.
+///
+/// A failed job does not stop the others from running.
+fn run_in_parallel<F>(groups: &[Vec<DistJob<'_>>], jobs: usize, f: F) -> Vec<(String, Result<()>)>
+where
+    F: Fn(&DistJob<'_>) -> Result<()> + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(vec![]);
+
+    let worker = || {
+        while let Some(group) = groups.get(next.fetch_add(1, Ordering::Relaxed)) {
+            for job in group {
+                let res = f(job);
+                let mut guard = results.lock().unwrap_or_else(|e| e.into_inner());
+                guard.push((job.target.triple().to_string(), res));
+            }
+        }
+    };
+
+    thread::scope(|scope| {
+        let handles = (0..jobs.clamp(1, groups.len().max(1)))
+            .map(|_| scope.spawn(worker))
+            .collect::<Vec<_>>();
+        for handle in handles {
+            if handle.join().is_err() {
+                println!("a dist thread panicked");
+            }
+        }
+    });
+
+    let mut results = results.into_inner().unwrap_or_else(|e| e.into_inner());
+    // keep the order as the targets were given
+    let order = groups
+        .iter()
+        .flatten()
+        .map(|job| job.target.triple())
+        .collect::<Vec<_>>();
+    results.sort_by_key(|(target, _)| order.iter().position(|t| t == target));
+    results
+}
+
+/// Write the SHA-256 checksum of every file under `dir` into a `SHA256SUMS` file,
+/// in the same format as the output of `sha256sum`.
+fn write_checksums(dir: &Path) -> Result<()> {
+    let mut files = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.file_name()
+                .is_some_and(|name| name != CHECKSUMS_FILENAME)
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+
+    let mut content = String::new();
+    for file in &files {
+        let checksum = calculate_sha256(file)?;
+        let filename = file
+            .file_name()
+            .and_then(|name| name.to_str())
+            .with_context(|| format!("file name of '{}' is not valid UTF-8", file.display()))?;
+        content.push_str(&format!("{checksum}  {filename}\n"));
     }
 
+    let dest = dir.join(CHECKSUMS_FILENAME);
+    fs::write(&dest, content)?;
+    println!("checksums written to '{}'", dest.display());
+    Ok(())
+}
+
+/// Sign the given binary using `signtool`, if `RIM_SIGNTOOL` env var is set,
+/// otherwise do nothing.
+fn sign_binary(path: &Path) -> Result<()> {
+    let Some(signtool) = env::var_os("RIM_SIGNTOOL") else {
+        return Ok(());
+    };
+
+    let mut cmd = Command::new(signtool);
+    cmd.args(["sign", "/fd", "SHA256"]);
+    if let Some(cert) = env::var_os("RIM_SIGN_CERT") {
+        cmd.arg("/f").arg(cert);
+        if let Some(password) = env::var_os("RIM_SIGN_CERT_PASSWORD") {
+            cmd.arg("/p").arg(password);
+        }
+    } else if let Some(thumbprint) = env::var_os("RIM_SIGN_CERT_SHA1") {
+        cmd.arg("/sha1").arg(thumbprint);
+    } else {
+        bail!(
+            "`RIM_SIGNTOOL` is set but no certificate was configured, \
+            please set either `RIM_SIGN_CERT` or `RIM_SIGN_CERT_SHA1`"
+        );
+    }
+    if let Some(timestamp_url) = env::var_os("RIM_SIGN_TIMESTAMP_URL") {
+        cmd.arg("/tr").arg(timestamp_url).args(["/td", "SHA256"]);
+    }
+    cmd.arg(path);
+
+    println!("signing binary: {}", path.display());
+    let output = cmd.output().context("unable to run `signtool`")?;
+    if !output.status.success() {
+        bail!(
+            "failed to sign '{}':\n{}{}",
+            path.display(),
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
     Ok(())
 }
 
@@ -319,14 +571,16 @@ fn compress_offline_package(dir: &Path, target: &str) -> Result<()> {
     Ok(())
 }
 
-/// Path to target release directory
-fn release_dir(target: &str) -> PathBuf {
-    let mut res = env::var("CARGO_TARGET_DIR")
+/// Path to the default cargo target directory
+fn default_target_dir() -> PathBuf {
+    env::var("CARGO_TARGET_DIR")
         .map(PathBuf::from)
-        .unwrap_or_else(|_| PathBuf::from(env!("CARGO_MANIFEST_DIR")).with_file_name("target"));
-    res.push(target);
-    res.push("release");
-    res
+        .unwrap_or_else(|_| PathBuf::from(env!("CARGO_MANIFEST_DIR")).with_file_name("target"))
+}
+
+/// Path to target release directory
+fn release_dir(target_dir: &Path, target: &str) -> PathBuf {
+    target_dir.join(target).join("release")
 }
 
 /// Path to the directory to store dist artifacts for given target
@@ -337,3 +591,66 @@ fn dist_dir(target: &str) -> Result<PathBuf> {
     ensure_dir(&res)?;
     Ok(res)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn group_dist_jobs() {
+        let build_target = "x86_64-pc-windows-msvc";
+        let targets = [
+            Target::Simple("x86_64-pc-windows-msvc".into()),
+            Target::Simple("x86_64-pc-windows-gnu".into()),
+            Target::Simple("x86_64-unknown-linux-gnu".into()),
+        ];
+        let jobs_with = |jobs| {
+            targets
+                .iter()
+                .map(|target| DistJob {
+                    target,
+                    target_dir: target_dir_for(build_target, target.triple(), jobs),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // everything shares the default target dir when not building in parallel
+        let groups = group_by_target_dir(jobs_with(1));
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].len(), 3);
+
+        let groups = group_by_target_dir(jobs_with(4));
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[0][0].target_dir, default_target_dir());
+        assert_eq!(
+            groups[1][0].target_dir,
+            default_target_dir()
+                .join("dist")
+                .join("x86_64-pc-windows-gnu")
+        );
+    }
+
+    #[test]
+    fn run_dist_jobs_without_aborting() {
+        let targets = [Target::Simple("a".into()), Target::Simple("b".into())];
+        let groups = vec![targets
+            .iter()
+            .map(|target| DistJob {
+                target,
+                target_dir: PathBuf::from("x"),
+            })
+            .collect::<Vec<_>>()];
+
+        let results = run_in_parallel(&groups, 2, |job| {
+            if job.target.triple() == "a" {
+                bail!("boom");
+            }
+            Ok(())
+        });
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, "a");
+        assert!(results[0].1.is_err());
+        assert_eq!(results[1].0, "b");
+        assert!(results[1].1.is_ok());
+    }
+}
@@ -8,7 +8,7 @@ mod vendor;
 
 use anyhow::{anyhow, Context, Result};
 use dist::DIST_HELP;
-use mocked::{installation, manager, server};
+use mocked::{installation, manager, server, tools};
 use std::env;
 use std::io::{stdout, Write};
 use std::path::PathBuf;
@@ -26,7 +26,7 @@ Commands:
     dist, d         Generate release binaries
     run-manager     Run in manager mode
     vendor          Download packages for offline package build
-    mock-rustup-server
+    mock-rustup-server, mock-server
                     Generate a mocked rustup dist server
 "#;
 
@@ -47,6 +47,9 @@ Usage: cargo dev mock-rustup-server [OPTIONS]
 
 Options:
         --root      Specify another directory for generated files
+        --with-tools
+                    Also generate mocked tool packages (.tar.gz, .zip, .7z),
+                    along with a toolkit manifest that uses them
     -h, -help       Print this help message
 "#;
 
@@ -55,9 +58,11 @@ enum DevCmd {
     Dist {
         mode: ReleaseMode,
         binary_only: bool,
+        no_checksums: bool,
         build_target: String,
         dist_targets: Vec<String>,
         name: Option<String>,
+        jobs: usize,
     },
     RunManager {
         no_gui: bool,
@@ -65,6 +70,7 @@ enum DevCmd {
     },
     Mock {
         root: Option<PathBuf>,
+        with_tools: bool,
     },
     Vendor {
         mode: VendorMode,
@@ -72,6 +78,10 @@ enum DevCmd {
         targets: Vec<String>,
         all_targets: bool,
         clear: bool,
+        jobs: Option<usize>,
+        skip_verify: bool,
+        force: bool,
+        restart: bool,
     },
 }
 
@@ -81,10 +91,20 @@ impl DevCmd {
             Self::Dist {
                 mode,
                 binary_only,
+                no_checksums,
                 build_target,
                 dist_targets,
                 name,
-            } => dist::dist(mode, binary_only, name, build_target, dist_targets)?,
+                jobs,
+            } => dist::dist(
+                mode,
+                binary_only,
+                no_checksums,
+                name,
+                build_target,
+                dist_targets,
+                jobs,
+            )?,
             Self::RunManager { no_gui, args } => {
                 println!("running manager with args: {args:?}");
                 // a mocked server is needed to run most of function in manager
@@ -104,8 +124,31 @@ impl DevCmd {
                 targets,
                 all_targets,
                 clear,
-            } => vendor::vendor(mode, name, targets, all_targets, clear)?,
-            Self::Mock { root } => server::generate_rustup_server_files(root)?,
+                jobs,
+                skip_verify,
+                force,
+                restart,
+            } => vendor::vendor(
+                mode,
+                name,
+                targets,
+                all_targets,
+                clear,
+                jobs,
+                skip_verify,
+                force,
+                restart,
+            )?,
+            Self::Mock { root, with_tools } => {
+                server::generate_rustup_server_files(root.clone())?;
+                if with_tools {
+                    let manifest = tools::generate_mock_tools(root)?;
+                    println!(
+                        "toolkit manifest with mocked tools generated at '{}'",
+                        manifest.display()
+                    );
+                }
+            }
         }
         Ok(())
     }
@@ -131,10 +174,12 @@ fn main() -> Result<ExitCode> {
         }
         "d" | "dist" => {
             let mut binary_only = false;
+            let mut no_checksums = false;
             let mut mode = ReleaseMode::Both;
             let mut build_target = env!("TARGET").to_string();
             let mut dist_targets = vec![];
             let mut name = None;
+            let mut jobs = 1;
 
             while let Some(arg) = args.next().as_deref() {
                 match arg {
@@ -150,15 +195,24 @@ fn main() -> Result<ExitCode> {
                     "--cli" => mode = ReleaseMode::Cli,
                     "--gui" => mode = ReleaseMode::Gui,
                     "-b" | "--binary-only" => binary_only = true,
+                    "--no-checksums" => no_checksums = true,
+                    "-j" | "--jobs" => {
+                        let value = args.next().context("expecting a number of jobs")?;
+                        jobs = value
+                            .parse()
+                            .with_context(|| format!("invalid number of jobs '{value}'"))?;
+                    }
                     _ => (),
                 }
             }
             DevCmd::Dist {
                 mode,
                 binary_only,
+                no_checksums,
                 name,
                 build_target,
                 dist_targets,
+                jobs,
             }
         }
         "vendor" => {
@@ -167,6 +221,10 @@ fn main() -> Result<ExitCode> {
             let mut targets = vec![];
             let mut all_targets = false;
             let mut clear = false;
+            let mut jobs = None;
+            let mut skip_verify = false;
+            let mut force = false;
+            let mut restart = false;
             while let Some(arg) = args.next().as_deref() {
                 match arg {
                     "-h" | "--help" => {
@@ -175,10 +233,21 @@ fn main() -> Result<ExitCode> {
                     }
                     "-a" | "--all-targets" => all_targets = true,
                     "-c" | "--clear" => clear = true,
+                    "--force" => force = true,
+                    "--restart" => restart = true,
                     "-n" | "--name" => name = args.next(),
                     "--download-only" => mode = VendorMode::DownloadOnly,
                     "--split-only" => mode = VendorMode::SplitOnly,
+                    "--skip-verify" => skip_verify = true,
                     "--for" => targets.extend(split_values_by_comma(args.next())?),
+                    "-j" | "--jobs" => {
+                        let value = args.next().context("expecting a number of jobs")?;
+                        jobs = Some(
+                            value
+                                .parse()
+                                .with_context(|| format!("invalid number of jobs '{value}'"))?,
+                        );
+                    }
                     s => {
                         writeln!(&mut stdout, "invalid argument '{s}'")?;
                         return Ok(ExitCode::FAILURE);
@@ -194,6 +263,10 @@ fn main() -> Result<ExitCode> {
                 targets,
                 all_targets,
                 clear,
+                jobs,
+                skip_verify,
+                force,
+                restart,
             }
         }
         "run-manager" => {
@@ -222,16 +295,24 @@ fn main() -> Result<ExitCode> {
                 args: extra_args,
             }
         }
-        "mock-rustup-server" => match args.next().as_deref() {
-            Some("-r" | "--root") => DevCmd::Mock {
-                root: Some(args.next().expect("missing arg value for 'root'").into()),
-            },
-            Some("-h" | "--help") => {
-                writeln!(&mut stdout, "{MOCK_HELP}")?;
-                return Ok(ExitCode::SUCCESS);
+        "mock-rustup-server" | "mock-server" => {
+            let mut root = None;
+            let mut with_tools = false;
+            while let Some(arg) = args.next().as_deref() {
+                match arg {
+                    "-h" | "--help" => {
+                        writeln!(&mut stdout, "{MOCK_HELP}")?;
+                        return Ok(ExitCode::SUCCESS);
+                    }
+                    "-r" | "--root" => {
+                        root = Some(args.next().context("missing arg value for 'root'")?.into())
+                    }
+                    "--with-tools" => with_tools = true,
+                    _ => (),
+                }
             }
-            _ => DevCmd::Mock { root: None },
-        },
+            DevCmd::Mock { root, with_tools }
+        }
         s => {
             writeln!(
                 &mut stdout,
@@ -7,6 +7,7 @@ use std::{
 pub mod installation;
 pub mod manager;
 pub mod server;
+pub mod tools;
 
 const TOOLKIT_NAME: &str = "Custom Rust Distribution";
 
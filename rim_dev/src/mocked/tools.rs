@@ -0,0 +1,170 @@
+//! Generate small tool packages in various archive formats, along with a toolkit manifest
+//! referencing them with `file://` URLs, so that the whole `install` process can be tested offline.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use rim_common::utils;
+use url::Url;
+
+use super::TOOLKIT_NAME;
+use crate::common;
+
+/// The rust version in the generated manifest, which should be one of the versions
+/// provided by the mocked rustup dist server.
+const RUST_VERSION: &str = "1.87.0";
+const TOOL_VERSION: &str = "0.1.0";
+
+static MOCK_TOOLS: &[MockTool] = &[
+    MockTool::new("mock-tool-gz", ArchiveFormat::TarGz),
+    MockTool::new("mock-tool-zip", ArchiveFormat::Zip),
+    MockTool::new("mock-tool-7z", ArchiveFormat::SevenZ),
+];
+
+#[derive(Debug, Clone, Copy)]
+enum ArchiveFormat {
+    TarGz,
+    Zip,
+    SevenZ,
+}
+
+impl ArchiveFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::TarGz => "tar.gz",
+            Self::Zip => "zip",
+            Self::SevenZ => "7z",
+        }
+    }
+
+    fn compress(self, src: &Path, dest: &Path) -> Result<()> {
+        match self {
+            Self::TarGz => common::compress_gz(src, dest),
+            Self::Zip => common::compress_zip(src, dest),
+            Self::SevenZ => common::compress_7z(src, dest),
+        }
+    }
+}
+
+/// A fake tool, which contains a single dummy executable under `bin/`.
+struct MockTool {
+    name: &'static str,
+    format: ArchiveFormat,
+}
+
+impl MockTool {
+    const fn new(name: &'static str, format: ArchiveFormat) -> Self {
+        Self { name, format }
+    }
+
+    fn package_name(&self) -> String {
+        format!("{}-{TOOL_VERSION}.{}", self.name, self.format.extension())
+    }
+
+    fn executable_name(&self) -> String {
+        if cfg!(windows) {
+            format!("{}.cmd", self.name)
+        } else {
+            self.name.to_string()
+        }
+    }
+
+    /// Write a dummy executable that prints the name and version of this tool.
+    fn write_executable(&self, bin_dir: &Path) -> Result<()> {
+        let content = if cfg!(windows) {
+            format!("@echo {} {TOOL_VERSION}\r\n", self.name)
+        } else {
+            format!("#!/bin/sh\necho \"{} {TOOL_VERSION}\"\n", self.name)
+        };
+        let path = bin_dir.join(self.executable_name());
+        utils::ensure_dir(bin_dir)?;
+        utils::write_file(&path, &content, false)?;
+        utils::set_exec_permission(&path)
+    }
+}
+
+/// Generate the mocked tool packages and a toolkit manifest using them
+/// under `<root>/tools`, then return the path to the manifest.
+pub(crate) fn generate_mock_tools(root: Option<PathBuf>) -> Result<PathBuf> {
+    let root = root.unwrap_or_else(|| super::mocked_dir().to_path_buf());
+    let dir = utils::to_normalized_absolute_path(root.join("tools"), None)?;
+    generate_in(&dir)
+}
+
+fn generate_in(dir: &Path) -> Result<PathBuf> {
+    utils::ensure_dir(dir)?;
+    let temp_dir = tempfile::tempdir_in(dir)?;
+
+    let mut packages = vec![];
+    for tool in MOCK_TOOLS {
+        println!("generating mocked tool package: {}", tool.package_name());
+        let src = temp_dir.path().join(tool.name);
+        tool.write_executable(&src.join("bin"))?;
+
+        let dest = dir.join(tool.package_name());
+        tool.format.compress(&src, &dest)?;
+        let url = Url::from_file_path(&dest)
+            .map_err(|_| anyhow!("path {} cannot be converted to URL", dest.display()))?;
+        packages.push((tool.name, url));
+    }
+
+    let manifest = dir.join("toolset-manifest.toml");
+    fs::write(&manifest, manifest_content(&packages))?;
+    Ok(manifest)
+}
+
+fn manifest_content(packages: &[(&str, Url)]) -> String {
+    let tools = packages
+        .iter()
+        .map(|(name, url)| {
+            format!("{name} = {{ optional = true, version = \"{TOOL_VERSION}\", url = \"{url}\" }}")
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!(
+        "
+name = \"{TOOLKIT_NAME}\"
+version = \"mock-tools\"
+
+[rust]
+version = \"{RUST_VERSION}\"
+group = \"Rust\"
+components = [\"clippy\", \"rustfmt\"]
+profile = \"minimal\"
+
+[tools.target.{}]
+{tools}
+",
+        env!("TARGET")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rim_common::types::{TomlParser, ToolkitManifest};
+
+    #[test]
+    fn generate_valid_tool_packages() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest_path = generate_in(dir.path()).unwrap();
+
+        let manifest = ToolkitManifest::load(&manifest_path).unwrap();
+        let tools = &manifest.tools.target[env!("TARGET")];
+        assert_eq!(tools.len(), MOCK_TOOLS.len());
+
+        for tool in MOCK_TOOLS {
+            assert!(tools.contains_key(tool.name));
+            let package = dir.path().join(tool.package_name());
+            let out = dir.path().join("extracted").join(tool.name);
+            let extracted = utils::Extractable::load(&package, None)
+                .unwrap()
+                .quiet(true)
+                .extract_then_skip_solo_dir(&out, Some("bin"))
+                .unwrap();
+            assert!(extracted.join("bin").join(tool.executable_name()).is_file());
+        }
+    }
+}
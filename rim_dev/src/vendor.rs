@@ -1,17 +1,32 @@
 use crate::{
-    common::{download, resources_dir},
+    common::{calculate_sha256, download, remote_file_size, resources_dir},
     toolkits_parser::{Component, Configuration, Toolkits},
 };
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use indexmap::IndexMap;
 use rim_common::{
-    types::{ToolInfo, ToolSource},
+    types::{ToolInfo, ToolSource, Tools},
     utils::{ensure_dir, ensure_parent_dir},
 };
-use std::{fs, path::Path};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+use url::Url;
 
 const TOOLS_DIRNAME: &str = "tools";
 const TOOLCHAIN_DIRNAME: &str = "toolchain";
+/// Name of the file under each toolkit's package directory that records
+/// the vendored packages, check [`Checkpoint`].
+const CHECKPOINT_FILENAME: &str = ".vendor-checkpoint";
 
 pub(super) const VENDOR_HELP: &str = r#"
 Split `toolkits.toml` and download packages specified in it for offline packaging
@@ -23,7 +38,12 @@ Options:
     -a, --all-targets
                     Download packages for all supporting targets
     -c, --clear     Clear the previously downloaded packages
+        --force     Download packages again even if they were already downloaded
+        --restart   Ignore and reset the checkpoint of packages vendored by previous runs
         --for       Specify the target(s) to downloading packages for, defaulting to current running target
+    -j, --jobs      Number of toolchain components to download concurrently, defaulting to the number of CPUs
+        --skip-verify
+                    Do not verify the downloaded toolchain components against the hashes in channel manifest
         --download-only
                     Do not update toolkit-manifests, just download packages
         --split-only
@@ -57,6 +77,24 @@ struct VendorArgs {
     /// Whether packages of all supported targets should be downloaded.
     all_targets: bool,
     clear: bool,
+    /// The number of toolchain components to download concurrently.
+    jobs: usize,
+    /// Whether to skip verifying the downloaded toolchain components.
+    skip_verify: bool,
+    /// Whether to download packages even if valid copies of them already exist.
+    force: bool,
+    /// Whether to reset the checkpoint instead of resuming from it.
+    restart: bool,
+    stats: DownloadStats,
+}
+
+/// Counters of the packages that were downloaded or reused during vendoring.
+#[derive(Debug, Default)]
+struct DownloadStats {
+    downloaded: AtomicUsize,
+    reused: AtomicUsize,
+    /// Packages that were skipped because they were recorded in the checkpoint.
+    checkpointed: AtomicUsize,
 }
 
 impl VendorArgs {
@@ -80,24 +118,205 @@ impl VendorArgs {
         }
         Ok(())
     }
+
+    /// Download `url` to `dest`, unless a valid copy of it already exists
+    /// and `--force` is not specified.
+    ///
+    /// If `expected_hash` is provided, the downloaded file will be verified with it,
+    /// and removed if it's corrupted, so that it can be downloaded again in the next run.
+    fn fetch(&self, url: &str, dest: &Path, expected_hash: Option<&str>) -> Result<()> {
+        if !self.force && is_reusable(url, dest, expected_hash)? {
+            println!("reusing: {}", dest.display());
+            self.stats.reused.fetch_add(1, Ordering::Relaxed);
+            return Ok(());
+        }
+
+        download(url, dest)?;
+        if let Some(expected) = expected_hash {
+            let actual = calculate_sha256(dest)?;
+            if !actual.eq_ignore_ascii_case(expected) {
+                fs::remove_file(dest)?;
+                bail!(
+                    "checksum mismatch for '{}': expected '{expected}', got '{actual}'",
+                    dest.display()
+                );
+            }
+        }
+        self.stats.downloaded.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Same as [`fetch`](Self::fetch), but skip the package entirely if it was recorded
+    /// in `checkpoint` and the file still exists, otherwise record it once it's fetched.
+    ///
+    /// Packages without an expected hash are never skipped, because their content might
+    /// change without changing the url (such as `rustup-init`), so they still need to be
+    /// checked by `fetch`.
+    fn fetch_package(&self, package: &Package, checkpoint: &Checkpoint) -> Result<()> {
+        if !self.force
+            && package.expected_hash.is_some()
+            && package.dest.is_file()
+            && checkpoint.is_done(package)
+        {
+            println!("skipping (vendored): {}", package.dest.display());
+            self.stats.checkpointed.fetch_add(1, Ordering::Relaxed);
+            return Ok(());
+        }
+        self.fetch(
+            &package.src,
+            &package.dest,
+            package.expected_hash.as_deref(),
+        )?;
+        checkpoint.mark_done(package)
+    }
 }
 
+/// Records of the packages that were downloaded and verified, so that an interrupted
+/// vendoring can be resumed without checking every package again.
+///
+/// Each record is appended to the checkpoint file as a `<target> <component> <src> [hash]`
+/// line right after the package was fetched, so nothing is lost if the process gets killed.
+/// The source url and hash are part of the record, because the same component (such as
+/// `rustup-init`, whose url has no version) might be changed to another package later.
+#[derive(Debug)]
+struct Checkpoint {
+    path: PathBuf,
+    done: Mutex<HashSet<String>>,
+}
+
+impl Checkpoint {
+    /// Load the checkpoint under `toolkit_root`, or remove it first if `restart` is `true`.
+    fn load(toolkit_root: &Path, restart: bool) -> Result<Self> {
+        let path = toolkit_root.join(CHECKPOINT_FILENAME);
+        if restart && path.is_file() {
+            println!("resetting vendor checkpoint: {}", path.display());
+            fs::remove_file(&path)?;
+        }
+        let done = if path.is_file() {
+            fs::read_to_string(&path)?
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(ToOwned::to_owned)
+                .collect()
+        } else {
+            HashSet::new()
+        };
+        Ok(Self {
+            path,
+            done: Mutex::new(done),
+        })
+    }
+
+    fn record(package: &Package) -> String {
+        let Package {
+            target,
+            component,
+            src,
+            expected_hash,
+            ..
+        } = package;
+        match expected_hash {
+            Some(hash) => format!("{target} {component} {src} {hash}"),
+            None => format!("{target} {component} {src}"),
+        }
+    }
+
+    fn is_done(&self, package: &Package) -> bool {
+        self.done.lock().unwrap().contains(&Self::record(package))
+    }
+
+    fn mark_done(&self, package: &Package) -> Result<()> {
+        let record = Self::record(package);
+        let mut done = self.done.lock().unwrap();
+        if !done.contains(&record) {
+            ensure_parent_dir(&self.path)?;
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+            writeln!(file, "{record}")?;
+            done.insert(record);
+        }
+        Ok(())
+    }
+}
+
+/// Get the url and filename of a tool's package if it's downloaded from a url.
+fn url_package(info: &ToolInfo) -> Result<Option<(&Url, &str)>> {
+    let ToolInfo::Complex(details) = info else {
+        return Ok(None);
+    };
+    let Some(ToolSource::Url { url, filename, .. }) = &details.source else {
+        return Ok(None);
+    };
+    let filename = if let Some(name) = filename {
+        name
+    } else {
+        url.as_str()
+            .rsplit_once("/")
+            .ok_or_else(|| anyhow!("missing filename for URL: {url}"))?
+            .1
+    };
+    Ok(Some((url, filename)))
+}
+
+/// Check whether a previously downloaded file at `dest` can be used as is.
+///
+/// The file is verified with `expected_hash` if provided, otherwise its size
+/// will be compared with the one reported by server (if there is).
+fn is_reusable(url: &str, dest: &Path, expected_hash: Option<&str>) -> Result<bool> {
+    if !dest.is_file() {
+        return Ok(false);
+    }
+    if let Some(expected) = expected_hash {
+        return Ok(calculate_sha256(dest)?.eq_ignore_ascii_case(expected));
+    }
+    let local_size = fs::metadata(dest)?.len();
+    Ok(!matches!(remote_file_size(url), Some(size) if size != local_size))
+}
+
+#[allow(clippy::too_many_arguments)]
 pub(super) fn vendor(
     mode: VendorMode,
     name: Option<String>,
     targets: Vec<String>,
     all_targets: bool,
     clear: bool,
+    jobs: Option<usize>,
+    skip_verify: bool,
+    force: bool,
+    restart: bool,
 ) -> Result<()> {
+    let jobs = jobs
+        .or_else(|| thread::available_parallelism().ok().map(|n| n.get()))
+        .unwrap_or(1)
+        .max(1);
     let args = VendorArgs {
         mode,
         name,
         targets,
         all_targets,
         clear,
+        jobs,
+        skip_verify,
+        force,
+        restart,
+        stats: DownloadStats::default(),
     };
     let mut toolkits = Toolkits::load()?;
-    gen_manifest_and_download_packages(&args, &mut toolkits)
+    gen_manifest_and_download_packages(&args, &mut toolkits)?;
+
+    if !matches!(args.mode, VendorMode::SplitOnly) {
+        println!(
+            "vendoring finished: {} file(s) downloaded, {} file(s) reused, \
+            {} file(s) skipped by checkpoint",
+            args.stats.downloaded.load(Ordering::Relaxed),
+            args.stats.reused.load(Ordering::Relaxed),
+            args.stats.checkpointed.load(Ordering::Relaxed)
+        );
+    }
+    Ok(())
 }
 
 /// Reads the `toolkits` value, and:
@@ -124,6 +343,7 @@ fn gen_manifest_and_download_packages(args: &VendorArgs, toolkits: &mut Toolkits
             );
             fs::remove_dir_all(&toolkit_root)?;
         }
+        let checkpoint = Checkpoint::load(&toolkit_root, args.restart)?;
 
         // splitting online manifest is easy, because every manifest section was
         // already considered as online manifest, we just need to write its string
@@ -139,39 +359,72 @@ fn gen_manifest_and_download_packages(args: &VendorArgs, toolkits: &mut Toolkits
         // and change it to a relative `path`
         // (assuming that path is valid, we will use it to download packages).
         let offline_manifest_path = offline_manifests_dir.join(format!("{name}.toml"));
-        let targeted_tools = &mut toolkit.manifest.tools.target;
-        for (target, tool_info) in targeted_tools {
-            let tools_dir = toolkit_root.join(target).join(TOOLS_DIRNAME);
 
-            for (_name, info_table) in tool_info.iter_mut() {
-                let ToolInfo::Complex(details) = info_table else {
+        // the keys of `[tools.target]` might be glob patterns or `all`, so the packages are
+        // downloaded for every supported target by resolving its tools, into the `tools`
+        // directory of that target, which is the one that gets copied into its offline package.
+        let resolved_tools = config
+            .targets
+            .iter()
+            .map(|target| {
+                let triple = target.triple();
+                let tools = toolkit.manifest.tools.for_target(triple);
+                (triple, tools.map(Cow::into_owned).unwrap_or_default())
+            })
+            .collect::<Vec<_>>();
+        for (triple, tools) in &resolved_tools {
+            if !args.should_download(name, triple) {
+                continue;
+            }
+            let tools_dir = toolkit_root.join(triple).join(TOOLS_DIRNAME);
+            for (tool_name, info) in tools.iter() {
+                let Some((url, filename)) = url_package(info)? else {
                     continue;
                 };
-                if let Some(ToolSource::Url {
-                    version: _,
-                    url,
-                    filename,
-                }) = &details.source
-                {
-                    let filename = if let Some(name) = filename {
-                        name
-                    } else {
-                        url.as_str()
-                            .rsplit_once("/")
-                            .ok_or_else(|| anyhow!("missing filename for URL: {url}"))?
-                            .1
-                    };
-                    let rel_path = format!("{TOOLS_DIRNAME}/{filename}");
-
-                    if args.should_download(name, target) {
-                        let dest = tools_dir.join(filename);
-                        ensure_parent_dir(&dest)?;
-                        download(url.as_str(), &dest)?;
-                    }
+                let dest = tools_dir.join(filename);
+                ensure_parent_dir(&dest)?;
+                let package = Package {
+                    target: triple.to_string(),
+                    component: tool_name.to_string(),
+                    src: url.to_string(),
+                    dest,
+                    expected_hash: None,
+                };
+                args.fetch_package(&package, &checkpoint)?;
+            }
+        }
 
-                    // convert url package source to path.
-                    info_table.url_to_path(rel_path);
-                }
+        for (key, tool_info) in &mut toolkit.manifest.tools.target {
+            for (tool_name, info_table) in tool_info.iter_mut() {
+                let Some((url, filename)) = url_package(info_table)? else {
+                    continue;
+                };
+                let filename = filename.to_string();
+                let rel_path = format!("{TOOLS_DIRNAME}/{filename}");
+
+                // record the checksum, so that the installer can tell if the package
+                // got corrupted after vendoring, the package is looked up in the targets
+                // that resolved this tool from the same url.
+                let url = url.clone();
+                let same_url =
+                    |info: &ToolInfo| matches!(url_package(info), Ok(Some((u, _))) if *u == url);
+                let downloaded = resolved_tools
+                    .iter()
+                    .filter(|(triple, tools)| {
+                        Tools::key_matches(key, triple)
+                            && tools.get(tool_name).is_some_and(same_url)
+                    })
+                    .map(|(triple, _)| {
+                        toolkit_root
+                            .join(triple)
+                            .join(TOOLS_DIRNAME)
+                            .join(&filename)
+                    })
+                    .find(|path| path.is_file());
+                let sha256 = downloaded.map(calculate_sha256).transpose()?;
+
+                // convert url package source to path.
+                info_table.url_to_path(rel_path, sha256);
             }
         }
         // Then, insert `[rust.offline-dist-server]` value and `[rust.rustup]` section
@@ -193,8 +446,14 @@ fn gen_manifest_and_download_packages(args: &VendorArgs, toolkits: &mut Toolkits
                 let url = config.rustup_dist_url(&format!("{triple}/{rustup_init}"));
                 let tools_dir = toolkit_root.join(triple).join(TOOLS_DIRNAME);
                 ensure_dir(&tools_dir)?;
-                let dest = tools_dir.join(rustup_init);
-                download(&url, &dest)?;
+                let package = Package {
+                    target: triple.into(),
+                    component: rustup_init.clone(),
+                    src: url,
+                    dest: tools_dir.join(&rustup_init),
+                    expected_hash: None,
+                };
+                args.fetch_package(&package, &checkpoint)?;
             }
 
             rustup_sources.insert(triple.into(), value);
@@ -215,6 +474,7 @@ fn gen_manifest_and_download_packages(args: &VendorArgs, toolkits: &mut Toolkits
                 toolkit.date(),
                 triple,
                 args,
+                &checkpoint,
             )?;
         }
 
@@ -232,6 +492,7 @@ fn download_toolchain_components(
     date: &str,
     triple: &str,
     args: &VendorArgs,
+    checkpoint: &Checkpoint,
 ) -> Result<()> {
     let components = &config.components;
 
@@ -246,9 +507,17 @@ fn download_toolchain_components(
     let manifest_hash_src = format!("{manifest_src}.sha256");
     let manifest_dest = toolchain_dir.join(manifest_name);
     let manifest_hash_dest = toolchain_dir.join(manifest_hash_name);
-    download(&manifest_src, &manifest_dest)?;
-    download(&manifest_hash_src, &manifest_hash_dest)?;
+    args.fetch(&manifest_src, &manifest_dest, None)?;
+    args.fetch(&manifest_hash_src, &manifest_hash_dest, None)?;
+    let hashes = if args.skip_verify {
+        HashMap::new()
+    } else {
+        let raw = fs::read_to_string(&manifest_dest)?;
+        package_hashes(&raw)
+            .with_context(|| format!("invalid channel manifest '{}'", manifest_dest.display()))?
+    };
 
+    let mut packages = vec![];
     for component in components {
         let comp_name = match component {
             Component::Simple(name) => format!("{name}-{version}-{triple}.tar.xz"),
@@ -275,10 +544,255 @@ fn download_toolchain_components(
             }
         };
 
+        let expected_hash = if args.skip_verify {
+            None
+        } else {
+            let hash = hashes.get(&comp_name).with_context(|| {
+                format!(
+                    "no hash of '{comp_name}' was found in channel manifest, \
+                    use '--skip-verify' to download it without verification"
+                )
+            })?;
+            Some(hash.clone())
+        };
         let pkg_src = config.rust_dist_url(&format!("{date}/{comp_name}"));
         let pkg_dest = date_dir.join(&comp_name);
-        download(&pkg_src, &pkg_dest)?;
+        packages.push(Package {
+            target: triple.into(),
+            component: comp_name,
+            src: pkg_src,
+            dest: pkg_dest,
+            expected_hash,
+        });
     }
 
-    Ok(())
+    download_concurrently(&packages, args, checkpoint)
+}
+
+/// Collect the SHA-256 hashes of every package listed in a channel manifest,
+/// as a map of package filename (such as `cargo-1.80.0-x86_64-unknown-linux-gnu.tar.xz`)
+/// to its hash.
+fn package_hashes(channel_manifest: &str) -> Result<HashMap<String, String>> {
+    let manifest: toml::Table = toml::from_str(channel_manifest)?;
+    let pkgs = manifest
+        .get("pkg")
+        .and_then(toml::Value::as_table)
+        .context("missing 'pkg' section")?;
+
+    let mut hashes = HashMap::new();
+    let targets = pkgs
+        .values()
+        .filter_map(|pkg| pkg.get("target")?.as_table())
+        .flat_map(|targets| targets.values());
+    for target in targets {
+        for (url_key, hash_key) in [("url", "hash"), ("xz_url", "xz_hash")] {
+            let (Some(url), Some(hash)) = (
+                target.get(url_key).and_then(toml::Value::as_str),
+                target.get(hash_key).and_then(toml::Value::as_str),
+            ) else {
+                continue;
+            };
+            if let Some((_, filename)) = url.rsplit_once('/') {
+                hashes.insert(filename.to_string(), hash.to_string());
+            }
+        }
+    }
+    Ok(hashes)
+}
+
+/// A package to download for a target, which is either a tool,
+/// `rustup-init` or a toolchain component.
+struct Package {
+    target: String,
+    /// The name of tool or the filename of toolchain component package,
+    /// which identifies this package in [`Checkpoint`].
+    component: String,
+    src: String,
+    dest: PathBuf,
+    /// The SHA-256 hash to verify the downloaded file with, `None` to skip verification.
+    expected_hash: Option<String>,
+}
+
+/// Download and verify a list of packages, using at most `args.jobs` threads at the same time.
+///
+/// No more downloads will be started once any of them fails.
+fn download_concurrently(
+    packages: &[Package],
+    args: &VendorArgs,
+    checkpoint: &Checkpoint,
+) -> Result<()> {
+    let total = packages.len();
+    let next = AtomicUsize::new(0);
+    let finished = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+
+    let worker = || -> Result<()> {
+        while !failed.load(Ordering::Relaxed) {
+            let Some(package) = packages.get(next.fetch_add(1, Ordering::Relaxed)) else {
+                break;
+            };
+            if let Err(e) = args.fetch_package(package, checkpoint) {
+                failed.store(true, Ordering::Relaxed);
+                return Err(e);
+            }
+            let done = finished.fetch_add(1, Ordering::Relaxed) + 1;
+            println!("[{done}/{total}] finished: {}", package.dest.display());
+        }
+        Ok(())
+    };
+
+    thread::scope(|scope| {
+        let handles = (0..args.jobs.min(total))
+            .map(|_| scope.spawn(worker))
+            .collect::<Vec<_>>();
+        handles.into_iter().try_for_each(|handle| {
+            handle
+                .join()
+                .map_err(|_| anyhow!("toolchain component download thread panicked"))?
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collect_package_hashes() {
+        let raw = r#"
+manifest-version = "2"
+
+[pkg.cargo.target.x86_64-unknown-linux-gnu]
+available = true
+url = "https://static.rust-lang.org/dist/2024-07-25/cargo-1.80.0-x86_64-unknown-linux-gnu.tar.gz"
+hash = "aaa"
+xz_url = "https://static.rust-lang.org/dist/2024-07-25/cargo-1.80.0-x86_64-unknown-linux-gnu.tar.xz"
+xz_hash = "bbb"
+
+[pkg.rust-src.target."*"]
+available = true
+xz_url = "https://static.rust-lang.org/dist/2024-07-25/rust-src-1.80.0.tar.xz"
+xz_hash = "ccc"
+
+[pkg.rust.target.aarch64-apple-darwin]
+available = false
+"#;
+        let hashes = package_hashes(raw).unwrap();
+        assert_eq!(hashes.len(), 3);
+        assert_eq!(
+            hashes["cargo-1.80.0-x86_64-unknown-linux-gnu.tar.gz"],
+            "aaa"
+        );
+        assert_eq!(
+            hashes["cargo-1.80.0-x86_64-unknown-linux-gnu.tar.xz"],
+            "bbb"
+        );
+        assert_eq!(hashes["rust-src-1.80.0.tar.xz"], "ccc");
+    }
+
+    #[test]
+    fn url_packages_of_wildcard_targets() {
+        use rim_common::types::{TomlParser, ToolkitManifest};
+
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-pc-windows-msvc]
+a = { url = "https://example.com/a-msvc.zip" }
+[tools.target."*-pc-windows-*"]
+a = { url = "https://example.com/a-windows.zip" }
+[tools.target.all]
+b = { url = "https://example.com/dl/b", filename = "b.tar.gz" }
+c = "0.1.0"
+"#;
+        let manifest = ToolkitManifest::from_str(input).unwrap();
+        let packages = |triple: &str| {
+            let tools = manifest.tools.for_target(triple).unwrap();
+            tools
+                .values()
+                .filter_map(|info| url_package(info).unwrap())
+                .map(|(_, filename)| filename.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            packages("x86_64-pc-windows-msvc"),
+            ["a-msvc.zip", "b.tar.gz"]
+        );
+        assert_eq!(
+            packages("aarch64-pc-windows-msvc"),
+            ["a-windows.zip", "b.tar.gz"]
+        );
+        assert_eq!(packages("x86_64-unknown-linux-gnu"), ["b.tar.gz"]);
+    }
+
+    #[test]
+    fn reuse_downloaded_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("pkg.tar.xz");
+        let url = "http://127.0.0.1:1/pkg.tar.xz";
+        let hash = rim_common::utils::sha256_hex("hello");
+        assert!(!is_reusable(url, &dest, Some(&hash)).unwrap());
+
+        fs::write(&dest, "hello").unwrap();
+        assert!(is_reusable(url, &dest, Some(&hash.to_uppercase())).unwrap());
+        fs::write(&dest, "corrupted").unwrap();
+        assert!(!is_reusable(url, &dest, Some(&hash)).unwrap());
+    }
+
+    #[test]
+    fn resume_from_checkpoint() {
+        let package = |target: &str, component: &str, src: &str, hash: Option<&str>| Package {
+            target: target.into(),
+            component: component.into(),
+            src: src.into(),
+            dest: PathBuf::new(),
+            expected_hash: hash.map(Into::into),
+        };
+        let cargo = package(
+            "x86_64-unknown-linux-gnu",
+            "cargo.tar.xz",
+            "https://example.com/cargo.tar.xz",
+            Some("aaa"),
+        );
+        let rustup = package(
+            "aarch64-apple-darwin",
+            "rustup-init",
+            "https://example.com/rustup-init",
+            None,
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let checkpoint = Checkpoint::load(dir.path(), false).unwrap();
+        assert!(!checkpoint.is_done(&cargo));
+
+        checkpoint.mark_done(&cargo).unwrap();
+        checkpoint.mark_done(&cargo).unwrap();
+        checkpoint.mark_done(&rustup).unwrap();
+        let content = fs::read_to_string(dir.path().join(CHECKPOINT_FILENAME)).unwrap();
+        assert_eq!(content.lines().count(), 2);
+
+        let resumed = Checkpoint::load(dir.path(), false).unwrap();
+        assert!(resumed.is_done(&cargo));
+        assert!(resumed.is_done(&rustup));
+        // same component from another source, or with another hash
+        let moved = package(
+            "aarch64-apple-darwin",
+            "rustup-init",
+            "https://mirror.example.com/rustup-init",
+            None,
+        );
+        assert!(!resumed.is_done(&moved));
+        let updated = package(
+            "x86_64-unknown-linux-gnu",
+            "cargo.tar.xz",
+            "https://example.com/cargo.tar.xz",
+            Some("bbb"),
+        );
+        assert!(!resumed.is_done(&updated));
+
+        let restarted = Checkpoint::load(dir.path(), true).unwrap();
+        assert!(!restarted.is_done(&cargo));
+        assert!(!dir.path().join(CHECKPOINT_FILENAME).exists());
+    }
 }
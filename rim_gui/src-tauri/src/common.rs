@@ -19,6 +19,7 @@ use rim::{
 use rim_common::{types::ToolkitManifest, utils};
 use serde::Serialize;
 use tauri::{App, AppHandle, Manager, Window, WindowUrl};
+use tokio_util::sync::CancellationToken;
 use url::Url;
 
 #[allow(clippy::type_complexity)]
@@ -27,6 +28,9 @@ static THREAD_POOL: LazyLock<Mutex<Vec<JoinHandle<anyhow::Result<()>>>>> =
 
 static SHARED_CONFIGS: Mutex<SharedConfigs> = Mutex::new(SharedConfigs::new());
 
+/// Token to cancel the ongoing installation, `None` if nothing is being installed.
+static CANCEL_TOKEN: Mutex<Option<CancellationToken>> = Mutex::new(None);
+
 /// Configure the logger to use a communication channel ([`mpsc`]),
 /// allowing us to send logs across threads.
 ///
@@ -95,10 +99,16 @@ pub(crate) fn install_toolkit_in_new_thread(
     install_dir: PathBuf,
     manifest: ToolkitManifest,
     is_update: bool,
+    no_shortcut: bool,
+    allow_hooks: bool,
 ) {
     UpdateCheckBlocker::block();
 
     let rustup_dist_server = SHARED_CONFIGS.lock().unwrap().rustup_dist_server.clone();
+    let no_shortcut = no_shortcut || shortcuts_disabled();
+    let allow_hooks = allow_hooks || SHARED_CONFIGS.lock().unwrap().allow_hooks;
+    let cancel_token = CancellationToken::new();
+    *CANCEL_TOKEN.lock().unwrap() = Some(cancel_token.clone());
     let handle = thread::spawn(move || -> anyhow::Result<()> {
         // FIXME: this is needed to make sure the other thread could receive the first couple messages
         // we sent in this thread. But it feels very wrong, there has to be better way.
@@ -113,15 +123,20 @@ pub(crate) fn install_toolkit_in_new_thread(
 
         // TODO: Use continuous progress
         let mut config = InstallConfiguration::new(&install_dir, &manifest)?
-            .with_progress_indicator(Some(progress));
+            .with_progress_indicator(Some(progress))
+            .no_shortcut(no_shortcut)
+            .allow_hooks(allow_hooks)
+            .with_cancel_token(cancel_token);
         if let Some(server) = rustup_dist_server {
             config = config.with_rustup_dist_server(server);
         }
-        if is_update {
-            config.update(components_list)?;
+        let result = if is_update {
+            config.update(components_list)
         } else {
-            config.install(components_list)?;
-        }
+            config.install(components_list)
+        };
+        CANCEL_TOKEN.lock().unwrap().take();
+        result?;
 
         // 安装完成后，发送安装完成事件
         window.emit(ON_COMPLETE_EVENT, ())?;
@@ -205,6 +220,15 @@ pub(crate) fn set_locale(language: String) -> Result<()> {
     Ok(())
 }
 
+/// Cancel the ongoing installation, which stops after the current step.
+#[tauri::command]
+pub(crate) fn cancel_installation() {
+    if let Some(token) = CANCEL_TOKEN.lock().unwrap().as_ref() {
+        log::warn!("{}", t!("install_cancelling_gui"));
+        token.cancel();
+    }
+}
+
 #[tauri::command]
 pub(crate) fn app_info() -> AppInfo {
     AppInfo::get().to_owned()
@@ -330,7 +354,7 @@ pub(crate) struct CliPayload {
 }
 
 pub(crate) fn handle_manager_args(app: AppHandle, cli: rim::cli::Manager) {
-    if let Some(ManagerSubcommands::Uninstall { keep_self }) = cli.command {
+    if let Some(ManagerSubcommands::Uninstall { keep_self, .. }) = cli.command {
         if !AppInfo::is_manager() {
             return;
         }
@@ -355,12 +379,18 @@ pub(crate) fn handle_manager_args(app: AppHandle, cli: rim::cli::Manager) {
 
 pub(crate) struct SharedConfigs {
     pub(crate) rustup_dist_server: Option<Url>,
+    /// Do not create shortcuts for the installed tools.
+    pub(crate) no_shortcut: bool,
+    /// Run the hook commands of tools without asking.
+    pub(crate) allow_hooks: bool,
 }
 
 impl SharedConfigs {
     pub(crate) const fn new() -> Self {
         Self {
             rustup_dist_server: None,
+            no_shortcut: false,
+            allow_hooks: false,
         }
     }
 }
@@ -369,6 +399,8 @@ impl From<&rim::cli::Installer> for SharedConfigs {
     fn from(value: &rim::cli::Installer) -> Self {
         Self {
             rustup_dist_server: value.rustup_dist_server.clone(),
+            no_shortcut: value.no_shortcut,
+            allow_hooks: value.allow_hooks,
         }
     }
 }
@@ -377,6 +409,8 @@ impl From<&rim::cli::Manager> for SharedConfigs {
     fn from(value: &rim::cli::Manager) -> Self {
         Self {
             rustup_dist_server: value.rustup_dist_server.clone(),
+            no_shortcut: false,
+            allow_hooks: false,
         }
     }
 }
@@ -385,3 +419,8 @@ pub(crate) fn update_shared_configs<T: Into<SharedConfigs>>(value: T) {
     let mut guard = SHARED_CONFIGS.lock().unwrap();
     *guard = value.into();
 }
+
+/// Check if shortcut creation was disabled via the `--no-shortcut` command line option.
+pub(crate) fn shortcuts_disabled() -> bool {
+    SHARED_CONFIGS.lock().unwrap().no_shortcut
+}
@@ -33,12 +33,14 @@ pub(super) fn main(
             get_restricted_components,
             updated_package_sources,
             install_toolchain,
+            default_create_shortcuts,
             run_app,
             welcome_label,
             load_manifest_and_ret_version,
             common::supported_languages,
             common::set_locale,
             common::app_info,
+            common::cancel_installation,
             common::get_label,
             get_home_page_url,
             common::get_build_cfg_locale_str,
@@ -62,7 +64,12 @@ fn default_install_dir() -> String {
     INSTALL_DIR
         .get()
         .cloned()
-        .unwrap_or_else(rim::default_install_dir)
+        .unwrap_or_else(|| {
+            rim::resolve_install_dir(None).unwrap_or_else(|e| {
+                warn!("{e}");
+                rim::default_install_dir()
+            })
+        })
         .to_string_lossy()
         .to_string()
 }
@@ -185,6 +192,8 @@ async fn install_toolchain(
     window: tauri::Window,
     components_list: Vec<Component>,
     install_dir: String,
+    create_shortcuts: bool,
+    allow_hooks: bool,
 ) {
     let install_dir = PathBuf::from(install_dir);
     common::install_toolkit_in_new_thread(
@@ -193,9 +202,18 @@ async fn install_toolchain(
         install_dir,
         cached_manifest().lock().await.to_owned(),
         false,
+        !create_shortcuts,
+        allow_hooks,
     );
 }
 
+/// Whether shortcuts should be created by default,
+/// which can be disabled by either the manifest or the command line option.
+#[tauri::command]
+async fn default_create_shortcuts() -> bool {
+    !common::shortcuts_disabled() && cached_manifest().lock().await.create_shortcuts()
+}
+
 /// Retrieve cached toolset manifest.
 ///
 /// # Panic
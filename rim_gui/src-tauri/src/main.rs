@@ -24,8 +24,6 @@ i18n!("../../locales", fallback = "en-US");
 static INSTALL_DIR: OnceLock<PathBuf> = OnceLock::new();
 
 fn main() -> Result<()> {
-    utils::use_current_locale();
-
     let mode = Mode::detect(
         Some(Box::new(|installer| {
             if let Some(dir) = installer.install_dir() {
@@ -35,11 +33,19 @@ fn main() -> Result<()> {
         None,
     );
     let msg_recv = common::setup_logger();
+    let locale = match &mode {
+        Mode::Manager(Ok(args)) => args.locale.as_deref(),
+        Mode::Installer(Ok(args)) => args.locale.as_deref(),
+        _ => None,
+    };
+    if let Err(e) = utils::use_requested_locale(locale) {
+        warn!("{e}");
+    }
     match mode {
         Mode::Manager(maybe_args) => {
             run_cli_else_hide_console(&maybe_args)?;
 
-            if let Err(e) = handle_autostart() {
+            if let Err(e) = set_autostart(Configuration::load_from_install_dir().autostart) {
                 // log the error but do NOT abort the program
                 error!("unable to setup autostart: {e}");
             }
@@ -53,11 +59,8 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-// TODO: add user setting for this
-fn handle_autostart() -> Result<()> {
-    // Load configuration to check if autostart is allowed
-    let allow_autostart = Configuration::load_from_install_dir().autostart;
-
+/// Enable or disable launching this application on system startup.
+fn set_autostart(allow_autostart: bool) -> Result<()> {
     let cur_exe = std::env::current_exe()?;
     let Some(exe_path) = cur_exe.to_str() else {
         log::error!("the path to this application contains invalid UTF-8 character");
@@ -85,8 +88,8 @@ fn handle_autostart() -> Result<()> {
 fn run_cli_else_hide_console<T: ExecutableCommand>(command_args: &anyhow::Result<T>) -> Result<()> {
     if let Ok(args) = command_args {
         if args.no_gui() {
-            args.execute()?;
-            std::process::exit(0);
+            let status = args.execute()?;
+            std::process::exit(status.exit_code);
         }
     }
 
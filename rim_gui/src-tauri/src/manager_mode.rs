@@ -80,10 +80,13 @@ pub(super) fn main(
             common::supported_languages,
             common::set_locale,
             common::app_info,
+            common::cancel_installation,
             common::get_label,
             self_update_now,
             toolkit_update_now,
             skip_version,
+            get_autostart,
+            set_autostart,
             notification::close,
             notification::notification_content,
             common::get_build_cfg_locale_str,
@@ -143,7 +146,11 @@ fn uninstall_toolkit(window: tauri::Window, remove_self: bool) {
 }
 
 #[tauri::command(rename_all = "snake_case")]
-fn install_toolkit(window: tauri::Window, components_list: Vec<Component>) -> Result<()> {
+fn install_toolkit(
+    window: tauri::Window,
+    components_list: Vec<Component>,
+    allow_hooks: bool,
+) -> Result<()> {
     UpdateOpt::new().update_toolkit(|p| {
         let guard = selected_toolset();
         let manifest = guard
@@ -155,6 +162,8 @@ fn install_toolkit(window: tauri::Window, components_list: Vec<Component>) -> Re
             p.to_path_buf(),
             manifest.to_owned(),
             true,
+            false,
+            allow_hooks,
         );
         Ok(())
     })?;
@@ -377,6 +386,22 @@ fn skip_version(app: AppHandle, target: UpdateTarget, version: String) -> Result
     Ok(())
 }
 
+#[tauri::command]
+fn get_autostart() -> bool {
+    Configuration::load_from_install_dir().autostart
+}
+
+#[tauri::command]
+fn set_autostart(enabled: bool) -> Result<()> {
+    log::info!("setting autostart to: {enabled}");
+    Configuration::load_from_install_dir()
+        .with_autostart(enabled)
+        .write_to_install_dir()?;
+    // apply it right away, so the user doesn't need to restart the app
+    crate::set_autostart(enabled)?;
+    Ok(())
+}
+
 enum WindowState {
     Normal(Window),
     Hidden(Window),
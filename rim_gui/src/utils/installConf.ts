@@ -11,6 +11,7 @@ class InstallConf {
   isCustomInstall: boolean;
   version: Ref<string>;
   restrictedComponents: Ref<RestrictedComponent[]>;
+  createShortcuts: Ref<boolean>;
 
   constructor(path: string, components: CheckItem<Component>[]) {
     this.path = ref(path);
@@ -18,6 +19,7 @@ class InstallConf {
     this.isCustomInstall = true;
     this.version = ref('');
     this.restrictedComponents = ref([]);
+    this.createShortcuts = ref(true);
   }
 
   /** The name and version of this application joined as a string. */
@@ -48,6 +50,10 @@ class InstallConf {
     this.restrictedComponents.value = comps;
   }
 
+  setCreateShortcuts(createShortcuts: boolean) {
+    this.createShortcuts.value = createShortcuts;
+  }
+
   setCustomInstall(isCustomInstall: boolean) {
     this.isCustomInstall = isCustomInstall;
   }
@@ -121,9 +127,17 @@ class InstallConf {
     }
   }
 
+  async loadCreateShortcuts() {
+    const createShortcuts = await invokeCommand('default_create_shortcuts');
+    if (typeof createShortcuts === 'boolean') {
+      this.setCreateShortcuts(createShortcuts);
+    }
+  }
+
   async loadAll() {
     await this.loadDefaultPath();
     await this.loadComponents();
+    await this.loadCreateShortcuts();
   }
 }
 
@@ -1,3 +1,15 @@
 export function progressFormat(value: number) {
   return value.toFixed(2).padStart(5, '0') + '%';
 }
+
+// Format a size in bytes, such as `1.50 MiB`.
+export function sizeFormat(bytes: number) {
+  const units = ['B', 'KiB', 'MiB', 'GiB', 'TiB'];
+  let value = bytes;
+  let unit = 0;
+  while (value >= 1024 && unit < units.length - 1) {
+    value /= 1024;
+    unit += 1;
+  }
+  return unit === 0 ? `${value} B` : `${value.toFixed(2)} ${units[unit]}`;
+}
@@ -24,6 +24,16 @@ export function componentUtils(component: Component) {
         return info.restricted;
       }
       return false;
+    },
+
+    hasHooks(): boolean {
+      const info = this.getToolInfo();
+      return !!(
+        info?.['post-install'] ||
+        info?.['pre-uninstall'] ||
+        info?.kind === 'script' ||
+        info?.['uninstall-script']
+      );
     }
   }
 }
@@ -41,6 +51,8 @@ export interface Component {
   toolInstaller?: string | ToolInfoDetails;
   kind: ComponentType;
   installed: boolean;
+  updateAvailable: boolean;
+  downloadSize?: number;
 }
 
 export type ToolInfoDetails =
@@ -57,6 +69,10 @@ export interface BaseToolInfoDetails {
   requires?: string[];
   obsoletes?: string[];
   conflicts?: string[];
+  'post-install'?: string;
+  'pre-uninstall'?: string;
+  kind?: string;
+  'uninstall-script'?: string;
 }
 
 export interface RestrictedTool extends BaseToolInfoDetails {
@@ -77,11 +93,14 @@ export interface UrlTool extends BaseToolInfoDetails {
   version?: string;
   url: string;
   filename?: string;
+  size?: number;
 }
 
 export interface PathTool extends BaseToolInfoDetails {
   version?: string;
   path: string;
+  size?: number;
+  sha256?: string;
 }
 
 export interface VersionTool extends BaseToolInfoDetails {
@@ -105,8 +124,8 @@ export function toChecked(components: Component[]): CheckItem<Component>[] {
   return components.map(
     (item) => {
       return {
-        label: `${item.displayName}${item.installed ? ' (installed)' : item.required ? ' (required)' : ''}`,
-        checked: !item.installed && (item.required || !item.optional),
+        label: `${item.displayName}${item.updateAvailable ? ' (update available)' : item.installed ? ' (installed)' : item.required ? ' (required)' : ''}`,
+        checked: (!item.installed || item.updateAvailable) && (item.required || !item.optional),
         required: item.required,
         disabled: item.installed ? false : item.required,
         value: item,
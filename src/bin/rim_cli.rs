@@ -14,5 +14,9 @@ fn main() -> Result<()> {
         rim::cli::pause().expect("unable to pause terminal window");
     }
 
-    res.map(|_| ())
+    let status = res?;
+    if status.exit_code != 0 {
+        std::process::exit(status.exit_code);
+    }
+    Ok(())
 }
@@ -1,14 +1,57 @@
+use std::path::{Path, PathBuf};
+
 use super::{ExecStatus, ManagerSubcommands};
 use crate::core::check;
-use anyhow::Result;
+use anyhow::{bail, Result};
+use clap::{Subcommand, ValueHint};
+use rim_common::types::{Severity, TomlParser, ToolkitManifest};
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum CheckCommand {
+    /// Check a toolkit manifest for mistakes, such as references to undefined tools
+    Manifest {
+        /// Path to the toolkit manifest file
+        #[arg(value_name = "PATH", value_hint = ValueHint::FilePath)]
+        path: PathBuf,
+    },
+}
 
 /// Execute `check` command.
 pub(super) fn execute(subcommand: &ManagerSubcommands) -> Result<ExecStatus> {
-    let ManagerSubcommands::Check { extra_args } = subcommand else {
+    let ManagerSubcommands::Check {
+        command,
+        extra_args,
+    } = subcommand
+    else {
         return Ok(ExecStatus::default());
     };
 
-    check::run(extra_args)?;
+    match command {
+        Some(CheckCommand::Manifest { path }) => lint_manifest(path)?,
+        None => check::run(extra_args)?,
+    }
     Ok(ExecStatus::new_executed().no_pause(true))
 }
 
+fn lint_manifest(path: &Path) -> Result<()> {
+    let manifest = ToolkitManifest::load(path)?;
+    let diagnostics = manifest.lint();
+
+    for diagnostic in &diagnostics {
+        println!("{diagnostic}");
+    }
+
+    let errors = diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .count();
+    if errors > 0 {
+        bail!(t!(
+            "manifest_check_failed",
+            path = path.display(),
+            count = errors
+        ));
+    }
+    info!("{}", t!("manifest_check_passed", path = path.display()));
+    Ok(())
+}
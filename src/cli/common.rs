@@ -12,8 +12,10 @@ use std::{
 
 use anyhow::{Context, Result};
 use indexmap::IndexMap;
+use rim_common::utils;
 
 use crate::components::Component;
+use crate::{ToolFailureAction, ToolFailureHandler};
 
 /// A "convenient" helper macro to [`question_single_choice`].
 ///
@@ -344,6 +346,9 @@ pub(crate) fn show_confirmation(
     for line in list_of_comp {
         writeln!(&mut stdout, "\t{line}")?;
     }
+    if !is_remove {
+        writeln!(&mut stdout, "\n{}", total_download_size(choices))?;
+    }
 
     // list obsoleted components
     let obsoletes_removal_list = choices
@@ -372,6 +377,21 @@ pub(crate) fn show_confirmation(
     Ok(())
 }
 
+/// Describe the total download size of the chosen components,
+/// noting whether some of them have unknown sizes.
+fn total_download_size(choices: &ComponentChoices<'_>) -> String {
+    let total = choices
+        .values()
+        .filter_map(|c| c.download_size)
+        .sum::<u64>();
+    let size = utils::format_size(total);
+    if choices.values().any(|c| c.download_size.is_none()) {
+        t!("total_download_size_partial", size = size).to_string()
+    } else {
+        t!("total_download_size", size = size).to_string()
+    }
+}
+
 /// Create a collection of component choices base of a filtering condition.
 /// Also taking component constrains, such as `requires`, `conflicts` into account.
 // TODO: handle conflicts
@@ -456,7 +476,9 @@ impl ComponentDecoration<'_> {
         match self {
             Self::None => String::new(),
             Self::Selection => {
-                if comp.installed {
+                if comp.update_available {
+                    format!(" ({})", t!("update_available"))
+                } else if comp.installed {
                     format!(" ({})", t!("installed"))
                 } else if comp.required {
                     format!(" ({})", t!("required"))
@@ -531,3 +553,30 @@ impl<'c, I: IntoIterator<Item = &'c Component>> ComponentListBuilder<'c, I> {
             .collect()
     }
 }
+
+/// Get the [`ToolFailureHandler`] that asks the user whether to retry, skip or abort
+/// when a tool fails to install, return `None` if there is no one to ask,
+/// i.e. when `--yes` or `--quiet` is specified.
+pub(crate) fn tool_failure_handler() -> Option<ToolFailureHandler<'static>> {
+    let g_opts = GlobalOpts::get();
+    if g_opts.yes_to_all || g_opts.quiet {
+        None
+    } else {
+        Some(&ask_tool_failure_action)
+    }
+}
+
+fn ask_tool_failure_action(name: &str, can_retry: bool) -> Result<ToolFailureAction> {
+    let mut actions = vec![];
+    if can_retry {
+        actions.push((t!("retry"), ToolFailureAction::Retry));
+    }
+    actions.extend([
+        (t!("skip"), ToolFailureAction::Skip),
+        (t!("abort"), ToolFailureAction::Abort),
+    ]);
+    let choices = actions.iter().map(|(label, _)| label).collect::<Vec<_>>();
+    let choice =
+        question_single_choice(t!("tool_install_failed_prompt", name = name), &choices, 1)?;
+    Ok(actions[choice - 1].1)
+}
@@ -2,10 +2,10 @@ use std::collections::{HashMap, HashSet};
 
 use anyhow::{bail, Result};
 use clap::Subcommand;
-use rim_common::types::{ToolInfo, ToolInfoDetails, ToolkitManifest};
+use rim_common::types::{ToolInfo, ToolInfoDetails, ToolMap, ToolkitManifest};
 
 use crate::{
-    components::{split_components, Component},
+    components::{split_components, Component, ToolchainComponent},
     fingerprint::InstallationRecord,
     AppInfo, InstallConfiguration, ToolkitManifestExt, UninstallConfiguration,
 };
@@ -23,6 +23,13 @@ pub enum ComponentCommand {
         /// Allow insecure connections when download packages from server.
         #[arg(short = 'k', long)]
         insecure: bool,
+        /// Uninstall the components that are already installed before installing them again,
+        /// useful for repairing broken installations.
+        #[arg(long)]
+        reinstall: bool,
+        /// Allow running the `post-install` and `pre-uninstall` commands and scripts of tools
+        #[arg(long)]
+        allow_hooks: bool,
         /// The list of components to install, check `list component` for available options
         #[arg(value_name = "COMPONENTS", value_delimiter = ',')]
         components: Vec<String>,
@@ -33,6 +40,9 @@ pub enum ComponentCommand {
         /// The list of components to uninstall, check `list component --installed` for available options
         #[arg(value_name = "COMPONENTS", value_delimiter = ',')]
         components: Vec<String>,
+        /// Allow running the `pre-uninstall` commands and uninstall scripts of tools
+        #[arg(long)]
+        allow_hooks: bool,
     },
 }
 
@@ -42,8 +52,13 @@ impl ComponentCommand {
             Self::Install {
                 components,
                 insecure,
-            } => install_components(components, *insecure),
-            Self::Uninstall { components } => uninstall_components(components),
+                reinstall,
+                allow_hooks,
+            } => install_components(components, *insecure, *reinstall, *allow_hooks),
+            Self::Uninstall {
+                components,
+                allow_hooks,
+            } => uninstall_components(components, *allow_hooks),
         }
     }
 }
@@ -58,7 +73,12 @@ pub(super) fn execute(cmd: &ManagerSubcommands) -> Result<ExecStatus> {
     Ok(ExecStatus::new_executed())
 }
 
-fn install_components(components: &[String], insecure: bool) -> Result<()> {
+fn install_components(
+    components: &[String],
+    insecure: bool,
+    reinstall: bool,
+    allow_hooks: bool,
+) -> Result<()> {
     let manifest = ToolkitManifest::load_from_install_dir()?;
     let all_comps = manifest.current_target_components(true)?;
 
@@ -86,9 +106,16 @@ fn install_components(components: &[String], insecure: bool) -> Result<()> {
     }
 
     let (tc_components, tools) = split_components(comps_to_install);
+    if reinstall {
+        remove_installed(&tc_components, &tools, allow_hooks)?;
+    }
 
-    let mut config =
-        InstallConfiguration::new(AppInfo::get_installed_dir(), &manifest)?.insecure(insecure);
+    // NB: the installation record must be loaded after the removal above,
+    // otherwise the removed components would be written back to it.
+    let mut config = InstallConfiguration::new(AppInfo::get_installed_dir(), &manifest)?
+        .insecure(insecure)
+        .allow_hooks(allow_hooks)
+        .with_tool_failure_handler(common::tool_failure_handler());
     config.install_toolchain_components(&tc_components)?;
     config.install_tools(&tools)?;
 
@@ -96,7 +123,45 @@ fn install_components(components: &[String], insecure: bool) -> Result<()> {
     Ok(())
 }
 
-fn uninstall_components(components: &[String]) -> Result<()> {
+/// Uninstall the components among `tc_components` and `tools` that are already installed,
+/// so that they can be installed again from a clean state.
+fn remove_installed(
+    tc_components: &[ToolchainComponent],
+    tools: &ToolMap,
+    allow_hooks: bool,
+) -> Result<()> {
+    let record = InstallationRecord::load_from_install_dir()?;
+    let installed_tc = record.installed_toolchain_components();
+    // the profile cannot be removed without removing the whole toolchain, skip it
+    let tc_comps_to_remove = tc_components
+        .iter()
+        .filter(|c| !c.is_profile && installed_tc.iter().any(|i| i.name == c.name))
+        .cloned()
+        .collect::<Vec<_>>();
+    let tools_to_remove = record
+        .tools
+        .into_iter()
+        .filter(|(name, _)| tools.contains_key(name))
+        .collect::<HashMap<_, _>>();
+    if tc_comps_to_remove.is_empty() && tools_to_remove.is_empty() {
+        return Ok(());
+    }
+
+    let names = tc_comps_to_remove
+        .iter()
+        .map(|c| c.name.as_str())
+        .chain(tools_to_remove.keys().map(|s| s.as_str()))
+        .collect::<Vec<_>>()
+        .join(", ");
+    info!("{}", t!("reinstalling_components", list = names));
+
+    let mut config = UninstallConfiguration::init(None)?.allow_hooks(allow_hooks);
+    config.remove_toolchain_components(&tc_comps_to_remove, 0.0)?;
+    config.remove_tools(tools_to_remove, 0.0)?;
+    Ok(())
+}
+
+fn uninstall_components(components: &[String], allow_hooks: bool) -> Result<()> {
     let record = InstallationRecord::load_from_install_dir()?;
 
     // make a set out of components to:
@@ -133,7 +198,7 @@ fn uninstall_components(components: &[String]) -> Result<()> {
         return Ok(());
     }
 
-    let mut config = UninstallConfiguration::init(None)?;
+    let mut config = UninstallConfiguration::init(None)?.allow_hooks(allow_hooks);
     config.remove_toolchain_components(&tc_comps_to_remove, 50.0)?;
     config.remove_tools(tools_to_remove, 50.0)?;
     info!("{}", t!("task_success"));
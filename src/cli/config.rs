@@ -0,0 +1,71 @@
+use anyhow::Result;
+use clap::Subcommand;
+use rim_common::types::TomlParser;
+
+use super::{ExecStatus, ManagerSubcommands};
+use crate::configuration::{ConfigKey, Configuration};
+use crate::AppInfo;
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigCommand {
+    /// Print the value of a setting, nothing will be printed if it is not set
+    Get {
+        /// The key of the setting, check `config list` for available keys
+        key: String,
+    },
+    /// Change the value of a setting
+    Set {
+        /// The key of the setting, check `config list` for available keys
+        key: String,
+        /// The new value, an empty string clears the version to skip of `update.<target>.skip`
+        value: String,
+    },
+    /// Show every setting along with its value
+    List,
+}
+
+impl ConfigCommand {
+    fn execute(&self) -> Result<()> {
+        match self {
+            Self::Get { key } => {
+                let key = key.parse::<ConfigKey>()?;
+                if let Some(value) = Configuration::load_from_install_dir().get(key) {
+                    println!("{value}");
+                }
+            }
+            Self::Set { key, value } => {
+                let key = key.parse::<ConfigKey>()?;
+                // unlike reading, a broken configuration file should not be
+                // silently replaced with the default one
+                let install_dir = AppInfo::get_installed_dir();
+                let mut config = if install_dir.join(Configuration::FILENAME).is_file() {
+                    Configuration::load_from_dir(install_dir)?
+                } else {
+                    Configuration::new()
+                };
+                config.set(key, value)?;
+                config.write_to_install_dir()?;
+                info!("{}", t!("config_value_updated", key = key, value = value));
+            }
+            Self::List => {
+                let config = Configuration::load_from_install_dir();
+                for key in ConfigKey::ALL {
+                    let value = config
+                        .get(*key)
+                        .unwrap_or_else(|| t!("config_value_not_set").to_string());
+                    println!("{key} = {value}");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Execute `config` command.
+pub(super) fn execute(cmd: &ManagerSubcommands) -> Result<ExecStatus> {
+    let ManagerSubcommands::Config { command } = cmd else {
+        return Ok(ExecStatus::default());
+    };
+    command.execute()?;
+    Ok(ExecStatus::new_executed().no_pause(true))
+}
@@ -0,0 +1,32 @@
+use super::{ExecStatus, ManagerSubcommands};
+use crate::core::doctor::{self, CheckStatus};
+use anyhow::{bail, Result};
+
+/// Execute `doctor` command.
+pub(super) fn execute(cmd: &ManagerSubcommands) -> Result<ExecStatus> {
+    let ManagerSubcommands::Doctor { insecure, offline } = cmd else {
+        return Ok(ExecStatus::default());
+    };
+
+    let diagnoses = doctor::diagnose(*insecure, *offline)?;
+    let mut failed_count = 0;
+    for diagnosis in &diagnoses {
+        let status = match diagnosis.status {
+            CheckStatus::Pass => "PASS",
+            CheckStatus::Warn => "WARN",
+            CheckStatus::Fail => {
+                failed_count += 1;
+                "FAIL"
+            }
+        };
+        println!("[{status:^6}] {}", diagnosis.message);
+        if let Some(remedy) = &diagnosis.remedy {
+            println!("         {remedy}");
+        }
+    }
+
+    if failed_count > 0 {
+        bail!(t!("doctor_failed", count = failed_count));
+    }
+    Ok(ExecStatus::new_executed().no_pause(true))
+}
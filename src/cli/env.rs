@@ -0,0 +1,215 @@
+use std::env;
+
+use anyhow::Result;
+use clap::ValueEnum;
+use rim_common::types::{ToolKind, ToolkitManifest};
+use rim_common::utils;
+use url::Url;
+
+use super::{ExecStatus, ManagerSubcommands};
+use crate::core::directories::RimDir;
+use crate::core::{RUSTUP_DIST_SERVER, RUSTUP_UPDATE_ROOT};
+use crate::fingerprint::InstallationRecord;
+use crate::{AppInfo, InstallConfiguration, ToolkitManifestExt};
+
+/// The shells that `env` subcommand can print commands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Shell {
+    /// POSIX compatible shells, such as `bash` and `zsh`
+    #[value(alias = "bash", alias = "zsh")]
+    Sh,
+    Fish,
+    #[value(alias = "pwsh")]
+    Powershell,
+    #[value(alias = "nushell")]
+    Nu,
+}
+
+impl Shell {
+    /// Guess the current shell, default to `powershell` on Windows and `sh` elsewhere.
+    fn detect() -> Self {
+        let Ok(shell) = env::var("SHELL") else {
+            return if cfg!(windows) {
+                Self::Powershell
+            } else {
+                Self::Sh
+            };
+        };
+        let name = shell.rsplit(['/', '\\']).next().unwrap_or_default();
+        match name.trim_end_matches(".exe") {
+            "fish" => Self::Fish,
+            "nu" => Self::Nu,
+            "pwsh" | "powershell" => Self::Powershell,
+            _ => Self::Sh,
+        }
+    }
+
+    /// Quote `val` as a string literal that will not be expanded by the shell.
+    fn quote(self, val: &str) -> String {
+        match self {
+            Self::Sh => format!(
+                "\"{}\"",
+                val.replace('\\', "\\\\")
+                    .replace('"', "\\\"")
+                    .replace('$', "\\$")
+                    .replace('`', "\\`")
+            ),
+            Self::Fish => format!(
+                "\"{}\"",
+                val.replace('\\', "\\\\")
+                    .replace('"', "\\\"")
+                    .replace('$', "\\$")
+            ),
+            Self::Powershell => format!("'{}'", val.replace('\'', "''")),
+            Self::Nu => format!("\"{}\"", val.replace('\\', "\\\\").replace('"', "\\\"")),
+        }
+    }
+
+    fn set_var(self, key: &str, val: &str) -> String {
+        let val = self.quote(val);
+        match self {
+            Self::Sh => format!("export {key}={val}"),
+            Self::Fish => format!("set -gx {key} {val}"),
+            Self::Powershell => format!("$env:{key} = {val}"),
+            Self::Nu => format!("$env.{key} = {val}"),
+        }
+    }
+
+    fn prepend_path(self, paths: &[String]) -> String {
+        match self {
+            Self::Sh => {
+                let joined = paths.iter().map(|p| format!("{p}:")).collect::<String>();
+                format!("export PATH={}\"$PATH\"", self.quote(&joined))
+            }
+            Self::Fish => {
+                let joined = paths.iter().map(|p| self.quote(p)).collect::<Vec<_>>();
+                format!("set -gx PATH {} $PATH", joined.join(" "))
+            }
+            Self::Powershell => {
+                let sep = if cfg!(windows) { ';' } else { ':' };
+                let joined = paths
+                    .iter()
+                    .map(|p| format!("{p}{sep}"))
+                    .collect::<String>();
+                format!("$env:PATH = {} + $env:PATH", self.quote(&joined))
+            }
+            Self::Nu => {
+                let joined = paths.iter().map(|p| self.quote(p)).collect::<Vec<_>>();
+                format!(
+                    "$env.PATH = ($env.PATH | split row (char esep) | prepend [{}])",
+                    joined.join(", ")
+                )
+            }
+        }
+    }
+}
+
+/// Execute `env` command.
+pub(super) fn execute(cmd: &ManagerSubcommands) -> Result<ExecStatus> {
+    let ManagerSubcommands::Env { shell } = cmd else {
+        return Ok(ExecStatus::default());
+    };
+    let shell = shell.unwrap_or_else(Shell::detect);
+
+    for line in env_commands(shell)? {
+        println!("{line}");
+    }
+    Ok(ExecStatus::new_executed().no_pause(true))
+}
+
+/// Get the commands to set up the environment variables of the current installation.
+fn env_commands(shell: Shell) -> Result<Vec<String>> {
+    let install_dir = AppInfo::get_installed_dir();
+    let manifest = ToolkitManifest::load_from_install_dir()?;
+    let mut config = InstallConfiguration::new(install_dir, &manifest)?;
+    // the servers were persisted as env vars during installation
+    if let Some(server) = env_url(RUSTUP_DIST_SERVER) {
+        config = config.with_rustup_dist_server(server);
+    }
+    if let Some(root) = env_url(RUSTUP_UPDATE_ROOT) {
+        config = config.with_rustup_update_root(root);
+    }
+
+    let mut lines = vec![];
+    for (key, val) in config.env_vars()? {
+        lines.push(shell.set_var(key, &val));
+    }
+
+    let record = InstallationRecord::load_from_install_dir()?;
+    let mut paths = vec![install_dir.to_path_buf(), config.cargo_bin().to_path_buf()];
+    paths.extend(
+        record
+            .tools
+            .values()
+            .filter(|tool| tool.tool_kind() == ToolKind::DirWithBin)
+            .filter_map(|tool| tool.paths.first().map(|dir| dir.join("bin"))),
+    );
+    let paths = paths
+        .iter()
+        .map(|p| utils::path_to_str(p).map(ToOwned::to_owned))
+        .collect::<Result<Vec<_>>>()?;
+    lines.push(shell.prepend_path(&paths));
+
+    Ok(lines)
+}
+
+fn env_url(key: &str) -> Option<Url> {
+    env::var(key).ok().and_then(|val| val.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // contains spaces, both kinds of quotes, `$` and other special characters
+    const VALUE: &str = r#"/a b/"c"/$d/'e'/`f`/\g"#;
+
+    #[test]
+    fn quote_special_characters() {
+        let cases = [
+            (Shell::Sh, r#"export KEY="/a b/\"c\"/\$d/'e'/\`f\`/\\g""#),
+            (Shell::Fish, r#"set -gx KEY "/a b/\"c\"/\$d/'e'/`f`/\\g""#),
+            (
+                Shell::Powershell,
+                r#"$env:KEY = '/a b/"c"/$d/''e''/`f`/\g'"#,
+            ),
+            (Shell::Nu, r#"$env.KEY = "/a b/\"c\"/$d/'e'/`f`/\\g""#),
+        ];
+        for (shell, expected) in cases {
+            assert_eq!(shell.set_var("KEY", VALUE), expected, "{shell:?}");
+        }
+    }
+
+    #[test]
+    fn prepend_quoted_paths() {
+        let paths = ["/a b".to_string(), "/$c/\"d\"".to_string()];
+        let sep = if cfg!(windows) { ';' } else { ':' };
+        let cases = [
+            (Shell::Sh, r#"export PATH="/a b:/\$c/\"d\":""$PATH""#.to_string()),
+            (Shell::Fish, r#"set -gx PATH "/a b" "/\$c/\"d\"" $PATH"#.to_string()),
+            (
+                Shell::Powershell,
+                format!(r#"$env:PATH = '/a b{sep}/$c/"d"{sep}' + $env:PATH"#),
+            ),
+            (
+                Shell::Nu,
+                r#"$env.PATH = ($env.PATH | split row (char esep) | prepend ["/a b", "/$c/\"d\""])"#
+                    .to_string(),
+            ),
+        ];
+        for (shell, expected) in cases {
+            assert_eq!(shell.prepend_path(&paths), expected, "{shell:?}");
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn sh_reads_back_quoted_value() {
+        let script = format!("{}; printf %s \"$KEY\"", Shell::Sh.set_var("KEY", VALUE));
+        let output = std::process::Command::new("sh")
+            .args(["-c", &script])
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), VALUE);
+    }
+}
@@ -1,19 +1,22 @@
 //! Separated module to handle installation related behaviors in command line.
 
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
+use std::thread;
 
 use crate::cli::common::{self, Confirm};
 use crate::cli::GlobalOpts;
-use crate::components::Component;
+use crate::components::{Component, ComponentsDiff};
 use crate::core::install::InstallConfiguration;
+use crate::core::update::UpdateOpt;
 use crate::core::{
     default_cargo_registry, default_rustup_dist_server, default_rustup_update_root,
-    get_toolkit_manifest, ToolkitManifestExt,
+    get_toolkit_manifest, override_target, try_it, ToolkitManifestExt,
 };
-use crate::default_install_dir;
+use crate::fingerprint::InstallationRecord;
+use crate::{resolve_install_dir, UninstallConfiguration};
 
 use super::common::{
     question_single_choice, ComponentChoices, ComponentDecoration, ComponentListBuilder,
@@ -21,7 +24,9 @@ use super::common::{
 use super::{ExecStatus, Installer, ManagerSubcommands};
 
 use anyhow::{bail, Result};
+use rim_common::types::{OpenEditorPolicy, TomlParser, ToolkitManifest};
 use rim_common::utils;
+use tokio_util::sync::CancellationToken;
 
 /// Perform installer actions.
 ///
@@ -31,36 +36,56 @@ pub(super) fn execute_installer(installer: &Installer) -> Result<ExecStatus> {
         prefix,
         registry_url,
         registry_name,
+        registry_token,
         rustup_dist_server,
         rustup_update_root,
         manifest: manifest_src,
         insecure,
+        rollback_on_failure,
         list_components,
         component,
+        components_from_file,
+        report_json,
+        max_download_speed,
+        keep_downloads,
+        package_dir,
+        temp_dir,
+        profile,
+        no_shortcut,
+        dry_run,
+        allow_hooks,
+        target,
+        offline,
         ..
     } = installer;
 
-    if matches!(&prefix, Some(p) if utils::is_root_dir(p)) {
+    let abs_prefix = resolve_install_dir(prefix.as_deref())?;
+    if utils::is_root_dir(&abs_prefix) {
         bail!(t!("notify_root_dir"));
     }
 
+    utils::set_offline(*offline);
     let manifest_url = manifest_src.as_ref().map(|s| s.to_url()).transpose()?;
     let mut manifest = blocking!(get_toolkit_manifest(manifest_url, *insecure))?;
+    if let Some(target) = target {
+        ensure_valid_target(&manifest, target)?;
+        warn!("{}", t!("target_overridden", target = target));
+        override_target(target);
+    }
 
     if *list_components {
         // print a list of available components then return, don't do anything else
-        super::list::list_components(false, Some(&manifest))?;
+        super::list::list_components(false, false, false, Some(&manifest))?;
         return Ok(ExecStatus::new_executed().no_pause(true));
     }
 
     manifest.adjust_paths()?;
 
     let component_list = manifest.current_target_components(true)?;
-    let abs_prefix = if let Some(path) = prefix {
-        utils::to_normalized_absolute_path(path, None)?
-    } else {
-        default_install_dir()
-    };
+    let component = merge_components_from_file(component, components_from_file.as_deref())?;
+    if let Some(names) = &component {
+        ensure_valid_components(&component_list, names)?;
+    }
     let mut user_opt =
         CustomInstallOpt::collect_from_user(&abs_prefix, component_list, component.as_deref())?;
 
@@ -72,9 +97,28 @@ pub(super) fn execute_installer(installer: &Installer) -> Result<ExecStatus> {
         .map(|u| (registry_name.as_str(), u))
         .unwrap_or(default_cargo_registry());
     let install_dir = user_opt.prefix;
+    let download_cache = keep_downloads
+        .as_ref()
+        .map(|dir| match dir {
+            Some(dir) => utils::to_normalized_absolute_path(dir, None),
+            None => Ok(install_dir.join("downloads")),
+        })
+        .transpose()?;
+    let package_dir = package_dir
+        .as_deref()
+        .map(|dir| utils::to_normalized_absolute_path(dir, None))
+        .transpose()?;
 
-    InstallConfiguration::new(&install_dir, &manifest)?
+    // NB: the deselected components are only removed after a successful installation,
+    // so a failed one leaves the existing installation as it was.
+    let Some(deselected) = reconcile_existing_install(&install_dir, &user_opt.components)? else {
+        return Ok(ExecStatus::new_executed());
+    };
+
+    let g_opts = GlobalOpts::get();
+    let config = InstallConfiguration::new(&install_dir, &manifest)?
         .with_cargo_registry(registry_name, registry_value)
+        .with_cargo_registry_token(registry_token.clone())
         .with_rustup_dist_server(
             rustup_dist_server
                 .clone()
@@ -86,9 +130,32 @@ pub(super) fn execute_installer(installer: &Installer) -> Result<ExecStatus> {
                 .unwrap_or_else(|| default_rustup_update_root().clone()),
         )
         .insecure(*insecure)
-        .install(user_opt.components)?;
+        .rollback_on_failure(*rollback_on_failure)
+        .with_max_download_speed(*max_download_speed)
+        .with_toolchain_profile(profile.as_deref())?
+        .with_download_cache(download_cache)
+        .with_package_dir(package_dir)
+        .with_temp_dir(temp_dir.as_deref())
+        .no_shortcut(*no_shortcut)
+        .dry_run(*dry_run)
+        .allow_hooks(*allow_hooks)
+        .with_tool_failure_handler(common::tool_failure_handler())
+        .with_cancel_token(cancel_on_ctrl_c());
+    if let Some(report_path) = report_json {
+        let (result, report) = config.install_and_report(user_opt.components);
+        if let Err(e) = report.write_json(report_path) {
+            error!("{e:#}");
+        }
+        result?;
+    } else {
+        config.install(user_opt.components)?;
+    }
 
-    let g_opts = GlobalOpts::get();
+    if *dry_run {
+        println!("\n{}\n", t!("dry_run_finish"));
+        return Ok(ExecStatus::new_executed());
+    }
+    remove_deselected_components(&install_dir, &deselected, *allow_hooks)?;
     if !g_opts.quiet {
         println!("\n{}\n", t!("install_finish_info"));
     }
@@ -98,15 +165,22 @@ pub(super) fn execute_installer(installer: &Installer) -> Result<ExecStatus> {
     // However, it is not ideal when passing `--yes` when the user just want a quick install,
     // and might gets annoying when the user is doing a 'quick install' on WSL. (a VSCode
     // window will pop open on Windows)
-    // let ask_to_try_demo = !g_opts.yes_to_all;
+    //
+    // Whether to open it is now configured by the toolkit manifest instead,
+    // and `--yes` still skips the question when it is left to the user.
 
     // trying the demo requires desktop environment, make sure the user has it before asking them
-    // if utils::has_desktop_environment()
-    //     && ask_to_try_demo
-    //     && common::confirm(t!("question_try_demo"), true)?
-    // {
-    //     try_it::try_it(Some(&install_dir))?;
-    // }
+    let open_editor = utils::has_desktop_environment()
+        && match manifest.open_editor_after_install() {
+            OpenEditorPolicy::Never => false,
+            OpenEditorPolicy::Always => true,
+            OpenEditorPolicy::Ask => {
+                !g_opts.yes_to_all && common::confirm(t!("question_try_demo"), true)?
+            }
+        };
+    if open_editor {
+        try_it::try_it(Some(&install_dir))?;
+    }
 
     #[cfg(unix)]
     if let Some(cmd) = crate::core::os::unix::source_command() {
@@ -154,10 +228,15 @@ impl CustomInstallOpt {
         #[cfg(windows)]
         const CLEAR_SCREEN_SPELL: &str = "";
 
+        let clear_screen = if GlobalOpts::get().no_color {
+            ""
+        } else {
+            CLEAR_SCREEN_SPELL
+        };
         let mut stdout = io::stdout();
         writeln!(
             &mut stdout,
-            "{CLEAR_SCREEN_SPELL}\n\n{}",
+            "{clear_screen}\n\n{}",
             t!("welcome", product = utils::build_cfg_locale("product"))
         )?;
         writeln!(&mut stdout, "\n\n{}", t!("what_this_is"))?;
@@ -191,17 +270,171 @@ impl CustomInstallOpt {
     }
 }
 
+/// Compare the selected `components` with the ones already installed in `install_dir`
+/// (if there is an existing installation), and ask the user to confirm the changes.
+///
+/// Return the names of the installed components that are no longer selected,
+/// which should be removed by [`remove_deselected_components`] once the installation succeeded,
+/// or `None` if the user refused to proceed.
+fn reconcile_existing_install(
+    install_dir: &Path,
+    components: &[Component],
+) -> Result<Option<Vec<String>>> {
+    if !install_dir.join(InstallationRecord::FILENAME).is_file() {
+        return Ok(Some(vec![]));
+    }
+    let record = InstallationRecord::load_from_dir(install_dir)?;
+    let diff = ComponentsDiff::new(&record, components);
+    // the same components are selected again, there is nothing to reconcile
+    if diff.is_unchanged() {
+        return Ok(Some(vec![]));
+    }
+
+    if !GlobalOpts::get().quiet {
+        show_components_diff(install_dir, &diff)?;
+    }
+    if !common::confirm(t!("question_apply_changes"), true)? {
+        return Ok(None);
+    }
+    Ok(Some(diff.to_remove))
+}
+
+/// Remove the installed components in `install_dir` that are listed in `names`.
+fn remove_deselected_components(
+    install_dir: &Path,
+    names: &[String],
+    allow_hooks: bool,
+) -> Result<()> {
+    if names.is_empty() {
+        return Ok(());
+    }
+
+    info!(
+        "{}",
+        t!("removing_deselected_components", list = names.join(", "))
+    );
+    let record = InstallationRecord::load_from_dir(install_dir)?;
+    let tc_comps_to_remove = record
+        .installed_toolchain_components()
+        .into_iter()
+        .filter(|c| names.contains(&c.name))
+        .collect::<Vec<_>>();
+    let tools_to_remove = record
+        .tools
+        .into_iter()
+        .filter(|(name, _)| names.contains(name))
+        .collect::<HashMap<_, _>>();
+
+    let mut config = UninstallConfiguration::init_in(install_dir, None)?.allow_hooks(allow_hooks);
+    config.remove_toolchain_components(&tc_comps_to_remove, 0.0)?;
+    config.remove_tools(tools_to_remove, 0.0)?;
+    Ok(())
+}
+
+fn show_components_diff(install_dir: &Path, diff: &ComponentsDiff) -> Result<()> {
+    let mut stdout = io::stdout();
+    writeln!(
+        &mut stdout,
+        "\n{}",
+        t!("existing_installation_found", dir = install_dir.display())
+    )?;
+    let sections = [
+        (t!("components_to_add"), &diff.to_add),
+        (t!("components_to_keep"), &diff.to_keep),
+        (t!("components_to_remove"), &diff.to_remove),
+    ];
+    for (title, names) in sections {
+        if names.is_empty() {
+            continue;
+        }
+        writeln!(&mut stdout, "\n{title}:")?;
+        for name in names {
+            writeln!(&mut stdout, "\t{name}")?;
+        }
+    }
+    writeln!(&mut stdout)?;
+    Ok(())
+}
+
+/// Spawn a thread that cancels the returned token once Ctrl-C was pressed,
+/// and exits the program right away if it was pressed again.
+fn cancel_on_ctrl_c() -> CancellationToken {
+    let token = CancellationToken::new();
+    let cloned = token.clone();
+    thread::spawn(move || -> Result<()> {
+        blocking!(async {
+            tokio::signal::ctrl_c().await?;
+            warn!("{}", t!("install_cancelling"));
+            cloned.cancel();
+            tokio::signal::ctrl_c().await?;
+            std::process::exit(130);
+        })
+    });
+    token
+}
+
 fn read_install_dir_input(default: &str) -> Result<Option<String>> {
     let dir_input = common::question_str(t!("question_install_dir"), None, default)?;
+    let abs_dir = utils::to_normalized_absolute_path(&dir_input, None)?;
     // verify path input before proceeding
-    if utils::is_root_dir(&dir_input) {
+    if utils::is_root_dir(&abs_dir) {
         warn!("{}", t!("notify_root_dir"));
         Ok(None)
     } else {
-        Ok(Some(dir_input))
+        Ok(Some(utils::path_to_str(&abs_dir)?.to_string()))
     }
 }
 
+/// Combine the components given by `--component` with the ones listed in
+/// the file of `--components-from-file`, if any.
+fn merge_components_from_file(
+    inline: &Option<Vec<String>>,
+    file: Option<&Path>,
+) -> Result<Option<Vec<String>>> {
+    let Some(path) = file else {
+        return Ok(inline.clone());
+    };
+
+    let content = utils::read_to_string("component list", path)?;
+    let mut components = inline.clone().unwrap_or_default();
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('#') {
+            continue;
+        }
+        let names = line.split(',').map(str::trim).filter(|s| !s.is_empty());
+        for name in names {
+            if !components.iter().any(|c| c == name) {
+                components.push(name.to_string());
+            }
+        }
+    }
+    Ok(Some(components))
+}
+
+/// Make sure every component in `names` can be installed, otherwise return an error.
+fn ensure_valid_components(all_components: &[Component], names: &[String]) -> Result<()> {
+    let invalid = names
+        .iter()
+        .filter(|name| !all_components.iter().any(|c| &c.name == *name))
+        .map(|s| s.as_str())
+        .collect::<Vec<_>>();
+    if !invalid.is_empty() {
+        bail!(t!("invalid_components", list = invalid.join(",")));
+    }
+    Ok(())
+}
+
+/// Make sure the toolkit provides anything for `target`, otherwise return an error.
+fn ensure_valid_target(manifest: &ToolkitManifest, target: &str) -> Result<()> {
+    let available = manifest.available_targets();
+    // tools might be available via the `all` or wildcard keys
+    if !available.contains(target) && manifest.tools_for_target(target).is_none() {
+        let list = available.into_iter().collect::<Vec<_>>().join(", ");
+        bail!(t!("unsupported_target", target = target, list = list));
+    }
+    Ok(())
+}
+
 fn default_component_choices<'a>(
     all_components: &'a [Component],
     user_selected_comps: Option<&[String]>,
@@ -212,23 +445,24 @@ fn default_component_choices<'a>(
     common::component_choices_with_constrains(
         all_components,
         |_idx, component: &Component| -> bool {
-            let not_optional_and_not_installed =
-                !component.installed && (component.required || !component.optional);
+            let not_optional_and_not_up_to_date = (!component.installed
+                || component.update_available)
+                && (component.required || !component.optional);
             let user_selected = selected_comps_set.contains(&component.name);
-            user_selected || not_optional_and_not_installed
+            user_selected || not_optional_and_not_up_to_date
         },
     )
 }
 
 fn custom_component_choices<'a>(
     all_components: &'a [Component],
-    user_selected_comps: Option<&[String]>,
+    default: &ComponentChoices<'a>,
 ) -> Result<ComponentChoices<'a>> {
     let list_of_comps = ComponentListBuilder::new(all_components)
         .show_desc(true)
         .decorate(ComponentDecoration::Selection)
         .build();
-    let default_ids = default_component_choices(all_components, user_selected_comps)
+    let default_ids = default
         .keys()
         .map(|idx| (idx + 1).to_string())
         .collect::<Vec<_>>()
@@ -266,23 +500,66 @@ fn read_component_selections<'a>(
         t!("install_custom"),
     ];
     let choice = question_single_choice(t!("question_components_profile"), profile_choices, "1")?;
-    let selection = match choice {
+    let mut selection = match choice {
         // Default set
         1 => default_component_choices(all_components, user_selected_comps),
-        // Full set, but exclude installed components
+        // Full set, but exclude installed components that are up to date
         2 => all_components
             .iter()
             .enumerate()
-            .filter(|(_, c)| !c.installed)
+            .filter(|(_, c)| !c.installed || c.update_available)
             .collect(),
         // Customized set
-        3 => custom_component_choices(all_components, user_selected_comps)?,
+        3 => {
+            let default = default_component_choices(all_components, user_selected_comps);
+            custom_component_choices(all_components, &default)?
+        }
         _ => unreachable!("out-of-range input should already be caught"),
     };
 
+    // don't let the user confirm a selection that will fail the installation later
+    while warn_conflicting_components(&selection) {
+        selection = custom_component_choices(all_components, &selection)?;
+    }
+
     Ok(selection)
 }
 
+/// Print a warning if any of the selected components conflict with each other,
+/// and return `true` if so.
+fn warn_conflicting_components(selection: &ComponentChoices<'_>) -> bool {
+    let selected_names: HashSet<&str> = selection.values().map(|c| c.name.as_str()).collect();
+    let mut conflicts = BTreeSet::new();
+    for comp in selection.values() {
+        let Some(tool) = &comp.tool_installer else {
+            continue;
+        };
+        for conflicted_name in tool.conflicts() {
+            if !selected_names.contains(conflicted_name.as_str()) {
+                continue;
+            }
+            // sort the conflicting pairs, so that (A, B) and (B, A) will be treated as the same
+            let (a, b) = (comp.name.as_str(), conflicted_name.as_str());
+            conflicts.insert(if a < b { (a, b) } else { (b, a) });
+        }
+    }
+    if conflicts.is_empty() {
+        return false;
+    }
+
+    let conflict_list = conflicts
+        .into_iter()
+        .map(|(a, b)| format!("\t{a} ({})", t!("conflicts_with", name = b)))
+        .collect::<Vec<_>>()
+        .join("\n");
+    warn!(
+        "{}:\n{conflict_list}\n{}",
+        t!("conflicting_components_selected"),
+        t!("deselect_conflicting_components")
+    );
+    true
+}
+
 static SHOW_MISSING_PKG_SRC_ONCE: OnceLock<()> = OnceLock::new();
 
 fn ask_tool_source(name: String) -> Result<String> {
@@ -296,9 +573,16 @@ fn ask_tool_source(name: String) -> Result<String> {
 }
 
 pub(super) fn execute_manager(manager: &ManagerSubcommands) -> Result<ExecStatus> {
-    let ManagerSubcommands::Install { version, .. } = manager else {
+    let ManagerSubcommands::Install { version, insecure } = manager else {
         return Ok(ExecStatus::default());
     };
 
-    todo!("install dist with version '{version}'");
+    UpdateOpt::new()
+        .insecure(*insecure)
+        .update_toolkit(|path| {
+            blocking!(super::update::install_toolkit_version(
+                path, version, *insecure
+            ))
+        })?;
+    Ok(ExecStatus::new_executed())
 }
@@ -1,15 +1,22 @@
 use std::io::Write;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use clap::Subcommand;
-use rim_common::types::ToolkitManifest;
+use rim_common::types::{ToolKind, ToolkitManifest};
+use rim_common::utils;
+use serde::Serialize;
+use url::Url;
 
+use super::update::UPDATES_AVAILABLE_EXIT_CODE;
 use super::{handle_user_choice, ExecStatus, GlobalOpts, ManagerSubcommands};
 use crate::{
-    components,
-    core::ToolkitManifestExt,
+    components::{self, Component},
+    core::{get_toolkit_manifest, tools, ToolkitManifestExt},
     fingerprint::InstallationRecord,
-    toolkit::{toolkits_from_server, Toolkit},
+    toolkit::{
+        self, latest_installable_toolkit, toolkits_from_cache, toolkits_from_server,
+        OutdatedComponent, Toolkit,
+    },
 };
 
 #[derive(Subcommand, Debug, Default, Clone, Copy)]
@@ -22,24 +29,46 @@ pub enum ListCommand {
 }
 
 impl ListCommand {
-    fn execute(&self, installed: bool) -> Result<()> {
+    fn execute(&self, installed: bool, json: bool, offline: bool, with_sizes: bool) -> Result<()> {
         match self {
-            Self::Component => list_components(installed, None),
-            Self::Toolkit => blocking!(list_toolkits(installed)),
+            Self::Component => list_components(installed, json, with_sizes, None),
+            Self::Toolkit => blocking!(list_toolkits(installed, offline)),
         }
     }
 }
 
 pub(super) fn execute(cmd: &ManagerSubcommands) -> Result<ExecStatus> {
-    let ManagerSubcommands::List { installed, command } = cmd else {
+    let ManagerSubcommands::List {
+        installed,
+        compare,
+        json,
+        outdated,
+        offline,
+        with_sizes,
+        command,
+    } = cmd
+    else {
         return Ok(ExecStatus::default());
     };
 
+    if let Some(url) = compare {
+        blocking!(compare_toolkit(url, *json))?;
+        return Ok(ExecStatus::new_executed());
+    }
+    if *outdated {
+        let status = ExecStatus::new_executed().no_pause(true);
+        return Ok(if blocking!(list_outdated(*json, *offline))? {
+            status.with_exit_code(UPDATES_AVAILABLE_EXIT_CODE)
+        } else {
+            status
+        });
+    }
+
     // `command` should either be passed from commandline option or being repeat
     // asked from user interaction until determined, which means it couldn't be `none`,
     // but we still fallback to default in case something bad happens.
     let sub_cmd = command.unwrap_or_default();
-    sub_cmd.execute(*installed)?;
+    sub_cmd.execute(*installed, *json, *offline, *with_sizes)?;
 
     Ok(ExecStatus::new_executed())
 }
@@ -57,17 +86,86 @@ pub(super) fn ask_list_command() -> Result<Option<ListCommand>> {
     Ok(cmd)
 }
 
+/// The information of a component to print when `--json` is specified.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ComponentSummary<'a> {
+    name: &'a str,
+    category: &'a str,
+    version: Option<&'a str>,
+    installed: bool,
+    update_available: bool,
+    required: bool,
+    optional: bool,
+    description: Option<&'a str>,
+    download_size: Option<u64>,
+}
+
+impl<'a> From<&'a Component> for ComponentSummary<'a> {
+    fn from(value: &'a Component) -> Self {
+        Self {
+            name: &value.name,
+            category: &value.category,
+            version: value.version.as_deref(),
+            installed: value.installed,
+            update_available: value.update_available,
+            required: value.required,
+            optional: value.optional,
+            description: value.desc.as_deref(),
+            download_size: value.download_size,
+        }
+    }
+}
+
+/// Print components as a JSON array, sorted by category then name.
+fn print_components_json<'a, I>(components: I) -> Result<()>
+where
+    I: Iterator<Item = &'a Component>,
+{
+    let mut summaries = components.map(ComponentSummary::from).collect::<Vec<_>>();
+    summaries.sort_by(|a, b| (a.category, a.name).cmp(&(b.category, b.name)));
+    writeln!(
+        std::io::stdout(),
+        "{}",
+        serde_json::to_string_pretty(&summaries)?
+    )?;
+    Ok(())
+}
+
 /// Print a list of components and return them.
 pub(crate) fn list_components(
     installed_only: bool,
+    json: bool,
+    with_sizes: bool,
     manifest: Option<&ToolkitManifest>,
 ) -> Result<()> {
-    let components = if let Some(mf) = manifest {
+    let mut components = if let Some(mf) = manifest {
         mf.current_target_components(true)?
     } else {
         let fp = InstallationRecord::load_from_install_dir()?;
         components::all_components_from_installation(&fp)?
     };
+    if with_sizes {
+        blocking!(components::fetch_download_sizes(&mut components, false));
+    }
+    let size_suffix = |comp: &Component| -> String {
+        if !with_sizes {
+            return String::new();
+        }
+        let size = comp
+            .download_size
+            .map(utils::format_size)
+            .unwrap_or_else(|| t!("unknown").to_string());
+        format!(" [{size}]")
+    };
+
+    if json {
+        return print_components_json(
+            components
+                .iter()
+                .filter(|comp| !installed_only || comp.installed),
+        );
+    }
 
     let comp_iter = components.iter();
     let verbose = GlobalOpts::get().verbose;
@@ -83,9 +181,9 @@ pub(crate) fn list_components(
                         .as_ref()
                         .map(|ver| format!(" {ver}"))
                         .unwrap_or_default();
-                    format!("{}{version}", comp.display_name)
+                    format!("{}{version}{}", comp.display_name, size_suffix(comp))
                 } else {
-                    comp.display_name.clone()
+                    format!("{}{}", comp.display_name, size_suffix(comp))
                 })
             })
             .collect::<Vec<_>>();
@@ -106,22 +204,25 @@ pub(crate) fn list_components(
             } else {
                 String::new()
             };
-            let installed_suffix = if comp.installed {
+            let installed_suffix = if comp.update_available {
+                format!(" ({})", t!("update_available"))
+            } else if comp.installed {
                 format!(" ({})", t!("installed"))
             } else {
                 String::new()
             };
             writeln!(
                 &mut stdout,
-                "{}{version}{installed_suffix}",
-                comp.display_name
+                "{}{version}{installed_suffix}{}",
+                comp.display_name,
+                size_suffix(comp)
             )?;
         }
     }
     Ok(())
 }
 
-async fn list_toolkits(installed_only: bool) -> Result<()> {
+async fn list_toolkits(installed_only: bool, offline: bool) -> Result<()> {
     let maybe_installed_tk = Toolkit::installed(false).await?;
     let mut stdout = std::io::stdout();
 
@@ -134,7 +235,12 @@ async fn list_toolkits(installed_only: bool) -> Result<()> {
             writeln!(&mut stdout, "{}", t!("no_toolkit_installed"))?;
         }
     } else {
-        let all_toolkits = toolkits_from_server(false).await?
+        let toolkits = if offline {
+            toolkits_from_cache()?
+        } else {
+            toolkits_from_server(false).await?
+        };
+        let all_toolkits = toolkits
             .into_iter()
             .map(|tk| async move {
                 let installed_suffix = if matches!(maybe_installed_tk, Some(mutex) if *mutex.lock().await == tk) {
@@ -151,3 +257,148 @@ async fn list_toolkits(installed_only: bool) -> Result<()> {
     }
     Ok(())
 }
+
+/// Print the component-level differences between the installed toolkit
+/// and the toolkit described by the manifest from `url`.
+async fn compare_toolkit(url: &Url, json: bool) -> Result<()> {
+    let Some(installed) = Toolkit::installed(false).await? else {
+        bail!(t!("no_toolkit_installed"));
+    };
+    let manifest = get_toolkit_manifest(Some(url.clone()), false).await?;
+    let other = Toolkit::try_from(&manifest)?;
+    let diff = installed.lock().await.diff(&other);
+
+    let mut stdout = std::io::stdout();
+    if json {
+        writeln!(&mut stdout, "{}", serde_json::to_string_pretty(&diff)?)?;
+        return Ok(());
+    }
+
+    writeln!(&mut stdout)?;
+    if diff.is_empty() {
+        writeln!(&mut stdout, "{}", t!("no_toolkit_difference"))?;
+        return Ok(());
+    }
+    let fmt_ver = |ver: &Option<String>| ver.as_deref().unwrap_or("N/A").to_string();
+    for comp in &diff.added {
+        writeln!(
+            &mut stdout,
+            "+ {} {}",
+            comp.name,
+            fmt_ver(&comp.new_version)
+        )?;
+    }
+    for comp in &diff.removed {
+        writeln!(
+            &mut stdout,
+            "- {} {}",
+            comp.name,
+            fmt_ver(&comp.old_version)
+        )?;
+    }
+    for comp in &diff.changed {
+        writeln!(
+            &mut stdout,
+            "~ {} {} -> {}",
+            comp.name,
+            fmt_ver(&comp.old_version),
+            fmt_ver(&comp.new_version)
+        )?;
+    }
+    Ok(())
+}
+
+/// Print the installed components that have newer versions available,
+/// return `true` if there are any.
+///
+/// The latest versions come from the manifest of the latest toolkit on server,
+/// or the manifest of the installed toolkit if there isn't a newer one or `offline` is true.
+/// The latest versions of tools installed by `cargo` are looked up on crates.io unless `offline`.
+async fn list_outdated(json: bool, offline: bool) -> Result<bool> {
+    let record = InstallationRecord::load_from_install_dir()?;
+    let installed = components::all_components_from_installation(&record)?;
+    let manifest = latest_manifest(offline).await?;
+    let latest = manifest.current_target_components(false)?;
+    let mut outdated = toolkit::outdated_components(&installed, &latest);
+
+    if !offline {
+        let cargo_tools = record
+            .tools
+            .iter()
+            .filter(|(_, tool)| tool.tool_kind() == ToolKind::CargoTool);
+        for (name, tool) in cargo_tools {
+            let Some(cur_ver) = tool.version() else {
+                continue;
+            };
+            let new_ver =
+                match tools::latest_crate_version(name, manifest.proxy.clone(), false).await {
+                    Ok(Some(ver)) => ver,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        warn!(
+                            "{}: {e}",
+                            t!("fetch_latest_crate_version_failed", name = name)
+                        );
+                        continue;
+                    }
+                };
+            let Some(found) = OutdatedComponent::check(name, cur_ver, &new_ver) else {
+                continue;
+            };
+            // the manifest might suggest an even newer version, keep the newest one
+            match outdated.iter_mut().find(|comp| comp.name == found.name) {
+                Some(existing) => {
+                    if OutdatedComponent::check(name, &existing.latest_version, &new_ver).is_some()
+                    {
+                        *existing = found;
+                    }
+                }
+                None => outdated.push(found),
+            }
+        }
+    }
+    outdated.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut stdout = std::io::stdout();
+    if json {
+        writeln!(&mut stdout, "{}", serde_json::to_string_pretty(&outdated)?)?;
+        return Ok(!outdated.is_empty());
+    }
+    if outdated.is_empty() {
+        info!("{}", t!("everything_up_to_date"));
+        return Ok(false);
+    }
+    writeln!(&mut stdout)?;
+    for comp in &outdated {
+        writeln!(
+            &mut stdout,
+            "{}: {} -> {}",
+            comp.name, comp.installed_version, comp.latest_version
+        )?;
+    }
+    Ok(true)
+}
+
+/// Get the manifest of the latest toolkit available on server, fallback to
+/// the manifest of the installed toolkit if there isn't a newer one.
+async fn latest_manifest(offline: bool) -> Result<ToolkitManifest> {
+    if !offline {
+        if let Some(installed) = Toolkit::installed(false).await? {
+            let installed = &*installed.lock().await;
+            match latest_installable_toolkit(installed, false).await {
+                Ok(Some(latest)) => {
+                    if let Some(url) = latest
+                        .manifest_url
+                        .as_deref()
+                        .and_then(|s| Url::parse(s).ok())
+                    {
+                        return get_toolkit_manifest(Some(url), false).await;
+                    }
+                }
+                Ok(None) => (),
+                Err(e) => warn!("{}: {e}", t!("fetch_latest_toolkit_version_failed")),
+            }
+        }
+    }
+    ToolkitManifest::load_from_install_dir()
+}
@@ -1,15 +1,21 @@
 //! Contains all the definition of command line arguments.
 
 mod check;
-mod common;
+pub(crate) mod common;
 mod component;
+mod config;
+mod doctor;
+mod env;
 mod install;
 mod list;
+mod search;
 mod tryit;
 mod uninstall;
 mod update;
+mod verify;
+mod which;
 
-use crate::core::{GlobalOpts, Language};
+use crate::core::{rustup::TOOLCHAIN_PROFILES, GlobalOpts, Language};
 use anyhow::{anyhow, bail, Result};
 use clap::error::ErrorKind;
 use clap::{Parser, Subcommand, ValueHint};
@@ -117,6 +123,9 @@ pub struct ExecStatus {
     pub executed: bool,
     /// A flag to skip pausing the console window (on Windows) after executing.
     pub no_pause: bool,
+    /// The code to exit the process with, a non-zero value will be reported
+    /// even if the command was successfully executed.
+    pub exit_code: i32,
 }
 
 impl ExecStatus {
@@ -130,6 +139,7 @@ impl ExecStatus {
 
     setter!(executed(self.executed, bool));
     setter!(no_pause(self.no_pause, bool));
+    setter!(with_exit_code(self.exit_code, i32));
 }
 
 /// Install rustup, rust toolchain, and various tools.
@@ -165,27 +175,53 @@ pub struct Installer {
     /// Do NOT use this if you don't know what you're doing.
     #[arg(long, conflicts_with = "no_modify_path")]
     no_modify_env: bool,
+    /// Disable colored output, this can also be done by setting the `NO_COLOR` environment variable.
+    #[arg(long)]
+    no_color: bool,
     /// Allow insecure connections when download packages from server.
     #[arg(short = 'k', long)]
     insecure: bool,
+    /// Revert the changes made to the system if the installation failed.
+    #[arg(long)]
+    rollback_on_failure: bool,
 
     /// Specify another language to display
     #[arg(short, long, value_name = "LANG", value_parser = Language::possible_values())]
     pub lang: Option<String>,
-    /// Set another path to install Rust.
+    /// Display messages in a specific locale, such as `en-US`,
+    /// this can also be done by setting the `RIM_LOCALE` environment variable.
+    #[arg(long, value_name = "LOCALE", conflicts_with = "lang")]
+    pub locale: Option<String>,
+    /// Set another path to install Rust,
+    /// this can also be done by setting the `RIM_INSTALL_DIR` environment variable.
     #[arg(long, value_name = "PATH", value_hint = ValueHint::DirPath)]
     prefix: Option<PathBuf>,
     /// Specify another cargo registry url to replace `crates.io`, could be `sparse+URL`.
-    #[arg(hide = true, long)]
+    #[arg(hide = true, long, value_parser = parse_registry_url)]
     registry_url: Option<String>,
     /// Specify another cargo registry name to replace `crates.io`.
     #[arg(hide = true, long, default_value = "mirror")]
     registry_name: String,
+    /// Specify a token to authenticate with the cargo registry.
+    #[arg(hide = true, long, value_name = "TOKEN")]
+    registry_token: Option<String>,
     /// Specify another server to download Rust toolchain.
-    #[arg(hide = true, long, value_name = "URL", value_hint = ValueHint::Url)]
+    #[arg(
+        hide = true,
+        long,
+        value_name = "URL",
+        value_hint = ValueHint::Url,
+        value_parser = parse_server_url
+    )]
     pub rustup_dist_server: Option<Url>,
     /// Specify another server to download rustup.
-    #[arg(hide = true, long, value_name = "URL", value_hint = ValueHint::Url)]
+    #[arg(
+        hide = true,
+        long,
+        value_name = "URL",
+        value_hint = ValueHint::Url,
+        value_parser = parse_server_url
+    )]
     rustup_update_root: Option<Url>,
     /// Specify a path or url of manifest file that contains package source and various configurations.
     #[arg(long, value_name = "PATH or URL")]
@@ -199,6 +235,52 @@ pub struct Installer {
     /// For the complete list, use `--list-components` option.
     #[arg(short, long, value_delimiter = ',')]
     component: Option<Vec<String>>,
+    /// Read a list of components to install from a file, in addition to `--component`.
+    ///
+    /// Components can be separated by newlines or commas, lines starting with `#` are ignored.
+    #[arg(long, value_name = "PATH", value_hint = ValueHint::FilePath)]
+    components_from_file: Option<PathBuf>,
+    /// Write a JSON report of the installation result to the given path.
+    #[arg(long, value_name = "PATH", value_hint = ValueHint::FilePath)]
+    report_json: Option<PathBuf>,
+    /// Limit the download speed, in bytes per second.
+    #[arg(long, value_name = "BYTES")]
+    max_download_speed: Option<u64>,
+    /// Keep the downloaded packages in a cache directory and reuse them in later installations,
+    /// default to the `downloads` directory under the install location.
+    #[arg(long, value_name = "DIR", value_hint = ValueHint::DirPath, num_args = 0..=1)]
+    keep_downloads: Option<Option<PathBuf>>,
+    /// Look for the packages in this directory before downloading them,
+    /// matched by the `filename` in the toolkit manifest or the last segment of the URL.
+    #[arg(long, value_name = "DIR", value_hint = ValueHint::DirPath)]
+    package_dir: Option<PathBuf>,
+    /// Store temporary files in this directory instead of the `temp` directory under
+    /// the install location, can also be set via `RIM_TEMP_DIR` environment variable.
+    #[arg(long, value_name = "DIR", value_hint = ValueHint::DirPath)]
+    temp_dir: Option<PathBuf>,
+    /// Override the toolchain profile set in the toolkit manifest.
+    #[arg(long, value_name = "PROFILE", value_parser = TOOLCHAIN_PROFILES)]
+    profile: Option<String>,
+    /// Do not create desktop or application menu shortcuts for the installed tools.
+    #[arg(long)]
+    pub no_shortcut: bool,
+    /// Show what would be installed without making any changes to the system.
+    #[arg(long)]
+    dry_run: bool,
+    /// Allow running the `post-install` commands and install scripts of tools
+    /// defined in the toolkit manifest.
+    #[arg(long)]
+    pub allow_hooks: bool,
+    /// Select the tools and default host toolchain for another target instead of the current one,
+    /// useful for preparing an installation for another machine.
+    ///
+    /// Note that the installed toolkit might not be able to run on the current machine.
+    #[arg(long, value_name = "TRIPLE")]
+    target: Option<String>,
+    /// Refuse any network access, only the bundled offline toolkit manifest
+    /// and local packages (or `file://` URLs) can be used.
+    #[arg(long)]
+    pub offline: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -270,13 +352,26 @@ pub struct Manager {
     /// uninstallation.
     #[arg(long, conflicts_with = "no_modify_path")]
     no_modify_env: bool,
+    /// Disable colored output, this can also be done by setting the `NO_COLOR` environment variable.
+    #[arg(long)]
+    no_color: bool,
     /// Specify another server to download Rust toolchain.
-    #[arg(hide = true, long, value_name = "URL", value_hint = ValueHint::Url)]
+    #[arg(
+        hide = true,
+        long,
+        value_name = "URL",
+        value_hint = ValueHint::Url,
+        value_parser = parse_server_url
+    )]
     pub rustup_dist_server: Option<Url>,
 
     /// Specify another language to display
     #[arg(short, long, value_name = "LANG", value_parser = Language::possible_values())]
     pub lang: Option<String>,
+    /// Display messages in a specific locale, such as `en-US`,
+    /// this can also be done by setting the `RIM_LOCALE` environment variable.
+    #[arg(long, value_name = "LOCALE", conflicts_with = "lang")]
+    pub locale: Option<String>,
     #[command(subcommand)]
     pub command: Option<ManagerSubcommands>,
 }
@@ -293,9 +388,11 @@ impl ExecutableCommand for Installer {
             self.verbose,
             self.quiet,
             self.yes_to_all,
+            self.no_color,
             self.no_modify_env,
             self.no_modify_path,
             self.lang.as_deref(),
+            self.locale.as_deref(),
         )?;
         install::execute_installer(self)
     }
@@ -312,9 +409,11 @@ impl ExecutableCommand for Manager {
             self.verbose,
             self.quiet,
             self.yes_to_all,
+            self.no_color,
             self.no_modify_env,
             self.no_modify_path,
             self.lang.as_deref(),
+            self.locale.as_deref(),
         )?;
 
         let Some(subcmd) = &self.command else {
@@ -337,7 +436,17 @@ impl ExecutableCommand for Manager {
         // (manager only) If any of these subcommand was invoked, do not start GUI
         matches!(
             self.command,
-            Some(ManagerSubcommands::Check { .. } | ManagerSubcommands::TryIt { .. })
+            Some(
+                ManagerSubcommands::Check { .. }
+                    | ManagerSubcommands::TryIt { .. }
+                    | ManagerSubcommands::Verify { .. }
+                    | ManagerSubcommands::Doctor { .. }
+                    | ManagerSubcommands::Search { .. }
+                    | ManagerSubcommands::Env { .. }
+                    | ManagerSubcommands::Which { .. }
+                    | ManagerSubcommands::Config { .. }
+                    | ManagerSubcommands::Update { check: true, .. }
+            )
         )
     }
 }
@@ -353,12 +462,13 @@ impl TryFrom<Vec<String>> for Manager {
 #[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, Debug, Clone)]
 pub enum ManagerSubcommands {
-    /// Install a specific dist version
-    #[command(hide = true)]
+    /// Install a specific version of toolkit from server
     Install {
         /// Allow insecure connections when download packages from server.
         #[arg(short = 'k', long)]
         insecure: bool,
+        /// The version to install, could be an exact version such as `1.0.0`,
+        /// or a version requirement such as `^1.0`.
         #[arg(value_name = "VERSION")]
         version: String,
     },
@@ -387,15 +497,61 @@ pub enum ManagerSubcommands {
         /// a and b, but also other components that were selected by default will get updated.
         #[arg(short, long, value_delimiter = ',')]
         component: Option<Vec<String>>,
+        /// Pin a toolchain component at its installed version, so that it will be skipped
+        /// in this and later updates, can be specified multiple times.
+        #[arg(long, value_name = "COMPONENT", value_delimiter = ',')]
+        pin: Vec<String>,
+        /// Unpin a previously pinned toolchain component, so that it will be updated again,
+        /// can be specified multiple times.
+        #[arg(long, value_name = "COMPONENT", value_delimiter = ',')]
+        unpin: Vec<String>,
+        /// Only report the available updates without downloading or installing anything.
+        ///
+        /// The program exits with code 10 if any update is available.
+        #[arg(long, alias = "dry-run")]
+        check: bool,
     },
     /// Display a list of toolkits or components
     List {
         /// Show installed only
         #[arg(long)]
         installed: bool,
+        /// Compare the installed toolkit with the one described by another manifest,
+        /// and show the differences of components without installing anything.
+        #[arg(long, value_name = "URL", value_hint = ValueHint::Url)]
+        compare: Option<Url>,
+        /// Print the output in JSON format
+        #[arg(long)]
+        json: bool,
+        /// Show installed components that have newer versions available, then exit with
+        /// a non-zero code if there are any
+        #[arg(long, conflicts_with = "compare")]
+        outdated: bool,
+        /// Use the locally cached list of toolkits instead of fetching it from server,
+        /// the latest versions of tools installed by `cargo` will not be looked up either
+        #[arg(long)]
+        offline: bool,
+        /// Show the download size of components, the ones missing from the toolkit manifest
+        /// will be requested from server
+        #[arg(long)]
+        with_sizes: bool,
         #[command(subcommand)]
         command: Option<list::ListCommand>,
     },
+    /// Search for components or toolkits by keyword
+    Search {
+        /// The keyword to look for in the name and description, case-insensitive
+        query: String,
+        /// Search installed components only
+        #[arg(long)]
+        installed: bool,
+        /// Search the toolkits available on server as well
+        #[arg(long, conflicts_with = "installed")]
+        toolkit: bool,
+        /// Allow insecure connections when fetching toolkits from server.
+        #[arg(short = 'k', long)]
+        insecure: bool,
+    },
     /// Install or uninstall components
     Component {
         #[command(subcommand)]
@@ -406,6 +562,12 @@ pub enum ManagerSubcommands {
         /// Keep this manager tool, only uninstall toolkit
         #[arg(long, alias = "keep-manager")]
         keep_self: bool,
+        /// Allow running the `pre-uninstall` commands and uninstall scripts of tools
+        #[arg(long)]
+        allow_hooks: bool,
+        /// Only print what would be removed, without removing anything
+        #[arg(long)]
+        dry_run: bool,
     },
     /// A subcommand to create a new Rust project template and let you start coding with it.
     TryIt {
@@ -415,6 +577,8 @@ pub enum ManagerSubcommands {
     },
     /// Check source code in the current directory using installed rule-set for errors
     Check {
+        #[command(subcommand)]
+        command: Option<check::CheckCommand>,
         #[arg(
             trailing_var_arg = true,
             allow_hyphen_values = true,
@@ -423,6 +587,38 @@ pub enum ManagerSubcommands {
         /// Additional args to run `cargo clippy`, see all options with `cargo clippy --help`.
         extra_args: Vec<String>,
     },
+    /// Verify that every installed component still exists and is intact
+    Verify {
+        /// Try re-creating missing toolchain proxies if possible
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Diagnose common environment issues of the current installation
+    Doctor {
+        /// Allow insecure connections when checking the dist servers.
+        #[arg(long)]
+        insecure: bool,
+        /// Skip the checks that require network access
+        #[arg(long)]
+        offline: bool,
+    },
+    /// Print the commands to set up the environment variables of this installation
+    Env {
+        /// The shell to print commands for, detected from the environment if not specified
+        #[arg(long, value_enum)]
+        shell: Option<env::Shell>,
+    },
+    /// Show where an installed component is located
+    Which {
+        /// The name of the installed component
+        #[arg(value_name = "COMPONENT")]
+        component: String,
+    },
+    /// View or edit the settings of this manager, such as `autostart`
+    Config {
+        #[command(subcommand)]
+        command: config::ConfigCommand,
+    },
 }
 
 impl ExecutableCommand for ManagerSubcommands {
@@ -431,10 +627,16 @@ impl ExecutableCommand for ManagerSubcommands {
             install::execute_manager(self)?,
             update::execute(self)?,
             list::execute(self)?,
+            search::execute(self)?,
             component::execute(self)?,
             uninstall::execute(self)?,
             tryit::execute(self)?,
-            check::execute(self)?
+            check::execute(self)?,
+            verify::execute(self)?,
+            doctor::execute(self)?,
+            env::execute(self)?,
+            which::execute(self)?,
+            config::execute(self)?
         }
         Ok(ExecStatus::default())
     }
@@ -484,7 +686,7 @@ impl ManagerSubcommands {
         let maybe_cmd = handle_user_choice!(
             t!("choose_an_option"), 5,
             {
-                1 t!("modify_option") => { Some(Self::Component { command: ComponentCommand::Uninstall { components: vec![] } }) },
+                1 t!("modify_option") => { Some(Self::Component { command: ComponentCommand::Uninstall { components: vec![], allow_hooks: false } }) },
                 2 t!("update") => {
                     let insecure = handle_user_choice!(
                         t!("choose_an_option"), 1,
@@ -493,9 +695,9 @@ impl ManagerSubcommands {
                             2 t!("skip_ssl_check") => { true }
                         }
                     );
-                    Some(Self::Update { insecure, toolkit_only: false, manager_only: false, component: None })
+                    Some(Self::Update { insecure, toolkit_only: false, manager_only: false, component: None, pin: vec![], unpin: vec![], check: false })
                 },
-                3 t!("uninstall") => { Some(Self::Uninstall { keep_self: false }) },
+                3 t!("uninstall") => { Some(Self::Uninstall { keep_self: false, allow_hooks: false, dry_run: false }) },
                 4 t!("list_option") => {
                     let installed = handle_user_choice!(
                         t!("choose_an_option"), 1,
@@ -504,7 +706,7 @@ impl ManagerSubcommands {
                             2 t!("installed") => { true }
                         }
                     );
-                    Some(Self::List { installed, command: list::ask_list_command()? })
+                    Some(Self::List { installed, compare: None, json: false, outdated: false, offline: false, with_sizes: false, command: list::ask_list_command()? })
                 },
                 5 t!("cancel") => { None }
             }
@@ -519,17 +721,19 @@ impl ManagerSubcommands {
         // component choices are asked after executing update command,
         // so it's ok to leave it as None for now.
         let component = None;
+        let pin = vec![];
+        let unpin = vec![];
         *self = handle_user_choice!(
             t!("choose_an_option"), 1,
             {
                 1 t!("update_all") => {
-                    Self::Update { insecure, toolkit_only: false, manager_only: false, component }
+                    Self::Update { insecure, toolkit_only: false, manager_only: false, component, pin, unpin, check: false }
                 },
                 2 t!("update_self_only") => {
-                    Self::Update { insecure, toolkit_only: false, manager_only: true, component }
+                    Self::Update { insecure, toolkit_only: false, manager_only: true, component, pin, unpin, check: false }
                 },
                 3 t!("update_toolkit_only") => {
-                    Self::Update { insecure, toolkit_only: true, manager_only: false, component }
+                    Self::Update { insecure, toolkit_only: true, manager_only: false, component, pin, unpin, check: false }
                 },
                 4 t!("back") => { return Ok(false) }
             }
@@ -544,8 +748,8 @@ impl ManagerSubcommands {
         *self = handle_user_choice!(
             t!("choose_an_option"), 1,
             {
-                1 t!("uninstall_all") => { Self::Uninstall { keep_self: false } },
-                2 t!("uninstall_toolkit_only") => { Self::Uninstall { keep_self: true } },
+                1 t!("uninstall_all") => { Self::Uninstall { keep_self: false, allow_hooks: false, dry_run: false } },
+                2 t!("uninstall_toolkit_only") => { Self::Uninstall { keep_self: true, allow_hooks: false, dry_run: false } },
                 3 t!("back") => { return Ok(false) }
             }
         );
@@ -570,11 +774,11 @@ impl ManagerSubcommands {
                         info!("{}", t!("no_component_selected"));
                         return Ok(false);
                     }
-                    Self::Component { command: ComponentCommand::Install { insecure, components } }
+                    Self::Component { command: ComponentCommand::Install { insecure, reinstall: false, allow_hooks: false, components } }
                 },
                 2 t!("remove") => {
                     let components = component::collect_components_to_remove()?;
-                    Self::Component { command: ComponentCommand::Uninstall { components } }
+                    Self::Component { command: ComponentCommand::Uninstall { components, allow_hooks: false } }
                 },
                 3 t!("back") => { return Ok(false) }
             }
@@ -597,6 +801,19 @@ pub fn parse_manager_cli() -> Result<Box<Manager>> {
         .map_err(manually_show_help_or_version)
 }
 
+/// Parse the URL of a download server, check [`utils::validate_server_url`].
+fn parse_server_url(val: &str) -> Result<Url> {
+    let url = Url::parse(val)?;
+    utils::validate_server_url(&url)?;
+    Ok(url)
+}
+
+/// Parse the index URL of a cargo registry, check [`utils::validate_registry_url`].
+fn parse_registry_url(val: &str) -> Result<String> {
+    utils::validate_registry_url(val)?;
+    Ok(val.to_string())
+}
+
 fn manually_show_help_or_version(error: clap::Error) -> anyhow::Error {
     match error.kind() {
         ErrorKind::DisplayHelp
@@ -606,25 +823,41 @@ fn manually_show_help_or_version(error: clap::Error) -> anyhow::Error {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn setup(
     verbose: bool,
     quiet: bool,
     yes: bool,
+    no_color: bool,
     no_modify_env: bool,
     no_modify_path: bool,
     lang: Option<&str>,
+    locale: Option<&str>,
 ) -> Result<()> {
     // Setup locale
-    if let Some(lang_str) = lang {
+    let locale_res = if let Some(lang_str) = lang {
         let parsed: Language = lang_str.parse()?;
         utils::set_locale(parsed.locale_str());
+        Ok(())
     } else {
-        utils::use_current_locale();
-    }
+        utils::use_requested_locale(locale)
+    };
+    // Setup output styling, this must be done before setting up logger
+    utils::set_no_color(no_color);
     // Setup logger
     utils::Logger::new().verbose(verbose).quiet(quiet).setup()?;
+    if let Err(e) = locale_res {
+        warn!("{e}");
+    }
     // Setup global options
-    GlobalOpts::set(verbose, quiet, yes, no_modify_env, no_modify_path);
+    GlobalOpts::set(
+        verbose,
+        quiet,
+        yes,
+        utils::no_color(),
+        no_modify_env,
+        no_modify_path,
+    );
 
     Ok(())
 }
@@ -0,0 +1,151 @@
+use std::io::Write;
+
+use anyhow::Result;
+use rim_common::types::ToolkitManifest;
+
+use super::{ExecStatus, ManagerSubcommands};
+use crate::{
+    components::{self, Component},
+    fingerprint::InstallationRecord,
+    toolkit::{toolkits_from_server, Toolkit},
+    ToolkitManifestExt,
+};
+
+/// Execute `search` command.
+pub(super) fn execute(cmd: &ManagerSubcommands) -> Result<ExecStatus> {
+    let ManagerSubcommands::Search {
+        query,
+        installed,
+        toolkit,
+        insecure,
+    } = cmd
+    else {
+        return Ok(ExecStatus::default());
+    };
+
+    let record = InstallationRecord::load_from_install_dir()?;
+    let components = searchable_components(&record, *installed)?;
+    let matched = components
+        .iter()
+        .filter(|comp| component_matches(comp, query))
+        .collect::<Vec<_>>();
+
+    let mut stdout = std::io::stdout();
+    writeln!(&mut stdout)?;
+    if matched.is_empty() {
+        writeln!(&mut stdout, "{}", t!("no_component_matched", query = query))?;
+    }
+    for comp in matched {
+        let version = comp
+            .version
+            .as_ref()
+            .map(|ver| format!(" {ver}"))
+            .unwrap_or_default();
+        let installed_suffix = if comp.installed {
+            format!(" ({})", t!("installed"))
+        } else {
+            String::new()
+        };
+        writeln!(
+            &mut stdout,
+            "{} [{}]{version}{installed_suffix}",
+            comp.display_name, comp.category
+        )?;
+        if let Some(desc) = &comp.desc {
+            writeln!(&mut stdout, "    {}", desc.trim())?;
+        }
+    }
+
+    if *toolkit {
+        blocking!(search_toolkits(query, *insecure))?;
+    }
+    Ok(ExecStatus::new_executed().no_pause(true))
+}
+
+/// Get the installed components, followed by the ones that are available in the
+/// installed toolkit but not installed yet, unless `installed_only` is `true`.
+fn searchable_components(
+    record: &InstallationRecord,
+    installed_only: bool,
+) -> Result<Vec<Component>> {
+    let mut components = components::all_components_from_installation(record)?;
+    if installed_only {
+        return Ok(components);
+    }
+
+    let manifest = ToolkitManifest::load_from_install_dir()?;
+    let available = manifest.current_target_components(false)?;
+    let not_installed = available
+        .into_iter()
+        .filter(|comp| !components.iter().any(|c| c.name == comp.name))
+        .collect::<Vec<_>>();
+    components.extend(not_installed);
+    Ok(components)
+}
+
+/// Print the toolkits on server whose name matches `query`.
+async fn search_toolkits(query: &str, insecure: bool) -> Result<()> {
+    let toolkits = toolkits_from_server(insecure).await?;
+    let matched = toolkits
+        .iter()
+        .filter(|tk| toolkit_matches(tk, query))
+        .collect::<Vec<_>>();
+
+    let mut stdout = std::io::stdout();
+    writeln!(&mut stdout)?;
+    if matched.is_empty() {
+        writeln!(&mut stdout, "{}", t!("no_toolkit_matched", query = query))?;
+    }
+    for tk in matched {
+        writeln!(&mut stdout, "{} {}", tk.name, tk.version)?;
+    }
+    Ok(())
+}
+
+/// Check if `haystack` contains `query`, ignoring case.
+fn contains_ignore_case(haystack: &str, query: &str) -> bool {
+    haystack.to_lowercase().contains(&query.to_lowercase())
+}
+
+fn component_matches(comp: &Component, query: &str) -> bool {
+    [
+        Some(comp.name.as_str()),
+        Some(comp.display_name.as_str()),
+        comp.desc.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .any(|field| contains_ignore_case(field, query))
+}
+
+fn toolkit_matches(toolkit: &Toolkit, query: &str) -> bool {
+    contains_ignore_case(&toolkit.name, query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rim_common::types::TomlParser;
+
+    #[test]
+    fn match_components_and_toolkits() {
+        let comp = Component::new("llvm-tools")
+            .with_display_name("LLVM Tools")
+            .with_description(Some("Tools of the LLVM compiler infrastructure"));
+        assert!(component_matches(&comp, "LLVM-TOOLS"));
+        assert!(component_matches(&comp, "llvm tools"));
+        assert!(component_matches(&comp, "Compiler"));
+        assert!(!component_matches(&comp, "clippy"));
+
+        let raw = r#"
+name = "XuanWu Rust Toolkit"
+
+[rust]
+version = "stable"
+"#;
+        let manifest = ToolkitManifest::from_str(raw).unwrap();
+        let toolkit = Toolkit::try_from(&manifest).unwrap();
+        assert!(toolkit_matches(&toolkit, "xuanwu"));
+        assert!(!toolkit_matches(&toolkit, "nightly"));
+    }
+}
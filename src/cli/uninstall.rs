@@ -9,11 +9,24 @@ use rim_common::build_config;
 
 /// Execute `uninstall` command.
 pub(super) fn execute(subcommand: &ManagerSubcommands) -> Result<ExecStatus> {
-    let ManagerSubcommands::Uninstall { keep_self } = subcommand else {
+    let ManagerSubcommands::Uninstall {
+        keep_self,
+        allow_hooks,
+        dry_run,
+    } = subcommand
+    else {
         return Ok(ExecStatus::default());
     };
 
-    let config = UninstallConfiguration::init(None)?;
+    let config = UninstallConfiguration::init(None)?.allow_hooks(*allow_hooks);
+    if *dry_run {
+        println!("{}", t!("uninstall_dry_run_header"));
+        for removal in config.dry_run(!keep_self)? {
+            println!("  {removal}");
+        }
+        return Ok(ExecStatus::new_executed().no_pause(true));
+    }
+
     let installed = config.install_record.print_installation();
 
     // Ask confirmation
@@ -5,9 +5,9 @@ use url::Url;
 
 use crate::components::Component;
 use crate::core::toolkit::Toolkit;
-use crate::core::update::UpdateOpt;
+use crate::core::update::{self, UpdateKind, UpdateOpt};
 use crate::core::{get_toolkit_manifest, ToolkitManifestExt};
-use crate::toolkit::latest_installable_toolkit;
+use crate::toolkit::{latest_installable_toolkit, toolkit_with_version};
 use crate::InstallConfiguration;
 
 use super::common::{
@@ -15,21 +15,51 @@ use super::common::{
 };
 use super::{common, ExecStatus, GlobalOpts, ManagerSubcommands};
 
+/// The exit code of `update --check` and `list --outdated` when there are updates available.
+pub(super) const UPDATES_AVAILABLE_EXIT_CODE: i32 = 10;
+
 pub(super) fn execute(cmd: &ManagerSubcommands) -> Result<ExecStatus> {
     let ManagerSubcommands::Update {
         toolkit_only,
         manager_only,
         insecure,
         component,
+        pin,
+        unpin,
+        check,
     } = cmd
     else {
         return Ok(ExecStatus::default());
     };
 
+    if *check {
+        let mut has_update = false;
+        if !manager_only {
+            has_update |= blocking!(check_toolkit_update_(*insecure))?;
+        }
+        if !toolkit_only {
+            has_update |= blocking!(check_manager_update_(*insecure))?;
+        }
+
+        let status = ExecStatus::new_executed().no_pause(true);
+        return Ok(if has_update {
+            status.with_exit_code(UPDATES_AVAILABLE_EXIT_CODE)
+        } else {
+            info!("{}", t!("everything_up_to_date"));
+            status
+        });
+    }
+
     let update_opt = UpdateOpt::new().insecure(*insecure);
     if !manager_only {
         update_opt.update_toolkit(|path| {
-            blocking!(update_toolkit_(path, *insecure, component.as_deref()))
+            blocking!(update_toolkit_(
+                path,
+                *insecure,
+                component.as_deref(),
+                pin,
+                unpin
+            ))
         })?;
     }
     if !toolkit_only {
@@ -43,6 +73,8 @@ async fn update_toolkit_(
     install_dir: &Path,
     insecure: bool,
     user_selected_comps: Option<&[String]>,
+    pinned_comps: &[String],
+    unpinned_comps: &[String],
 ) -> Result<()> {
     let Some(installed) = Toolkit::installed(false).await? else {
         info!("{}", t!("no_toolkit_installed"));
@@ -60,26 +92,122 @@ async fn update_toolkit_(
         &latest_toolkit.version
     );
 
-    // load the latest manifest
-    let manifest_url = latest_toolkit
-        .manifest_url
-        .as_deref()
-        .and_then(|s| Url::parse(s).ok())
-        .ok_or_else(|| {
-            anyhow!(
-                "invalid dist manifest downloaded from server: \
-            must contains a valid `manifest_url`"
-            )
-        })?;
-    let manifest = get_toolkit_manifest(Some(manifest_url), insecure).await?;
+    update_to_toolkit_(
+        install_dir,
+        installed,
+        &latest_toolkit,
+        insecure,
+        user_selected_comps,
+        pinned_comps,
+        unpinned_comps,
+    )
+    .await
+}
+
+/// Print the version of the latest toolkit and its components without installing anything,
+/// return `true` if there is a newer toolkit available.
+async fn check_toolkit_update_(insecure: bool) -> Result<bool> {
+    let Some(installed) = Toolkit::installed(false).await? else {
+        info!("{}", t!("no_toolkit_installed"));
+        return Ok(false);
+    };
+    let installed = &*installed.lock().await;
+    let Some(latest_toolkit) = latest_installable_toolkit(installed, insecure).await? else {
+        return Ok(false);
+    };
+
+    let manifest = get_toolkit_manifest(Some(manifest_url(&latest_toolkit)?), insecure).await?;
     let new_components = manifest.current_target_components(false)?;
+    let updater = ComponentsUpdater::new(&installed.components, &new_components);
 
-    // notify user that we will install the latest update to replace their current installation
+    println!(
+        "{}",
+        t!(
+            "toolkit_can_be_updated",
+            name = installed.name,
+            current = installed.version,
+            latest = latest_toolkit.version
+        )
+    );
+    let mut changed = updater
+        .version_diff
+        .iter()
+        .filter(|(_, diff)| diff.from != diff.to)
+        .collect::<Vec<_>>();
+    changed.sort_by_key(|(name, _)| *name);
+    for (name, diff) in changed {
+        println!(
+            "    {name}: {} -> {}",
+            diff.from.unwrap_or("-"),
+            diff.to.unwrap_or("-")
+        );
+    }
+    Ok(true)
+}
+
+/// Print the version of the latest manager release without installing it,
+/// return `true` if it is newer than the current one.
+async fn check_manager_update_(insecure: bool) -> Result<bool> {
+    info!("{}", t!("checking_manager_updates"));
+    let UpdateKind::Newer { current, latest } = update::available_self_update(insecure).await?
+    else {
+        return Ok(false);
+    };
+    println!(
+        "{}",
+        t!(
+            "manager_update_available",
+            current = current,
+            latest = latest
+        )
+    );
+    Ok(true)
+}
+
+/// Install a specific `version` of the toolkit from server to replace the installed one.
+pub(super) async fn install_toolkit_version(
+    install_dir: &Path,
+    version: &str,
+    insecure: bool,
+) -> Result<()> {
+    let Some(installed) = Toolkit::installed(false).await? else {
+        info!("{}", t!("no_toolkit_installed"));
+        return Ok(());
+    };
+    let installed = &*installed.lock().await;
+
+    let target_toolkit = toolkit_with_version(installed, version, insecure).await?;
+    update_to_toolkit_(
+        install_dir,
+        installed,
+        &target_toolkit,
+        insecure,
+        None,
+        &[],
+        &[],
+    )
+    .await
+}
+
+async fn update_to_toolkit_(
+    install_dir: &Path,
+    installed: &Toolkit,
+    target_toolkit: &Toolkit,
+    insecure: bool,
+    user_selected_comps: Option<&[String]>,
+    pinned_comps: &[String],
+    unpinned_comps: &[String],
+) -> Result<()> {
+    // load the target manifest
+    let manifest = get_toolkit_manifest(Some(manifest_url(target_toolkit)?), insecure).await?;
+    let new_components = manifest.current_target_components(false)?;
+
+    // notify user that we will install the target toolkit to replace their current installation
     info!(
         "{}",
         t!(
             "pre_update_note",
-            target_version = latest_toolkit.version,
+            target_version = target_toolkit.version,
             current_version = installed.version
         )
     );
@@ -88,13 +216,29 @@ async fn update_toolkit_(
     // let user choose if they want to update installed component only, or want to select more components to install
     if let UpdateOption::Yes(components) = updater.to_update_option(user_selected_comps)? {
         // install update for selected components
-        let config = InstallConfiguration::new(install_dir, &manifest)?.insecure(insecure);
+        let config = InstallConfiguration::new(install_dir, &manifest)?
+            .insecure(insecure)
+            .pin_components(pinned_comps)
+            .unpin_components(unpinned_comps);
         config.update(components.into_values().cloned().collect())
     } else {
         Ok(())
     }
 }
 
+fn manifest_url(toolkit: &Toolkit) -> Result<Url> {
+    toolkit
+        .manifest_url
+        .as_deref()
+        .and_then(|s| Url::parse(s).ok())
+        .ok_or_else(|| {
+            anyhow!(
+                "invalid dist manifest downloaded from server: \
+            must contains a valid `manifest_url`"
+            )
+        })
+}
+
 enum UpdateOption<'c> {
     Yes(ComponentChoices<'c>),
     NoUpdate,
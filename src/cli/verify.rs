@@ -0,0 +1,32 @@
+use super::{ExecStatus, ManagerSubcommands};
+use crate::core::verify::{self, VerifyStatus};
+use anyhow::{bail, Result};
+
+/// Execute `verify` command.
+pub(super) fn execute(cmd: &ManagerSubcommands) -> Result<ExecStatus> {
+    let ManagerSubcommands::Verify { fix } = cmd else {
+        return Ok(ExecStatus::default());
+    };
+
+    let entries = verify::verify_installation(*fix)?;
+    let mut broken_count = 0;
+    for entry in &entries {
+        let status = match entry.status {
+            VerifyStatus::Ok => "OK",
+            VerifyStatus::Missing => {
+                broken_count += 1;
+                "MISSING"
+            }
+            VerifyStatus::Fixed => "FIXED",
+        };
+        println!("[{status:^7}] {}", entry.name);
+        for path in &entry.broken {
+            println!("          {}", path.display());
+        }
+    }
+
+    if broken_count > 0 {
+        bail!(t!("verify_failed", count = broken_count));
+    }
+    Ok(ExecStatus::new_executed().no_pause(true))
+}
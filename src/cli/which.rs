@@ -0,0 +1,131 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use rim_common::types::ToolKind;
+use rim_common::utils;
+
+use super::{ExecStatus, ManagerSubcommands};
+use crate::core::directories::RimDir;
+use crate::fingerprint::{InstallationRecord, ToolRecord};
+
+/// Execute `which` command.
+pub(super) fn execute(cmd: &ManagerSubcommands) -> Result<ExecStatus> {
+    let ManagerSubcommands::Which { component } = cmd else {
+        return Ok(ExecStatus::default());
+    };
+
+    let record = InstallationRecord::load_from_install_dir()?;
+    if let Some((name, tool)) = find_tool(&record, component) {
+        println!("{name}");
+        for path in &tool.paths {
+            println!("  path: {}", path.display());
+        }
+        for exe in executables_of(name, tool, record.cargo_bin()) {
+            println!("  executable: {}", exe.display());
+        }
+    } else if let Some((toolchain, comp)) = find_toolchain_component(&record, component) {
+        println!("{comp}");
+        println!("  rustup component: {comp}");
+        println!("  toolchain: {toolchain}");
+    } else {
+        bail!(t!("component_not_installed", name = component));
+    }
+    Ok(ExecStatus::new_executed().no_pause(true))
+}
+
+/// Find an installed tool by its name, falling back to a case-insensitive lookup.
+fn find_tool<'a>(record: &'a InstallationRecord, name: &str) -> Option<(&'a str, &'a ToolRecord)> {
+    record
+        .tools
+        .get_key_value(name)
+        .or_else(|| {
+            record
+                .tools
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        })
+        .map(|(key, tool)| (key.as_str(), tool))
+}
+
+fn find_toolchain_component<'a>(
+    record: &'a InstallationRecord,
+    name: &str,
+) -> Option<(&'a str, &'a str)> {
+    let (toolchain, components) = record.installed_toolchain()?;
+    components
+        .iter()
+        .find(|comp| comp.eq_ignore_ascii_case(name))
+        .map(|comp| (toolchain, comp.as_str()))
+}
+
+/// Get the primary executables of an installed tool, which are the executables
+/// under its `bin` directory, or directly under its installed directories if it has none.
+fn executables_of(name: &str, tool: &ToolRecord, cargo_bin: &Path) -> Vec<PathBuf> {
+    if tool.tool_kind() == ToolKind::CargoTool {
+        let exe = cargo_bin.join(exe!(name));
+        return if exe.is_file() { vec![exe] } else { vec![] };
+    }
+
+    let mut exes = vec![];
+    for path in &tool.paths {
+        if path.is_file() {
+            if utils::is_executable(path) {
+                exes.push(path.clone());
+            }
+            continue;
+        }
+        let bin_dir = path.join("bin");
+        let dir = if bin_dir.is_dir() { &bin_dir } else { path };
+        let Ok(entries) = utils::walk_dir(dir, false) else {
+            continue;
+        };
+        exes.extend(
+            entries
+                .into_iter()
+                .filter(|p| p.is_file() && utils::is_executable(p)),
+        );
+    }
+    exes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rim_common::types::TomlParser;
+
+    #[test]
+    fn lookup_installed_components() {
+        let root = tempfile::tempdir().unwrap();
+        let cargo_bin = root.path().join("cargo").join("bin");
+        utils::ensure_dir(&cargo_bin).unwrap();
+        utils::write_file(cargo_bin.join(exe!("Tool-A")), "", false).unwrap();
+        let record = InstallationRecord::from_str(&format!(
+            r#"
+root = '{0}'
+
+[rust]
+version = "stable"
+components = ["minimal", "clippy"]
+
+[tools.Tool-A]
+kind = "cargo-tool"
+paths = ['{0}']"#,
+            root.path().display()
+        ))
+        .unwrap();
+
+        let (name, tool) = find_tool(&record, "tool-a").unwrap();
+        assert_eq!(name, "Tool-A");
+        assert_eq!(
+            executables_of(name, tool, &cargo_bin),
+            [cargo_bin.join(exe!("Tool-A"))]
+        );
+        assert!(find_tool(&record, "clippy").is_none());
+
+        assert_eq!(
+            find_toolchain_component(&record, "Clippy"),
+            Some(("stable", "clippy"))
+        );
+        assert!(find_toolchain_component(&record, "rustfmt").is_none());
+    }
+}
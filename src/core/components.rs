@@ -1,7 +1,10 @@
 use super::ToolkitManifestExt;
 use crate::fingerprint::InstallationRecord;
 use anyhow::Result;
-use rim_common::types::{ToolInfo, ToolInfoDetails, ToolKind, ToolMap, ToolkitManifest};
+use rim_common::types::{
+    ToolInfo, ToolInfoDetails, ToolKind, ToolMap, ToolSource, ToolkitManifest,
+};
+use rim_common::utils;
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicU32, Ordering};
 
@@ -38,6 +41,12 @@ pub struct Component {
     pub kind: ComponentType,
     /// Indicates whether this component was already installed or not.
     pub installed: bool,
+    /// Indicates whether this component was installed but with an older version
+    /// than the one provided by the toolkit.
+    pub update_available: bool,
+    /// The size of the package to download in bytes, `None` if it's unknown,
+    /// such as the tools installed by `cargo install`.
+    pub download_size: Option<u64>,
 }
 
 impl Component {
@@ -69,12 +78,14 @@ impl Component {
     setter!(required(self.required, bool));
     setter!(optional(self.optional, bool));
     setter!(installed(self.installed, bool));
+    setter!(update_available(self.update_available, bool));
     setter!(with_type(self.kind, ComponentType));
     setter!(with_category(self.category, name: impl ToString) { name.to_string() });
     setter!(with_tool_installer(self.tool_installer, installer: &ToolInfo) { Some(installer.clone()) });
     setter!(with_version(self.version, version: Option<&str>) { version.map(ToOwned::to_owned) });
     setter!(with_display_name(self.display_name, name: impl ToString) { name.to_string() });
     setter!(with_description(self.desc, desc: Option<&str>) { desc.map(ToOwned::to_owned) });
+    setter!(with_download_size(self.download_size, Option<u64>));
 }
 
 /// A Rust toolchain component, such as `rustc`, `cargo`, `rust-docs`
@@ -208,3 +219,117 @@ pub fn split_components(components: Vec<Component>) -> (Vec<ToolchainComponent>,
 
     (toolchain_components, toolset_components)
 }
+
+/// Fill the missing [`download_size`](Component::download_size) of `components`,
+/// by checking the size of local packages, or sending requests for the remote ones.
+///
+/// The size of a component remains `None` if it cannot be determined.
+pub async fn fetch_download_sizes(components: &mut [Component], insecure: bool) {
+    for comp in components.iter_mut().filter(|c| c.download_size.is_none()) {
+        let Some(tool) = &comp.tool_installer else {
+            continue;
+        };
+        comp.download_size = match tool.details().and_then(|d| d.source.as_ref()) {
+            Some(ToolSource::Path { path, .. }) => path
+                .metadata()
+                .ok()
+                .filter(|m| m.is_file())
+                .map(|m| m.len()),
+            Some(ToolSource::Url { url, .. }) => utils::DownloadOpt::new(&comp.name, true)
+                .insecure(insecure)
+                .with_proxy(tool.proxy().cloned())
+                .content_length(url)
+                .await
+                .unwrap_or_else(|e| {
+                    debug!("unable to get the size of '{}': {e}", comp.name);
+                    None
+                }),
+            _ => None,
+        };
+    }
+}
+
+/// The differences between an existing installation and the components selected
+/// when installing over it again, which is applied as a reconciliation.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ComponentsDiff {
+    /// Selected components that are not installed yet.
+    pub to_add: Vec<String>,
+    /// Selected components that are already installed.
+    pub to_keep: Vec<String>,
+    /// Installed components that are no longer selected.
+    pub to_remove: Vec<String>,
+}
+
+impl ComponentsDiff {
+    pub fn new(record: &InstallationRecord, selected: &[Component]) -> Self {
+        let installed_tc = record
+            .installed_toolchain()
+            .map(|(_, comps)| comps)
+            .unwrap_or_default();
+        let is_installed =
+            |name: &str| installed_tc.iter().any(|c| c == name) || record.tools.contains_key(name);
+
+        let mut diff = Self::default();
+        for comp in selected {
+            if is_installed(&comp.name) {
+                diff.to_keep.push(comp.name.clone());
+            } else {
+                diff.to_add.push(comp.name.clone());
+            }
+        }
+
+        let is_selected = |name: &str| selected.iter().any(|c| c.name == name);
+        // the first recorded toolchain component is the profile, which cannot be
+        // removed without removing the whole toolchain, so it's always kept.
+        diff.to_remove = installed_tc
+            .iter()
+            .skip(1)
+            .chain(record.tools.keys())
+            .filter(|name| !is_selected(name))
+            .cloned()
+            .collect();
+        diff.to_remove.sort();
+        diff
+    }
+
+    /// Returns `true` if nothing will be added or removed.
+    pub fn is_unchanged(&self) -> bool {
+        self.to_add.is_empty() && self.to_remove.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rim_common::types::TomlParser;
+
+    #[test]
+    fn diff_with_existing_installation() {
+        let input = r#"
+root = '/path/to/something'
+
+[rust]
+version = "stable"
+components = ["Rust", "clippy", "rustfmt"]
+
+[tools.a]
+kind = "cargo-tool"
+paths = []
+
+[tools.b]
+kind = "cargo-tool"
+paths = []"#;
+        let record = InstallationRecord::from_str(input).unwrap();
+        let selected = ["Rust", "clippy", "a", "c"].map(Component::new);
+
+        let diff = ComponentsDiff::new(&record, &selected);
+        assert_eq!(diff.to_add, ["c"]);
+        assert_eq!(diff.to_keep, ["Rust", "clippy", "a"]);
+        assert_eq!(diff.to_remove, ["b", "rustfmt"]);
+        assert!(!diff.is_unchanged());
+
+        let selected = ["Rust", "clippy", "rustfmt", "a", "b"].map(Component::new);
+        assert!(ComponentsDiff::new(&record, &selected).is_unchanged());
+    }
+}
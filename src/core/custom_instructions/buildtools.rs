@@ -4,6 +4,7 @@ use cc::windows_registry;
 use rim_common::utils;
 use crate::core::directories::RimDir;
 use crate::core::install::InstallConfiguration;
+use crate::core::uninstall::Removal;
 
 pub(super) fn install(path: &Path, config: &InstallConfiguration) -> Result<Vec<PathBuf>> {
     let mut args = vec![
@@ -61,6 +62,10 @@ pub(super) fn uninstall<T: RimDir>(_config: T) -> Result<()> {
     Ok(())
 }
 
+pub(super) fn removals<T: RimDir>(_config: T) -> Vec<Removal> {
+    vec![]
+}
+
 pub(super) fn is_installed() -> bool {
     // Other targets don't need MSVC, so assume it has already installed
     if !env!("TARGET").contains("msvc") {
@@ -1,5 +1,9 @@
 use rim_common::utils;
-use std::{collections::HashMap, sync::LazyLock};
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
+use std::{collections::HashMap, sync::LazyLock, thread};
 
 macro_rules! declare_instructions {
     ($($name:ident),+) => {
@@ -24,6 +28,15 @@ macro_rules! declare_instructions {
             }
         }
 
+        pub(crate) fn removals<T: $crate::core::directories::RimDir>(tool: &str, config: T) -> anyhow::Result<Vec<$crate::core::uninstall::Removal>> {
+            match tool.replace('-', "_").as_str() {
+                $(
+                    stringify!($name) => Ok($name::removals(config)),
+                )*
+                _ => anyhow::bail!("no custom uninstall instruction for '{tool}'")
+            }
+        }
+
         fn supported_tool_is_installed(tool: &str) -> bool {
             match tool.replace('-', "_").as_str() {
                 $(
@@ -54,6 +67,91 @@ static SEMI_SUPPORTED_TOOLS: LazyLock<HashMap<&str, Vec<String>>> = LazyLock::ne
     ])
 });
 
+/// A map with tool's name and the program that can tell the version of that tool,
+/// for tools whose name is not the same as their program.
+static VERSION_PROGRAMS: LazyLock<HashMap<&str, String>> = LazyLock::new(|| {
+    HashMap::from([
+        ("vscode", exe!("code")),
+        ("vscodium", exe!("codium")),
+        ("codearts-rust", "codearts-rust".into()),
+        ("mingw64", exe!("gcc")),
+    ])
+});
+
+/// How long to wait for a program to print its version before giving up on it.
+const VERSION_PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Try detecting the version of a tool that was installed in user's environment,
+/// by running its program with `--version` argument.
+///
+/// Return `None` if the program cannot be found, does not finish in time,
+/// or its version cannot be parsed.
+pub(crate) fn installed_version(name: &str) -> Option<String> {
+    let program = VERSION_PROGRAMS
+        .get(name)
+        .cloned()
+        .unwrap_or_else(|| exe!(name));
+    let path = utils::program_path(program)?;
+    let output = output_with_timeout(cmd!(path, "--version"), VERSION_PROBE_TIMEOUT)?;
+    parse_version(&output).map(ToOwned::to_owned)
+}
+
+/// Run `cmd` and collect its standard output, killing it if that takes longer than `timeout`,
+/// so a program that hangs (or waits for input) cannot block us forever.
+fn output_with_timeout(mut cmd: Command, timeout: Duration) -> Option<String> {
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let mut stdout = child.stdout.take()?;
+
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buf = vec![];
+        if stdout.read_to_end(&mut buf).is_ok() {
+            _ = tx.send(String::from_utf8_lossy(&buf).into_owned());
+        }
+    });
+    let output = rx.recv_timeout(timeout).ok();
+    if output.is_none() {
+        _ = child.kill();
+    }
+    _ = child.wait();
+    output
+}
+
+/// Find the first version-like word in the output of `--version`,
+/// such as `13.2.0` in `gcc (GCC) 13.2.0`.
+fn parse_version(output: &str) -> Option<&str> {
+    output
+        .split_whitespace()
+        .map(|word| word.trim_start_matches('v'))
+        .find(|word| version_numbers(word).is_some())
+}
+
+fn version_numbers(version: &str) -> Option<Vec<u64>> {
+    let version = version.trim_start_matches(|c: char| !c.is_ascii_digit());
+    // ignore pre-release or build metadata, such as `-nightly` or `+abc`
+    let core = version.split(['-', '+']).next()?;
+    let numbers = core
+        .split('.')
+        .map(|n| n.parse().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    (numbers.len() > 1).then_some(numbers)
+}
+
+/// Check if the `installed` version is older than the `target` one.
+///
+/// Return `false` if either of them does not look like a version, since we cannot tell.
+pub(crate) fn is_outdated(installed: &str, target: &str) -> bool {
+    match (version_numbers(installed), version_numbers(target)) {
+        (Some(installed), Some(target)) => installed < target,
+        _ => false,
+    }
+}
+
 /// Checking if a certain tool is installed by:
 ///
 /// 1. It's custom instruction.
@@ -73,3 +171,44 @@ pub(crate) fn is_installed(name: &str) -> bool {
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_version_output() {
+        assert_eq!(parse_version("gcc (GCC) 13.2.0\nCopyright"), Some("13.2.0"));
+        assert_eq!(
+            parse_version("1.93.1\n38c31bc77e0dd6ae88a4e9cc93428cc27a56ba40\nx64"),
+            Some("1.93.1")
+        );
+        assert_eq!(parse_version("tool v0.4.2-nightly"), Some("0.4.2-nightly"));
+        assert_eq!(parse_version("no version here 42"), None);
+    }
+
+    #[test]
+    fn version_comparison() {
+        assert!(is_outdated("1.90.0", "1.93.1"));
+        assert!(is_outdated("8.1.0", "13.2"));
+        assert!(!is_outdated("1.93.1", "1.93.1"));
+        assert!(!is_outdated("1.94.0", "1.93.1"));
+        assert!(!is_outdated("1.93.1", "latest"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn kill_version_probe_on_timeout() {
+        let output =
+            output_with_timeout(cmd!("sh", "-c", "echo tool 1.2.3"), VERSION_PROBE_TIMEOUT);
+        assert_eq!(output.as_deref().and_then(parse_version), Some("1.2.3"));
+
+        let start = std::time::Instant::now();
+        let output = output_with_timeout(
+            cmd!("sh", "-c", "exec sleep 30"),
+            Duration::from_millis(200),
+        );
+        assert!(output.is_none());
+        assert!(start.elapsed() < VERSION_PROBE_TIMEOUT);
+    }
+}
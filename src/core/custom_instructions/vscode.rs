@@ -8,6 +8,7 @@
 use std::path::{Path, PathBuf};
 use crate::core::directories::RimDir;
 use crate::core::install::InstallConfiguration;
+use crate::core::uninstall::Removal;
 use crate::core::os::add_to_path;
 use anyhow::Result;
 use rim_common::utils;
@@ -43,6 +44,9 @@ impl VSCodeInstaller<'_> {
 
         // Step 3: Create a shortcuts
         // Shortcuts are not important, make sure it won't throw error even if it fails.
+        if !config.create_shortcuts() {
+            return Ok(vec![vscode_dir]);
+        }
         #[cfg(windows)]
         {
             // TODO: (?) do we need to create a start menu shortcut as well?
@@ -118,34 +122,52 @@ Keywords=vscode;
         use crate::core::os::remove_from_path;
 
         // We've added a path for VSCode at `<InstallDir>/tools/vscode/bin`, try removing it from `PATH`.
-        let mut vscode_path = config.tools_dir().to_path_buf();
-        vscode_path.push(self.tool_name);
-        vscode_path.push("bin");
-        remove_from_path(&vscode_path)?;
+        remove_from_path(&self.bin_dir(config))?;
 
         // TODO: Remove desktop shortcut and `%USERPROFILE%/.vscode`.
         // We need to see if the shortcut has the correct target before removing it,
         // and we also need to ask user if they want to remove the user profile
         // before doing so, since that folder might be shared with other vscode variants.
-        #[cfg(unix)]
-        {
-            let Some(filepath)  = dirs::data_local_dir()
-                .map(|d| d.join(format!("applications/{}.desktop", self.cmd)))
-                .filter(|f| f.is_file())
-            else {
-                return Ok(());
-            };
-            if let Ok(content) = utils::read_to_string("program shortcut", &filepath) {
-                if content.contains(&format!("# Generated by {}", env!("CARGO_PKG_NAME"))) && utils::remove(&filepath).is_err() {
-                    warn!("{}", t!("remove_vscode_shortcut_warn", path = filepath.display()));
-                    return Ok(());
-                }
+        if let Some(filepath) = self.generated_shortcut() {
+            if utils::remove(&filepath).is_err() {
+                warn!("{}", t!("remove_vscode_shortcut_warn", path = filepath.display()));
             }
         }
 
         Ok(())
     }
 
+    /// Get what [`uninstall`](Self::uninstall) will remove, without actually removing anything.
+    pub(crate) fn removals<T: RimDir>(&self, config: T) -> Vec<Removal> {
+        let mut removals = vec![Removal::PathEntry(self.bin_dir(config))];
+        removals.extend(self.generated_shortcut().map(Removal::Shortcut));
+        removals
+    }
+
+    fn bin_dir<T: RimDir>(&self, config: T) -> PathBuf {
+        let mut vscode_path = config.tools_dir().to_path_buf();
+        vscode_path.push(self.tool_name);
+        vscode_path.push("bin");
+        vscode_path
+    }
+
+    /// The desktop shortcut that was generated by us during installation, if there is one.
+    #[cfg(unix)]
+    fn generated_shortcut(&self) -> Option<PathBuf> {
+        let filepath = dirs::data_local_dir()
+            .map(|d| d.join(format!("applications/{}.desktop", self.cmd)))
+            .filter(|f| f.is_file())?;
+        let content = utils::read_to_string("program shortcut", &filepath).ok()?;
+        content
+            .contains(&format!("# Generated by {}", env!("CARGO_PKG_NAME")))
+            .then_some(filepath)
+    }
+
+    #[cfg(not(unix))]
+    fn generated_shortcut(&self) -> Option<PathBuf> {
+        None
+    }
+
     pub(crate) fn is_installed(&self) -> bool {
         utils::cmd_exist(self.cmd)
     }
@@ -169,6 +191,10 @@ pub(super) fn uninstall<T: RimDir>(config: T) -> Result<()> {
     VSCODE.uninstall(config)
 }
 
+pub(super) fn removals<T: RimDir>(config: T) -> Vec<Removal> {
+    VSCODE.removals(config)
+}
+
 pub(super) fn is_installed() -> bool {
     VSCODE.is_installed()
 }
@@ -5,7 +5,7 @@ use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 
-use crate::{core::directories::RimDir, InstallConfiguration};
+use crate::{core::directories::RimDir, core::uninstall::Removal, InstallConfiguration};
 use super::vscode::VSCodeInstaller;
 
 const VSCODE: VSCodeInstaller = VSCodeInstaller {
@@ -26,6 +26,10 @@ pub(super) fn uninstall<T: RimDir>(config: T) -> Result<()> {
     VSCODE.uninstall(config)
 }
 
+pub(super) fn removals<T: RimDir>(config: T) -> Vec<Removal> {
+    VSCODE.removals(config)
+}
+
 pub(super) fn is_installed() -> bool {
     VSCODE.is_installed()
 }
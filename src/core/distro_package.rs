@@ -0,0 +1,216 @@
+//! Module for installing tools shipped as Linux distribution packages (`.deb` / `.rpm`),
+//! check [`ToolKind::DistroPackage`](rim_common::types::ToolKind::DistroPackage).
+//!
+//! Packages are installed using the system package manager, which requires root privileges.
+//! A copy of each package is kept under the tools directory, so that the name
+//! of the package can be read from it, and be removed by the same package manager later.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use rim_common::utils;
+
+use super::directories::RimDir;
+use super::uninstall::Removal;
+use crate::InstallConfiguration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PackageFormat {
+    Deb,
+    Rpm,
+}
+
+impl PackageFormat {
+    /// Detect the package format from the extension of `path`.
+    pub(crate) fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?;
+        if ext.eq_ignore_ascii_case("deb") {
+            Some(Self::Deb)
+        } else if ext.eq_ignore_ascii_case("rpm") {
+            Some(Self::Rpm)
+        } else {
+            None
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Deb => "deb",
+            Self::Rpm => "rpm",
+        }
+    }
+
+    /// The low level package manager that must be present to handle this format.
+    fn package_manager(self) -> &'static str {
+        match self {
+            Self::Deb => "dpkg",
+            Self::Rpm => "rpm",
+        }
+    }
+
+    /// The high level package manager that also resolves dependencies,
+    /// which is preferred if it's available.
+    fn frontend(self) -> &'static str {
+        match self {
+            Self::Deb => "apt-get",
+            Self::Rpm => "dnf",
+        }
+    }
+
+    /// The arguments to install the package file at `path`.
+    fn install_args(self, path: &str) -> Vec<String> {
+        let args = if utils::cmd_exist(self.frontend()) {
+            vec![self.frontend(), "install", "-y", path]
+        } else {
+            vec![self.package_manager(), "--install", path]
+        };
+        args.into_iter().map(ToString::to_string).collect()
+    }
+
+    /// The arguments to remove an installed package by its `name`.
+    fn remove_args(self, name: &str) -> Vec<String> {
+        let args = if utils::cmd_exist(self.frontend()) {
+            vec![self.frontend(), "remove", "-y", name]
+        } else {
+            match self {
+                Self::Deb => vec!["dpkg", "--remove", name],
+                Self::Rpm => vec!["rpm", "--erase", name],
+            }
+        };
+        args.into_iter().map(ToString::to_string).collect()
+    }
+
+    /// Read the name of the package file at `path`.
+    fn package_name(self, path: &Path) -> Result<String> {
+        let mut cmd = match self {
+            Self::Deb => cmd!("dpkg-deb", "--field", path, "Package"),
+            Self::Rpm => cmd!(
+                "rpm",
+                "--query",
+                "--package",
+                "--queryformat",
+                "%{NAME}",
+                path
+            ),
+        };
+        let output = cmd
+            .output()
+            .with_context(|| format!("failed to execute {cmd:?}"))?;
+        let name = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if !output.status.success() || name.is_empty() {
+            bail!(
+                "unable to read the package name of '{}': {}",
+                path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(name)
+    }
+}
+
+/// Make sure the package at `path` can be handled on the current platform,
+/// then return its format.
+fn ensure_supported(name: &str, path: &Path) -> Result<PackageFormat> {
+    let Some(format) = PackageFormat::from_path(path) else {
+        bail!("'{}' is not a distribution package", path.display());
+    };
+    if !cfg!(target_os = "linux") {
+        bail!(t!(
+            "distro_package_unsupported_platform",
+            name = name,
+            format = format.as_str()
+        ));
+    }
+    if !utils::cmd_exist(format.package_manager()) {
+        bail!(t!(
+            "distro_package_manager_missing",
+            name = name,
+            format = format.as_str(),
+            manager = format.package_manager()
+        ));
+    }
+    Ok(format)
+}
+
+/// Check whether the current user is `root`.
+fn is_root() -> bool {
+    cmd!("id", "-u")
+        .output()
+        .is_ok_and(|out| String::from_utf8_lossy(&out.stdout).trim() == "0")
+}
+
+/// Create a command running with root privileges, either directly if the current user
+/// is `root`, or through `sudo` if it can be used without asking for a password.
+fn privileged_command(name: &str, args: &[String]) -> Result<Command> {
+    let (program, rest) = args
+        .split_first()
+        .expect("package manager arguments should never be empty");
+    if is_root() {
+        let mut cmd = cmd!(program);
+        cmd.args(rest);
+        return Ok(cmd);
+    }
+    if utils::cmd_exist("sudo")
+        && cmd!("sudo", "-n", "true")
+            .status()
+            .is_ok_and(|s| s.success())
+    {
+        let mut cmd = cmd!("sudo", "-n");
+        cmd.args(args);
+        return Ok(cmd);
+    }
+    bail!(t!("distro_package_no_privilege", name = name));
+}
+
+/// Install the package at `path` as tool `name`, return the path to the copy of the package.
+pub(crate) fn install(config: &InstallConfiguration, name: &str, path: &Path) -> Result<PathBuf> {
+    let format = ensure_supported(name, path)?;
+    let package = format.package_name(path)?;
+    info!(
+        "{}",
+        t!("installing_distro_package", name = name, package = package)
+    );
+    let cmd = privileged_command(name, &format.install_args(utils::path_to_str(path)?))?;
+    utils::execute(cmd)?;
+
+    let dir = config.tools_dir().join(name);
+    utils::copy_into(path, dir)
+}
+
+/// Remove the package installed from the package file at `path` (the copy made
+/// during installation), then the package file itself.
+pub(crate) fn uninstall(name: &str, path: &Path) -> Result<()> {
+    let format = ensure_supported(name, path)?;
+    let package = format.package_name(path)?;
+    let cmd = privileged_command(name, &format.remove_args(&package))?;
+    utils::execute(cmd)?;
+    utils::remove(path)
+}
+
+pub(crate) fn removals(name: &str, path: &Path) -> Result<Vec<Removal>> {
+    let format = ensure_supported(name, path)?;
+    let package = format.package_name(path)?;
+    Ok(vec![
+        Removal::Command(format.remove_args(&package).join(" ")),
+        Removal::Path(path.to_path_buf()),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_package_format() {
+        let detect = |p: &str| PackageFormat::from_path(Path::new(p));
+        assert_eq!(detect("tool_1.0.0_amd64.deb"), Some(PackageFormat::Deb));
+        assert_eq!(
+            detect("/tmp/tool-1.0.0-1.x86_64.rpm"),
+            Some(PackageFormat::Rpm)
+        );
+        assert_eq!(detect("TOOL.DEB"), Some(PackageFormat::Deb));
+        assert_eq!(detect("tool.tar.gz"), None);
+        assert_eq!(detect("deb"), None);
+    }
+}
@@ -0,0 +1,208 @@
+//! Module for diagnosing common environment issues of an existing installation.
+
+use super::os::is_same_path;
+use super::parser::dist_manifest::DistManifest;
+use super::{CARGO_HOME, RUSTUP_DIST_SERVER, RUSTUP_HOME};
+use crate::fingerprint::InstallationRecord;
+use anyhow::Result;
+use rim_common::{
+    types::{TomlParser, ToolkitManifest},
+    utils,
+};
+use std::{env, path::Path};
+use url::Url;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CheckStatus {
+    Pass,
+    /// Something looks off but will not necessarily break the installation.
+    Warn,
+    /// Something is definitely broken.
+    Fail,
+}
+
+/// The result of a single diagnostic check.
+#[derive(Debug)]
+pub(crate) struct Diagnosis {
+    pub(crate) status: CheckStatus,
+    pub(crate) message: String,
+    /// Suggestion on how to fix the problem, `None` if the check passed.
+    pub(crate) remedy: Option<String>,
+}
+
+impl Diagnosis {
+    fn pass<S: Into<String>>(message: S) -> Self {
+        Self {
+            status: CheckStatus::Pass,
+            message: message.into(),
+            remedy: None,
+        }
+    }
+
+    fn warn<S: Into<String>, R: Into<String>>(message: S, remedy: R) -> Self {
+        Self {
+            status: CheckStatus::Warn,
+            message: message.into(),
+            remedy: Some(remedy.into()),
+        }
+    }
+
+    fn fail<S: Into<String>, R: Into<String>>(message: S, remedy: R) -> Self {
+        Self {
+            status: CheckStatus::Fail,
+            message: message.into(),
+            remedy: Some(remedy.into()),
+        }
+    }
+}
+
+/// Run every diagnostic check against the current installation, and
+/// return the result of each one of them.
+///
+/// Set `offline` to `true` to skip the checks that require network access.
+pub(crate) fn diagnose(insecure: bool, offline: bool) -> Result<Vec<Diagnosis>> {
+    let record = InstallationRecord::load_from_install_dir()?;
+    let install_dir = record.root.as_path();
+
+    let mut result = vec![
+        check_env_var(CARGO_HOME, &install_dir.join("cargo")),
+        check_env_var(RUSTUP_HOME, &install_dir.join("rustup")),
+    ];
+    let cargo_bin = install_dir.join("cargo").join("bin");
+    result.push(check_program("cargo", &cargo_bin));
+    result.push(check_program("rustup", &cargo_bin));
+    if !offline {
+        result.extend(check_dist_servers(insecure));
+    }
+    result.push(check_manifest(install_dir));
+
+    Ok(result)
+}
+
+/// Check whether the value of env var `key` points to the `expected` path.
+fn check_env_var(key: &str, expected: &Path) -> Diagnosis {
+    let Some(value) = env::var_os(key) else {
+        return Diagnosis::warn(
+            t!("doctor_env_unset", key = key),
+            t!("doctor_env_remedy", key = key, path = expected.display()),
+        );
+    };
+    if is_same_path(Path::new(&value), expected) {
+        Diagnosis::pass(t!("doctor_env_ok", key = key, path = expected.display()))
+    } else {
+        Diagnosis::fail(
+            t!(
+                "doctor_env_mismatch",
+                key = key,
+                actual = Path::new(&value).display(),
+                path = expected.display()
+            ),
+            t!("doctor_env_remedy", key = key, path = expected.display()),
+        )
+    }
+}
+
+/// Check whether `program` can be found in `PATH`, and whether it is the one
+/// located in `bin_dir`.
+fn check_program(program: &str, bin_dir: &Path) -> Diagnosis {
+    let Some(found) = utils::program_path(exe!(program)) else {
+        return Diagnosis::fail(
+            t!("doctor_program_missing", name = program),
+            t!("doctor_program_remedy", path = bin_dir.display()),
+        );
+    };
+    if found.parent().is_some_and(|dir| is_same_path(dir, bin_dir)) {
+        Diagnosis::pass(t!(
+            "doctor_program_ok",
+            name = program,
+            path = found.display()
+        ))
+    } else {
+        Diagnosis::warn(
+            t!(
+                "doctor_program_shadowed",
+                name = program,
+                path = found.display()
+            ),
+            t!("doctor_program_remedy", path = bin_dir.display()),
+        )
+    }
+}
+
+fn check_dist_servers(insecure: bool) -> Vec<Diagnosis> {
+    let rustup_server = env::var(RUSTUP_DIST_SERVER)
+        .ok()
+        .and_then(|s| s.parse::<Url>().ok())
+        .unwrap_or_else(|| super::default_rustup_dist_server().clone());
+    let rim_server = super::rim_dist_server();
+
+    [
+        (
+            rustup_server,
+            "dist/channel-rust-stable.toml.sha256".to_string(),
+        ),
+        (rim_server, format!("dist/{}", DistManifest::FILENAME)),
+    ]
+    .into_iter()
+    .map(|(server, probe)| check_server(&server, &probe, insecure))
+    .collect()
+}
+
+/// Check whether a server is reachable by fetching a small, well-known file from it.
+fn check_server(server: &Url, probe: &str, insecure: bool) -> Diagnosis {
+    let res = utils::url_join(server, probe).and_then(|url| {
+        blocking!(utils::DownloadOpt::new("dist server", true)
+            .insecure(insecure)
+            .read(&url))
+    });
+    match res {
+        Ok(_) => Diagnosis::pass(t!("doctor_server_ok", url = server)),
+        Err(e) => Diagnosis::warn(
+            t!("doctor_server_unreachable", url = server, reason = e),
+            t!("doctor_server_remedy"),
+        ),
+    }
+}
+
+fn check_manifest(install_dir: &Path) -> Diagnosis {
+    let path = install_dir.join(ToolkitManifest::FILENAME);
+    if !path.is_file() {
+        return Diagnosis::warn(
+            t!("doctor_manifest_missing", path = path.display()),
+            t!("doctor_manifest_remedy"),
+        );
+    }
+    match ToolkitManifest::load(&path) {
+        Ok(_) => Diagnosis::pass(t!("doctor_manifest_ok", path = path.display())),
+        Err(e) => Diagnosis::fail(
+            t!("doctor_manifest_invalid", path = path.display(), reason = e),
+            t!("doctor_manifest_remedy"),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_manifest_in_install_dir() {
+        let root = tempfile::tempdir().unwrap();
+        assert_eq!(check_manifest(root.path()).status, CheckStatus::Warn);
+
+        let path = root.path().join(ToolkitManifest::FILENAME);
+        utils::write_file(&path, "[rust\nversion = ", false).unwrap();
+        assert_eq!(check_manifest(root.path()).status, CheckStatus::Fail);
+
+        utils::write_file(&path, "[rust]\nversion = \"1.0.0\"\n", false).unwrap();
+        assert_eq!(check_manifest(root.path()).status, CheckStatus::Pass);
+    }
+
+    #[test]
+    fn check_missing_program() {
+        let root = tempfile::tempdir().unwrap();
+        let diagnosis = check_program("rim-doctor-test-nonexistent", root.path());
+        assert_eq!(diagnosis.status, CheckStatus::Fail);
+        assert!(diagnosis.remedy.is_some());
+    }
+}
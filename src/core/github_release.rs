@@ -0,0 +1,125 @@
+//! Module for resolving tools published as GitHub release assets,
+//! check [`ToolSource::GitHubRelease`](rim_common::types::ToolSource::GitHubRelease).
+
+use anyhow::{Context, Result};
+use rim_common::types::{Proxy, LATEST_TAG};
+use rim_common::utils;
+use serde::Deserialize;
+use url::Url;
+
+const GITHUB_API: &str = "https://api.github.com";
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    #[serde(default)]
+    assets: Vec<Asset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: Url,
+}
+
+/// Fill in the `{target}` and `{version}` placeholders of an asset pattern.
+fn asset_name(pattern: &str, target: &str, tag: &str) -> String {
+    let version = tag.strip_prefix('v').unwrap_or(tag);
+    pattern
+        .replace("{target}", target)
+        .replace("{version}", version)
+}
+
+/// Query the GitHub releases API of `repo` (in `owner/name` form),
+/// and return the download URL of the asset that matches `asset_pattern`
+/// for the current target.
+///
+/// If `tag` is `None` or `latest`, the latest release will be used.
+pub(crate) fn resolve_asset_url(
+    name: &str,
+    repo: &str,
+    tag: Option<&str>,
+    asset_pattern: &str,
+    proxy: Option<Proxy>,
+    insecure: bool,
+) -> Result<Url> {
+    let api_url = match tag {
+        None | Some(LATEST_TAG) => format!("{GITHUB_API}/repos/{repo}/releases/latest"),
+        Some(tag) => format!("{GITHUB_API}/repos/{repo}/releases/tags/{tag}"),
+    };
+    let api_url = Url::parse(&api_url)
+        .with_context(|| format!("invalid GitHub repository '{repo}' for tool '{name}'"))?;
+
+    let raw = blocking!(utils::DownloadOpt::new(name, true)
+        .insecure(insecure)
+        .with_proxy(proxy)
+        .read(&api_url))?;
+    let release: Release = serde_json::from_str(&raw)
+        .with_context(|| format!("unable to parse GitHub release info of '{repo}'"))?;
+
+    find_asset(&release, asset_pattern, super::target_triple())
+        .cloned()
+        .with_context(|| {
+            let available = release
+                .assets
+                .iter()
+                .map(|a| a.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            t!(
+                "github_asset_not_found",
+                name = asset_name(asset_pattern, super::target_triple(), &release.tag_name),
+                repo = repo,
+                tag = release.tag_name,
+                available = available
+            )
+        })
+}
+
+fn find_asset<'a>(release: &'a Release, pattern: &str, target: &str) -> Option<&'a Url> {
+    let expected = asset_name(pattern, target, &release.tag_name);
+    release
+        .assets
+        .iter()
+        .find(|asset| asset.name == expected)
+        .map(|asset| &asset.browser_download_url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_asset_by_pattern() {
+        let raw = r#"{
+            "tag_name": "v1.2.0",
+            "assets": [
+                {
+                    "name": "tool-1.2.0-x86_64-pc-windows-msvc.zip",
+                    "browser_download_url": "https://example.com/tool-1.2.0-x86_64-pc-windows-msvc.zip"
+                },
+                {
+                    "name": "tool-1.2.0-x86_64-unknown-linux-gnu.tar.gz",
+                    "browser_download_url": "https://example.com/tool-1.2.0-x86_64-unknown-linux-gnu.tar.gz"
+                }
+            ]
+        }"#;
+        let release: Release = serde_json::from_str(raw).unwrap();
+
+        let url = find_asset(
+            &release,
+            "tool-{version}-{target}.tar.gz",
+            "x86_64-unknown-linux-gnu",
+        );
+        assert_eq!(
+            url.map(Url::as_str),
+            Some("https://example.com/tool-1.2.0-x86_64-unknown-linux-gnu.tar.gz")
+        );
+        assert!(find_asset(
+            &release,
+            "tool-{version}-{target}.zip",
+            "aarch64-apple-darwin"
+        )
+        .is_none());
+    }
+}
@@ -0,0 +1,137 @@
+//! Module for running the hook commands of tools, check
+//! [`ToolInfoDetails::post_install`](rim_common::types::ToolInfoDetails::post_install)
+//! and [`ToolInfoDetails::pre_uninstall`](rim_common::types::ToolInfoDetails::pre_uninstall).
+//!
+//! Hooks are arbitrary commands coming from the toolkit manifest,
+//! therefore they only run when the user explicitly allows them.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use rim_common::utils;
+
+use super::directories::RimDir;
+use super::{CARGO_HOME, RUSTUP_HOME};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HookStage {
+    PostInstall,
+    PreUninstall,
+}
+
+impl HookStage {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::PostInstall => "post-install",
+            Self::PreUninstall => "pre-uninstall",
+        }
+    }
+}
+
+/// Run the hook `command` of tool `name`, with the tool's install directory
+/// (which is deduced from the installed `paths`) as the working directory.
+///
+/// If hooks are not `allowed`, the command will be skipped with a warning.
+pub(crate) fn run<T: RimDir>(
+    name: &str,
+    stage: HookStage,
+    command: &str,
+    paths: &[PathBuf],
+    config: T,
+    allowed: bool,
+) -> Result<()> {
+    if !allowed {
+        warn!(
+            "{}",
+            t!("hook_not_allowed", name = name, stage = stage.as_str())
+        );
+        return Ok(());
+    }
+
+    info!(
+        "{}",
+        t!("running_hook", name = name, stage = stage.as_str())
+    );
+    let cwd = working_dir(paths, config.tools_dir());
+    let mut cmd = shell_command(command);
+    cmd.env(CARGO_HOME, config.cargo_home())
+        .env(RUSTUP_HOME, config.rustup_home());
+    execute_in(cmd, name, &cwd).with_context(|| {
+        t!(
+            "hook_failed",
+            name = name,
+            stage = stage.as_str(),
+            command = command
+        )
+    })
+}
+
+pub(super) fn execute_in(mut cmd: Command, name: &str, cwd: &Path) -> Result<()> {
+    cmd.current_dir(cwd)
+        .env("RIM_TOOL_NAME", name)
+        .env("RIM_TOOL_DIR", cwd);
+    debug!("running hook command: {cmd:?}");
+    // the output is logged line by line, so it will be captured into the install log
+    utils::execute(cmd)
+}
+
+#[cfg(unix)]
+fn shell_command(command: &str) -> Command {
+    cmd!("sh", "-c", command)
+}
+
+#[cfg(windows)]
+fn shell_command(command: &str) -> Command {
+    cmd!("cmd", "/C", command)
+}
+
+/// The first installed directory, or the parent directory of the first installed file,
+/// fallback to `default` if nothing was installed, such as tools installed by `cargo`.
+pub(super) fn working_dir(paths: &[PathBuf], default: &Path) -> PathBuf {
+    let Some(first) = paths.first() else {
+        return default.to_path_buf();
+    };
+    if first.is_dir() {
+        first.clone()
+    } else {
+        first
+            .parent()
+            .filter(|dir| dir.is_dir())
+            .unwrap_or(default)
+            .to_path_buf()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hook_working_dir() {
+        let root = tempfile::tempdir().unwrap();
+        let tool_dir = root.path().join("tool");
+        let file = root.path().join("file");
+        utils::ensure_dir(&tool_dir).unwrap();
+        utils::write_file(&file, "", false).unwrap();
+
+        assert_eq!(working_dir(&[], root.path()), root.path());
+        assert_eq!(working_dir(std::slice::from_ref(&tool_dir), Path::new("x")), tool_dir);
+        assert_eq!(working_dir(&[file], Path::new("x")), root.path());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_hook_in_tool_dir() {
+        let root = tempfile::tempdir().unwrap();
+        let tool_dir = root.path().join("tool");
+        utils::ensure_dir(&tool_dir).unwrap();
+
+        let command = shell_command("echo \"$RIM_TOOL_NAME\" > hooked");
+        execute_in(command, "a", &tool_dir).unwrap();
+        let content = std::fs::read_to_string(tool_dir.join("hooked")).unwrap();
+        assert_eq!(content.trim(), "a");
+
+        assert!(execute_in(shell_command("exit 1"), "a", &tool_dir).is_err());
+    }
+}
@@ -3,28 +3,52 @@ use super::dependency_handler::DependencyHandler;
 use super::{
     components::Component,
     directories::RimDir,
+    github_release,
+    hooks::{self, HookStage},
+    package_manager::{self, PackageManager},
     parser::{
-        cargo_config::CargoConfig,
+        cargo_config::{CargoConfig, CargoCredentials},
         fingerprint::{InstallationRecord, ToolRecord},
     },
-    rustup::ToolchainInstaller,
-    tools::Tool,
+    rustup::{ToolchainInstaller, TOOLCHAIN_PROFILES},
+    tools::{self, Tool},
+    uninstall::{UninstallConfiguration, Uninstallation},
     GlobalOpts, CARGO_HOME, RUSTUP_DIST_SERVER, RUSTUP_HOME, RUSTUP_UPDATE_ROOT,
 };
-use crate::core::baked_in_manifest_raw;
-use crate::core::os::add_to_path;
+use crate::core::os::{add_to_path, remove_from_path};
+use crate::core::{baked_in_manifest_raw, drop_in_dir};
 use anyhow::{anyhow, bail, Context, Result};
-use rim_common::types::{TomlParser, ToolInfo, ToolMap, ToolSource, ToolkitManifest};
+use indexmap::{IndexMap, IndexSet};
+use rim_common::types::{
+    Proxy, TomlParser, ToolInfo, ToolMap, ToolSource, ToolkitManifest, LATEST_TAG,
+};
 use rim_common::{build_config, utils};
+use serde::Serialize;
 use std::collections::HashSet;
-use std::{
-    collections::HashMap,
-    path::{Path, PathBuf},
-};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use tempfile::TempDir;
+use tokio_util::sync::CancellationToken;
 use url::Url;
 
 const DEFAULT_FOLDER_NAME: &str = "rust";
+/// How many times a failed tool installation can be retried when asked by the
+/// [`ToolFailureHandler`].
+const MAX_TOOL_RETRIES: usize = 3;
+/// The assumed size (in bytes) of tools that are built via `cargo install`,
+/// used to weight their install progress.
+const ESTIMATED_CARGO_TOOL_SIZE: u64 = 10 * 1024 * 1024;
+/// Env var to override the root directory of temporary files.
+pub(crate) const RIM_TEMP_DIR: &str = "RIM_TEMP_DIR";
+/// Env var to set the installation directory when it's not specified explicitly.
+pub(crate) const RIM_INSTALL_DIR: &str = "RIM_INSTALL_DIR";
+/// The free space (in bytes) required for a custom temporary directory to be used,
+/// since large archives might briefly need double their size when being extracted.
+const MIN_TEMP_DIR_SPACE: u64 = 2 * 1024 * 1024 * 1024;
+/// The maximum levels of nested archives (such as a `.tar.gz` inside of a `.zip`)
+/// to extract for a tool package, which prevents zip bombs from being extracted endlessly.
+const MAX_NESTED_ARCHIVE_DEPTH: usize = 3;
 
 /// Contains definition of installation steps, including pre-install configs.
 pub trait EnvConfig {
@@ -32,12 +56,17 @@ pub trait EnvConfig {
     ///
     /// This will set persistent environment variables including
     /// `RUSTUP_DIST_SERVER`, `RUSTUP_UPDATE_ROOT`, `CARGO_HOME`, `RUSTUP_HOME`, etc.
-    fn config_env_vars(&self) -> Result<()>;
+    ///
+    /// Returns `true` if this created the persistent configuration,
+    /// meaning that it should be reverted when the installation is rolled back.
+    fn config_env_vars(&self) -> Result<bool>;
 }
 
 /// Contains every information that the installation process needs.
 pub struct InstallConfiguration<'a> {
     pub cargo_registry: Option<(String, String)>,
+    /// Token of the cargo registry, this is a secret and should never be logged.
+    cargo_registry_token: Option<String>,
     /// Path to install everything.
     ///
     /// Note that this folder will includes `cargo` and `rustup` folders as well.
@@ -55,6 +84,250 @@ pub struct InstallConfiguration<'a> {
     pub(crate) progress_indicator: Option<utils::Progress<'a>>,
     pub(crate) manifest: &'a ToolkitManifest,
     insecure: bool,
+    /// Revert the recorded side effects if the installation failed fatally.
+    rollback_on_failure: bool,
+    journal: InstallJournal,
+    errors: InstallationErrors,
+    /// Toolchain components that should be kept at their installed version when updating.
+    pinned_components: Vec<String>,
+    /// The maximum download speed in bytes per second, overriding the one in manifest.
+    max_download_speed: Option<u64>,
+    /// Toolchain profile that overrides the one in manifest.
+    toolchain_profile: Option<String>,
+    /// A persistent directory to keep the downloaded packages in, so that
+    /// they can be reused in later installations.
+    download_cache: Option<PathBuf>,
+    /// A directory of pre-downloaded packages, which are used instead of
+    /// downloading the packages of `Url` sources if a matching file exists.
+    package_dir: Option<PathBuf>,
+    /// The root directory of temporary files, default to `install_dir/temp` if `None`.
+    temp_root: Option<PathBuf>,
+    /// Whether to create desktop or application menu shortcuts for the installed tools.
+    create_shortcuts: bool,
+    /// Only log what would be done instead of actually installing anything.
+    dry_run: bool,
+    /// Decide whether to retry, skip or abort when a tool fails to install,
+    /// instead of failing right away.
+    tool_failure_handler: Option<ToolFailureHandler<'a>>,
+    /// Run the `post-install` hooks and install scripts of tools, which are skipped by default.
+    allow_hooks: bool,
+    /// Token to cancel the installation with, which is checked between tools
+    /// and while downloading.
+    cancel_token: CancellationToken,
+}
+
+/// What to do after a tool failed to install, as decided by a [`ToolFailureHandler`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolFailureAction {
+    Retry,
+    Skip,
+    Abort,
+}
+
+/// A callback to decide what to do when the tool of the given name failed to install,
+/// the second argument tells whether retrying is still allowed.
+pub type ToolFailureHandler<'a> = &'a dyn Fn(&str, bool) -> Result<ToolFailureAction>;
+
+/// Errors that occurred during installation.
+#[derive(Debug, Default)]
+pub struct InstallationErrors {
+    /// Errors of individual tools, as pairs of tool name and error message.
+    pub tool_errors: Vec<(String, String)>,
+    /// Error of the Rust toolchain installation.
+    pub rust_error: Option<String>,
+    /// Errors of other installation steps, such as creating the installation
+    /// directory or configuring environment variables,
+    /// as pairs of step name and error message.
+    pub step_errors: Vec<(String, String)>,
+}
+
+impl InstallationErrors {
+    /// Returns `true` if the installation is unusable, i.e. the Rust toolchain
+    /// or any of the basic steps failed.
+    ///
+    /// Failing to install some tools is not considered fatal, because the user
+    /// can always add them later using the manager.
+    pub fn is_fatal(&self) -> bool {
+        self.rust_error.is_some() || !self.step_errors.is_empty()
+    }
+
+    /// Convert the errors into a serializable [`InstallReport`].
+    ///
+    /// `tools` are the tools that were requested to install, and the status of each
+    /// of them is determined by this errors and the installation `record`.
+    pub fn to_report(
+        &self,
+        tools: &ToolMap,
+        record: &InstallationRecord,
+        duration: Duration,
+    ) -> InstallReport {
+        let status_of = |failed: bool, installed: bool| {
+            if failed {
+                InstallStatus::Failed
+            } else if installed {
+                InstallStatus::Installed
+            } else {
+                InstallStatus::Skipped
+            }
+        };
+
+        let tools = tools
+            .iter()
+            .map(|(name, _)| ToolReport {
+                name: name.to_string(),
+                status: status_of(
+                    self.tool_errors.iter().any(|(n, _)| n == name),
+                    record.tools.contains_key(name),
+                ),
+            })
+            .collect();
+        let toolchain = status_of(self.rust_error.is_some(), record.rust.is_some());
+
+        let mut errors = vec![];
+        for (step, message) in &self.step_errors {
+            errors.push(ErrorReport {
+                kind: ErrorKind::Step,
+                name: step.clone(),
+                message: message.clone(),
+            });
+        }
+        if let Some(message) = &self.rust_error {
+            errors.push(ErrorReport {
+                kind: ErrorKind::Toolchain,
+                name: "rust".into(),
+                message: message.clone(),
+            });
+        }
+        for (tool, message) in &self.tool_errors {
+            errors.push(ErrorReport {
+                kind: ErrorKind::Tool,
+                name: tool.clone(),
+                message: message.clone(),
+            });
+        }
+
+        InstallReport {
+            success: errors.is_empty(),
+            toolchain,
+            tools,
+            errors,
+            duration_secs: duration.as_secs_f64(),
+        }
+    }
+}
+
+/// A serializable summary of an installation, check [`InstallationErrors::to_report`].
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallReport {
+    pub success: bool,
+    pub toolchain: InstallStatus,
+    pub tools: Vec<ToolReport>,
+    pub errors: Vec<ErrorReport>,
+    pub duration_secs: f64,
+}
+
+impl InstallReport {
+    /// Write this report as JSON to the given path.
+    pub fn write_json<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        utils::ensure_parent_dir(path.as_ref())?;
+        utils::write_file(path.as_ref(), &content, false).with_context(|| {
+            format!(
+                "unable to write installation report to '{}'",
+                path.as_ref().display()
+            )
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InstallStatus {
+    Installed,
+    Failed,
+    /// The component was not installed because the installation stopped before it.
+    Skipped,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ToolReport {
+    pub name: String,
+    pub status: InstallStatus,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ErrorKind {
+    Step,
+    Toolchain,
+    Tool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ErrorReport {
+    pub kind: ErrorKind,
+    pub name: String,
+    pub message: String,
+}
+
+/// A side effect made during installation that can be reverted.
+#[derive(Debug)]
+enum JournalEntry {
+    CreatedDir(PathBuf),
+    CreatedFile(PathBuf),
+    AddedToPath(PathBuf),
+    /// Persistent environment variables such as `CARGO_HOME`, `RUSTUP_HOME`.
+    EnvVars,
+    /// The entry of "installed programs" in Windows registry.
+    #[cfg(windows)]
+    ProgramsEntry,
+}
+
+/// Records every side effect of an installation in order,
+/// so they can be undone in reverse if the installation fails.
+#[derive(Debug, Default)]
+struct InstallJournal {
+    entries: Vec<JournalEntry>,
+}
+
+impl InstallJournal {
+    fn record(&mut self, entry: JournalEntry) {
+        self.entries.push(entry);
+    }
+
+    fn rollback(&mut self, install_dir: &Path) {
+        while let Some(entry) = self.entries.pop() {
+            debug!("reverting installation change: {entry:?}");
+            let res = match &entry {
+                JournalEntry::CreatedDir(path) | JournalEntry::CreatedFile(path) => {
+                    utils::remove(path)
+                }
+                JournalEntry::AddedToPath(path) => remove_from_path(path),
+                JournalEntry::EnvVars => UninstallConfiguration {
+                    install_dir: install_dir.to_path_buf(),
+                    install_record: InstallationRecord::default(),
+                    progress_indicator: None,
+                    allow_hooks: false,
+                }
+                .remove_rustup_env_vars(),
+                #[cfg(windows)]
+                JournalEntry::ProgramsEntry => super::os::windows::do_remove_from_programs(
+                    super::os::windows::uninstall_entry(),
+                ),
+            };
+            if let Err(e) = res {
+                warn!(
+                    "{}",
+                    t!(
+                        "rollback_entry_fail",
+                        entry = format!("{entry:?}"),
+                        error = e
+                    )
+                );
+            }
+        }
+    }
 }
 
 impl RimDir for InstallConfiguration<'_> {
@@ -71,18 +344,44 @@ impl RimDir for &InstallConfiguration<'_> {
 
 impl<'a> InstallConfiguration<'a> {
     pub fn new(install_dir: &'a Path, manifest: &'a ToolkitManifest) -> Result<Self> {
+        validate_install_dir(install_dir)?;
         let (reg_name, reg_url) = super::default_cargo_registry();
+        let mut journal = InstallJournal::default();
+        if !install_dir.exists() {
+            journal.record(JournalEntry::CreatedDir(install_dir.to_path_buf()));
+            ensure_writable_dir(install_dir)?;
+        }
+        // Note: `InstallationRecord::load_from_dir` creates `install_dir` if it does not exist
+        let install_record = InstallationRecord::load_from_dir(install_dir)?;
         Ok(Self {
             install_dir: install_dir.to_path_buf(),
-            // Note: `InstallationRecord::load_from_dir` creates `install_dir` if it does not exist
-            install_record: InstallationRecord::load_from_dir(install_dir)?,
+            pinned_components: install_record.pinned_components.clone(),
+            toolchain_profile: install_record.toolchain_profile.clone(),
+            install_record,
             cargo_registry: Some((reg_name.into(), reg_url.into())),
+            cargo_registry_token: manifest
+                .config
+                .as_ref()
+                .and_then(|c| c.cargo_registry_token.clone()),
             rustup_dist_server: super::default_rustup_dist_server().clone(),
             rustup_update_root: super::default_rustup_update_root().clone(),
             toolchain_is_installed: false,
             progress_indicator: None,
             manifest,
             insecure: false,
+            rollback_on_failure: false,
+            max_download_speed: None,
+            download_cache: None,
+            package_dir: None,
+            temp_root: std::env::var_os(RIM_TEMP_DIR)
+                .and_then(|dir| usable_temp_root(Path::new(&dir), install_dir)),
+            create_shortcuts: manifest.create_shortcuts(),
+            dry_run: false,
+            tool_failure_handler: None,
+            allow_hooks: false,
+            cancel_token: CancellationToken::new(),
+            journal,
+            errors: InstallationErrors::default(),
         })
     }
     /// Creating install directory and other preparations related to filesystem.
@@ -91,7 +390,13 @@ impl<'a> InstallConfiguration<'a> {
     pub fn setup(&mut self) -> Result<()> {
         let install_dir = &self.install_dir;
 
+        if self.dry_run {
+            info!("{}", t!("dry_run_setup", dir = install_dir.display()));
+            return self.inc_progress(5.0);
+        }
+
         info!("{}", t!("install_init", dir = install_dir.display()));
+        ensure_writable_dir(install_dir)?;
 
         // Create a copy of the manifest which is later used for component management.
         // NB: This `setup` function only gets called during the first installation,
@@ -99,11 +404,21 @@ impl<'a> InstallConfiguration<'a> {
         // NB: If this is an offline build, meaning the manifest is likely to contain
         // local paths, which is not useful for adding components afterwards, therefore
         // we better store the online version instead,
+        let manifest_path = install_dir.join(ToolkitManifest::FILENAME);
+        let manifest_existed = manifest_path.exists();
         if self.manifest.is_offline {
-            ToolkitManifest::from_str(baked_in_manifest_raw(false))?.write_to_dir(install_dir)?;
+            let mut online = ToolkitManifest::from_str(baked_in_manifest_raw(false))?;
+            if let Some(dir) = drop_in_dir() {
+                online.merge_drop_ins(&dir)?;
+            }
+            online.write_to_dir(install_dir)?;
         } else {
             self.manifest.write_to_dir(install_dir)?;
         }
+        if !manifest_existed {
+            self.journal
+                .record(JournalEntry::CreatedFile(manifest_path));
+        }
 
         // Create a copy of this binary
         let self_exe = std::env::current_exe()?;
@@ -113,12 +428,26 @@ impl<'a> InstallConfiguration<'a> {
 
         // Add this manager to the `PATH` environment
         let manager_exe = install_dir.join(exe!(manager_name));
+        let manager_existed = manager_exe.exists();
         utils::copy_as(self_exe, &manager_exe)?;
-        add_to_path(install_dir)?;
+        if !manager_existed {
+            self.journal
+                .record(JournalEntry::CreatedFile(manager_exe.clone()));
+        }
+        if add_to_path(install_dir)? {
+            self.journal
+                .record(JournalEntry::AddedToPath(install_dir.clone()));
+        }
+
+        self.warn_path_shadowing();
 
         #[cfg(windows)]
-        // Create registry entry to add this program into "installed programs".
-        super::os::windows::do_add_to_programs(&manager_exe)?;
+        {
+            // Create registry entry to add this program into "installed programs".
+            if super::os::windows::do_add_to_programs(&manager_exe)? {
+                self.journal.record(JournalEntry::ProgramsEntry);
+            }
+        }
 
         if let Some(prog) = &self.progress_indicator {
             prog.inc(Some(5.0))?;
@@ -127,17 +456,101 @@ impl<'a> InstallConfiguration<'a> {
         Ok(())
     }
 
-    pub fn install(mut self, components: Vec<Component>) -> Result<()> {
+    /// Warn the user if there are existing `cargo`, `rustc` or `rustup` in `PATH`,
+    /// as they might shadow the ones we are about to install.
+    fn warn_path_shadowing(&self) {
+        let conflicts =
+            super::os::conflicting_path_entries(&["cargo", "rustc", "rustup"], self.cargo_bin());
+        if conflicts.is_empty() {
+            return;
+        }
+        let list = conflicts
+            .iter()
+            .map(|p| format!("  {}", p.display()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        warn!("{}", t!("path_shadowing_detected", list = list));
+    }
+
+    pub fn install(self, components: Vec<Component>) -> Result<()> {
+        self.install_and_report(components).0
+    }
+
+    /// Same as [`install`](Self::install), but also returns an [`InstallReport`]
+    /// describing the result of each component, whether the installation succeeded or not.
+    pub fn install_and_report(mut self, components: Vec<Component>) -> (Result<()>, InstallReport) {
+        let start = Instant::now();
         let (tc_components, tools) = split_components(components);
-        reject_conflicting_tools(&tools)?;
 
-        self.setup()?;
-        self.config_env_vars()?;
-        self.config_cargo()?;
+        let result = self
+            .step_("check_conflicts", |_| reject_conflicting_tools(&tools))
+            .and_then(|_| self.install_(&tc_components, &tools));
+        if result.is_err() && self.is_cancelled() {
+            // cancellation leaves the installation incomplete, just like any fatal errors
+            self.errors
+                .step_errors
+                .push(("cancel".to_string(), t!("install_cancelled").to_string()));
+        }
+        if result.is_err() && self.rollback_on_failure && self.errors.is_fatal() {
+            warn!("{}", t!("install_rollback"));
+            self.journal.rollback(&self.install_dir);
+        } else if self.dry_run {
+            // The only possible change in dry run mode is the installation directory
+            // created when loading the installation record, so just revert it.
+            self.journal.rollback(&self.install_dir);
+        }
+
+        let report = self
+            .errors
+            .to_report(&tools, &self.install_record, start.elapsed());
+        (result, report)
+    }
+
+    fn install_(&mut self, tc_components: &[ToolchainComponent], tools: &ToolMap) -> Result<()> {
+        self.step_("setup", Self::setup)?;
+        self.step_("config_env_vars", |this| {
+            if this.dry_run {
+                return this.dry_run_env_vars();
+            }
+            if this.config_env_vars()? {
+                this.journal.record(JournalEntry::EnvVars);
+            }
+            Ok(())
+        })?;
+        self.step_("config_cargo", |this| this.config_cargo())?;
         // This step taking cares of requirements, such as `MSVC`, also third-party app such as `VS Code`.
-        self.install_tools(&tools)?;
-        self.install_rust(&tc_components)?;
-        self.install_tools_late(&tools)?;
+        self.install_tools(tools)?;
+        self.check_cancelled()?;
+        if let Err(e) = self.install_rust(tc_components) {
+            self.errors.rust_error = Some(format!("{e:#}"));
+            return Err(e);
+        }
+        self.install_tools_late(tools)?;
+        Ok(())
+    }
+
+    /// Run an installation step, and record its error if it fails.
+    fn step_<F>(&mut self, name: &str, step: F) -> Result<()>
+    where
+        F: FnOnce(&mut Self) -> Result<()>,
+    {
+        let result = step(self);
+        if let Err(e) = &result {
+            self.errors
+                .step_errors
+                .push((name.to_string(), format!("{e:#}")));
+        }
+        result
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel_token.is_cancelled()
+    }
+
+    fn check_cancelled(&self) -> Result<()> {
+        if self.is_cancelled() {
+            bail!(t!("install_cancelled"));
+        }
         Ok(())
     }
 
@@ -157,23 +570,133 @@ impl<'a> InstallConfiguration<'a> {
     setter!(with_rustup_update_root(self.rustup_update_root, Url));
     setter!(with_progress_indicator(self.progress_indicator, Option<utils::Progress<'a>>));
     setter!(insecure(self.insecure, bool));
+    setter!(rollback_on_failure(self.rollback_on_failure, bool));
+    setter!(with_max_download_speed(self.max_download_speed, Option<u64>));
+    setter!(with_download_cache(self.download_cache, Option<PathBuf>));
+    setter!(with_package_dir(self.package_dir, Option<PathBuf>));
+    setter!(dry_run(self.dry_run, bool));
+    setter!(with_tool_failure_handler(self.tool_failure_handler, Option<ToolFailureHandler<'a>>));
+    setter!(allow_hooks(self.allow_hooks, bool));
+    setter!(with_cancel_token(self.cancel_token, CancellationToken));
+
+    /// Use `dir` as the root of temporary files instead of `install_dir/temp`,
+    /// overriding the one set by `RIM_TEMP_DIR` env var.
+    ///
+    /// If `dir` is not writable or does not have enough free space, a warning will be
+    /// logged and the default location will be used.
+    pub fn with_temp_dir(mut self, dir: Option<&Path>) -> Self {
+        if let Some(dir) = dir {
+            self.temp_root = usable_temp_root(dir, &self.install_dir);
+        }
+        self
+    }
+
+    /// Set the token to authenticate with the cargo registry,
+    /// overriding the `cargo-registry-token` option in manifest.
+    pub fn with_cargo_registry_token(mut self, token: Option<String>) -> Self {
+        if token.is_some() {
+            self.cargo_registry_token = token;
+        }
+        self
+    }
+
+    /// Stop creating shortcuts for the installed tools if `yes` is `true`,
+    /// overriding the `create-shortcuts` option in manifest.
+    pub fn no_shortcut(mut self, yes: bool) -> Self {
+        if yes {
+            self.create_shortcuts = false;
+        }
+        self
+    }
+
+    /// Whether shortcuts should be created for the installed tools.
+    pub(crate) fn create_shortcuts(&self) -> bool {
+        self.create_shortcuts
+    }
+
+    /// Whether the hooks and install scripts of tools are allowed to run.
+    pub(crate) fn hooks_allowed(&self) -> bool {
+        self.allow_hooks
+    }
+
+    /// The maximum download speed in bytes per second, either specified by user
+    /// or configured in the toolkit manifest.
+    pub(crate) fn download_rate_limit(&self) -> Option<u64> {
+        self.max_download_speed
+            .or_else(|| self.manifest.max_download_speed())
+    }
+
+    /// Override the toolchain profile set in manifest, which must be one of
+    /// [`TOOLCHAIN_PROFILES`](super::rustup::TOOLCHAIN_PROFILES).
+    ///
+    /// Passing `None` keeps the previously recorded profile (if any).
+    pub fn with_toolchain_profile(mut self, profile: Option<&str>) -> Result<Self> {
+        let Some(profile) = profile else {
+            return Ok(self);
+        };
+        if !TOOLCHAIN_PROFILES.contains(&profile) {
+            bail!(t!(
+                "invalid_toolchain_profile",
+                profile = profile,
+                available = TOOLCHAIN_PROFILES.join(", ")
+            ));
+        }
+        self.toolchain_profile = Some(profile.to_string());
+        Ok(self)
+    }
+
+    /// The toolchain profile to install, either specified by user
+    /// or configured in the toolkit manifest.
+    pub(crate) fn toolchain_profile(&self) -> Option<&str> {
+        self.toolchain_profile
+            .as_deref()
+            .or_else(|| self.manifest.rust.profile())
+    }
+
+    /// Pin toolchain components at their installed version, in addition to the
+    /// ones that were pinned previously.
+    pub fn pin_components<S: ToString>(mut self, components: &[S]) -> Self {
+        for comp in components.iter().map(ToString::to_string) {
+            if !self.pinned_components.contains(&comp) {
+                self.pinned_components.push(comp);
+            }
+        }
+        self
+    }
+
+    /// Unpin toolchain components that were pinned previously, so they will be updated again.
+    pub fn unpin_components<S: AsRef<str>>(mut self, components: &[S]) -> Self {
+        self.pinned_components
+            .retain(|pinned| !components.iter().any(|c| c.as_ref() == pinned));
+        self
+    }
 
     #[cfg(test)]
     pub(crate) fn insecure_flag(&self) -> bool {
         self.insecure
     }
 
-    pub(crate) fn env_vars(&self) -> Result<HashMap<&'static str, String>> {
-        let cargo_home = self
-            .cargo_home()
+    /// Get the environment variables to set for this installation.
+    ///
+    /// Each variable appears exactly once, in the order of insertion.
+    pub(crate) fn env_vars(&self) -> Result<IndexMap<&'static str, String>> {
+        self.env_vars_(self.cargo_home(), self.rustup_home())
+    }
+
+    fn env_vars_(
+        &self,
+        cargo_home: &Path,
+        rustup_home: &Path,
+    ) -> Result<IndexMap<&'static str, String>> {
+        let cargo_home = cargo_home
             .to_str()
             .map(ToOwned::to_owned)
             .context("`install-dir` cannot contains invalid unicode")?;
         // This `unwrap` is safe here because we've already make sure the `install_dir`'s path can be
         // converted to string with the `cargo_home` variable.
-        let rustup_home = self.rustup_home().to_str().unwrap().to_string();
+        let rustup_home = rustup_home.to_str().unwrap().to_string();
 
-        let mut env_vars = HashMap::from([
+        let mut env_vars = IndexMap::from([
             (RUSTUP_DIST_SERVER, self.rustup_dist_server.to_string()),
             (RUSTUP_UPDATE_ROOT, self.rustup_update_root.to_string()),
             (CARGO_HOME, cargo_home),
@@ -189,18 +712,8 @@ impl<'a> InstallConfiguration<'a> {
                 env_vars.insert("https_proxy", url.to_string());
             }
             if let Some(s) = &proxy.no_proxy {
-                // keep use's original no_proxy var.
-                #[cfg(windows)]
-                let prev_np = std::env::var("no_proxy").unwrap_or_default();
-                #[cfg(unix)]
-                let prev_np = "$no_proxy";
-
-                let no_proxy = if prev_np.is_empty() {
-                    s.to_string()
-                } else {
-                    format!("{s},{prev_np}")
-                };
-                env_vars.insert("no_proxy", no_proxy);
+                // keep user's original no_proxy var.
+                env_vars.insert("no_proxy", merge_no_proxy(s));
             }
         }
 
@@ -224,24 +737,104 @@ impl<'a> InstallConfiguration<'a> {
         if to_install.is_empty() {
             return self.inc_progress(weight);
         }
-        let sub_progress_delta = weight / to_install.len() as f32;
 
         to_install = to_install.topological_sorted();
         // topological sort place the tool with more dependencies at the back,
         // which is what we need to install first, therefore we need to reverse it.
         to_install.reverse();
 
-        for (name, tool) in to_install {
-            info!("{}", t!("installing_tool_info", name = name));
-            self.install_tool(name, tool)?;
-            self.inc_progress(sub_progress_delta)?;
+        let sizes = to_install
+            .iter()
+            .map(|(name, tool)| self.estimated_size(name, tool))
+            .collect::<Vec<_>>();
+        let sub_progress_deltas = progress_weights(&sizes, weight);
+
+        for ((name, tool), delta) in to_install.into_iter().zip(sub_progress_deltas) {
+            // tools that take long to install (i.e. compiled by `cargo install`) might
+            // report part of their progress within `delta` while being installed.
+            let start_pos = self.progress_indicator.as_mut().map(|prog| {
+                prog.len = delta;
+                prog.pos()
+            });
+            let result = self.check_cancelled().and_then(|_| {
+                info!("{}", t!("installing_tool_info", name = name));
+                self.install_tool_or_ask(name, tool)
+            });
+            if let Err(e) = result {
+                // keep the tools that were already installed in record, so
+                // they don't get installed again on next run.
+                if self.is_cancelled() && !self.dry_run {
+                    self.install_record.write()?;
+                }
+                return Err(e);
+            }
+            let reported = match (&self.progress_indicator, start_pos) {
+                (Some(prog), Some(start)) => prog.pos() - start,
+                _ => 0.0,
+            };
+            self.inc_progress((delta - reported).max(0.0))?;
         }
 
-        self.install_record.write()?;
+        if !self.dry_run {
+            self.install_record.write()?;
+        }
 
         Ok(())
     }
 
+    /// Get the known or estimated size of a tool, which is only used to weight install progress.
+    fn estimated_size(&self, name: &str, tool: &ToolInfo) -> Option<u64> {
+        if tool.is_cargo_tool() {
+            return Some(ESTIMATED_CARGO_TOOL_SIZE);
+        }
+        if let Some(size) = tool.size() {
+            return Some(size);
+        }
+        let source = tool.details()?.source.as_ref()?;
+        if let ToolSource::Url { url, .. } = source {
+            if let Some(local) = self.local_package(url, tool) {
+                return local.metadata().ok().map(|m| m.len());
+            }
+        }
+        match source {
+            ToolSource::Path { path, .. } => path
+                .metadata()
+                .ok()
+                .filter(|m| m.is_file())
+                .map(|m| m.len()),
+            // don't send any request in dry-run mode
+            ToolSource::Url { url, .. } if !self.dry_run => {
+                match self.fetch_remote_size(name, url, tool) {
+                    Ok(size) => size,
+                    Err(e) => {
+                        debug!("unable to get the size of '{name}': {e}");
+                        None
+                    }
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn fetch_remote_size(&self, name: &str, url: &Url, tool: &ToolInfo) -> Result<Option<u64>> {
+        blocking!(utils::DownloadOpt::new(name, true)
+            .insecure(self.insecure)
+            .with_proxy(self.proxy_for(tool))
+            .content_length(url))
+    }
+
+    fn fetch_remote_filename(
+        &self,
+        name: &str,
+        url: &Url,
+        tool: &ToolInfo,
+    ) -> Result<Option<String>> {
+        blocking!(utils::DownloadOpt::new(name, true)
+            .insecure(self.insecure)
+            .with_proxy(self.proxy_for(tool))
+            .remote_filename(url))
+    }
+
     pub fn install_tools(&mut self, tools: &ToolMap) -> Result<()> {
         info!("{}", t!("install_tools"));
         self.install_tools_(false, tools, 30.0)
@@ -254,19 +847,43 @@ impl<'a> InstallConfiguration<'a> {
     }
 
     pub fn install_rust(&mut self, components: &[ToolchainComponent]) -> Result<()> {
-        info!("{}", t!("install_toolchain"));
-
         let manifest = self.manifest;
 
+        if self.dry_run {
+            let components = components
+                .iter()
+                .map(|c| c.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            info!(
+                "{}",
+                t!(
+                    "dry_run_toolchain",
+                    version = manifest.rust.channel,
+                    components = components,
+                    server = self.rustup_dist_server
+                )
+            );
+            return self.inc_progress(30.0);
+        }
+
+        info!("{}", t!("install_toolchain"));
+
         ToolchainInstaller::init(&*self)
             .insecure(self.insecure)
             .install(self, components)?;
-        add_to_path(self.cargo_bin())?;
+        let cargo_bin = self.cargo_bin().to_path_buf();
+        if add_to_path(&cargo_bin)? {
+            self.journal.record(JournalEntry::AddedToPath(cargo_bin));
+        }
         self.toolchain_is_installed = true;
 
         // Add the rust info to the fingerprint.
         self.install_record
             .add_rust_record(&manifest.rust.channel, components);
+        self.install_record
+            .toolchain_profile
+            .clone_from(&self.toolchain_profile);
         // record meta info
         // TODO(?): Maybe this should be moved as a separate step?
         self.install_record
@@ -289,19 +906,60 @@ impl<'a> InstallConfiguration<'a> {
         Ok(())
     }
 
+    /// Install a single tool, and if it fails, ask the [`ToolFailureHandler`] (if there is one)
+    /// whether to retry, skip it, or abort the installation.
+    ///
+    /// Skipped tools are recorded as errors without failing the installation,
+    /// and retrying is no longer offered once [`MAX_TOOL_RETRIES`] is reached.
+    fn install_tool_or_ask(&mut self, name: &str, tool: &ToolInfo) -> Result<()> {
+        let mut retries = 0;
+        loop {
+            let Err(e) = self.install_tool(name, tool) else {
+                return Ok(());
+            };
+            self.errors
+                .tool_errors
+                .push((name.to_string(), format!("{e:#}")));
+            let Some(handler) = self.tool_failure_handler.filter(|_| !self.is_cancelled()) else {
+                return Err(e);
+            };
+
+            error!("{e:#}");
+            let can_retry = retries < MAX_TOOL_RETRIES;
+            match handler(name, can_retry)? {
+                ToolFailureAction::Retry if can_retry => {
+                    retries += 1;
+                    self.errors.tool_errors.pop();
+                }
+                ToolFailureAction::Skip => {
+                    warn!("{}", t!("skipping_tool", name = name));
+                    return Ok(());
+                }
+                _ => return Err(e),
+            }
+        }
+    }
+
     fn install_tool(&mut self, name: &str, tool: &ToolInfo) -> Result<()> {
+        if self.dry_run {
+            self.dry_run_tool(name, tool);
+            return Ok(());
+        }
+
         self.remove_obsoleted_tools(tool)?;
 
         let record = match tool {
             ToolInfo::Basic(version) => {
-                Tool::cargo_tool(name, Some(vec![name, "--version", version]))
+                let version = tools::cargo_version_arg(name, version)?;
+                Tool::cargo_tool(name, Some(vec![name, "--version", &version]))
                     .install(self, tool)?
             }
             ToolInfo::Complex(details) => match details.source.as_ref().with_context(|| {
                 format!("tool '{name}' cannot be installed because it's lacking a package source")
             })? {
                 ToolSource::Version { version } => {
-                    Tool::cargo_tool(name, Some(vec![name, "--version", version]))
+                    let version = tools::cargo_version_arg(name, version)?;
+                    Tool::cargo_tool(name, Some(vec![name, "--version", &version]))
                         .install(self, tool)?
                 }
                 ToolSource::Git {
@@ -323,10 +981,65 @@ impl<'a> InstallConfiguration<'a> {
 
                     Tool::cargo_tool(name, Some(args)).install(self, tool)?
                 }
-                ToolSource::Path { path, .. } => {
+                ToolSource::Path { path, sha256, .. } => {
+                    if let Some(expected) = sha256 {
+                        verify_package_checksum(name, path, expected)?;
+                    }
                     self.try_install_from_path(name, path, tool, None)?
                 }
-                ToolSource::Url { url, .. } => self.download_and_try_install(name, url, tool)?,
+                ToolSource::Url { url, .. } => match self.local_package(url, tool) {
+                    Some(local) if is_valid_local_package(name, &local, tool) => {
+                        self.install_local_package(name, &local, tool)?
+                    }
+                    local => {
+                        if let (None, Some(dir)) = (&local, &self.package_dir) {
+                            warn!(
+                                "{}",
+                                t!(
+                                    "package_not_found_locally",
+                                    name = name,
+                                    dir = dir.display()
+                                )
+                            );
+                        }
+                        self.download_and_try_install(name, url, tool)?
+                    }
+                },
+                ToolSource::GitHubRelease {
+                    repo,
+                    tag,
+                    asset_pattern,
+                } => {
+                    let url = github_release::resolve_asset_url(
+                        name,
+                        repo,
+                        tag.as_deref(),
+                        asset_pattern,
+                        self.proxy_for(tool),
+                        self.insecure,
+                    )?;
+                    self.download_and_try_install(name, &url, tool)?
+                }
+                ToolSource::Npm { package, version } => PackageManager::Npm.install(
+                    name,
+                    package,
+                    version.as_deref(),
+                    &[],
+                    tool,
+                    self,
+                )?,
+                ToolSource::Pip {
+                    package,
+                    version,
+                    extras,
+                } => PackageManager::Pip.install(
+                    name,
+                    package,
+                    version.as_deref(),
+                    extras,
+                    tool,
+                    self,
+                )?,
                 ToolSource::Restricted { source, .. } => {
                     // the source should be filled before installation, if not, then it means
                     // the program hasn't ask for user input yet, which we should through an error.
@@ -349,11 +1062,59 @@ impl<'a> InstallConfiguration<'a> {
             },
         };
 
+        let record = record.with_pre_uninstall(tool.pre_uninstall());
+        // record the tool before running its hook, otherwise the installed files
+        // won't be tracked (nor uninstallable) if the hook fails and gets skipped.
+        let paths = record.paths.clone();
         self.install_record.add_tool_record(name, record);
+        if let Some(command) = tool.post_install() {
+            hooks::run(
+                name,
+                HookStage::PostInstall,
+                command,
+                &paths,
+                &*self,
+                self.allow_hooks,
+            )?;
+        }
 
         Ok(())
     }
 
+    /// Get the proxy for downloading a tool, which is the tool's own proxy
+    /// if specified, otherwise the one from the manifest.
+    fn proxy_for(&self, tool: &ToolInfo) -> Option<Proxy> {
+        tool.proxy().or(self.manifest.proxy.as_ref()).cloned()
+    }
+
+    /// Find the package of a `Url` source in the package directory, by the
+    /// `filename` in manifest, or the last segment of its `url`.
+    fn local_package(&self, url: &Url, info: &ToolInfo) -> Option<PathBuf> {
+        let dir = self.package_dir.as_deref()?;
+        find_local_package(dir, url, info.filename())
+    }
+
+    fn install_local_package(
+        &self,
+        name: &str,
+        path: &Path,
+        info: &ToolInfo,
+    ) -> Result<ToolRecord> {
+        info!(
+            "{}",
+            t!("use_local_package", name = name, path = path.display())
+        );
+        // same as cached downloads, non-archive files might get moved during
+        // installation, so install from a copy to keep the package directory intact.
+        if utils::Extractable::is_supported(path) {
+            return self.try_install_from_path(name, path, info, None);
+        }
+        let temp_dir = self.create_temp_dir("download")?;
+        let dest = temp_dir.path().join(path.file_name().unwrap_or_default());
+        utils::copy_as(path, &dest)?;
+        self.try_install_from_path(name, &dest, info, Some(temp_dir))
+    }
+
     fn download_and_try_install(
         &self,
         name: &str,
@@ -361,25 +1122,88 @@ impl<'a> InstallConfiguration<'a> {
         info: &ToolInfo,
     ) -> Result<ToolRecord> {
         let temp_dir = self.create_temp_dir("download")?;
-        let downloaded_file_name = if let Some(name) = info.filename() {
-            name
-        } else {
-            url.path_segments()
-                .ok_or_else(|| anyhow!("unsupported url format '{url}'"))?
-                .next_back()
-                // Sadly, a path segment could be empty string, so we need to filter that out
-                .filter(|seg| !seg.is_empty())
-                .ok_or_else(|| anyhow!("'{url}' doesn't appear to be a downloadable file"))?
+
+        let Some(cache_dir) = &self.download_cache else {
+            let dest = temp_dir
+                .path()
+                .join(self.download_file_name(name, url, info)?);
+            self.download_tool(name, url, info, &dest)?;
+            return self.try_install_from_path(name, &dest, info, Some(temp_dir));
         };
-        let dest = temp_dir.path().join(downloaded_file_name);
-        utils::DownloadOpt::new(name, GlobalOpts::get().quiet)
-            .insecure(self.insecure)
-            .with_proxy(self.manifest.proxy.clone())
-            .blocking_download(url, &dest)?;
 
+        // The cache entry is keyed by the url and the expected checksum only, so it can be
+        // found without asking the server for the filename first (i.e. when offline).
+        let cache_key = match info.sha256() {
+            Some(sha256) => format!("{url}#{sha256}"),
+            None => url.to_string(),
+        };
+        let entry_dir = cache_dir.join(utils::sha256_hex(cache_key));
+        let cached = if let Some(cached) = find_cached_download(&entry_dir, info.sha256()) {
+            info!(
+                "{}",
+                t!(
+                    "reuse_cached_download",
+                    name = name,
+                    path = cached.display()
+                )
+            );
+            cached
+        } else {
+            // remove the corrupted leftovers, which might be named differently
+            utils::remove(&entry_dir)?;
+            let cached = entry_dir.join(self.download_file_name(name, url, info)?);
+            utils::ensure_parent_dir(&cached)?;
+            self.download_tool(name, url, info, &cached)?;
+            let checksum = utils::calculate_sha256(&cached)?;
+            utils::write_file(checksum_file(&cached), &checksum, false)?;
+            cached
+        };
+        // archives are extracted elsewhere, but other files might get moved
+        // during installation, so make a copy to keep the cache intact.
+        if utils::Extractable::is_supported(&cached) {
+            return self.try_install_from_path(name, &cached, info, Some(temp_dir));
+        }
+        let dest = temp_dir.path().join(cached.file_name().unwrap_or_default());
+        utils::copy_as(&cached, &dest)?;
         self.try_install_from_path(name, &dest, info, Some(temp_dir))
     }
 
+    /// Get the name to save the package of tool `name` downloaded from `url` as.
+    fn download_file_name(&self, name: &str, url: &Url, info: &ToolInfo) -> Result<String> {
+        // prefer the real filename suggested by server, which tells the file type
+        // more reliably than the one specified in manifest or guessed from url.
+        let remote_filename = self
+            .fetch_remote_filename(name, url, info)
+            .unwrap_or_else(|e| {
+                debug!("unable to get the filename of '{name}' from server: {e}");
+                None
+            });
+        if let Some(name) = remote_filename {
+            return Ok(name);
+        }
+        if let Some(name) = info.filename() {
+            return Ok(name.to_string());
+        }
+        utils::url_filename(url)
+            .ok_or_else(|| anyhow!("'{url}' doesn't appear to be a downloadable file"))
+    }
+
+    fn download_tool(&self, name: &str, url: &Url, info: &ToolInfo, dest: &Path) -> Result<()> {
+        let download = utils::DownloadOpt::new(name, GlobalOpts::get().quiet)
+            .insecure(self.insecure)
+            .with_proxy(self.proxy_for(info))
+            .with_rate_limit(self.download_rate_limit())
+            .with_mirrors(self.manifest.mirrors().to_vec())
+            .with_sha256(info.sha256().map(ToOwned::to_owned))
+            .download(url, dest);
+        blocking!(async {
+            tokio::select! {
+                res = download => res,
+                _ = self.cancel_token.cancelled() => bail!(t!("install_cancelled")),
+            }
+        })
+    }
+
     fn try_install_from_path(
         &self,
         name: &str,
@@ -392,7 +1216,8 @@ impl<'a> InstallConfiguration<'a> {
             path.to_path_buf()
         } else if utils::Extractable::is_supported(path) {
             let extract_temp = self.create_temp_dir(name)?;
-            let tool_installer_path = self.extract_or_copy_to(path, extract_temp.path())?;
+            let tool_installer_path =
+                self.extract_or_copy_to(path, extract_temp.path(), info.strip_components())?;
             // we don't need the download temp dir anymore,
             // we should keep the extraction temp dir alive instead.
             maybe_temp = Some(extract_temp);
@@ -422,11 +1247,44 @@ impl<'a> InstallConfiguration<'a> {
     ///
     /// This will write a `config.toml` file to `CARGO_HOME`.
     pub fn config_cargo(&self) -> Result<()> {
+        if self.dry_run {
+            let path = self.install_dir.join("cargo").join(CargoConfig::FILENAME);
+            info!("{}", t!("dry_run_cargo_config", path = path.display()));
+            return self.inc_progress(3.0);
+        }
+
         info!("{}", t!("install_cargo_config"));
 
         let mut config = CargoConfig::new();
         if let Some((name, url)) = &self.cargo_registry {
             config.add_source(name, url, true);
+            let credential_process = self
+                .manifest
+                .config
+                .as_ref()
+                .and_then(|c| c.cargo_registry_credential_process.as_deref());
+            if self.cargo_registry_token.is_some() || credential_process.is_some() {
+                config.add_registry(name, url, credential_process);
+            }
+        }
+        if let Some(manifest_config) = &self.manifest.config {
+            if let Some(yes) = manifest_config.cargo_git_fetch_with_cli {
+                config.git_fetch_with_cli(yes);
+            }
+            if let Some(retry) = manifest_config.cargo_net_retry {
+                config.net_retry(retry);
+            }
+            if let Some(yes) = manifest_config.cargo_check_revoke {
+                config.check_revoke(yes);
+            }
+        }
+        if let Some(proxy) = self
+            .manifest
+            .proxy
+            .as_ref()
+            .and_then(|p| p.https.as_ref().or(p.http.as_ref()))
+        {
+            config.http_proxy(proxy.as_str());
         }
 
         let config_toml = config.to_toml()?;
@@ -434,13 +1292,97 @@ impl<'a> InstallConfiguration<'a> {
             let config_path = self.cargo_home().join(CargoConfig::FILENAME);
             utils::write_file(config_path, &config_toml, false)?;
         }
+        if let (Some((name, _)), Some(token)) = (&self.cargo_registry, &self.cargo_registry_token) {
+            let mut credentials = CargoCredentials::load_from_dir(self.cargo_home())?;
+            credentials
+                .add_token(name, token)
+                .write_to_dir(self.cargo_home())?;
+        }
 
         self.inc_progress(3.0)
     }
 
-    /// Creates a temporary directory under `install_dir/temp`, with a certain prefix.
+    /// Log the environment variables that would be set, instead of setting them.
+    fn dry_run_env_vars(&self) -> Result<()> {
+        // Don't use `cargo_home` or `rustup_home` here as they create the directories
+        let vars = self.env_vars_(
+            &self.install_dir.join("cargo"),
+            &self.install_dir.join("rustup"),
+        )?;
+        for (key, val) in vars {
+            info!("{}", t!("dry_run_env_var", key = key, val = val));
+        }
+        self.inc_progress(2.0)
+    }
+
+    /// Log where a tool would be installed from, instead of installing it.
+    fn dry_run_tool(&self, name: &str, tool: &ToolInfo) {
+        let cargo_install = |args: &str| format!("cargo install {name} {args}");
+        let (source, path) = match tool {
+            ToolInfo::Basic(version) => (cargo_install(&format!("--version {version}")), None),
+            ToolInfo::Complex(details) => match &details.source {
+                Some(ToolSource::Version { version }) => {
+                    (cargo_install(&format!("--version {version}")), None)
+                }
+                Some(ToolSource::Git { git, .. }) => (cargo_install(&format!("--git {git}")), None),
+                Some(ToolSource::Path { path, .. }) => (path.display().to_string(), Some(path)),
+                Some(ToolSource::Url { url, .. }) => (url.to_string(), None),
+                Some(ToolSource::GitHubRelease { repo, tag, .. }) => (
+                    format!(
+                        "https://github.com/{repo}/releases ({})",
+                        tag.as_deref().unwrap_or(LATEST_TAG)
+                    ),
+                    None,
+                ),
+                Some(ToolSource::Npm { package, version }) => (
+                    format!(
+                        "npm install {package}@{}",
+                        version.as_deref().unwrap_or(LATEST_TAG)
+                    ),
+                    None,
+                ),
+                Some(ToolSource::Pip {
+                    package,
+                    version,
+                    extras,
+                }) => (
+                    format!(
+                        "pip install {}",
+                        package_manager::requirement(package, version.as_deref(), extras)
+                    ),
+                    None,
+                ),
+                Some(ToolSource::Restricted { source, .. }) => {
+                    (source.clone().unwrap_or_default(), None)
+                }
+                None => (String::new(), None),
+            },
+        };
+
+        // Only the size of local packages is known without downloading them
+        let size = path
+            .and_then(|p| p.metadata().ok())
+            .filter(|meta| meta.is_file())
+            .map(|meta| meta.len());
+        if let Some(size) = size {
+            info!(
+                "{}",
+                t!(
+                    "dry_run_tool_with_size",
+                    name = name,
+                    source = source,
+                    size = size
+                )
+            );
+        } else {
+            info!("{}", t!("dry_run_tool", name = name, source = source));
+        }
+    }
+
+    /// Creates a temporary directory under `install_dir/temp` (or the one specified
+    /// by [`with_temp_dir`](Self::with_temp_dir)), with a certain prefix.
     pub(crate) fn create_temp_dir(&self, prefix: &str) -> Result<TempDir> {
-        let root = self.temp_dir();
+        let root = self.temp_root.as_deref().unwrap_or_else(|| self.temp_dir());
 
         tempfile::Builder::new()
             .prefix(&format!("{prefix}_"))
@@ -452,10 +1394,17 @@ impl<'a> InstallConfiguration<'a> {
     ///
     /// If `maybe_file` is a path to compressed file, this will try to extract it to `dest`;
     /// otherwise this will copy that file into dest.
-    fn extract_or_copy_to(&self, maybe_file: &Path, dest: &Path) -> Result<PathBuf> {
+    fn extract_or_copy_to(
+        &self,
+        maybe_file: &Path,
+        dest: &Path,
+        strip_components: Option<usize>,
+    ) -> Result<PathBuf> {
         if let Ok(extractable) = utils::Extractable::load(maybe_file, None) {
             extractable
                 .quiet(GlobalOpts::get().quiet)
+                .max_depth(MAX_NESTED_ARCHIVE_DEPTH)
+                .strip_components(strip_components.unwrap_or_default())
                 .extract_then_skip_solo_dir(dest, Some("bin"))
         } else {
             utils::copy_into(maybe_file, dest)
@@ -466,16 +1415,24 @@ impl<'a> InstallConfiguration<'a> {
 // For updates
 impl InstallConfiguration<'_> {
     pub fn update(mut self, components: Vec<Component>) -> Result<()> {
+        let (toolchain, tools) = split_components(components);
+        self.ensure_pinned_channel(&toolchain)?;
+
         // Create a copy of the manifest which is later used for component management.
         self.manifest.write_to_dir(&self.install_dir)?;
 
-        let (toolchain, tools) = split_components(components);
         // setup env for current process
         for (key, val) in self.env_vars()? {
             std::env::set_var(key, val);
         }
         self.inc_progress(10.0)?;
 
+        // persist the pinned components for later updates
+        self.install_record
+            .pinned_components
+            .clone_from(&self.pinned_components);
+        self.install_record.write()?;
+
         // don't update toolchain if no toolchain components are selected
         if !toolchain.is_empty() {
             self.update_toolchain(&toolchain)?;
@@ -484,15 +1441,56 @@ impl InstallConfiguration<'_> {
         Ok(())
     }
 
+    /// Make sure the toolchain is not switched to another channel while some of the
+    /// `components` to update are pinned, because the new toolchain would be missing them,
+    /// as they are only installed in the current one.
+    fn ensure_pinned_channel(&self, components: &[ToolchainComponent]) -> Result<()> {
+        let Some(installed) = &self.install_record.rust else {
+            return Ok(());
+        };
+        let channel = &self.manifest.rust.channel;
+        if installed.version() == channel {
+            return Ok(());
+        }
+        let pinned = components
+            .iter()
+            .filter(|c| !c.is_profile && self.pinned_components.contains(&c.name))
+            .map(|c| c.name.as_str())
+            .collect::<Vec<_>>();
+        if !pinned.is_empty() {
+            bail!(t!(
+                "pinned_components_block_channel_switch",
+                from = installed.version(),
+                to = channel,
+                list = pinned.join(", ")
+            ));
+        }
+        Ok(())
+    }
+
     fn update_toolchain(&mut self, components: &[ToolchainComponent]) -> Result<()> {
         info!("{}", t!("update_toolchain"));
 
+        let (pinned, to_update): (Vec<_>, Vec<_>) = components
+            .iter()
+            .cloned()
+            .partition(|c| !c.is_profile && self.pinned_components.contains(&c.name));
+        if !pinned.is_empty() {
+            let list = pinned
+                .iter()
+                .map(|c| c.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            info!("{}", t!("skip_pinned_components", list = list));
+        }
+
         ToolchainInstaller::init(&*self)
             .insecure(self.insecure)
-            .update(self, components)?;
+            .update(self, &to_update)?;
 
         let record = &mut self.install_record;
-        // Add the rust info to the fingerprint.
+        // Add the rust info to the fingerprint,
+        // pinned components are kept in the record as they remain installed.
         record.add_rust_record(&self.manifest.rust.channel, components);
         // record meta info
         record.clone_toolkit_meta_from_manifest(self.manifest);
@@ -529,8 +1527,8 @@ impl InstallConfiguration<'_> {
     }
 }
 
-// TODO: Conflict resolve should take place during user interaction, not here,
-// but it's kind hard to do with how we handle CLI interaction now, figure out a way.
+// NB: Conflicts should already be resolved during user interaction,
+// this is only a backstop for non-interactive installations.
 fn reject_conflicting_tools(tools: &ToolMap) -> Result<()> {
     // use a HashSet to collect conflicting pairs to remove duplicates.
     let mut conflicts = HashSet::new();
@@ -565,16 +1563,257 @@ fn reject_conflicting_tools(tools: &ToolMap) -> Result<()> {
     Ok(())
 }
 
+/// Distribute the `total` progress weight to each item proportionally by their `sizes`.
+///
+/// Items with unknown size are treated as average sized, and if none of the sizes
+/// is known, the weight will be evenly distributed.
+fn progress_weights(sizes: &[Option<u64>], total: f32) -> Vec<f32> {
+    let known = sizes.iter().flatten().copied().collect::<Vec<_>>();
+    let known_sum: u64 = known.iter().sum();
+    if known_sum == 0 {
+        return vec![total / sizes.len() as f32; sizes.len()];
+    }
+    let average = known_sum as f32 / known.len() as f32;
+    let sizes = sizes
+        .iter()
+        .map(|s| s.map(|s| s as f32).unwrap_or(average))
+        .collect::<Vec<_>>();
+    let sum: f32 = sizes.iter().sum();
+    sizes.into_iter().map(|s| total * s / sum).collect()
+}
+
+/// Make sure the SHA-256 checksum of the package file at `path` matches `expected`,
+/// directories are skipped as there is no single checksum to compare.
+fn verify_package_checksum(name: &str, path: &Path, expected: &str) -> Result<()> {
+    if !path.is_file() {
+        return Ok(());
+    }
+    let actual = utils::calculate_sha256(path)?;
+    if !actual.eq_ignore_ascii_case(expected.trim()) {
+        bail!(t!(
+            "package_checksum_mismatch",
+            name = name,
+            path = path.display(),
+            expected = expected,
+            actual = actual
+        ));
+    }
+    Ok(())
+}
+
+/// Check the package of tool `name` found in the package directory against the checksum
+/// in manifest (if any), a mismatched one is reported so it will be downloaded instead.
+fn is_valid_local_package(name: &str, path: &Path, info: &ToolInfo) -> bool {
+    let Some(expected) = info.sha256() else {
+        return true;
+    };
+    match verify_package_checksum(name, path, expected) {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("{}", t!("local_package_mismatch", reason = e));
+            false
+        }
+    }
+}
+
+/// Path to the file storing the SHA-256 checksum of a cached download.
+fn checksum_file(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".sha256");
+    path.with_file_name(name)
+}
+
+/// Look for a file named `filename`, or the last path segment of `url`, in `dir`.
+fn find_local_package(dir: &Path, url: &Url, filename: Option<&str>) -> Option<PathBuf> {
+    filename
+        .map(ToOwned::to_owned)
+        .into_iter()
+        .chain(utils::url_filename(url))
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Find the cached download in the cache entry `dir`, which must be intact, and match
+/// the `expected` checksum from manifest if there is one.
+fn find_cached_download(dir: &Path, expected: Option<&str>) -> Option<PathBuf> {
+    std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && checksum_file(path).is_file())
+        .find(|path| is_intact_download(path, expected))
+}
+
+/// Check whether a cached download exists and matches its recorded checksum,
+/// as well as the `expected` one if there is.
+fn is_intact_download(path: &Path, expected: Option<&str>) -> bool {
+    let Ok(recorded) = std::fs::read_to_string(checksum_file(path)) else {
+        return false;
+    };
+    utils::calculate_sha256(path).is_ok_and(|actual| {
+        actual == recorded.trim()
+            && expected.map_or(true, |expected| {
+                actual.eq_ignore_ascii_case(expected.trim())
+            })
+    })
+}
+
+/// The maximum length of the install dir on Windows without long paths support,
+/// leaving enough room for the deepest files of the toolchain within the 260 characters
+/// limit (`MAX_PATH`), such as `rustup\toolchains\<name>\lib\rustlib\src\rust\library\...`.
+#[cfg(windows)]
+const MAX_INSTALL_DIR_LEN: usize = 100;
+
+/// Make sure the install dir can be used before doing any work, that is, its path
+/// is valid UTF-8, and on Windows, not too long unless long paths are enabled.
+fn validate_install_dir(install_dir: &Path) -> Result<()> {
+    if install_dir.to_str().is_none() {
+        bail!(t!(
+            "install_dir_not_utf8",
+            path = install_dir.to_string_lossy()
+        ));
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::ffi::OsStrExt;
+
+        let len = install_dir.as_os_str().encode_wide().count();
+        if len > MAX_INSTALL_DIR_LEN && !super::os::windows::long_paths_enabled() {
+            bail!(t!(
+                "install_dir_too_long",
+                path = install_dir.display(),
+                max = MAX_INSTALL_DIR_LEN
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Make sure `dir` can be created (if not exists) and written to, by creating then
+/// removing a temporary file in it, so that we can fail early with a clear message
+/// instead of an opaque OS error in the middle of installation.
+fn ensure_writable_dir(dir: &Path) -> Result<()> {
+    let probe = || -> std::io::Result<()> {
+        std::fs::create_dir_all(dir)?;
+        tempfile::Builder::new()
+            .prefix(".write-probe")
+            .tempfile_in(dir)?
+            .close()
+    };
+    let Err(e) = probe() else {
+        return Ok(());
+    };
+
+    #[cfg(windows)]
+    if is_under_program_files(dir) {
+        bail!(t!(
+            "install_dir_requires_elevation",
+            dir = dir.display(),
+            reason = e
+        ));
+    }
+    bail!(t!(
+        "install_dir_not_writable",
+        dir = dir.display(),
+        reason = e
+    ));
+}
+
+/// Return the absolute path of `dir` if it can be used as the root of temporary files,
+/// otherwise log a warning and return `None` so that the default one will be used.
+fn usable_temp_root(dir: &Path, install_dir: &Path) -> Option<PathBuf> {
+    let check = || -> Result<PathBuf> {
+        let dir = utils::to_normalized_absolute_path(dir, None)?;
+        ensure_writable_dir(&dir)?;
+        let available = fs4::available_space(&dir)?;
+        if available < MIN_TEMP_DIR_SPACE {
+            bail!(t!(
+                "temp_dir_insufficient_space",
+                available = available / 1024 / 1024,
+                required = MIN_TEMP_DIR_SPACE / 1024 / 1024
+            ));
+        }
+        Ok(dir)
+    };
+    check()
+        .inspect_err(|e| {
+            warn!(
+                "{}",
+                t!(
+                    "temp_dir_fallback",
+                    dir = dir.display(),
+                    reason = e,
+                    default = install_dir.join("temp").display()
+                )
+            );
+        })
+        .ok()
+}
+
+/// Check if `path` is located under one of the `Program Files` directories,
+/// which normally requires administrator privileges to write to.
+#[cfg(windows)]
+fn is_under_program_files(path: &Path) -> bool {
+    let path = path.to_string_lossy().to_lowercase();
+    ["ProgramFiles", "ProgramFiles(x86)", "ProgramW6432"]
+        .into_iter()
+        .filter_map(std::env::var_os)
+        .any(|pf| path.starts_with(&pf.to_string_lossy().to_lowercase()))
+}
+
 /// Get the default installation directory,
 /// which is a directory under [`home_dir`](utils::home_dir).
 pub fn default_install_dir() -> PathBuf {
     utils::home_dir().join(DEFAULT_FOLDER_NAME)
 }
 
+/// Resolve the installation directory, in the order of precedence:
+///
+/// 1. `prefix`, which is the one specified explicitly, such as the `--prefix` option,
+///    relative paths are resolved against the current directory.
+/// 2. The `RIM_INSTALL_DIR` env var, which must be an absolute path.
+/// 3. [`default_install_dir`].
+pub fn resolve_install_dir(prefix: Option<&Path>) -> Result<PathBuf> {
+    resolve_install_dir_(prefix, std::env::var_os(RIM_INSTALL_DIR))
+}
+
+fn resolve_install_dir_(prefix: Option<&Path>, from_env: Option<OsString>) -> Result<PathBuf> {
+    if let Some(path) = prefix {
+        return utils::to_normalized_absolute_path(path, None);
+    }
+    let Some(dir) = from_env.filter(|dir| !dir.is_empty()).map(PathBuf::from) else {
+        return Ok(default_install_dir());
+    };
+    if !dir.is_absolute() {
+        bail!(t!(
+            "install_dir_env_not_absolute",
+            var = RIM_INSTALL_DIR,
+            path = dir.display()
+        ));
+    }
+    if utils::is_root_dir(&dir) {
+        bail!(t!("notify_root_dir"));
+    }
+    utils::to_normalized_absolute_path(dir, None)
+}
+
+/// Merge the configured `no_proxy` entries with the ones currently set in `no_proxy`
+/// and `NO_PROXY` env vars, where the configured ones come first and duplicates are removed.
+fn merge_no_proxy(configured: &str) -> String {
+    let current = ["no_proxy", "NO_PROXY"].map(|key| std::env::var(key).unwrap_or_default());
+    let entries = std::iter::once(configured)
+        .chain(current.iter().map(String::as_str))
+        .flat_map(|list| list.split(','))
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .collect::<IndexSet<_>>();
+    entries.into_iter().collect::<Vec<_>>().join(",")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::get_toolkit_manifest;
+    use crate::core::{get_toolkit_manifest, ToolkitManifestExt};
+    use rim_common::types::{ToolInfoDetails, ToolKind};
 
     #[tokio::test]
     async fn init_install_config() {
@@ -612,6 +1851,262 @@ version = "1.0.0"
         assert!(config.insecure_flag());
     }
 
+    #[test]
+    fn rollback_created_files() {
+        let raw = r#"
+[rust]
+version = "1.0.0"
+"#;
+        let manifest = ToolkitManifest::from_str(raw).unwrap();
+        let root = tempfile::tempdir().unwrap();
+        let install_dir = root.path().join("rust");
+
+        let mut config = InstallConfiguration::new(&install_dir, &manifest).unwrap();
+        let file = install_dir.join("some_file");
+        utils::write_file(&file, "", false).unwrap();
+        config
+            .journal
+            .record(JournalEntry::CreatedFile(file.clone()));
+        assert!(file.is_file());
+
+        config.journal.rollback(&install_dir);
+        assert!(!file.exists());
+        assert!(!install_dir.exists());
+        assert!(config.journal.entries.is_empty());
+    }
+
+    #[test]
+    fn dry_run_changes_nothing() {
+        let raw = r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-pc-windows-msvc]
+a = { url = "https://example.com/a.zip" }
+[tools.target.x86_64-pc-windows-gnu]
+a = { url = "https://example.com/a.zip" }
+[tools.target.x86_64-unknown-linux-gnu]
+a = { url = "https://example.com/a.zip" }
+[tools.target.aarch64-unknown-linux-gnu]
+a = { url = "https://example.com/a.zip" }
+"#;
+        let manifest = ToolkitManifest::from_str(raw).unwrap();
+        let root = tempfile::tempdir().unwrap();
+        let install_dir = root.path().join("rust");
+
+        let components = manifest.current_target_components(false).unwrap();
+        let config = InstallConfiguration::new(&install_dir, &manifest)
+            .unwrap()
+            .dry_run(true);
+        config.install(components).unwrap();
+
+        assert!(!install_dir.exists());
+    }
+
+    #[test]
+    fn remove_obsoleted_tool_in_shared_dir() {
+        let raw = r#"
+[rust]
+version = "1.0.0"
+"#;
+        let manifest = ToolkitManifest::from_str(raw).unwrap();
+        let install_dir = tempfile::tempdir().unwrap();
+        let mut config = InstallConfiguration::new(install_dir.path(), &manifest).unwrap();
+
+        let group_dir = config.tools_dir().join("group");
+        for name in ["a", "b"] {
+            let data_dir = group_dir.join(name).join("data");
+            utils::ensure_dir(&data_dir).unwrap();
+            utils::write_file(data_dir.join("file"), name, false).unwrap();
+            config.install_record.add_tool_record(
+                name,
+                ToolRecord::new(ToolKind::RuleSet).with_paths(vec![data_dir]),
+            );
+        }
+
+        let new_tool = ToolInfo::new_detailed(ToolInfoDetails {
+            obsoletes: vec!["a".into()],
+            ..Default::default()
+        });
+        config.remove_obsoleted_tools(&new_tool).unwrap();
+
+        assert!(!group_dir.join("a").exists());
+        assert!(group_dir.join("b").join("data").join("file").is_file());
+        assert!(!config.install_record.tools.contains_key("a"));
+    }
+
+    #[test]
+    fn cancel_before_installing_tools() {
+        let raw = r#"
+[rust]
+version = "1.0.0"
+"#;
+        let manifest = ToolkitManifest::from_str(raw).unwrap();
+        let root = tempfile::tempdir().unwrap();
+        let tool_dir = root.path().join("a");
+        utils::ensure_dir(&tool_dir).unwrap();
+        let tools: ToolMap =
+            toml::from_str(&format!("a = {{ path = '{}' }}", tool_dir.display())).unwrap();
+
+        let token = CancellationToken::new();
+        let install_dir = root.path().join("rust");
+        let mut config = InstallConfiguration::new(&install_dir, &manifest)
+            .unwrap()
+            .with_cancel_token(token.clone());
+        token.cancel();
+
+        assert!(config.install_tools(&tools).is_err());
+        assert!(!config.install_record.tools.contains_key("a"));
+        assert!(install_dir.join(InstallationRecord::FILENAME).is_file());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn record_tool_with_failed_hook() {
+        let raw = r#"
+[rust]
+version = "1.0.0"
+"#;
+        let manifest = ToolkitManifest::from_str(raw).unwrap();
+        let root = tempfile::tempdir().unwrap();
+        let tool_dir = root.path().join("a");
+        utils::ensure_dir(&tool_dir).unwrap();
+        let tools: ToolMap = toml::from_str(&format!(
+            "a = {{ path = '{}', post-install = 'exit 1' }}",
+            tool_dir.display()
+        ))
+        .unwrap();
+
+        let install_dir = root.path().join("rust");
+        let mut config = InstallConfiguration::new(&install_dir, &manifest)
+            .unwrap()
+            .allow_hooks(true);
+        assert!(config.install_tool("a", &tools["a"]).is_err());
+        // the tool was installed, so it should be recorded to be uninstalled later
+        assert!(config.install_record.tools.contains_key("a"));
+    }
+
+    #[test]
+    fn refuse_switching_channel_with_pinned_components() {
+        let raw = r#"
+[rust]
+version = "1.1.0"
+"#;
+        let manifest = ToolkitManifest::from_str(raw).unwrap();
+        let root = tempfile::tempdir().unwrap();
+        let mut config = InstallConfiguration::new(root.path(), &manifest)
+            .unwrap()
+            .pin_components(&["clippy"]);
+        let components = [
+            ToolchainComponent::new("minimal").is_profile(true),
+            ToolchainComponent::new("clippy"),
+        ];
+        // nothing installed yet, there is nothing to keep
+        assert!(config.ensure_pinned_channel(&components).is_ok());
+
+        config.install_record.add_rust_record("1.1.0", &components);
+        assert!(config.ensure_pinned_channel(&components).is_ok());
+
+        config.install_record.add_rust_record("1.0.0", &components);
+        assert!(config.ensure_pinned_channel(&components).is_err());
+        assert!(config.ensure_pinned_channel(&components[..1]).is_ok());
+
+        let config = config.unpin_components(&["clippy"]);
+        assert!(config.pinned_components.is_empty());
+        assert!(config.ensure_pinned_channel(&components).is_ok());
+    }
+
+    #[test]
+    fn ask_handler_when_tool_failed() {
+        let raw = r#"
+[rust]
+version = "1.0.0"
+"#;
+        let manifest = ToolkitManifest::from_str(raw).unwrap();
+        let root = tempfile::tempdir().unwrap();
+        let tools: ToolMap = toml::from_str(&format!(
+            "a = {{ path = '{}' }}",
+            root.path().join("missing").display()
+        ))
+        .unwrap();
+        let install_dir = root.path().join("rust");
+
+        let asked = std::cell::Cell::new(0);
+        let retry_then_skip = |_: &str, can_retry: bool| {
+            asked.set(asked.get() + 1);
+            assert!(can_retry);
+            Ok(if asked.get() == 1 {
+                ToolFailureAction::Retry
+            } else {
+                ToolFailureAction::Skip
+            })
+        };
+        let mut config = InstallConfiguration::new(&install_dir, &manifest)
+            .unwrap()
+            .with_tool_failure_handler(Some(&retry_then_skip));
+        assert!(config.install_tool_or_ask("a", &tools["a"]).is_ok());
+        assert_eq!(asked.get(), 2);
+        // the retried failure is not reported, but the skipped one is
+        assert_eq!(config.errors.tool_errors.len(), 1);
+
+        let abort = |_: &str, _: bool| Ok(ToolFailureAction::Abort);
+        let mut config = InstallConfiguration::new(&install_dir, &manifest)
+            .unwrap()
+            .with_tool_failure_handler(Some(&abort));
+        assert!(config.install_tool_or_ask("a", &tools["a"]).is_err());
+    }
+
+    #[test]
+    fn fatal_installation_errors() {
+        let mut errors = InstallationErrors::default();
+        assert!(!errors.is_fatal());
+
+        errors.tool_errors.push(("a".into(), "error".into()));
+        assert!(!errors.is_fatal());
+
+        errors.rust_error = Some("error".into());
+        assert!(errors.is_fatal());
+    }
+
+    #[test]
+    fn report_without_errors() {
+        let tools: ToolMap = toml::from_str(r#"a = "0.1.0""#).unwrap();
+        let mut record = InstallationRecord::default();
+        record.add_tool_record("a", ToolRecord::default());
+
+        let report = InstallationErrors::default().to_report(&tools, &record, Duration::ZERO);
+        assert!(report.success);
+        assert_eq!(report.toolchain, InstallStatus::Skipped);
+        assert_eq!(report.tools[0].status, InstallStatus::Installed);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&report).unwrap()).unwrap();
+        assert_eq!(json["errors"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn report_with_errors() {
+        let tools: ToolMap = toml::from_str(
+            r#"
+a = "0.1.0"
+b = "0.1.0"
+"#,
+        )
+        .unwrap();
+        let errors = InstallationErrors {
+            tool_errors: vec![("a".into(), "failed to install a".into())],
+            rust_error: Some("failed to install rust".into()),
+            ..Default::default()
+        };
+
+        let report = errors.to_report(&tools, &InstallationRecord::default(), Duration::ZERO);
+        assert!(!report.success);
+        assert_eq!(report.toolchain, InstallStatus::Failed);
+        assert_eq!(report.tools[0].status, InstallStatus::Failed);
+        assert_eq!(report.tools[1].status, InstallStatus::Skipped);
+        assert_eq!(report.errors.len(), 2);
+    }
+
     #[test]
     fn detect_package_conflicts() {
         let raw = r#"
@@ -628,6 +2123,41 @@ c = { version = "0.1.0", conflicts = ["d", "a"] }
         println!("{error}");
     }
 
+    #[test]
+    fn env_vars_are_unique() {
+        let raw = r#"
+[rust]
+version = "1.0.0"
+
+[proxy]
+http = "http://127.0.0.1:7890"
+https = "http://127.0.0.1:7890"
+no_proxy = "localhost"
+"#;
+
+        let manifest = ToolkitManifest::from_str(raw).unwrap();
+        let install_dir = tempfile::tempdir().unwrap();
+        let install_cfg = InstallConfiguration::new(install_dir.path(), &manifest).unwrap();
+
+        let keys = install_cfg
+            .env_vars()
+            .unwrap()
+            .into_keys()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            keys,
+            [
+                RUSTUP_DIST_SERVER,
+                RUSTUP_UPDATE_ROOT,
+                CARGO_HOME,
+                RUSTUP_HOME,
+                "http_proxy",
+                "https_proxy",
+                "no_proxy"
+            ]
+        );
+    }
+
     #[test]
     fn no_proxy_env_var() {
         let raw = r#"
@@ -647,36 +2177,239 @@ no_proxy = "localhost,.example.com,.foo.com"
         // with mocked env, that we don't need to set no_proxy var here and potentially
         // mess up other concurrent test cases.
         let no_proxy_backup = std::env::var("no_proxy");
+        let upper_no_proxy_backup = std::env::var("NO_PROXY");
         std::env::remove_var("no_proxy");
+        std::env::remove_var("NO_PROXY");
 
         let env_vars = install_cfg.env_vars().unwrap();
         let new_no_proxy_var = env_vars.get("no_proxy").unwrap();
-
-        #[cfg(windows)]
         assert_eq!(new_no_proxy_var, "localhost,.example.com,.foo.com");
-        #[cfg(unix)]
+
+        // existing entries are appended, without duplicating the configured ones
+        std::env::set_var("no_proxy", ".bar.com, localhost,baz.com");
+        let env_vars = install_cfg.env_vars().unwrap();
+        let new_no_proxy_var = env_vars.get("no_proxy").unwrap();
         assert_eq!(
             new_no_proxy_var,
-            "localhost,.example.com,.foo.com,$no_proxy"
+            "localhost,.example.com,.foo.com,.bar.com,baz.com"
         );
 
-        std::env::set_var("no_proxy", ".bar.com,baz.com");
+        std::env::remove_var("no_proxy");
+        std::env::set_var("NO_PROXY", ".foo.com,qux.com");
         let env_vars = install_cfg.env_vars().unwrap();
         let new_no_proxy_var = env_vars.get("no_proxy").unwrap();
+        assert_eq!(new_no_proxy_var, "localhost,.example.com,.foo.com,qux.com");
 
-        #[cfg(windows)]
+        std::env::remove_var("NO_PROXY");
+        if let Ok(bck) = upper_no_proxy_backup {
+            std::env::set_var("NO_PROXY", bck);
+        }
+        if let Ok(bck) = no_proxy_backup {
+            std::env::set_var("no_proxy", bck);
+        }
+    }
+
+    #[test]
+    fn progress_weighted_by_size() {
+        // the one without size is treated as average sized
+        let weights = progress_weights(&[Some(300), Some(100), None], 60.0);
+        assert_eq!(weights, vec![30.0, 10.0, 20.0]);
+
+        let weights = progress_weights(&[None, None, None, None], 30.0);
+        assert_eq!(weights, vec![7.5; 4]);
+
+        let weights = progress_weights(&[Some(0), None], 30.0);
+        assert_eq!(weights, vec![15.0; 2]);
+    }
+
+    #[test]
+    fn probe_writable_dir() {
+        let root = tempfile::tempdir().unwrap();
+        let new_dir = root.path().join("a").join("b");
+        ensure_writable_dir(&new_dir).unwrap();
+        assert!(new_dir.is_dir());
+        assert_eq!(std::fs::read_dir(&new_dir).unwrap().count(), 0);
+
+        // a directory can never be created under a regular file
+        let file = root.path().join("file");
+        utils::write_file(&file, "", false).unwrap();
+        assert!(ensure_writable_dir(&file.join("sub")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn reject_non_utf8_install_dir() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let manifest = ToolkitManifest::from_str("[rust]\nversion = \"1.0.0\"").unwrap();
+        let root = tempfile::tempdir().unwrap();
+        let install_dir = root.path().join(OsStr::from_bytes(b"rust\xff"));
+
+        let err = InstallConfiguration::new(&install_dir, &manifest)
+            .err()
+            .expect("non UTF-8 path should be rejected");
+        assert!(err.to_string().contains("rust\u{FFFD}"));
+        assert!(!install_dir.exists());
+    }
+
+    #[test]
+    fn fallback_to_default_temp_dir() {
+        let manifest = ToolkitManifest::from_str("[rust]\nversion = \"1.0.0\"").unwrap();
+        let install_dir = tempfile::tempdir().unwrap();
+        let file = install_dir.path().join("file");
+        utils::write_file(&file, "", false).unwrap();
+
+        let config = InstallConfiguration::new(install_dir.path(), &manifest)
+            .unwrap()
+            .with_temp_dir(Some(&file.join("temp")));
+        assert!(config.temp_root.is_none());
+    }
+
+    #[test]
+    fn override_toolchain_profile() {
+        let raw = "[rust]\nversion = \"1.0.0\"\nprofile = \"complete\"";
+        let manifest = ToolkitManifest::from_str(raw).unwrap();
+        let install_dir = tempfile::tempdir().unwrap();
+
+        let config = InstallConfiguration::new(install_dir.path(), &manifest).unwrap();
+        assert_eq!(config.toolchain_profile(), Some("complete"));
+        let config = config.with_toolchain_profile(None).unwrap();
+        assert_eq!(config.toolchain_profile(), Some("complete"));
+        let config = config.with_toolchain_profile(Some("minimal")).unwrap();
+        assert_eq!(config.toolchain_profile(), Some("minimal"));
+        assert!(config.with_toolchain_profile(Some("tiny")).is_err());
+    }
+
+    #[test]
+    fn verify_cached_download() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pkg.tar.gz");
+        assert_eq!(checksum_file(&path), dir.path().join("pkg.tar.gz.sha256"));
+        assert!(!is_intact_download(&path, None));
+
+        utils::write_file(&path, "hello", false).unwrap();
+        // no checksum recorded
+        assert!(!is_intact_download(&path, None));
+
+        let checksum = utils::calculate_sha256(&path).unwrap();
+        utils::write_file(checksum_file(&path), &checksum, false).unwrap();
+        assert!(is_intact_download(&path, None));
+        assert!(is_intact_download(&path, Some(&checksum.to_uppercase())));
         assert_eq!(
-            new_no_proxy_var,
-            "localhost,.example.com,.foo.com,.bar.com,baz.com"
+            find_cached_download(dir.path(), Some(&checksum)),
+            Some(path.clone())
         );
-        #[cfg(unix)]
+        // does not match the one in manifest
+        assert!(!is_intact_download(
+            &path,
+            Some(&utils::sha256_hex("world"))
+        ));
         assert_eq!(
-            new_no_proxy_var,
-            "localhost,.example.com,.foo.com,$no_proxy"
+            find_cached_download(dir.path(), Some(&utils::sha256_hex("world"))),
+            None
         );
 
-        if let Ok(bck) = no_proxy_backup {
-            std::env::set_var("no_proxy", bck);
-        }
+        // corrupted
+        utils::write_file(&path, "hell", false).unwrap();
+        assert!(!is_intact_download(&path, None));
+        assert_eq!(find_cached_download(dir.path(), None), None);
+    }
+
+    #[test]
+    fn find_package_in_local_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let url: Url = "https://example.com/dl/tool-1.0.tar.gz".parse().unwrap();
+        assert_eq!(find_local_package(dir.path(), &url, None), None);
+
+        let by_url = dir.path().join("tool-1.0.tar.gz");
+        utils::write_file(&by_url, "", false).unwrap();
+        assert_eq!(
+            find_local_package(dir.path(), &url, None),
+            Some(by_url.clone())
+        );
+        // fallback to url basename if the file in manifest does not exist
+        assert_eq!(
+            find_local_package(dir.path(), &url, Some("tool.zip")),
+            Some(by_url)
+        );
+
+        let by_filename = dir.path().join("tool.zip");
+        utils::write_file(&by_filename, "", false).unwrap();
+        assert_eq!(
+            find_local_package(dir.path(), &url, Some("tool.zip")),
+            Some(by_filename)
+        );
+
+        let dir_url: Url = "https://example.com/dl/".parse().unwrap();
+        assert_eq!(find_local_package(dir.path(), &dir_url, None), None);
+
+        // percent-encoded url basename
+        let encoded_url: Url = "https://example.com/dl/my%20tool.tar.gz".parse().unwrap();
+        let decoded = dir.path().join("my tool.tar.gz");
+        utils::write_file(&decoded, "", false).unwrap();
+        assert_eq!(
+            find_local_package(dir.path(), &encoded_url, None),
+            Some(decoded)
+        );
+    }
+
+    #[test]
+    fn install_dir_precedence() {
+        let cwd = std::env::current_dir().unwrap();
+        let env_dir = OsString::from(if cfg!(windows) {
+            "C:\\rust-from-env"
+        } else {
+            "/opt/rust-from-env"
+        });
+        let from_env = Some(env_dir.clone());
+
+        // flag > env > default
+        let flag = Path::new("rust-from-flag");
+        let dir = resolve_install_dir_(Some(flag), from_env.clone()).unwrap();
+        assert_eq!(dir, cwd.join("rust-from-flag"));
+        let dir = resolve_install_dir_(None, from_env).unwrap();
+        assert_eq!(dir, PathBuf::from(env_dir));
+        let dir = resolve_install_dir_(None, None).unwrap();
+        assert_eq!(dir, default_install_dir());
+        let dir = resolve_install_dir_(None, Some(OsString::new())).unwrap();
+        assert_eq!(dir, default_install_dir());
+
+        // invalid values from env
+        assert!(resolve_install_dir_(None, Some("relative/rust".into())).is_err());
+        let root = if cfg!(windows) { "C:\\" } else { "/" };
+        assert!(resolve_install_dir_(None, Some(root.into())).is_err());
+    }
+
+    #[test]
+    fn verify_vendored_package() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pkg.tar.gz");
+        utils::write_file(&path, "hello", false).unwrap();
+        let checksum = utils::calculate_sha256(&path).unwrap();
+
+        verify_package_checksum("pkg", &path, &checksum).unwrap();
+        verify_package_checksum("pkg", &path, &checksum.to_uppercase()).unwrap();
+        assert!(verify_package_checksum("pkg", &path, "abc").is_err());
+        // directories have no checksum to verify
+        verify_package_checksum("pkg", dir.path(), "abc").unwrap();
+
+        // packages found in the package directory are checked against the manifest
+        let raw = format!(
+            r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-unknown-linux-gnu]
+a = {{ url = "https://example.com/a.tar.gz", sha256 = "{checksum}" }}
+b = {{ url = "https://example.com/b.tar.gz", sha256 = "abc" }}
+c = {{ url = "https://example.com/c.tar.gz" }}
+"#
+        );
+        let manifest = ToolkitManifest::from_str(&raw).unwrap();
+        let tools = &manifest.tools.target["x86_64-unknown-linux-gnu"];
+        assert!(is_valid_local_package("a", &path, &tools["a"]));
+        assert!(!is_valid_local_package("b", &path, &tools["b"]));
+        assert!(is_valid_local_package("c", &path, &tools["c"]));
     }
 }
@@ -7,20 +7,27 @@ pub mod components;
 mod custom_instructions;
 mod dependency_handler;
 pub(crate) mod directories;
+mod distro_package;
+pub(crate) mod doctor;
 #[cfg(windows)]
 pub(crate) mod env_backup;
+mod github_release;
+mod hooks;
 pub mod install;
 mod locales;
 pub(crate) mod os;
+mod package_manager;
 pub(crate) mod parser;
 mod path_ext;
 pub(crate) mod rustup;
+mod script;
 pub mod toolkit;
 mod toolkit_manifest_ext;
 pub(crate) mod tools;
 pub mod try_it;
 pub(crate) mod uninstall;
 pub mod update;
+pub(crate) mod verify;
 
 // re-exports
 pub use locales::Language;
@@ -57,6 +64,30 @@ declare_env_vars!(
 static GLOBAL_OPTS: Mutex<Option<GlobalOpts>> = Mutex::new(None);
 static APP_INFO: OnceLock<AppInfo> = OnceLock::new();
 static INSTALL_DIR_ONCE: OnceLock<PathBuf> = OnceLock::new();
+static TARGET_OVERRIDE: OnceLock<String> = OnceLock::new();
+
+/// The target triple used to select tools and the default host of the toolchain,
+/// which is the build target of this program unless overridden by [`override_target`].
+pub(crate) fn target_triple() -> &'static str {
+    TARGET_OVERRIDE
+        .get()
+        .map(String::as_str)
+        .unwrap_or_else(host_triple)
+}
+
+/// The build target of this program, which is never overridden, because programs
+/// that run on this machine (such as `rustup-init`) must be built for it.
+pub(crate) fn host_triple() -> &'static str {
+    env!("TARGET")
+}
+
+/// Use another target triple instead of the build target of this program,
+/// this can only be done once, further calls will be ignored.
+pub(crate) fn override_target(target: &str) {
+    if TARGET_OVERRIDE.set(target.to_string()).is_err() {
+        debug!("target was already overridden as '{}'", target_triple());
+    }
+}
 
 pub(crate) fn default_rustup_dist_server() -> &'static Url {
     build_config().rustup_dist_server(env!("EDITION"))
@@ -94,6 +125,8 @@ pub(crate) struct GlobalOpts {
     pub(crate) verbose: bool,
     pub(crate) quiet: bool,
     pub(crate) yes_to_all: bool,
+    /// Disable ANSI styling of the output.
+    pub(crate) no_color: bool,
     no_modify_env: bool,
     no_modify_path: bool,
 }
@@ -105,6 +138,7 @@ impl GlobalOpts {
         verbose: bool,
         quiet: bool,
         yes: bool,
+        no_color: bool,
         no_modify_env: bool,
         no_modify_path: bool,
     ) {
@@ -112,6 +146,7 @@ impl GlobalOpts {
             verbose,
             quiet,
             yes_to_all: yes,
+            no_color,
             no_modify_env,
             no_modify_path,
         };
@@ -298,7 +333,7 @@ mod tests {
 
     #[test]
     fn global_opts_set_and_get() {
-        GlobalOpts::set(true, false, true, true, false);
+        GlobalOpts::set(true, false, true, false, true, false);
 
         let opts = GlobalOpts::get();
         assert_eq!(opts.verbose, true);
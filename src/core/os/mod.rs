@@ -10,21 +10,24 @@ pub(crate) mod unix;
 pub(crate) mod windows;
 
 use anyhow::Result;
-use std::path::Path;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
 
 /// Add a given path to OS's `PATH` variable.
 ///
 /// Note this will do nothing if either
 /// [`no_modify_path`](crate::core::GlobalOpts::no_modify_path) or
 /// [`no_modify_env`](crate::core::GlobalOpts::no_modify_env) return `true`.
-pub(crate) fn add_to_path(path: &Path) -> Result<()> {
+///
+/// Returns `true` if the persistent `PATH` configuration was changed.
+pub(crate) fn add_to_path(path: &Path) -> Result<bool> {
     #[cfg(windows)]
-    windows::add_to_path(path)?;
+    let changed = windows::add_to_path(path)?;
 
     #[cfg(unix)]
-    unix::add_to_path(path)?;
+    let changed = unix::add_to_path(path)?;
 
-    Ok(())
+    Ok(changed)
 }
 
 /// Remove a given path from OS's `PATH` variable.
@@ -41,3 +44,65 @@ pub(crate) fn remove_from_path(path: &Path) -> Result<()> {
 
     Ok(())
 }
+
+/// Compare two paths, resolving symlinks and relative components if they exist.
+pub(crate) fn is_same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Find every other `programs` in the current `PATH` that does not live in `expected_dir`.
+///
+/// Such programs could shadow the ones in `expected_dir` when running them
+/// by name, depending on the order of `PATH` entries.
+pub(crate) fn conflicting_path_entries(programs: &[&str], expected_dir: &Path) -> Vec<PathBuf> {
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    conflicting_path_entries_(&path, programs, expected_dir)
+}
+
+fn conflicting_path_entries_(
+    path_var: &OsStr,
+    programs: &[&str],
+    expected_dir: &Path,
+) -> Vec<PathBuf> {
+    std::env::split_paths(path_var)
+        .filter(|dir| !dir.as_os_str().is_empty() && !is_same_path(dir, expected_dir))
+        .flat_map(|dir| {
+            programs
+                .iter()
+                .map(move |prog| dir.join(exe!(prog)))
+                .filter(|p| p.is_file())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rim_common::utils;
+
+    #[test]
+    fn find_conflicting_path_entries() {
+        let root = tempfile::tempdir().unwrap();
+        let expected = root.path().join("expected");
+        let other = root.path().join("other");
+        let empty = root.path().join("empty");
+        for dir in [&expected, &other, &empty] {
+            utils::ensure_dir(dir).unwrap();
+        }
+        for dir in [&expected, &other] {
+            utils::write_file(dir.join(exe!("cargo")), "", false).unwrap();
+        }
+        utils::write_file(other.join(exe!("rustc")), "", false).unwrap();
+
+        let path_var = std::env::join_paths([&other, &empty, &expected]).unwrap();
+        let conflicts =
+            conflicting_path_entries_(&path_var, &["cargo", "rustc", "rustup"], &expected);
+        assert_eq!(
+            conflicts,
+            vec![other.join(exe!("cargo")), other.join(exe!("rustc"))]
+        );
+    }
+}
@@ -1,11 +1,13 @@
-use std::collections::HashMap;
-use std::{env, path::Path};
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
 
 use crate::core::install::{EnvConfig, InstallConfiguration};
-use crate::core::uninstall::{UninstallConfiguration, Uninstallation};
+use crate::core::uninstall::{Removal, UninstallConfiguration, Uninstallation};
 use crate::core::GlobalOpts;
 use anyhow::Result;
-use indexmap::IndexSet;
+use indexmap::{IndexMap, IndexSet};
 use rim_common::utils;
 
 impl EnvConfig for InstallConfiguration<'_> {
@@ -14,8 +16,11 @@ impl EnvConfig for InstallConfiguration<'_> {
     // to invoke `$CARGO_HOME/env.{sh|fish}`. Sadly we'll have to re-implement a similar procedure here,
     // because rustup will not write those file if a user has choose to pass `--no-modify-path`.
     // Which is not ideal for env vars such as `RUSTUP_DIST_SERVER`.
-    fn config_env_vars(&self) -> Result<()> {
+    fn config_env_vars(&self) -> Result<bool> {
         let vars_raw = self.env_vars()?;
+        // Rolling back removes the whole config section, so only report a change
+        // when no shell profile had one before.
+        let changed = !GlobalOpts::get().no_modify_env() && rcs_with_config_section().is_empty();
 
         if !GlobalOpts::get().no_modify_env() {
             info!("{}", t!("install_env_config"));
@@ -55,7 +60,8 @@ impl EnvConfig for InstallConfiguration<'_> {
             env::set_var(key, val);
         }
 
-        self.inc_progress(2.0)
+        self.inc_progress(2.0)?;
+        Ok(changed)
     }
 }
 
@@ -97,6 +103,24 @@ impl Uninstallation for UninstallConfiguration<'_> {
         }
         Ok(())
     }
+
+    fn rustup_env_vars_removals(&self) -> Result<Vec<Removal>> {
+        if GlobalOpts::get().no_modify_env() {
+            return Ok(vec![]);
+        }
+        let removals = rcs_with_config_section()
+            .into_iter()
+            .map(|file| Removal::ConfigSection {
+                file,
+                section: shell::RC_FILE_SECTION_START.to_string(),
+            })
+            .collect();
+        Ok(removals)
+    }
+
+    fn self_removals(&self) -> Result<Vec<Removal>> {
+        Ok(vec![Removal::Path(self.install_dir.clone())])
+    }
 }
 
 fn remove_section_or_warn_<F>(path: &Path, to_remove_sum: &str, mut operation: F)
@@ -152,6 +176,25 @@ fn remove_all_config_section() {
     }
 }
 
+/// Get the shell profiles that contain our dedicated configuration section,
+/// which are the ones to be modified by [`remove_all_config_section`].
+fn rcs_with_config_section() -> Vec<PathBuf> {
+    let mut rcs = vec![];
+    for sh in shell::get_available_shells() {
+        for rc in sh.rcfiles().into_iter().filter(|rc| rc.is_file()) {
+            let has_section = utils::read_to_string("rc", &rc).is_ok_and(|content| {
+                content
+                    .lines()
+                    .any(|line| line == shell::RC_FILE_SECTION_START)
+            });
+            if has_section && !rcs.contains(&rc) {
+                rcs.push(rc);
+            }
+        }
+    }
+    rcs
+}
+
 fn remove_sub_string_between(input: String, start: &str, end: &str) -> Option<String> {
     let start_pos = input.lines().position(|line| line == start)?;
     let end_pos = input.lines().position(|line| line == end)?;
@@ -190,7 +233,8 @@ fn get_sub_string_between(input: &str, start: &str, end: &str) -> Option<String>
     Some(result)
 }
 
-fn modify_path(path: &Path, remove: bool) -> Result<()> {
+/// Returns `true` if any shell profile was changed.
+fn modify_path(path: &Path, remove: bool) -> Result<bool> {
     let path_str = utils::path_to_str(path)?;
 
     // Apply the new path to current process
@@ -206,9 +250,11 @@ fn modify_path(path: &Path, remove: bool) -> Result<()> {
     }
 
     if GlobalOpts::get().no_modify_path() {
-        return Ok(());
+        return Ok(false);
     }
 
+    let mut changed = false;
+
     // Add the new path to bash profiles
     for sh in shell::get_available_shells() {
         for rc in sh.update_rcs().iter().filter(|rc| rc.is_file()) {
@@ -238,22 +284,27 @@ fn modify_path(path: &Path, remove: bool) -> Result<()> {
                 warn!("{warn}");
                 continue;
             };
+            if new_content == rc_content {
+                continue;
+            }
             if let Err(e) = utils::write_file(rc, &new_content, false) {
                 warn!(
                     "failed to update PATH in shell profile '{}': {e}",
                     rc.display()
                 );
+            } else {
+                changed = true;
             }
         }
     }
 
-    Ok(())
+    Ok(changed)
 }
 
 fn rc_content_with_env_vars(
     sh: &dyn shell::UnixShell,
     old_content: &str,
-    vars: &HashMap<&'static str, String>,
+    vars: &IndexMap<&'static str, String>,
 ) -> String {
     // converts env vars such as [(KEY, value), (KEY2, value2)] to ["export KEY='value'"", "export KEY2='value2'"]
     let vars_as_exports = vars.iter().map(|(k, v)| sh.to_env_var_string(k, v));
@@ -291,20 +342,24 @@ fn rc_content_with_env_vars(
 ///
 /// This prevents a false-positive where `/path/to/bin` would match `/path/to/bin2`.
 fn path_str_in_export(path_str: &str, export_line: &str) -> bool {
-    // Extract the value part after the first `=` or after `PATH ` (fish style)
+    // Extract the value part after the first `=`, after `PATH ` (fish style),
+    // or after `fish_add_path`.
     let value_part = if let Some(idx) = export_line.find('=') {
         &export_line[idx + 1..]
     } else if let Some(idx) = export_line.find("PATH ") {
         &export_line[idx + 5..]
+    } else if let Some(rest) = export_line.strip_prefix(shell::FISH_ADD_PATH) {
+        rest
     } else {
         return false;
     };
     // Strip surrounding quotes
     let value_part = value_part.trim_matches('"').trim_matches('\'');
-    // Split by `:` (bash) or ` ` (fish) and check for exact match
-    value_part
-        .split([':', ' '])
-        .any(|segment| segment.trim_matches('"').trim_matches('\'') == path_str)
+    // Split by `:` (bash) or ` ` (fish, nushell) and check for exact match,
+    // also strip the brackets of nushell's list.
+    value_part.split([':', ' ']).any(|segment| {
+        segment.trim_matches(|c| matches!(c, '"' | '\'' | '[' | ']' | '(' | ')')) == path_str
+    })
 }
 
 fn rc_content_with_path(
@@ -319,7 +374,9 @@ fn rc_content_with_path(
         shell::RC_FILE_SECTION_END,
     ) {
         // Find the line that is setting path variable
-        let maybe_setting_path = existing_configs.lines().find(|line| line.contains("PATH"));
+        let maybe_setting_path = existing_configs
+            .lines()
+            .find(|line| sh.is_path_command(line));
 
         // Check if the path was already exported using exact boundary matching.
         if let Some(path_export) = maybe_setting_path {
@@ -354,12 +411,12 @@ fn rc_content_with_path(
     }
 }
 
-pub(super) fn add_to_path(path: &Path) -> Result<()> {
+pub(super) fn add_to_path(path: &Path) -> Result<bool> {
     modify_path(path, false)
 }
 
 pub(super) fn remove_from_path(path: &Path) -> Result<()> {
-    modify_path(path, true)
+    modify_path(path, true).map(|_| ())
 }
 
 /// Returns a string that looks like `source [rc]` where `[rc]` is a path
@@ -386,6 +443,10 @@ mod shell {
 
     pub(super) const RC_FILE_SECTION_START: &str = "# ===== rustup config section START =====";
     pub(super) const RC_FILE_SECTION_END: &str = "# ===== rustup config section END =====";
+    /// The fish command to prepend paths to `PATH` for current session.
+    pub(super) const FISH_ADD_PATH: &str = "fish_add_path -g";
+    /// The prefix of nushell's command to prepend a list of paths to `PATH`.
+    const NU_PREPEND_PATH: &str = "$env.PATH = ($env.PATH | split row (char esep) | prepend [";
 
     pub(super) trait UnixShell {
         // Detects if a shell "exists". Users have multiple shells, so an "eager"
@@ -404,6 +465,11 @@ mod shell {
             format!("export {key}={val}")
         }
 
+        /// Check if the given line in config section is the one that updates `PATH`.
+        fn is_path_command(&self, line: &str) -> bool {
+            line.contains("PATH")
+        }
+
         /// Wraps given content between a pair of identifiers.
         ///
         /// Such identifiers are comments defined as [`RC_FILE_SECTION_START`] and [`RC_FILE_SECTION_END`].
@@ -416,7 +482,7 @@ mod shell {
         }
 
         /// Update the PATH export command, which should be `export PATH="..."` on bash like shells,
-        /// and `set -Ux PATH ...` on fish shell.
+        /// `fish_add_path -g ...` on fish shell, and `$env.PATH = (...)` on nushell.
         ///
         /// If the remove flag is set to `true`, this will attempt to return the `old_command` but without `path_str`.
         fn command_to_update_path(
@@ -459,6 +525,26 @@ mod shell {
     pub(super) struct Bash;
     pub(super) struct Zsh;
     pub(super) struct Fish;
+    pub(super) struct Nushell;
+
+    /// Get the user's config directory, which is `$XDG_CONFIG_HOME` if set,
+    /// otherwise `~/.config`.
+    fn config_dirs() -> Vec<PathBuf> {
+        let mut res = env::var("XDG_CONFIG_HOME")
+            .ok()
+            .filter(|p| !p.is_empty())
+            .map(|p| vec![PathBuf::from(p)])
+            .unwrap_or_default();
+        res.push(utils::home_dir().join(".config"));
+        res
+    }
+
+    /// Check if the user's login shell is the one with given binary name.
+    fn is_current_shell(name: &str) -> bool {
+        env::var_os("SHELL")
+            .map(PathBuf::from)
+            .is_some_and(|sh| sh.file_name().is_some_and(|n| n == name))
+    }
 
     impl UnixShell for Posix {
         fn does_exist(&self) -> bool {
@@ -553,8 +639,12 @@ mod shell {
 
     impl UnixShell for Fish {
         fn does_exist(&self) -> bool {
-            // fish has to either be the shell or be callable for fish setup.
-            matches!(env::var("SHELL"), Ok(sh) if sh.contains("fish")) || utils::cmd_exist("fish")
+            // fish has to either be the shell, be callable, or has been configured for fish setup.
+            matches!(env::var("SHELL"), Ok(sh) if sh.contains("fish"))
+                || utils::cmd_exist("fish")
+                || config_dirs()
+                    .iter()
+                    .any(|dir| dir.join("fish").join("config.fish").is_file())
         }
 
         // > "$XDG_CONFIG_HOME/fish/conf.d" (or "~/.config/fish/conf.d" if that variable is unset) for the user
@@ -573,6 +663,11 @@ mod shell {
             format!("set -Ux {key} {val}")
         }
 
+        fn is_path_command(&self, line: &str) -> bool {
+            // `set -Ux PATH ...` was used before switching to `fish_add_path`
+            line.starts_with(FISH_ADD_PATH) || line.contains("PATH")
+        }
+
         fn update_rcs(&self) -> Vec<PathBuf> {
             // The first rcfile takes precedence.
             match self.rcfiles().into_iter().next() {
@@ -600,6 +695,8 @@ mod shell {
                     } else {
                         None
                     }
+                } else if let Some(paths) = cmd.strip_prefix(FISH_ADD_PATH) {
+                    Some(format!("{FISH_ADD_PATH} {path_str}{paths}"))
                 } else {
                     let (before_path, after_path) = cmd.split_once("PATH")?;
                     Some(format!("{before_path}PATH {path_str}{after_path}"))
@@ -608,18 +705,79 @@ mod shell {
                 if remove {
                     None
                 } else {
-                    Some(self.to_env_var_string("PATH", &format!("{path_str} $PATH")))
+                    Some(format!("{FISH_ADD_PATH} {path_str}"))
                 }
             }
         }
     }
 
+    impl UnixShell for Nushell {
+        fn does_exist(&self) -> bool {
+            is_current_shell("nu") || self.rcfiles().iter().any(|rc| rc.is_file())
+        }
+
+        fn rcfiles(&self) -> Vec<PathBuf> {
+            config_dirs()
+                .into_iter()
+                .map(|dir| dir.join("nushell").join("env.nu"))
+                .collect()
+        }
+
+        fn update_rcs(&self) -> Vec<PathBuf> {
+            // Prefer the existing one, otherwise create the first one.
+            self.rcfiles()
+                .into_iter()
+                .filter(|rc| rc.is_file())
+                .chain(self.rcfiles())
+                .take(1)
+                .collect()
+        }
+
+        fn to_env_var_string(&self, key: &'static str, val: &str) -> String {
+            format!("$env.{key} = '{val}'")
+        }
+
+        fn command_to_update_path(
+            &self,
+            old_command: Option<&str>,
+            path_str: &str,
+            remove: bool,
+        ) -> Option<String> {
+            let quoted = format!("'{path_str}'");
+            if let Some(cmd) = old_command {
+                if remove {
+                    let with_trailing = format!("{quoted} ");
+                    let with_leading = format!(" {quoted}");
+                    [with_trailing, with_leading, quoted]
+                        .into_iter()
+                        .find(|s| cmd.contains(s.as_str()))
+                        .map(|s| cmd.replace(&s, ""))
+                } else {
+                    let paths = cmd.strip_prefix(NU_PREPEND_PATH)?;
+                    Some(format!("{NU_PREPEND_PATH}{quoted} {paths}"))
+                }
+            } else {
+                if remove {
+                    None
+                } else {
+                    Some(format!("{NU_PREPEND_PATH}{quoted}])"))
+                }
+            }
+        }
+    }
+
+    /// Get every supported shell that the user might be using.
+    ///
+    /// Note that a user can have multiple shells installed, in which case all
+    /// of them are returned, so that the environment is configured for (and later
+    /// removed from) each of them no matter which one the user ends up using.
     pub(super) fn get_available_shells() -> impl Iterator<Item = Shell> {
         let supported_shells: Vec<Shell> = vec![
             Box::new(Posix),
             Box::new(Bash),
             Box::new(Zsh),
             Box::new(Fish),
+            Box::new(Nushell),
         ];
 
         supported_shells.into_iter().filter(|sh| sh.does_exist())
@@ -845,7 +1003,7 @@ export PATH=/path/to/bin:$PATH
         let path_str = "/path/to/bin";
         let cmd = shell.command_to_update_path(None, path_str, false);
 
-        assert_eq!(cmd, Some("set -Ux PATH /path/to/bin $PATH".to_string()));
+        assert_eq!(cmd, Some("fish_add_path -g /path/to/bin".to_string()));
     }
 
     #[test]
@@ -1026,4 +1184,45 @@ export PATH="/path/to/rust/bin:$PATH"
         assert!(!result.ends_with('\n'), "no trailing newline when original had none");
         assert_eq!(result, "line1\nline2");
     }
+
+    #[test]
+    fn update_path_with_fish_add_path() {
+        let shell = shell::Fish;
+        let old_cmd = "fish_add_path -g /path/to/tool/bin";
+        let cmd = shell.command_to_update_path(Some(old_cmd), "/path/to/bin", false);
+        assert_eq!(
+            cmd.as_deref(),
+            Some("fish_add_path -g /path/to/bin /path/to/tool/bin")
+        );
+        assert!(super::path_str_in_export("/path/to/tool/bin", old_cmd));
+
+        let cmd = shell.command_to_update_path(cmd.as_deref(), "/path/to/tool/bin", true);
+        assert_eq!(cmd.as_deref(), Some("fish_add_path -g /path/to/bin"));
+    }
+
+    #[test]
+    fn update_path_nushell() {
+        let shell = shell::Nushell;
+        let cmd = shell.command_to_update_path(None, "/path/to/tool/bin", false);
+        assert_eq!(
+            cmd.as_deref(),
+            Some("$env.PATH = ($env.PATH | split row (char esep) | prepend ['/path/to/tool/bin'])")
+        );
+
+        let cmd = shell.command_to_update_path(cmd.as_deref(), "/path/to/bin", false);
+        assert_eq!(
+            cmd.as_deref(),
+            Some("$env.PATH = ($env.PATH | split row (char esep) | prepend ['/path/to/bin' '/path/to/tool/bin'])")
+        );
+        assert!(super::path_str_in_export(
+            "/path/to/bin",
+            cmd.as_deref().unwrap()
+        ));
+
+        let cmd = shell.command_to_update_path(cmd.as_deref(), "/path/to/tool/bin", true);
+        assert_eq!(
+            cmd.as_deref(),
+            Some("$env.PATH = ($env.PATH | split row (char esep) | prepend ['/path/to/bin'])")
+        );
+    }
 }
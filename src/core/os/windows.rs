@@ -3,7 +3,7 @@ use std::env::current_exe;
 use crate::core::directories::RimDir;
 use crate::core::env_backup::EnvBackup;
 use crate::core::install::{EnvConfig, InstallConfiguration};
-use crate::core::uninstall::{UninstallConfiguration, Uninstallation};
+use crate::core::uninstall::{Removal, UninstallConfiguration, Uninstallation};
 use crate::core::GlobalOpts;
 use anyhow::Result;
 use rim_common::utils;
@@ -11,7 +11,7 @@ use rim_common::utils;
 pub(crate) use rustup::*;
 
 impl EnvConfig for InstallConfiguration<'_> {
-    fn config_env_vars(&self) -> Result<()> {
+    fn config_env_vars(&self) -> Result<bool> {
         info!("{}", t!("install_env_config"));
 
         // Backup existing environment variables from registry before setting new ones
@@ -26,18 +26,24 @@ impl EnvConfig for InstallConfiguration<'_> {
             }
             vars
         };
+        let vars = self.env_vars()?;
+        let changed = !GlobalOpts::get().no_modify_env()
+            && vars
+                .iter()
+                .any(|(key, val)| pre_existing.get(*key) != Some(val));
         if let Err(e) = EnvBackup::backup_env_vars(pre_existing) {
             warn!("{}", t!("backup_env_vars_fail", error = e.to_string()));
         } else {
             info!("{}", t!("backup_env_vars_success"));
         }
 
-        for (key, val) in self.env_vars()? {
+        for (key, val) in vars {
             set_env_var(key, val.encode_utf16().collect())?;
         }
         update_env();
 
-        self.inc_progress(2.0)
+        self.inc_progress(2.0)?;
+        Ok(changed)
     }
 }
 
@@ -127,6 +133,53 @@ impl Uninstallation for UninstallConfiguration<'_> {
         _ = utils::remove(&self.install_dir);
         Ok(())
     }
+
+    fn rustup_env_vars_removals(&self) -> Result<Vec<Removal>> {
+        let mut removals = vec![];
+        if !GlobalOpts::get().no_modify_path() {
+            removals.push(Removal::PathEntry(self.cargo_home().join("bin")));
+        }
+        if GlobalOpts::get().no_modify_env() {
+            return Ok(removals);
+        }
+
+        let backup = EnvBackup::load().ok().flatten().map(|b| b.variables);
+        let env = environment().ok();
+        for var in crate::core::ALL_VARS {
+            let restore = backup.as_ref().and_then(|vars| vars.get(*var)).cloned();
+            let is_set = env
+                .as_ref()
+                .is_some_and(|env| env.get_value::<String, _>(var).is_ok());
+            if restore.is_some() || is_set {
+                removals.push(Removal::EnvVar {
+                    name: var.to_string(),
+                    restore,
+                });
+            }
+        }
+        Ok(removals)
+    }
+
+    fn self_removals(&self) -> Result<Vec<Removal>> {
+        let mut removals = vec![Removal::Registry(uninstall_entry().to_string())];
+        if !GlobalOpts::get().no_modify_path() {
+            removals.push(Removal::PathEntry(self.install_dir.clone()));
+        }
+        removals.push(Removal::Path(self.install_dir.clone()));
+        Ok(removals)
+    }
+}
+
+/// Check whether long paths support is enabled in registry, which lifts the
+/// 260 characters limitation of file paths (`MAX_PATH`).
+pub(crate) fn long_paths_enabled() -> bool {
+    use winreg::enums::HKEY_LOCAL_MACHINE;
+    use winreg::RegKey;
+
+    RegKey::predef(HKEY_LOCAL_MACHINE)
+        .open_subkey("SYSTEM\\CurrentControlSet\\Control\\FileSystem")
+        .and_then(|key| key.get_value::<u32, _>("LongPathsEnabled"))
+        .is_ok_and(|val| val == 1)
 }
 
 /// A module that contains functions that are modified from `rustup`:
@@ -146,7 +199,7 @@ pub(crate) mod rustup {
 
     static UNINSTALL_ENTRY: OnceLock<String> = OnceLock::new();
 
-    pub(super) fn uninstall_entry() -> &'static str {
+    pub(crate) fn uninstall_entry() -> &'static str {
         UNINSTALL_ENTRY.get_or_init(|| {
             format!(
                 "Software\\Microsoft\\Windows\\CurrentVersion\\Uninstall\\{}",
@@ -155,7 +208,9 @@ pub(crate) mod rustup {
         })
     }
 
-    pub(crate) fn do_add_to_programs(program_bin: &Path) -> Result<()> {
+    /// Add an "installed programs" entry for `program_bin`,
+    /// returns `true` if the entry was written.
+    pub(crate) fn do_add_to_programs(program_bin: &Path) -> Result<bool> {
         use std::path::PathBuf;
 
         let key = RegKey::predef(HKEY_CURRENT_USER)
@@ -171,7 +226,7 @@ pub(crate) mod rustup {
             let mut path = PathBuf::from(OsString::from_wide(&s));
             path.pop();
             if path.exists() {
-                return Ok(());
+                return Ok(false);
             }
         }
 
@@ -191,7 +246,7 @@ pub(crate) mod rustup {
         key.set_value("DisplayName", &product)
             .context("Failed to set `DisplayName`")?;
 
-        Ok(())
+        Ok(true)
     }
 
     /// This is used to decode the value of HKCU\Environment\PATH. If that key is
@@ -415,24 +470,26 @@ pub(crate) mod rustup {
 
     /// Add a path permanently to user's `PATH` environment variable,
     /// also work for current running process.
-    pub(crate) fn add_to_path(path: &Path) -> Result<()> {
+    ///
+    /// Returns `true` if the user's `PATH` was changed.
+    pub(crate) fn add_to_path(path: &Path) -> Result<bool> {
         // Note: Windows's PATH variables are splitted into User's and System's,
         // we cannot use `env::set_var` with this `user_path_new` because it would
         // write it with user PATH value and erase all the system PATH values in it.
         set_path_for_current_process(path, false)?;
 
         if GlobalOpts::get().no_modify_path() {
-            return Ok(());
+            return Ok(false);
         }
 
         let Some(user_path_orig) = get_user_path_var()? else {
-            return Ok(());
+            return Ok(false);
         };
         let path_bytes = path.as_os_str().encode_wide().collect::<Vec<_>>();
 
         if find_path_in_env(&user_path_orig, &path_bytes).is_some() {
             // The path was already added, return without doing anything.
-            return Ok(());
+            return Ok(false);
         }
 
         let mut user_path_new = path_bytes;
@@ -444,7 +501,7 @@ pub(crate) mod rustup {
         // Sync changes
         update_env();
 
-        Ok(())
+        Ok(true)
     }
 
     /// Remove a path permanently from user's `PATH` environment variable,
@@ -0,0 +1,209 @@
+//! Module for installing tools published to the registries of language package managers,
+//! including npm packages (check [`ToolSource::Npm`](rim_common::types::ToolSource::Npm))
+//! and Python packages (check [`ToolSource::Pip`](rim_common::types::ToolSource::Pip)).
+//!
+//! Each package is installed into its own prefix under the tools directory,
+//! and the directory containing the executables of that prefix will be added to `PATH`:
+//! - npm packages are installed using either a bundled or a system-wide `npm` (or `pnpm`).
+//! - Python packages are installed into a virtual environment, which is created
+//!   by a Python 3 interpreter found in `PATH`.
+
+use super::directories::RimDir;
+use super::parser::fingerprint::ToolRecord;
+use super::uninstall::Removal;
+use crate::InstallConfiguration;
+use anyhow::{bail, Result};
+use rim_common::{
+    types::{ToolInfo, ToolKind, LATEST_TAG},
+    utils,
+};
+use std::path::{Path, PathBuf};
+
+/// The name of the directory under `tools` where a bundled Node.js runtime might be installed.
+const BUNDLED_NODE_DIRNAME: &str = "node";
+
+/// Supported Node.js package managers, in the order of preference.
+#[cfg(windows)]
+const NODE_PACKAGE_MANAGERS: &[&str] = &["npm.cmd", "pnpm.cmd"];
+#[cfg(not(windows))]
+const NODE_PACKAGE_MANAGERS: &[&str] = &["npm", "pnpm"];
+
+/// Python launchers that can be used to create virtual environments, in the order of preference.
+#[cfg(windows)]
+const PYTHON_LAUNCHERS: &[&str] = &["py.exe", "python3.exe", "python.exe"];
+#[cfg(not(windows))]
+const PYTHON_LAUNCHERS: &[&str] = &["python3", "python"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PackageManager {
+    Npm,
+    Pip,
+}
+
+impl PackageManager {
+    /// The kind of tools installed by this package manager, which is saved in the
+    /// installation record, so that they can be uninstalled accordingly.
+    fn tool_kind(self) -> ToolKind {
+        match self {
+            Self::Npm => ToolKind::NpmPackage,
+            Self::Pip => ToolKind::PipPackage,
+        }
+    }
+
+    /// The directory containing the executables of the packages installed under `prefix`.
+    fn bin_dir(self, prefix: &Path) -> PathBuf {
+        match self {
+            Self::Npm => prefix.join("node_modules").join(".bin"),
+            Self::Pip if cfg!(windows) => prefix.join("Scripts"),
+            Self::Pip => prefix.join("bin"),
+        }
+    }
+
+    /// Install `package` of a specific `version` (or the latest version if `None`)
+    /// as tool `name`, `extras` are the optional features of Python packages.
+    pub(crate) fn install(
+        self,
+        name: &str,
+        package: &str,
+        version: Option<&str>,
+        extras: &[String],
+        tool: &ToolInfo,
+        config: &InstallConfiguration,
+    ) -> Result<ToolRecord> {
+        let tools_dir = config.tools_dir();
+        let prefix = tools_dir.join(name);
+        match self {
+            Self::Npm => install_npm_package(name, package, version, tools_dir, &prefix)?,
+            Self::Pip => {
+                install_pip_package(name, &requirement(package, version, extras), &prefix)?
+            }
+        }
+        super::os::add_to_path(&self.bin_dir(&prefix))?;
+
+        Ok(ToolRecord::new(self.tool_kind())
+            .with_paths(vec![prefix])
+            .with_version(version)
+            .with_dependencies(tool.dependencies().to_vec()))
+    }
+
+    /// Uninstall a package that was installed into `prefix`.
+    pub(crate) fn uninstall(self, prefix: &Path) -> Result<()> {
+        super::os::remove_from_path(&self.bin_dir(prefix))?;
+        utils::remove(prefix)
+    }
+
+    /// Get what [`uninstall`](Self::uninstall) will remove, without actually removing anything.
+    pub(crate) fn removals(self, prefix: &Path) -> Vec<Removal> {
+        vec![
+            Removal::PathEntry(self.bin_dir(prefix)),
+            Removal::Path(prefix.to_path_buf()),
+        ]
+    }
+}
+
+/// Find a program in the bundled Node.js runtime first, then fallback to `PATH`.
+fn find_node_program(tools_dir: &Path, program: &str) -> Option<PathBuf> {
+    let bundled = tools_dir.join(BUNDLED_NODE_DIRNAME);
+    [bundled.join("bin"), bundled]
+        .into_iter()
+        .map(|dir| dir.join(program))
+        .find(|p| p.is_file())
+        .or_else(|| utils::program_path(program))
+}
+
+fn install_npm_package(
+    name: &str,
+    package: &str,
+    version: Option<&str>,
+    tools_dir: &Path,
+    prefix: &Path,
+) -> Result<()> {
+    if find_node_program(tools_dir, &exe!("node")).is_none() {
+        bail!(t!("npm_no_node_runtime", name = name));
+    }
+    let Some(package_manager) = NODE_PACKAGE_MANAGERS
+        .iter()
+        .find_map(|pm| find_node_program(tools_dir, pm))
+    else {
+        bail!(t!("npm_no_node_runtime", name = name));
+    };
+
+    utils::ensure_dir(prefix)?;
+    let spec = format!("{package}@{}", version.unwrap_or(LATEST_TAG));
+    let is_pnpm = package_manager
+        .file_stem()
+        .is_some_and(|stem| stem == "pnpm");
+    if is_pnpm {
+        run!(&package_manager, "add", "--dir", prefix, &spec)
+    } else {
+        run!(&package_manager, "install", "--prefix", prefix, &spec)
+    }
+}
+
+fn install_pip_package(name: &str, requirement: &str, venv: &Path) -> Result<()> {
+    let Some(python) = PYTHON_LAUNCHERS.iter().find_map(utils::program_path) else {
+        bail!(t!("pip_no_python", name = name));
+    };
+
+    run!(&python, "-m", "venv", venv)?;
+    run!(
+        PackageManager::Pip.bin_dir(venv).join(exe!("python")),
+        "-m",
+        "pip",
+        "install",
+        requirement
+    )
+}
+
+/// Format the requirement specifier of a Python package, such as `black[jupyter]==24.4.2`.
+pub(crate) fn requirement(package: &str, version: Option<&str>, extras: &[String]) -> String {
+    let mut spec = package.to_string();
+    if !extras.is_empty() {
+        spec.push_str(&format!("[{}]", extras.join(",")));
+    }
+    match version {
+        // an exact version
+        Some(ver) if ver.starts_with(|c: char| c.is_ascii_digit()) => {
+            spec.push_str(&format!("=={ver}"));
+        }
+        // a version specifier, such as `>=1.0`
+        Some(ver) => spec.push_str(ver),
+        None => (),
+    }
+    spec
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requirement_specifier() {
+        assert_eq!(requirement("black", None, &[]), "black");
+        assert_eq!(requirement("black", Some("24.4.2"), &[]), "black==24.4.2");
+        assert_eq!(
+            requirement("black", Some(">=24.0"), &["jupyter".into(), "d".into()]),
+            "black[jupyter,d]>=24.0"
+        );
+    }
+
+    #[test]
+    fn package_removals() {
+        let prefix = Path::new("tools").join("a");
+        assert_eq!(
+            PackageManager::Npm.removals(&prefix),
+            [
+                Removal::PathEntry(prefix.join("node_modules").join(".bin")),
+                Removal::Path(prefix.clone()),
+            ]
+        );
+        let bin_dir = if cfg!(windows) { "Scripts" } else { "bin" };
+        assert_eq!(
+            PackageManager::Pip.removals(&prefix),
+            [
+                Removal::PathEntry(prefix.join(bin_dir)),
+                Removal::Path(prefix.clone()),
+            ]
+        );
+    }
+}
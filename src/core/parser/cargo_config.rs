@@ -1,6 +1,8 @@
 //! Module defining types that could be serialized to a working `config.toml` for cargo.
 
-use std::path::PathBuf;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 use indexmap::IndexMap;
 use rim_common::types::TomlParser;
@@ -18,6 +20,8 @@ pub(crate) struct CargoConfig {
     #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
     source: IndexMap<String, Source>,
     #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    registries: IndexMap<String, Registry>,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
     patch: IndexMap<String, DependencyPatch>,
 }
 
@@ -46,16 +50,26 @@ impl CargoConfig {
     }
 
     pub(crate) fn git_fetch_with_cli(&mut self, yes: bool) -> &mut Self {
-        self.net = Some(CargoNetConfig {
-            git_fetch_with_cli: Some(yes),
-        });
+        self.net
+            .get_or_insert_with(Default::default)
+            .git_fetch_with_cli = Some(yes);
+        self
+    }
+
+    /// Set the number of times to retry possibly spurious network errors.
+    pub(crate) fn net_retry(&mut self, retry: u32) -> &mut Self {
+        self.net.get_or_insert_with(Default::default).retry = Some(retry);
         self
     }
 
     pub(crate) fn check_revoke(&mut self, yes: bool) -> &mut Self {
-        self.http = Some(CargoHttpConfig {
-            check_revoke: Some(yes),
-        });
+        self.http.get_or_insert_with(Default::default).check_revoke = Some(yes);
+        self
+    }
+
+    /// Set the HTTP/HTTPS proxy that cargo uses.
+    pub(crate) fn http_proxy(&mut self, proxy: &str) -> &mut Self {
+        self.http.get_or_insert_with(Default::default).proxy = Some(proxy.to_string());
         self
     }
 
@@ -91,6 +105,24 @@ impl CargoConfig {
         self
     }
 
+    /// Insert a registry, so that it can be authenticated using the token in
+    /// `credentials.toml` (check [`CargoCredentials`]) or the given `credential_provider`.
+    pub(crate) fn add_registry(
+        &mut self,
+        name: &str,
+        index: &str,
+        credential_provider: Option<&str>,
+    ) -> &mut Self {
+        self.registries.insert(
+            name.to_string(),
+            Registry {
+                index: Some(index.to_string()),
+                credential_provider: credential_provider.map(ToOwned::to_owned),
+            },
+        );
+        self
+    }
+
     /// Insert a dependency patch ([`DependencyPatch`]) into the patch section.
     pub(crate) fn add_patch<S, P>(&mut self, name: S, patch_path: P) -> &mut Self
     where
@@ -140,11 +172,13 @@ pub(crate) enum DependencyKind {
 #[serde(rename_all = "kebab-case")]
 pub(crate) struct CargoNetConfig {
     git_fetch_with_cli: Option<bool>,
+    retry: Option<u32>,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub(crate) struct CargoHttpConfig {
+    proxy: Option<String>,
     check_revoke: Option<bool>,
 }
 
@@ -155,6 +189,86 @@ pub(crate) struct Source {
     pub(crate) registry: Option<String>,
 }
 
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) struct Registry {
+    index: Option<String>,
+    credential_provider: Option<String>,
+}
+
+/// A struct representing cargo's `credentials.toml`, which contains the tokens of registries.
+///
+/// Since the tokens are secrets, this file is only readable and writable by
+/// the current user on unix, and its content should never be logged.
+///
+/// Only the tokens of `[registries]` are modeled, everything else (such as the
+/// `[registry]` token of crates.io) is kept as it is when writing back.
+#[derive(Default, Serialize, Deserialize)]
+pub(crate) struct CargoCredentials {
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    registries: IndexMap<String, RegistryToken>,
+    #[serde(flatten)]
+    rest: toml::Table,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct RegistryToken {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    token: String,
+    #[serde(flatten)]
+    rest: toml::Table,
+}
+
+impl TomlParser for CargoCredentials {
+    const FILENAME: &'static str = "credentials.toml";
+
+    /// Load from a directory or return a default if it doesn't exists
+    fn load_from_dir<P: AsRef<Path>>(parent: P) -> anyhow::Result<Self>
+    where
+        Self: Sized + serde::de::DeserializeOwned + Default,
+    {
+        let src: PathBuf = parent.as_ref().join(Self::FILENAME);
+        if !src.is_file() {
+            Ok(Self::default())
+        } else {
+            Self::load(src)
+        }
+    }
+
+    /// Write `credentials.toml` under `parent` directory, with `0600` permission on unix.
+    fn write_to_dir<P: AsRef<Path>>(&self, parent: P) -> anyhow::Result<()>
+    where
+        Self: Sized + Serialize,
+    {
+        let content = self.to_toml()?;
+        let path = parent.as_ref().join(Self::FILENAME);
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&path)?;
+        // the mode above only applies to newly created files
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        }
+        file.write_all(content.as_bytes())?;
+        Ok(())
+    }
+}
+
+impl CargoCredentials {
+    /// Set the token of registry `name`.
+    pub(crate) fn add_token(&mut self, name: &str, token: &str) -> &mut Self {
+        self.registries.entry(name.to_string()).or_default().token = token.to_string();
+        self
+    }
+}
+
 /// Flip all backward splashes (`\`) to forward splash (`/`) when serializing paths.
 /// To make sure `cargo` can read this config on Windows.
 fn flip_backslash<S>(path: &PathBuf, serializer: S) -> Result<S::Ok, S::Error>
@@ -167,7 +281,7 @@ where
 
 #[cfg(test)]
 mod tests {
-    use super::{CargoConfig, TomlParser};
+    use super::{CargoConfig, CargoCredentials, TomlParser};
 
     #[test]
     fn cargo_config_default_serialize() {
@@ -218,4 +332,105 @@ path = \"/path/to/foo\"\n
 path = \"/path/to/bar\"\n"
         );
     }
+
+    #[test]
+    fn cargo_config_net_and_http() {
+        let config = CargoConfig::new()
+            .net_retry(5)
+            .git_fetch_with_cli(true)
+            .check_revoke(false)
+            .http_proxy("http://proxy.example.com:8080")
+            .to_toml()
+            .unwrap();
+        let expected = r#"[net]
+git-fetch-with-cli = true
+retry = 5
+
+[http]
+proxy = "http://proxy.example.com:8080"
+check-revoke = false
+"#;
+        assert_eq!(config, expected);
+
+        // the output should stays the same after loading and serializing it again
+        let reloaded = CargoConfig::from_str(&config).unwrap();
+        assert_eq!(reloaded.to_toml().unwrap(), expected);
+
+        // sections without any value should not be written
+        let config = CargoConfig::new().net_retry(3).to_toml().unwrap();
+        assert_eq!(config, "[net]\nretry = 3\n");
+    }
+
+    #[test]
+    fn cargo_config_registry() {
+        let config = CargoConfig::new()
+            .add_source("mirror", "sparse+https://example.com/index/", true)
+            .add_registry(
+                "mirror",
+                "sparse+https://example.com/index/",
+                Some("cargo:token"),
+            )
+            .to_toml()
+            .unwrap();
+        assert!(config.contains(
+            r#"[registries.mirror]
+index = "sparse+https://example.com/index/"
+credential-provider = "cargo:token"
+"#
+        ));
+    }
+
+    #[test]
+    fn write_cargo_credentials() {
+        let dir = tempfile::tempdir().unwrap();
+        CargoCredentials::default()
+            .add_token("mirror", "secret")
+            .write_to_dir(dir.path())
+            .unwrap();
+
+        let path = dir.path().join(CargoCredentials::FILENAME);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "[registries.mirror]\ntoken = \"secret\"\n"
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        // existing tokens are kept when adding new ones
+        let mut credentials = CargoCredentials::load_from_dir(dir.path()).unwrap();
+        credentials
+            .add_token("other", "another")
+            .write_to_dir(dir.path())
+            .unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("[registries.mirror]"));
+        assert!(content.contains("[registries.other]"));
+    }
+
+    #[test]
+    fn keep_unknown_credentials() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CargoCredentials::FILENAME);
+        std::fs::write(
+            &path,
+            "[registry]\ntoken = \"crates-io\"\n\n\
+            [registries.mirror]\ntoken = \"old\"\nsecret-key = \"key\"\n",
+        )
+        .unwrap();
+
+        let mut credentials = CargoCredentials::load_from_dir(dir.path()).unwrap();
+        credentials
+            .add_token("mirror", "new")
+            .write_to_dir(dir.path())
+            .unwrap();
+        let content: toml::Table = std::fs::read_to_string(&path).unwrap().parse().unwrap();
+        assert_eq!(content["registry"]["token"].as_str(), Some("crates-io"));
+        let mirror = &content["registries"]["mirror"];
+        assert_eq!(mirror["token"].as_str(), Some("new"));
+        assert_eq!(mirror["secret-key"].as_str(), Some("key"));
+    }
 }
@@ -1,11 +1,11 @@
 //! The major configuration file for this app, containing information about which version to skip,
 //! when the updates are checked, how long until next updates will be checked etc.
 
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use chrono::{NaiveDateTime, Utc};
-use rim_common::types::TomlParser;
+use rim_common::{types::TomlParser, utils};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fmt::Display, time::Duration};
+use std::{collections::HashMap, fmt::Display, str::FromStr, time::Duration};
 
 use crate::AppInfo;
 
@@ -16,8 +16,8 @@ pub const DEFAULT_UPDATE_CHECK_DURATION: Duration =
     Duration::from_secs(60 * DEFAULT_UPDATE_CHECK_TIMEOUT_IN_MINUTES);
 
 fn default_autostart_policy() -> bool {
-    // We don't have a setting for auto-start yet,
-    // therefore it'd better to disable auto-start by default so it won't annoys the user.
+    // Disable auto-start by default so it won't annoys the user,
+    // it can be turned on in the GUI settings.
     false
 }
 
@@ -64,16 +64,143 @@ impl Configuration {
         Self::load_from_dir(install_dir).unwrap_or_default()
     }
 
+    /// Write this configuration to the installation directory.
+    ///
+    /// The file is written atomically so it won't get corrupted if
+    /// the program was killed in the middle of writing.
     pub fn write_to_install_dir(&self) -> Result<()> {
-        let install_dir = AppInfo::get_installed_dir();
-        self.write_to_dir(install_dir)
+        let path = AppInfo::get_installed_dir().join(Self::FILENAME);
+        utils::write_file_atomic(path, &self.to_toml()?)
     }
 
+    setter!(with_autostart(self.autostart, bool));
+
     pub fn update_skipped<T: AsRef<str>>(&self, target: UpdateTarget, version: T) -> bool {
         self.update.is_skipped(target, version)
     }
 }
 
+/// A setting of [`Configuration`] that can be viewed or edited by key,
+/// such as `autostart` or `update.manager.skip`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigKey {
+    Autostart,
+    UpdateLastRun(UpdateTarget),
+    UpdateTimeout(UpdateTarget),
+    UpdateSkip(UpdateTarget),
+}
+
+impl ConfigKey {
+    /// Every key, in the order of them being listed.
+    pub const ALL: &[Self] = &[
+        Self::Autostart,
+        Self::UpdateLastRun(UpdateTarget::Manager),
+        Self::UpdateTimeout(UpdateTarget::Manager),
+        Self::UpdateSkip(UpdateTarget::Manager),
+        Self::UpdateLastRun(UpdateTarget::Toolkit),
+        Self::UpdateTimeout(UpdateTarget::Toolkit),
+        Self::UpdateSkip(UpdateTarget::Toolkit),
+    ];
+
+    /// Return `false` if the value of this key is maintained by the program,
+    /// and should not be edited by the user.
+    pub fn is_writable(self) -> bool {
+        !matches!(self, Self::UpdateLastRun(_))
+    }
+}
+
+impl Display for ConfigKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Autostart => f.write_str("autostart"),
+            Self::UpdateLastRun(target) => write!(f, "update.{target}.last-run"),
+            Self::UpdateTimeout(target) => write!(f, "update.{target}.timeout"),
+            Self::UpdateSkip(target) => write!(f, "update.{target}.skip"),
+        }
+    }
+}
+
+impl FromStr for ConfigKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .iter()
+            .find(|key| key.to_string() == s)
+            .copied()
+            .ok_or_else(|| {
+                let available = Self::ALL
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                anyhow!(t!("config_unknown_key", key = s, available = available))
+            })
+    }
+}
+
+impl Configuration {
+    /// Get the value of `key` as a string, or `None` if it is not set.
+    pub fn get(&self, key: ConfigKey) -> Option<String> {
+        let conf = |target| self.update.0.get(&target);
+        match key {
+            ConfigKey::Autostart => Some(self.autostart.to_string()),
+            ConfigKey::UpdateLastRun(target) => conf(target).map(|c| c.last_run.to_string()),
+            ConfigKey::UpdateTimeout(target) => Some(
+                conf(target)
+                    .and_then(|c| c.timeout)
+                    .unwrap_or(DEFAULT_UPDATE_CHECK_TIMEOUT_IN_MINUTES)
+                    .to_string(),
+            ),
+            ConfigKey::UpdateSkip(target) => conf(target).and_then(|c| c.skip.clone()),
+        }
+    }
+
+    /// Set `key` to `value` after validating it, where an empty value of
+    /// `update.<target>.skip` means not skipping any version.
+    pub fn set(&mut self, key: ConfigKey, value: &str) -> Result<()> {
+        let invalid = |expected: &str| {
+            anyhow!(t!(
+                "config_invalid_value",
+                key = key,
+                value = value,
+                expected = expected
+            ))
+        };
+        let value = value.trim();
+        match key {
+            ConfigKey::Autostart => {
+                self.autostart = value
+                    .parse()
+                    .map_err(|_| invalid(&t!("config_expect_bool")))?;
+            }
+            ConfigKey::UpdateLastRun(_) => bail!(t!("config_read_only_key", key = key)),
+            ConfigKey::UpdateTimeout(target) => {
+                let minutes = value
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|m| *m > 0)
+                    .ok_or_else(|| invalid(&t!("config_expect_minutes")))?;
+                self.update.conf_mut(target).timeout = Some(minutes);
+            }
+            ConfigKey::UpdateSkip(target) => {
+                // toolkit versions might have prefixes (such as `stable 1.80.1`),
+                // so only the manager version is required to be a semver
+                let is_semver = || semver::Version::parse(value).is_ok();
+                let skip = if value.is_empty() {
+                    None
+                } else if target == UpdateTarget::Manager && !is_semver() {
+                    return Err(invalid(&t!("config_expect_version")));
+                } else {
+                    Some(value.to_string())
+                };
+                self.update.conf_mut(target).skip = skip;
+            }
+        }
+        Ok(())
+    }
+}
+
 // If we ever need to support more things for update checker,
 // just add one in this enum, without breaking compatibility.
 #[derive(Clone, Copy, Debug, Deserialize, Serialize, Hash, PartialEq, Eq)]
@@ -247,4 +374,43 @@ manager = { last-run = "1970-01-01T00:00:00" }"#;
         expected = expected.remind_later(manager, 60);
         assert_eq!(expected.conf_mut(manager).timeout, Some(120));
     }
+
+    #[test]
+    fn get_and_set_by_key() {
+        let mut config = Configuration::new();
+        let key = |s: &str| s.parse::<ConfigKey>().unwrap();
+
+        assert_eq!(config.get(key("autostart")).as_deref(), Some("false"));
+        config.set(key("autostart"), "true").unwrap();
+        assert!(config.autostart);
+        assert!(config.set(key("autostart"), "yes").is_err());
+
+        assert_eq!(
+            config.get(key("update.manager.timeout")).as_deref(),
+            Some("1440")
+        );
+        config.set(key("update.manager.timeout"), "60").unwrap();
+        assert_eq!(
+            config.get(key("update.manager.timeout")).as_deref(),
+            Some("60")
+        );
+        assert!(config.set(key("update.manager.timeout"), "0").is_err());
+        assert!(config.set(key("update.manager.timeout"), "-1").is_err());
+
+        assert_eq!(config.get(key("update.toolkit.skip")), None);
+        config
+            .set(key("update.toolkit.skip"), "stable 1.80.1")
+            .unwrap();
+        assert!(config.update_skipped(UpdateTarget::Toolkit, "stable 1.80.1"));
+        config.set(key("update.toolkit.skip"), "").unwrap();
+        assert!(!config.update_skipped(UpdateTarget::Toolkit, "stable 1.80.1"));
+        config.set(key("update.manager.skip"), "0.5.0").unwrap();
+        assert!(config.set(key("update.manager.skip"), "latest").is_err());
+
+        assert!(config.set(key("update.manager.last-run"), "").is_err());
+        assert!("update.foo.skip".parse::<ConfigKey>().is_err());
+        for k in ConfigKey::ALL {
+            assert_eq!(key(&k.to_string()), *k);
+        }
+    }
 }
@@ -9,6 +9,7 @@ use std::{
 };
 
 use crate::components::ToolchainComponent;
+use crate::core::directories::RimDir;
 use crate::AppInfo;
 
 /// Load fingerprint file just to get the list of installed tools.
@@ -26,6 +27,13 @@ pub struct InstallationRecord {
     pub version: Option<String>,
     pub edition: Option<String>,
     pub root: PathBuf,
+    /// Toolchain components that are held at their installed version,
+    /// which will be skipped when updating the toolchain.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pinned_components: Vec<String>,
+    /// Toolchain profile specified by user, which overrides the one in toolkit manifest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toolchain_profile: Option<String>,
     pub rust: Option<RustRecord>,
     #[serde(default)]
     pub tools: HashMap<String, ToolRecord>,
@@ -58,6 +66,12 @@ impl TomlParser for InstallationRecord {
     }
 }
 
+impl RimDir for InstallationRecord {
+    fn install_dir(&self) -> &Path {
+        &self.root
+    }
+}
+
 impl InstallationRecord {
     /// Used to detect whether a fingerprint file exists in parent directory.
     ///
@@ -96,9 +110,16 @@ impl InstallationRecord {
         self.edition.clone_from(&manifest.edition);
     }
 
-    pub(crate) fn remove_toolkit_meta(&mut self) {
-        self.name = None;
-        self.version = None;
+    /// Remove everything about the installed toolkit, including its metadata and the
+    /// records of tools and toolchain, leaving only the `root` of this installation.
+    ///
+    /// This keeps the record in a consistent state after the toolkit was uninstalled
+    /// while keeping the manager, so that another toolkit can be installed afterward.
+    pub(crate) fn clear_toolkit(&mut self) {
+        *self = Self {
+            root: std::mem::take(&mut self.root),
+            ..Default::default()
+        };
     }
 
     /// Adds installation record for Rust toolchain
@@ -188,6 +209,11 @@ pub struct RustRecord {
 }
 
 impl RustRecord {
+    /// The toolchain channel (or version) that was installed.
+    pub(crate) fn version(&self) -> &str {
+        &self.version
+    }
+
     pub(crate) fn print_rust_info(&self) -> String {
         format!(
             "rust-version: {}\ncomponents: {:?}\n",
@@ -213,6 +239,13 @@ pub struct ToolRecord {
     pub(crate) paths: Vec<PathBuf>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub(crate) dependencies: Vec<String>,
+    /// The command to run before uninstalling this tool, copied from the toolkit manifest
+    /// because the manifest might not be available during uninstallation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) pre_uninstall: Option<String>,
+    /// The uninstall script of a [`ToolKind::Script`] tool, relative to its installed directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) uninstall_script: Option<String>,
 }
 
 impl ToolRecord {
@@ -242,6 +275,8 @@ impl ToolRecord {
     setter!(with_paths(self.paths, Vec<PathBuf>));
     setter!(with_version(self.version, ver: Option<impl Into<String>>) { ver.map(Into::into) });
     setter!(with_dependencies(self.dependencies, Vec<String>));
+    setter!(with_pre_uninstall(self.pre_uninstall, cmd: Option<impl Into<String>>) { cmd.map(Into::into) });
+    setter!(with_uninstall_script(self.uninstall_script, script: Option<impl Into<String>>) { script.map(Into::into) });
 }
 
 // `use-cargo = true/false` was used during [0.2.0, 0.3.0], in order not to break
@@ -313,6 +348,40 @@ paths = [{QUOTE}{}{QUOTE}]
         assert_eq!(v0, fp.to_toml().unwrap());
     }
 
+    #[test]
+    fn clear_toolkit_keeps_root() {
+        let input = r#"
+name = "rust bundle"
+version = "0.1"
+edition = "professional"
+root = '/path/to/something'
+pinned_components = ["clippy"]
+toolchain_profile = "minimal"
+
+[rust]
+version = "stable"
+components = ["clippy", "rustfmt"]
+
+[tools.aaa]
+kind = "custom"
+paths = ['/path/to/something/tools/aaa']"#;
+
+        let mut record = InstallationRecord::from_str(input).unwrap();
+        record.clear_toolkit();
+        assert!(record.name.is_none());
+        assert!(record.version.is_none());
+        assert!(record.edition.is_none());
+        assert!(record.pinned_components.is_empty());
+        assert!(record.toolchain_profile.is_none());
+        assert!(record.installed_toolchain_components().is_empty());
+        assert!(record.tools.is_empty());
+
+        let cleared = InstallationRecord::from_str(&record.to_toml().unwrap()).unwrap();
+        assert_eq!(cleared.root, PathBuf::from("/path/to/something"));
+        assert!(cleared.rust.is_none());
+        assert!(cleared.tools.is_empty());
+    }
+
     #[test]
     fn with_name_and_ver() {
         let input = r#"
@@ -380,6 +449,39 @@ c = { paths = ['some/other/path'] }"#;
         assert_eq!(hm["c"], ToolKind::Unknown);
     }
 
+    #[test]
+    fn with_pinned_components() {
+        let input = r#"
+root = '/path/to/something'
+pinned_components = ["rust-analyzer"]
+
+[rust]
+version = "stable"
+components = ["rustfmt", "rust-analyzer"]"#;
+
+        let expected = InstallationRecord::from_str(input).unwrap();
+        assert_eq!(expected.pinned_components, ["rust-analyzer"]);
+        assert!(expected.toolchain_profile.is_none());
+    }
+
+    #[test]
+    fn with_toolchain_profile() {
+        let input = r#"
+root = '/path/to/something'
+toolchain_profile = "minimal"
+
+[rust]
+version = "stable"
+components = []"#;
+
+        let expected = InstallationRecord::from_str(input).unwrap();
+        assert_eq!(expected.toolchain_profile.as_deref(), Some("minimal"));
+        assert!(expected
+            .to_toml()
+            .unwrap()
+            .contains("toolchain_profile = \"minimal\""));
+    }
+
     #[test]
     fn do_not_ser_use_cargo() {
         let record = InstallationRecord {
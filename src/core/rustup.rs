@@ -11,7 +11,7 @@ use super::components::ToolchainComponent;
 use super::default_rustup_dist_server;
 use super::directories::RimDir;
 use super::install::InstallConfiguration;
-use super::uninstall::UninstallConfiguration;
+use super::uninstall::{Removal, UninstallConfiguration};
 use super::GlobalOpts;
 use super::ToolkitManifestExt;
 use super::CARGO_HOME;
@@ -23,6 +23,9 @@ pub(crate) const RUSTUP_INIT: &str = "rustup-init.exe";
 #[cfg(not(windows))]
 pub(crate) const RUSTUP_INIT: &str = "rustup-init";
 
+/// Toolchain profiles that are known by `rustup`.
+pub(crate) const TOOLCHAIN_PROFILES: [&str; 3] = ["minimal", "default", "complete"];
+
 #[cfg(windows)]
 const RUSTUP: &str = "rustup.exe";
 #[cfg(not(windows))]
@@ -90,7 +93,7 @@ impl ToolchainInstaller {
             "-c",
             &components_arg
         );
-        if let Some(profile) = config.manifest.rust.profile() {
+        if let Some(profile) = config.toolchain_profile() {
             cmd.args(["--profile", profile]);
         }
 
@@ -291,6 +294,17 @@ impl ToolchainInstaller {
         info!("{}", t!("rust_toolchain_uninstalled"));
         Ok(())
     }
+
+    /// Get what [`remove_self`](Self::remove_self) will remove, which is everything
+    /// under `RUSTUP_HOME` and `CARGO_HOME` that `rustup self uninstall` cleans up.
+    pub(crate) fn self_removals(config: &UninstallConfiguration) -> Vec<Removal> {
+        let rustup = config.cargo_bin().join(RUSTUP);
+        vec![
+            Removal::Command(format!("{} self uninstall -y", rustup.display())),
+            Removal::Path(config.rustup_home().to_path_buf()),
+            Removal::Path(config.cargo_home().to_path_buf()),
+        ]
+    }
 }
 
 /// Spawn a fully detached `cmd /c rd /s /q` process to delete a directory tree
@@ -379,6 +393,8 @@ fn ensure_rustup_dist_server_env(
             // is guaranteed to be `Ok`.
             server.set_scheme("http").unwrap();
         }
+        // `rustup` will download the toolchain from this server by itself
+        utils::ensure_network_allowed(&server)?;
         std::env::set_var(RUSTUP_DIST_SERVER, server.as_str());
     }
 
@@ -408,6 +424,7 @@ fn ensure_rustup(config: &InstallConfiguration, insecure: bool) -> Result<PathBu
                 &config.rustup_update_root,
                 config.manifest.proxy.as_ref(),
                 insecure,
+                config.download_rate_limit(),
             )?;
             (rustup_init, Some(temp_dir))
         };
@@ -424,14 +441,19 @@ fn download_rustup_init(
     server: &Url,
     proxy: Option<&Proxy>,
     insecure: bool,
+    rate_limit: Option<u64>,
 ) -> Result<()> {
     info!("{}", t!("downloading_rustup_init"));
 
-    let download_url = utils::url_join(server, format!("dist/{}/{RUSTUP_INIT}", env!("TARGET")))
-        .context("Failed to init rustup download url.")?;
+    let download_url = utils::url_join(
+        server,
+        format!("dist/{}/{RUSTUP_INIT}", super::host_triple()),
+    )
+    .context("Failed to init rustup download url.")?;
     utils::DownloadOpt::new(RUSTUP_INIT, GlobalOpts::get().quiet)
         .insecure(insecure)
         .with_proxy(proxy.cloned())
+        .with_rate_limit(rate_limit)
         .blocking_download(&download_url, dest)
         .context("Failed to download rustup.")
 }
@@ -446,7 +468,7 @@ fn install_rustup(rustup_init: &PathBuf) -> Result<()> {
         "--default-toolchain",
         "none",
         "--default-host",
-        env!("TARGET"),
+        super::target_triple(),
         "-y",
     ];
     if GlobalOpts::get().verbose {
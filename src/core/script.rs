@@ -0,0 +1,169 @@
+//! Module for installing [`ToolKind::Script`](rim_common::types::ToolKind::Script) tools,
+//! which are installed by running a script with the tool's install directory as argument,
+//! and might be uninstalled by running an
+//! [`uninstall-script`](rim_common::types::ToolInfoDetails::uninstall_script).
+//!
+//! Just like hooks, scripts are arbitrary code coming from the toolkit manifest,
+//! therefore they only run when the user explicitly allows them.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use rim_common::utils;
+
+use super::directories::RimDir;
+use super::hooks;
+use super::{CARGO_HOME, RUSTUP_HOME};
+
+/// The program to run a script with, which is chosen by the script's extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Interpreter {
+    Sh,
+    Bash,
+    PowerShell,
+    Python,
+}
+
+impl Interpreter {
+    fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "sh" => Some(Self::Sh),
+            "bash" => Some(Self::Bash),
+            "ps1" => Some(Self::PowerShell),
+            "py" => Some(Self::Python),
+            _ => None,
+        }
+    }
+
+    /// The default interpreter of current platform, used for scripts without
+    /// a known extension.
+    fn native() -> Self {
+        if cfg!(windows) {
+            Self::PowerShell
+        } else {
+            Self::Sh
+        }
+    }
+
+    fn command(self, script: &Path) -> Command {
+        match self {
+            Self::Sh => cmd!("sh", script),
+            Self::Bash => cmd!("bash", script),
+            Self::PowerShell => {
+                let program = if cfg!(windows) { "powershell" } else { "pwsh" };
+                cmd!(
+                    program,
+                    "-NoProfile",
+                    "-ExecutionPolicy",
+                    "Bypass",
+                    "-File",
+                    script
+                )
+            }
+            Self::Python => cmd!(if cfg!(windows) { "python" } else { "python3" }, script),
+        }
+    }
+}
+
+/// Install tool `name` by running its install `script`, returning the installed directory.
+///
+/// The script is copied into the tool's directory under `tools_dir` first, so it will be
+/// removed along with the tool, then it runs with that directory as its argument.
+pub(crate) fn install<T: RimDir>(
+    name: &str,
+    script: &Path,
+    config: T,
+    allowed: bool,
+) -> Result<PathBuf> {
+    if !allowed {
+        bail!(t!("script_not_allowed", name = name));
+    }
+
+    let tool_dir = config.tools_dir().join(name);
+    utils::ensure_dir(&tool_dir)?;
+    let script = utils::copy_into(script, &tool_dir)?;
+
+    info!("{}", t!("running_install_script", name = name));
+    run_script(name, &script, &tool_dir, &config)
+        .with_context(|| t!("script_failed", name = name, script = script.display()))?;
+    Ok(tool_dir)
+}
+
+/// Run the uninstall `script` of tool `name`, which is a path relative to the tool's
+/// install directory (deduced from the installed `paths`).
+///
+/// If scripts are not `allowed`, or the script no longer exists, it will be skipped
+/// with a warning.
+pub(crate) fn run_uninstall_script<T: RimDir>(
+    name: &str,
+    script: &str,
+    paths: &[PathBuf],
+    config: T,
+    allowed: bool,
+) -> Result<()> {
+    if !allowed {
+        warn!("{}", t!("uninstall_script_not_allowed", name = name));
+        return Ok(());
+    }
+
+    let tool_dir = hooks::working_dir(paths, config.tools_dir());
+    let script_path = tool_dir.join(script);
+    if !script_path.is_file() {
+        warn!(
+            "{}",
+            t!(
+                "uninstall_script_not_found",
+                name = name,
+                script = script_path.display()
+            )
+        );
+        return Ok(());
+    }
+
+    info!("{}", t!("running_uninstall_script", name = name));
+    run_script(name, &script_path, &tool_dir, &config)
+        .with_context(|| t!("script_failed", name = name, script = script_path.display()))
+}
+
+fn run_script<T: RimDir>(name: &str, script: &Path, tool_dir: &Path, config: &T) -> Result<()> {
+    let interpreter = Interpreter::from_path(script).unwrap_or_else(Interpreter::native);
+    let mut cmd = interpreter.command(script);
+    cmd.arg(tool_dir)
+        .env(CARGO_HOME, config.cargo_home())
+        .env(RUSTUP_HOME, config.rustup_home());
+    hooks::execute_in(cmd, name, tool_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpreter_from_extension() {
+        let from = |p: &str| Interpreter::from_path(Path::new(p));
+        assert_eq!(from("install.sh"), Some(Interpreter::Sh));
+        assert_eq!(from("install.bash"), Some(Interpreter::Bash));
+        assert_eq!(from("Install.PS1"), Some(Interpreter::PowerShell));
+        assert_eq!(from("install.py"), Some(Interpreter::Python));
+        assert_eq!(from("install"), None);
+        assert_eq!(from("install.exe"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_script_with_tool_dir() {
+        let root = tempfile::tempdir().unwrap();
+        let tool_dir = root.path().join("tool");
+        utils::ensure_dir(&tool_dir).unwrap();
+        let script = root.path().join("install.sh");
+        utils::write_file(&script, "echo \"$RIM_TOOL_NAME\" > \"$1/installed\"", false).unwrap();
+
+        let mut cmd = Interpreter::Sh.command(&script);
+        cmd.arg(&tool_dir);
+        hooks::execute_in(cmd, "a", &tool_dir).unwrap();
+        let content = std::fs::read_to_string(tool_dir.join("installed")).unwrap();
+        assert_eq!(content.trim(), "a");
+    }
+}
@@ -1,12 +1,15 @@
 use crate::components;
 use crate::core::{parser::dist_manifest::DistManifest, GlobalOpts};
 use crate::fingerprint::InstallationRecord;
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use rim_common::types::ToolkitManifest;
-use rim_common::{types::TomlParser, utils};
-use semver::Version;
-use serde::Serialize;
+use rim_common::{build_config, types::TomlParser, utils};
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tokio::sync::{Mutex, OnceCell};
+use url::Url;
 
 use super::parser::dist_manifest::DistPackage;
 use super::ToolkitManifestExt;
@@ -79,6 +82,116 @@ impl Toolkit {
             Ok(Some(mutex))
         }
     }
+
+    /// Compare the components of this toolkit with another one, assuming that
+    /// every component of `self` is currently installed.
+    ///
+    /// - Components that will be installed by default (required or not optional)
+    ///   in `other` but are missing in `self` are considered as `added`.
+    /// - Components in `self` that are missing in `other` are considered as `removed`.
+    /// - Components that are present in both but have different versions are considered as `changed`.
+    pub fn diff(&self, other: &Toolkit) -> ToolkitDiff {
+        let mut diff = ToolkitDiff::default();
+
+        for comp in &other.components {
+            let maybe_current = self.components.iter().find(|c| c.name == comp.name);
+            match maybe_current {
+                Some(cur) if cur.version != comp.version => {
+                    diff.changed.push(ComponentChange {
+                        name: comp.name.clone(),
+                        old_version: cur.version.clone(),
+                        new_version: comp.version.clone(),
+                    });
+                }
+                None if comp.required || !comp.optional => {
+                    diff.added.push(ComponentChange {
+                        name: comp.name.clone(),
+                        old_version: None,
+                        new_version: comp.version.clone(),
+                    });
+                }
+                _ => (),
+            }
+        }
+        for comp in &self.components {
+            if !other.components.iter().any(|c| c.name == comp.name) {
+                diff.removed.push(ComponentChange {
+                    name: comp.name.clone(),
+                    old_version: comp.version.clone(),
+                    new_version: None,
+                });
+            }
+        }
+
+        diff
+    }
+}
+
+/// The component-level differences between two toolkits, check [`Toolkit::diff`] for details.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct ToolkitDiff {
+    pub added: Vec<ComponentChange>,
+    pub removed: Vec<ComponentChange>,
+    pub changed: Vec<ComponentChange>,
+}
+
+impl ToolkitDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentChange {
+    pub name: String,
+    pub old_version: Option<String>,
+    pub new_version: Option<String>,
+}
+
+/// An installed component that has a newer version available.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutdatedComponent {
+    pub name: String,
+    pub installed_version: String,
+    pub latest_version: String,
+}
+
+impl OutdatedComponent {
+    /// Return an [`OutdatedComponent`] if `latest` is newer than the `installed` version.
+    ///
+    /// Versions are compared using `semver` after being trimmed, and the ones that
+    /// cannot be compared are never considered as outdated.
+    pub fn check(name: &str, installed: &str, latest: &str) -> Option<Self> {
+        let parse = |raw: &str| trim_version(raw).parse::<Version>().ok();
+        let (cur, new) = (parse(installed)?, parse(latest)?);
+        (new > cur).then(|| Self {
+            name: name.to_string(),
+            installed_version: installed.to_string(),
+            latest_version: latest.to_string(),
+        })
+    }
+}
+
+/// Find the `installed` components that have newer versions in the `latest` ones.
+pub fn outdated_components(
+    installed: &[components::Component],
+    latest: &[components::Component],
+) -> Vec<OutdatedComponent> {
+    installed
+        .iter()
+        .filter(|comp| comp.installed)
+        .filter_map(|comp| {
+            let cur_ver = comp.version.as_deref()?;
+            let new_ver = latest
+                .iter()
+                .find(|c| c.name == comp.name)?
+                .version
+                .as_deref()?;
+            OutdatedComponent::check(&comp.name, cur_ver, new_ver)
+        })
+        .collect()
 }
 
 impl From<DistPackage> for Toolkit {
@@ -113,33 +226,99 @@ impl TryFrom<&ToolkitManifest> for Toolkit {
     }
 }
 
-/// Download the dist manifest from server to get the list of all provided toolkits.
+/// Get a list of available toolkits from the distribution manifest of the dist server.
 ///
 /// The result will be sorted by the version.
 ///
-/// The collection will always be cached to reduce the number of server requests.
-// TODO: track how many times this function was called, are all server requests necessary?
-// if not, cached them locally.
+/// The distribution manifest will be cached locally, and the cached copy will be used
+/// directly if it is not older than [`MAX_CACHE_AGE`]. If the server is unreachable,
+/// the cached copy will be used as well regardless of its age, with a warning.
 pub(crate) async fn toolkits_from_server(insecure: bool) -> Result<Vec<Toolkit>> {
     let dist_server = super::rim_dist_server();
+    let cache = DistManifestCache::load(&dist_server);
+    if let Some(fresh) = cache.as_ref().filter(|c| !c.is_expired()) {
+        debug!("using cached distribution manifest");
+        return toolkits_from_dist_manifest(&fresh.content);
+    }
 
+    // network access is checked when downloading, so that the cached copy
+    // can still be used when offline.
+    let fetched = fetch_dist_manifest(&dist_server, insecure).await;
+    if let Ok(raw) = &fetched {
+        if let Err(e) = DistManifestCache::new(dist_server.clone(), raw.clone()).write() {
+            warn!("unable to cache distribution manifest: {e}");
+        }
+    }
+    toolkits_from_fetched_or_cache(&dist_server, fetched, cache)
+}
+
+/// Get a list of toolkits from the `fetched` distribution manifest, falling back to
+/// the (possibly outdated) `cache` if the fetch failed.
+fn toolkits_from_fetched_or_cache(
+    dist_server: &Url,
+    fetched: Result<String>,
+    cache: Option<DistManifestCache>,
+) -> Result<Vec<Toolkit>> {
+    let err = match fetched {
+        Ok(raw) => return toolkits_from_dist_manifest(&raw),
+        Err(e) => e,
+    };
+    let Some(stale) = cache else {
+        return Err(err.context(t!("dist_manifest_unavailable", server = dist_server)));
+    };
+    warn!(
+        "{}",
+        t!(
+            "using_cached_dist_manifest",
+            hours = stale.age().as_secs() / 3600,
+            reason = err
+        )
+    );
+    toolkits_from_dist_manifest(&stale.content)
+}
+
+/// Get a list of available toolkits from the locally cached distribution manifest only,
+/// without accessing the dist server.
+pub(crate) fn toolkits_from_cache() -> Result<Vec<Toolkit>> {
+    let cache = DistManifestCache::load(&super::rim_dist_server())
+        .with_context(|| t!("no_cached_dist_manifest"))?;
+    if cache.is_expired() {
+        warn!(
+            "{}",
+            t!(
+                "cached_dist_manifest_outdated",
+                hours = cache.age().as_secs() / 3600
+            )
+        );
+    }
+    toolkits_from_dist_manifest(&cache.content)
+}
+
+/// Download the distribution manifest from `dist_server`, failed attempts caused by
+/// network errors or server errors will be retried with backoff.
+async fn fetch_dist_manifest(dist_server: &Url, insecure: bool) -> Result<String> {
     // download dist manifest from server
     let dist_m_filename = DistManifest::FILENAME;
     info!("{} {dist_m_filename}", t!("fetching"));
-    let dist_m_url = utils::url_join(&dist_server, format!("dist/{dist_m_filename}"))?;
+    let dist_m_url = utils::url_join(dist_server, format!("dist/{dist_m_filename}"))?;
     let dist_m_file = utils::make_temp_file("dist-manifest-", None)?;
     utils::DownloadOpt::new("distribution manifest", GlobalOpts::get().quiet)
         .insecure(insecure)
+        .localized(true)
         .download(&dist_m_url, dist_m_file.path())
         .await?;
     debug!("distribution manifest file successfully downloaded!");
 
+    utils::read_to_string("distribution manifest", dist_m_file.path())
+}
+
+fn toolkits_from_dist_manifest(raw: &str) -> Result<Vec<Toolkit>> {
     // load dist "pacakges" then convert them into `toolkit`s
-    let packages = DistManifest::load(dist_m_file.path())?.packages;
+    let packages = DistManifest::from_str(raw)?.packages;
     let mut toolkits: Vec<Toolkit> = packages.into_iter().map(Toolkit::from).collect();
     toolkits.sort_by(|a, b| trim_version(&b.version).cmp(trim_version(&a.version)));
     debug!(
-        "detected {} available toolkits by accessing server:\n{}",
+        "detected {} available toolkits:\n{}",
         toolkits.len(),
         toolkits
             .iter()
@@ -150,6 +329,67 @@ pub(crate) async fn toolkits_from_server(insecure: bool) -> Result<Vec<Toolkit>>
     Ok(toolkits)
 }
 
+/// The maximum age of the cached distribution manifest, a fresh fetch will be
+/// forced once the cache gets older than this.
+const MAX_CACHE_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A local copy of the last successfully fetched [`DistManifest`].
+#[derive(Debug, Deserialize, Serialize)]
+struct DistManifestCache {
+    /// The dist server that the manifest was fetched from.
+    server: Url,
+    /// Unix timestamp (in seconds) of when the manifest was fetched.
+    timestamp: u64,
+    /// The raw content of the distribution manifest.
+    content: String,
+}
+
+impl TomlParser for DistManifestCache {
+    const FILENAME: &'static str = "distribution-manifest-cache.toml";
+}
+
+impl DistManifestCache {
+    fn new(server: Url, content: String) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        Self {
+            server,
+            timestamp,
+            content,
+        }
+    }
+
+    fn cache_dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(&build_config().identifier))
+    }
+
+    /// Load the cached manifest that was fetched from `server`,
+    /// return `None` if there isn't one.
+    fn load(server: &Url) -> Option<Self> {
+        let cache = Self::load_from_dir(Self::cache_dir()?).ok()?;
+        (&cache.server == server).then_some(cache)
+    }
+
+    fn write(&self) -> Result<()> {
+        let dir = Self::cache_dir().context("unable to determine the config directory")?;
+        utils::ensure_dir(&dir)?;
+        self.write_to_dir(dir)
+    }
+
+    fn age(&self) -> Duration {
+        let fetched_at = UNIX_EPOCH + Duration::from_secs(self.timestamp);
+        SystemTime::now()
+            .duration_since(fetched_at)
+            .unwrap_or_default()
+    }
+
+    fn is_expired(&self) -> bool {
+        self.age() > MAX_CACHE_AGE
+    }
+}
+
 /// Return a list of all toolkits that are not currently installed.
 pub async fn installable_toolkits(reload_cache: bool, insecure: bool) -> Result<Vec<Toolkit>> {
     info!("{}", t!("checking_toolkit_updates"));
@@ -207,6 +447,58 @@ pub async fn latest_installable_toolkit(
     }
 }
 
+/// Get the toolkit with specific `version` from server, which has the same edition
+/// as the `installed` one.
+///
+/// `version` could be an exact version, such as `1.0.0` or `stable 1.80.1`, or a
+/// semver requirement, such as `^1.0` or `>=1.0, <2.0`, in which case the highest
+/// matching version will be returned.
+pub async fn toolkit_with_version(
+    installed: &Toolkit,
+    version: &str,
+    insecure: bool,
+) -> Result<Toolkit> {
+    let toolkits = toolkits_from_server(insecure)
+        .await?
+        .into_iter()
+        // make sure they are the same **product**
+        .filter(|tk| tk.edition == installed.edition)
+        .collect();
+    find_toolkit_by_version(toolkits, version)
+}
+
+fn find_toolkit_by_version(toolkits: Vec<Toolkit>, version: &str) -> Result<Toolkit> {
+    let exact_match = toolkits
+        .iter()
+        .position(|tk| tk.version == version || trim_version(&tk.version) == version);
+    let matched = exact_match.or_else(|| {
+        let req = VersionReq::parse(version).ok()?;
+        toolkits
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, tk)| {
+                let ver = trim_version(&tk.version).parse::<Version>().ok()?;
+                req.matches(&ver).then_some((idx, ver))
+            })
+            .max_by(|(_, a), (_, b)| a.cmp(b))
+            .map(|(idx, _)| idx)
+    });
+
+    let Some(idx) = matched else {
+        let available = toolkits
+            .iter()
+            .map(|tk| tk.version.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+        bail!(t!(
+            "toolkit_version_not_found",
+            version = version,
+            available = available
+        ));
+    };
+    Ok(toolkits.into_iter().nth(idx).unwrap())
+}
+
 // For some reason, the version might contains prefixes such as "stable 1.80.1",
 // therefore we need to trim them so that `semver` can be used to parse the actual
 // version string.
@@ -215,3 +507,168 @@ pub async fn latest_installable_toolkit(
 fn trim_version(raw: &str) -> &str {
     raw.trim_start_matches(|c| !char::is_ascii_digit(&c))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::Component;
+
+    fn toolkit_with(components: Vec<Component>) -> Toolkit {
+        Toolkit {
+            name: "test".into(),
+            version: "1.0.0".into(),
+            edition: None,
+            desc: None,
+            info: None,
+            manifest_url: None,
+            components,
+        }
+    }
+
+    #[test]
+    fn toolkit_components_diff() {
+        let installed = toolkit_with(vec![
+            Component::new("rustc").with_version(Some("1.80.0")),
+            Component::new("a").with_version(Some("0.1.0")),
+            Component::new("b").with_version(Some("0.1.0")),
+        ]);
+        let other = toolkit_with(vec![
+            Component::new("rustc").with_version(Some("1.81.0")),
+            Component::new("a").with_version(Some("0.1.0")),
+            Component::new("c").with_version(Some("1.0.0")),
+            Component::new("d")
+                .with_version(Some("1.0.0"))
+                .optional(true),
+        ]);
+
+        let diff = installed.diff(&other);
+        assert_eq!(
+            diff.added,
+            vec![ComponentChange {
+                name: "c".into(),
+                old_version: None,
+                new_version: Some("1.0.0".into()),
+            }]
+        );
+        assert_eq!(
+            diff.removed,
+            vec![ComponentChange {
+                name: "b".into(),
+                old_version: Some("0.1.0".into()),
+                new_version: None,
+            }]
+        );
+        assert_eq!(
+            diff.changed,
+            vec![ComponentChange {
+                name: "rustc".into(),
+                old_version: Some("1.80.0".into()),
+                new_version: Some("1.81.0".into()),
+            }]
+        );
+
+        assert!(installed.diff(&installed).is_empty());
+    }
+
+    #[test]
+    fn find_outdated_components() {
+        let installed = vec![
+            Component::new("rustc")
+                .with_version(Some("stable 1.80.0"))
+                .installed(true),
+            Component::new("a")
+                .with_version(Some("0.1.0"))
+                .installed(true),
+            Component::new("b")
+                .with_version(Some("0.2.0"))
+                .installed(true),
+            Component::new("c")
+                .with_version(Some("nightly"))
+                .installed(true),
+            Component::new("d").with_version(Some("0.1.0")),
+        ];
+        let latest = vec![
+            Component::new("rustc").with_version(Some("1.81.0")),
+            Component::new("a").with_version(Some("0.1.0")),
+            Component::new("b").with_version(Some("0.1.0")),
+            Component::new("c").with_version(Some("1.0.0")),
+            Component::new("d").with_version(Some("1.0.0")),
+        ];
+
+        assert_eq!(
+            outdated_components(&installed, &latest),
+            vec![OutdatedComponent {
+                name: "rustc".into(),
+                installed_version: "stable 1.80.0".into(),
+                latest_version: "1.81.0".into(),
+            }]
+        );
+        assert!(outdated_components(&installed, &[]).is_empty());
+    }
+
+    #[test]
+    fn dist_manifest_cache_expiration() {
+        let server: Url = "https://example.com/".parse().unwrap();
+        let mut cache = DistManifestCache::new(server, "[[packages]]".into());
+        assert!(!cache.is_expired());
+
+        cache.timestamp -= MAX_CACHE_AGE.as_secs() + 60;
+        assert!(cache.is_expired());
+        assert!(cache.age() > MAX_CACHE_AGE);
+
+        let raw = cache.to_toml().unwrap();
+        let loaded = DistManifestCache::from_str(&raw).unwrap();
+        assert_eq!(loaded.timestamp, cache.timestamp);
+        assert_eq!(loaded.content, cache.content);
+    }
+
+    #[test]
+    fn fallback_to_cached_dist_manifest() {
+        let server: Url = "https://example.com/".parse().unwrap();
+        let raw = r#"
+[[packages]]
+name = "A"
+version = "1.0"
+manifest-url = "https://example.com/a/manifest-1.0"
+"#;
+        let mut stale = DistManifestCache::new(server.clone(), raw.into());
+        stale.timestamp -= MAX_CACHE_AGE.as_secs() + 60;
+
+        let failed = || Err(anyhow::anyhow!("connection refused"));
+        let toolkits = toolkits_from_fetched_or_cache(&server, failed(), Some(stale)).unwrap();
+        assert_eq!(toolkits.len(), 1);
+        assert_eq!(toolkits[0].version, "1.0");
+
+        let toolkits = toolkits_from_fetched_or_cache(&server, Ok(raw.into()), None).unwrap();
+        assert_eq!(toolkits.len(), 1);
+
+        let err = toolkits_from_fetched_or_cache(&server, failed(), None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            t!("dist_manifest_unavailable", server = server)
+        );
+        assert!(format!("{err:#}").contains("connection refused"));
+    }
+
+    #[test]
+    fn find_toolkit_with_version() {
+        let toolkits = || {
+            ["1.2.0", "stable 1.1.5", "1.1.0", "1.0.0"]
+                .into_iter()
+                .map(|ver| Toolkit {
+                    version: ver.into(),
+                    ..toolkit_with(vec![])
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let found = |ver: &str| find_toolkit_by_version(toolkits(), ver).map(|tk| tk.version);
+        assert_eq!(found("1.1.0").unwrap(), "1.1.0");
+        assert_eq!(found("stable 1.1.5").unwrap(), "stable 1.1.5");
+        assert_eq!(found("1.1.5").unwrap(), "stable 1.1.5");
+        assert_eq!(found("~1.1").unwrap(), "stable 1.1.5");
+        assert_eq!(found(">=1.0, <1.1").unwrap(), "1.0.0");
+        assert_eq!(found("1").unwrap(), "1.2.0");
+        assert!(found("2.0.0").is_err());
+    }
+}
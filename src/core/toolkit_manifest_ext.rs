@@ -1,19 +1,23 @@
 //! `ToolsetManifest` contains information about each dist package,
 //! such as its name, version, and what's included etc.
 
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
 
-use anyhow::{anyhow, Result};
-use rim_common::types::{TomlParser, ToolInfo, ToolMap, ToolkitManifest};
-use rim_common::utils;
+use anyhow::{anyhow, bail, Context, Result};
+use minisign_verify::{PublicKey, Signature};
+use rim_common::types::{TomlParser, ToolInfo, ToolMap, ToolkitManifest, DROP_IN_DIR};
+use rim_common::utils::{CacheValidators, ConditionalText};
+use rim_common::{build_config, utils};
 use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 use url::Url;
 
 use crate::components::{Component, ComponentType};
-use crate::core::{custom_instructions, GlobalOpts};
+use crate::core::{custom_instructions, host_triple, target_triple, GlobalOpts};
 
 use super::AppInfo;
 
@@ -32,16 +36,26 @@ where
         Self::load(root.join(Self::FILENAME))
     }
 
-    /// Get the tools that are only available in current target.
+    /// Get the tools that are available in current target,
+    /// check [`tools_for_target`](ToolkitManifestExt::tools_for_target) for details.
     ///
     /// Return `None` if there are no available tools in the current target.
-    fn current_target_tools(&self) -> Option<&ToolMap>;
+    fn current_target_tools(&self) -> Option<Cow<'_, ToolMap>>;
 
     /// Get the mut reference to the tools that are only available in current target.
     ///
+    /// Note that only the tools under the exact target key are included, tools of
+    /// wildcard keys and `all` are not.
+    ///
     /// Return `None` if there are no available tools in the current target.
     fn current_target_tools_mut(&mut self) -> Option<&mut ToolMap>;
 
+    /// Get the tools that are available in `triple`, including the ones under the glob pattern
+    /// and `all` keys, check [`Tools::for_target`](rim_common::types::Tools::for_target).
+    ///
+    /// Return `None` if there are no available tools in `triple`.
+    fn tools_for_target(&self, triple: &str) -> Option<Cow<'_, ToolMap>>;
+
     /// Like [`current_target_tools`](ToolkitExt::current_target_tools) but
     /// getting a list of tools and components as [`Component`].
     ///
@@ -49,7 +63,7 @@ where
     /// to see if a specific tool is already installed or not.
     fn current_target_components(&self, check_for_existence: bool) -> Result<Vec<Component>>;
 
-    /// Get the path to bundled `rustup-init` binary if there has one.
+    /// Get the path to bundled `rustup-init` binary of the host if there has one.
     fn rustup_bin(&self) -> Result<Option<PathBuf>>;
 
     /// Returns the absolute path of the package root.
@@ -77,8 +91,12 @@ where
     /// 2. If the manifest was not loaded from path,
     ///    all relative paths will be forced to combine with the parent directory of this executable.
     ///    (Assuming the manifest was baked in the executable)
+    /// 3. Environment variables in the form of `${VAR}` or `$VAR` will be expanded
+    ///    before converting, this applies to `path` sources and `offline-dist-server`.
     ///
     /// # Errors
+    /// Return `Result::Err` if any referenced environment variable is not set.
+    ///
     /// Return `Result::Err` if the manifest was not loaded from path, and the current executable path
     /// cannot be determined as well.
     fn adjust_paths(&mut self) -> Result<()>;
@@ -102,7 +120,7 @@ where
 
 impl ToolkitManifestExt for ToolkitManifest {
     fn rustup_bin(&self) -> Result<Option<PathBuf>> {
-        let cur_target = env!("TARGET");
+        let cur_target = host_triple();
         let par_dir = self.package_root()?;
         let rel_path = self.rust.rustup.get(cur_target);
 
@@ -121,16 +139,19 @@ impl ToolkitManifestExt for ToolkitManifest {
             .map_err(|_| anyhow!("path '{}' cannot be converted to URL", full_path.display()))
     }
 
-    fn current_target_tools(&self) -> Option<&ToolMap> {
-        let cur_target = env!("TARGET");
-        self.tools.target.get(cur_target)
+    fn current_target_tools(&self) -> Option<Cow<'_, ToolMap>> {
+        self.tools_for_target(target_triple())
     }
 
     fn current_target_tools_mut(&mut self) -> Option<&mut ToolMap> {
-        let cur_target = env!("TARGET");
+        let cur_target = target_triple();
         self.tools.target.get_mut(cur_target)
     }
 
+    fn tools_for_target(&self, triple: &str) -> Option<Cow<'_, ToolMap>> {
+        self.tools.for_target(triple)
+    }
+
     fn current_target_components(&self, check_for_existence: bool) -> Result<Vec<Component>> {
         let tc_channel = &self.rust.channel;
 
@@ -158,27 +179,32 @@ impl ToolkitManifestExt for ToolkitManifest {
         }
 
         if let Some(tools) = self.current_target_tools() {
-            let installed_in_env = if check_for_existence {
-                // components that are already installed in user's machine, such as vscode, or mingw.
+            // components that are already installed in user's machine, such as vscode, or mingw,
+            // with their versions if those can be detected.
+            let installed_in_env: HashMap<&str, Option<String>> = if check_for_existence {
                 tools
                     .keys()
-                    .filter_map(|name| {
-                        custom_instructions::is_installed(name).then_some(name.as_str())
-                    })
+                    .filter(|name| custom_instructions::is_installed(name))
+                    .map(|name| (name.as_str(), custom_instructions::installed_version(name)))
                     .collect()
             } else {
-                vec![]
+                HashMap::new()
             };
             let filter_out_gui_tools = |a: &(&str, &ToolInfo)| -> bool {
                 utils::has_desktop_environment() || !a.1.is_gui_only()
             };
 
             for (tool_name, tool_info) in tools.iter().filter(filter_out_gui_tools) {
-                let installed = installed_in_env.contains(&tool_name);
-                let version = if check_for_existence && installed {
+                let installed_version = installed_in_env.get(tool_name);
+                let installed = installed_version.is_some();
+                let update_available = installed_version
+                    .and_then(Option::as_deref)
+                    .zip(tool_info.version())
+                    .is_some_and(|(cur, target)| custom_instructions::is_outdated(cur, target));
+                let version = if installed && !update_available {
                     // if the tool is already installed but we are doing a fresh install here,
                     // which means it was installed by user not by `rim`,
-                    // therefore we don't know the version.
+                    // therefore we don't know the version, unless it can be updated.
                     None
                 } else {
                     tool_info.version()
@@ -191,8 +217,10 @@ impl ToolkitManifestExt for ToolkitManifest {
                         .required(tool_info.is_required())
                         .optional(tool_info.is_optional())
                         .installed(installed)
+                        .update_available(update_available)
                         .with_version(version)
-                        .with_display_name(tool_info.display_name().unwrap_or(tool_name)),
+                        .with_display_name(tool_info.display_name().unwrap_or(tool_name))
+                        .with_download_size(tool_info.size()),
                 );
             }
         }
@@ -215,7 +243,7 @@ impl ToolkitManifestExt for ToolkitManifest {
                     .map(|s| format!("-{s}"))
                     .unwrap_or_default()
             ));
-            dir.push(env!("TARGET"));
+            dir.push(target_triple());
             dir
         } else {
             std::env::current_exe()?
@@ -229,9 +257,15 @@ impl ToolkitManifestExt for ToolkitManifest {
     fn adjust_paths(&mut self) -> Result<()> {
         let parent_dir = self.package_root()?;
 
+        if let Some(server) = &mut self.rust.offline_dist_server {
+            *server = expand_env_vars(server)?;
+        }
         for tool in self.tools.target.values_mut() {
             for tool_info in tool.values_mut() {
                 if let Some(path) = tool_info.path_mut() {
+                    if let Some(raw) = path.to_str() {
+                        *path = expand_env_vars(raw)?.into();
+                    }
                     *path = utils::to_normalized_absolute_path(path.as_path(), Some(&parent_dir))?;
                 }
             }
@@ -273,6 +307,64 @@ impl ToolkitManifestExt for ToolkitManifest {
     }
 }
 
+/// Replace every `${VAR}` or `$VAR` token in the given string with
+/// the value of the corresponding environment variable.
+///
+/// A `$` that is not followed by a valid variable name is kept as is.
+///
+/// # Errors
+/// Return `Result::Err` if any referenced variable is not set.
+fn expand_env_vars(raw: &str) -> Result<String> {
+    let mut result = String::with_capacity(raw.len());
+    let mut rest = raw;
+
+    while let Some(pos) = rest.find('$') {
+        result.push_str(&rest[..pos]);
+        rest = &rest[pos + 1..];
+
+        let (name, remaining) = if let Some(braced) = rest.strip_prefix('{') {
+            let end = braced
+                .find('}')
+                .ok_or_else(|| anyhow!("unclosed variable reference in '{raw}'"))?;
+            (&braced[..end], &braced[end + 1..])
+        } else {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            rest.split_at(end)
+        };
+
+        if name.is_empty() {
+            result.push('$');
+            continue;
+        }
+        let value = std::env::var(name).map_err(|_| {
+            anyhow!("environment variable '{name}' referenced in '{raw}' is not set")
+        })?;
+        result.push_str(&value);
+        rest = remaining;
+    }
+    result.push_str(rest);
+
+    Ok(result)
+}
+
+/// Env var to specify the directory of drop-in manifests, check [`drop_in_dir`].
+pub(crate) const RIM_COMPONENTS_DIR: &str = "RIM_COMPONENTS_DIR";
+
+/// Get the directory of drop-in manifests, which is the one specified by `RIM_COMPONENTS_DIR`,
+/// or the [`DROP_IN_DIR`] next to this program (which is the install dir in manager mode).
+///
+/// Check [`ToolkitManifest::merge_drop_ins`] for how drop-in manifests are merged.
+pub(crate) fn drop_in_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os(RIM_COMPONENTS_DIR).filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(dir));
+    }
+    utils::parent_dir_of_cur_exe()
+        .ok()
+        .map(|dir| dir.join(DROP_IN_DIR))
+}
+
 /// Get the content of baked-in toolset manifest as `str`.
 pub(crate) fn baked_in_manifest_raw(no_web: bool) -> &'static str {
     if no_web {
@@ -295,6 +387,8 @@ pub(crate) fn baked_in_manifest_raw(no_web: bool) -> &'static str {
 /// - Download from specific url, which could have file schema.
 /// - Load from `baked_in_manifest_raw`.
 ///
+/// Then merge the drop-in manifests under [`drop_in_dir`] into it.
+///
 pub async fn get_toolkit_manifest(url: Option<Url>, insecure: bool) -> Result<ToolkitManifest> {
     /// During the lifetime of program (in manager mode), manifest could be loaded multiple times,
     /// each time requires communicating with server if not cached, which is not ideal.
@@ -317,30 +411,183 @@ pub async fn get_toolkit_manifest(url: Option<Url>, insecure: bool) -> Result<To
     }
 
     // ========== We don't have it yet, so, load the manifest and cache it ============
-    let manifest = if let Some(url) = &url {
+    let mut manifest = if let Some(url) = &url {
+        utils::ensure_network_allowed(url)?;
         debug!("downloading toolset manifest from {url}");
-        let temp = utils::make_temp_file("toolset-manifest-", None)?;
-        utils::DownloadOpt::new("toolset manifest", GlobalOpts::get().quiet)
-            .insecure(insecure)
-            .download(url, temp.path())
-            .await?;
-        ToolkitManifest::load(temp.path())?
+        download_toolkit_manifest(url, insecure).await?
     } else {
         debug!("loading built-in toolset manifest");
-        cfg_if::cfg_if! {
-            if #[cfg(feature = "no-web")] {
-                ToolkitManifest::from_str(baked_in_manifest_raw(true))?.offline(true)
-            } else {
-                ToolkitManifest::from_str(baked_in_manifest_raw(false))?
-            }
-        }
+        // the online manifest is useless when network access was disabled at runtime
+        let no_web = cfg!(feature = "no-web") || utils::is_offline();
+        ToolkitManifest::from_str(baked_in_manifest_raw(no_web))?.offline(no_web)
     };
+    if let Some(dir) = drop_in_dir() {
+        manifest.merge_drop_ins(&dir)?;
+    }
+    // fail early on misconfigured URLs, rather than when downloading with them
+    manifest.validate()?;
     debug!("caching toolset manifest in memory");
     guard.insert(url, manifest.clone());
 
     Ok(manifest)
 }
 
+/// Download the manifest from `url`, for remote ones, a conditional request will be sent
+/// with the validators of the cached copy on disk, which will be used instead
+/// if the server responded with `304 Not Modified`.
+async fn download_toolkit_manifest(url: &Url, insecure: bool) -> Result<ToolkitManifest> {
+    let temp = utils::make_temp_file("toolset-manifest-", None)?;
+    let opt = utils::DownloadOpt::new("toolset manifest", GlobalOpts::get().quiet)
+        .insecure(insecure)
+        .localized(true);
+    if url.scheme() == "file" {
+        opt.download(url, temp.path()).await?;
+        return load_verified_manifest(url, temp.path(), insecure).await;
+    }
+
+    let cache = ManifestCache::load(url);
+    let validators = cache
+        .as_ref()
+        .map(|c| c.validators.clone())
+        .unwrap_or_default();
+    let (content, fresh_cache) = match opt.read_if_modified(url, &validators).await? {
+        ConditionalText::NotModified => {
+            debug!("toolset manifest was not modified, using the cached one");
+            let cache = cache
+                .with_context(|| format!("unexpected '304 Not Modified' response from '{url}'"))?;
+            (cache.content, None)
+        }
+        ConditionalText::Modified(content, validators) => {
+            let fresh_cache = (!validators.is_empty())
+                .then(|| ManifestCache::new(url.clone(), validators, content.clone()));
+            (content, fresh_cache)
+        }
+    };
+    // NB: not using `utils::write_file` as it appends a newline, breaking the signature
+    std::fs::write(temp.path(), &content)?;
+    // the cached copy is verified as well, in case it was modified locally
+    let manifest = match load_verified_manifest(url, temp.path(), insecure).await {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            // drop the bad copy, so the next run downloads the manifest again
+            // instead of being told it was not modified
+            if fresh_cache.is_none() {
+                ManifestCache::remove(url);
+            }
+            return Err(e);
+        }
+    };
+    // only cache the manifest once it was verified, otherwise an invalid one would be
+    // used for as long as the server responds with `304 Not Modified`
+    if let Some(cache) = fresh_cache {
+        if let Err(e) = cache.write() {
+            debug!("unable to cache toolset manifest: {e:#}");
+        }
+    }
+    Ok(manifest)
+}
+
+async fn load_verified_manifest(url: &Url, path: &Path, insecure: bool) -> Result<ToolkitManifest> {
+    if build_config().require_signature {
+        verify_downloaded_manifest(url, path, insecure).await?;
+    }
+    ToolkitManifest::load(path)
+}
+
+/// A local copy of a toolkit manifest downloaded from server, along with its
+/// [`CacheValidators`], so that the manifest can be revalidated instead of
+/// downloaded again in later sessions.
+#[derive(Debug, Deserialize, Serialize)]
+struct ManifestCache {
+    url: Url,
+    /// The locale that the manifest was requested in, as its content might be localized.
+    locale: String,
+    #[serde(flatten)]
+    validators: CacheValidators,
+    content: String,
+}
+
+impl TomlParser for ManifestCache {
+    const FILENAME: &'static str = "toolkit-manifest-cache.toml";
+}
+
+impl ManifestCache {
+    fn new(url: Url, validators: CacheValidators, content: String) -> Self {
+        Self {
+            url,
+            locale: rust_i18n::locale().to_string(),
+            validators,
+            content,
+        }
+    }
+
+    /// Each manifest is cached in its own file under the user's cache directory,
+    /// named after the hash of its url and locale.
+    fn path(url: &Url, locale: &str) -> Option<PathBuf> {
+        let dir = dirs::cache_dir()?
+            .join(&build_config().identifier)
+            .join("manifests");
+        let name = utils::sha256_hex(format!("{url}#{locale}"));
+        Some(dir.join(format!("{name}.toml")))
+    }
+
+    /// Load the cached manifest that was downloaded from `url` in the current locale,
+    /// return `None` if there isn't one.
+    fn load(url: &Url) -> Option<Self> {
+        let locale = rust_i18n::locale().to_string();
+        let cache = <Self as TomlParser>::load(Self::path(url, &locale)?).ok()?;
+        (&cache.url == url && cache.locale == locale).then_some(cache)
+    }
+
+    fn write(&self) -> Result<()> {
+        let path = Self::path(&self.url, &self.locale)
+            .context("unable to determine the cache directory")?;
+        if let Some(dir) = path.parent() {
+            utils::ensure_dir(dir)?;
+        }
+        utils::write_file(path, &self.to_toml()?, false)
+    }
+
+    /// Remove the cached manifest that was downloaded from `url` in the current locale.
+    fn remove(url: &Url) {
+        if let Some(path) = Self::path(url, &rust_i18n::locale()) {
+            _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Fetch the detached `minisign` signature (`<url>.minisig`) of the manifest downloaded
+/// from `url`, then verify the manifest located at `path` with it.
+async fn verify_downloaded_manifest(url: &Url, path: &Path, insecure: bool) -> Result<()> {
+    let public_key = build_config()
+        .manifest_public_key
+        .as_deref()
+        .ok_or_else(|| anyhow!(t!("manifest_public_key_missing")))?;
+
+    let mut sig_url = url.clone();
+    sig_url.set_path(&format!("{}.minisig", url.path()));
+    debug!("downloading toolset manifest signature from {sig_url}");
+    let signature = utils::DownloadOpt::new("toolset manifest signature", true)
+        .insecure(insecure)
+        .read(&sig_url)
+        .await?;
+    let content = std::fs::read(path)?;
+
+    verify_signature(&content, &signature, public_key)
+        .with_context(|| t!("manifest_signature_invalid", url = url))
+}
+
+/// Verify `content` with a `minisign` signature and a base64 encoded public key.
+fn verify_signature(content: &[u8], signature: &str, public_key: &str) -> Result<()> {
+    let public_key = PublicKey::from_base64(public_key.trim())
+        .map_err(|e| anyhow!("invalid public key: {e}"))?;
+    let signature = Signature::decode(signature).map_err(|e| anyhow!("invalid signature: {e}"))?;
+    if let Err(e) = public_key.verify(content, &signature, false) {
+        bail!("signature verification failed: {e}");
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -364,6 +611,7 @@ d = "0.1.0"
 
         let manifest = ToolkitManifest::from_str(input).unwrap();
         let tools = manifest.current_target_tools();
+        let tools = tools.as_deref();
 
         cfg_if::cfg_if! {
             if #[cfg(all(windows, target_env = "gnu"))] {
@@ -384,6 +632,70 @@ d = "0.1.0"
         }
     }
 
+    #[test]
+    fn tools_for_wildcard_targets() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-pc-windows-msvc]
+a = "0.1.0"
+[tools.target."*-pc-windows-*"]
+a = "0.2.0"
+b = "0.2.0"
+[tools.target."x86_64-*-linux-gnu"]
+c = "0.1.0"
+[tools.target.all]
+b = "0.3.0"
+d = { version = "0.1.0", identifier = "a" }
+e = "0.1.0"
+"#;
+
+        let manifest = ToolkitManifest::from_str(input).unwrap();
+        let versions = |triple: &str| {
+            manifest
+                .tools_for_target(triple)
+                .unwrap()
+                .iter()
+                .map(|(name, info)| format!("{name}@{}", info.version().unwrap()))
+                .collect::<Vec<_>>()
+        };
+
+        // exact > glob > all, and `d` is deduplicated by its identifier
+        assert_eq!(
+            versions("x86_64-pc-windows-msvc"),
+            ["a@0.1.0", "b@0.2.0", "e@0.1.0"]
+        );
+        assert_eq!(
+            versions("aarch64-pc-windows-gnullvm"),
+            ["a@0.2.0", "b@0.2.0", "e@0.1.0"]
+        );
+        assert_eq!(
+            versions("x86_64-unknown-linux-gnu"),
+            ["c@0.1.0", "b@0.3.0", "a@0.1.0", "e@0.1.0"]
+        );
+        assert_eq!(
+            versions("aarch64-unknown-linux-gnu"),
+            ["b@0.3.0", "a@0.1.0", "e@0.1.0"]
+        );
+    }
+
+    #[test]
+    fn tools_for_exact_target_are_borrowed() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-unknown-linux-gnu]
+a = "0.1.0"
+"#;
+
+        let manifest = ToolkitManifest::from_str(input).unwrap();
+        let tools = manifest.tools_for_target("x86_64-unknown-linux-gnu");
+        assert!(matches!(tools, Some(Cow::Borrowed(_))));
+        assert!(manifest.tools_for_target("aarch64-apple-darwin").is_none());
+    }
+
     #[test]
     fn with_offline_dist_server() {
         let input = r#"
@@ -410,6 +722,50 @@ offline-dist-server = "packages/"
         );
     }
 
+    #[test]
+    fn expand_env_vars_in_paths() {
+        std::env::set_var("RIM_TEST_EXPAND_PATH_DIR", "/path/to");
+        let input = r#"
+name = "kit"
+[rust]
+version = "1.0.0"
+offline-dist-server = "${RIM_TEST_EXPAND_PATH_DIR}/packages/"
+
+[tools.target.x86_64-pc-windows-msvc]
+a = { path = "${RIM_TEST_EXPAND_PATH_DIR}/a.zip" }
+[tools.target.x86_64-pc-windows-gnu]
+a = { path = "$RIM_TEST_EXPAND_PATH_DIR/a.zip" }
+[tools.target.x86_64-unknown-linux-gnu]
+a = { path = "$RIM_TEST_EXPAND_PATH_DIR/a.zip" }
+"#;
+        let mut manifest = ToolkitManifest::from_str(input).unwrap();
+        manifest.adjust_paths().unwrap();
+
+        assert_eq!(
+            manifest.rust.offline_dist_server.as_deref(),
+            Some("/path/to/packages/")
+        );
+        for tools in manifest.tools.target.values_mut() {
+            let path = tools.get_mut("a").unwrap().path_mut().unwrap();
+            assert!(path.ends_with("path/to/a.zip"));
+        }
+        assert_eq!(expand_env_vars("$ and ${}").unwrap(), "$ and ${}");
+    }
+
+    #[test]
+    fn expand_unset_env_var() {
+        let input = r#"
+[rust]
+version = "1.0.0"
+
+[tools.target.x86_64-unknown-linux-gnu]
+a = { path = "${RIM_TEST_UNSET_VAR}/a.zip" }
+"#;
+        let mut manifest = ToolkitManifest::from_str(input).unwrap();
+        let err = manifest.adjust_paths().unwrap_err();
+        assert!(err.to_string().contains("RIM_TEST_UNSET_VAR"));
+    }
+
     #[test]
     fn with_bundled_rustup() {
         let input = r#"
@@ -499,4 +855,43 @@ vscode-installer = { version = "1.97.1", url = "https://example.com", kind = "in
         assert_eq!(name, "vscode-installer");
         assert_eq!(info.kind(), Some(ToolKind::Installer));
     }
+
+    #[test]
+    fn verify_manifest_signature() {
+        const PUBLIC_KEY: &str = "RWRKWEtLx+YsoMNFpel/od67ymb3BW97YDYgx0VAVuwkxYMrekJcJdiC";
+        const SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RURKWEtLx+YsoCEh+Lm2ufCEoB9BW3nf+C1JcbMpMOW5PSeX6hU67gLLXf9rmDgPUvjGdTGNuuApEyaCFdhq5U4bQEBOR+CcDQ8=
+trusted comment: timestamp:1700000000 file:toolset-manifest.toml
+F2EdTJj2Fphf+bqYcqaNmQv6nc+Rkpx+HE5Gq7xjsWBzmHym0WolcLu4LjrY1+Zae9g/ZLSP3ShNb09VbwwaDg==
+";
+        let content = b"[rust]\nversion = \"1.0.0\"\n";
+        verify_signature(content, SIGNATURE, PUBLIC_KEY).unwrap();
+
+        let tampered = b"[rust]\nversion = \"6.6.6\"\n";
+        assert!(verify_signature(tampered, SIGNATURE, PUBLIC_KEY).is_err());
+
+        let other_key = "RWRfHgz9lcfluYQd0rk9L9SNEpYOVVwjGc1QT7GM6mflD9PUnKATfiWn";
+        assert!(verify_signature(content, SIGNATURE, other_key).is_err());
+    }
+
+    #[test]
+    fn manifest_cache_with_validators() {
+        let url: Url = "https://example.com/toolset-manifest.toml".parse().unwrap();
+        let validators = CacheValidators {
+            etag: Some("\"v1\"".into()),
+            last_modified: None,
+        };
+        let cache = ManifestCache::new(url.clone(), validators.clone(), "[rust]\n".into());
+
+        let parsed = ManifestCache::from_str(&cache.to_toml().unwrap()).unwrap();
+        assert_eq!(parsed.url, url);
+        assert_eq!(parsed.validators, validators);
+        assert_eq!(parsed.content, "[rust]\n");
+
+        // manifest of different locales are cached separately
+        assert_ne!(
+            ManifestCache::path(&url, "en-US"),
+            ManifestCache::path(&url, "zh-CN")
+        );
+    }
 }
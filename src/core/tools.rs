@@ -3,21 +3,30 @@ use std::{
     path::{Path, PathBuf},
     str::FromStr,
     sync::LazyLock,
+    time::{Duration, Instant},
 };
 
 use anyhow::{anyhow, bail, Context, Result};
 use rim_common::{
-    types::{TomlParser, ToolInfo, ToolKind},
+    types::{Proxy, TomlParser, ToolInfo, ToolKind},
     utils,
 };
+use url::Url;
 
 use super::{
     directories::RimDir,
     parser::{cargo_config::CargoConfig, cargo_manifest::CargoManifest, fingerprint::ToolRecord},
+    uninstall::Removal,
     GlobalOpts, PathExt, CARGO_HOME,
 };
 use crate::{
-    core::{check::RUNNER_TOOLCHAIN_NAME, custom_instructions},
+    core::{
+        check::RUNNER_TOOLCHAIN_NAME,
+        custom_instructions,
+        distro_package::{self, PackageFormat},
+        package_manager::PackageManager,
+        script,
+    },
     InstallConfiguration,
 };
 
@@ -91,7 +100,11 @@ impl<'a> Tool<'a> {
         }
 
         // Step 2: Identify from file extension (if it's a file ofc).
-        if utils::is_executable(path) {
+        if is_appimage(path) {
+            return Ok(Self::new(name, ToolKind::AppImage).with_path(path));
+        } else if path.is_file() && PackageFormat::from_path(path).is_some() {
+            return Ok(Self::new(name, ToolKind::DistroPackage).with_path(path));
+        } else if utils::is_executable(path) {
             return Ok(Self::new(name, ToolKind::Executables).with_path(path));
         } else if Plugin::is_supported(path) {
             return Ok(Self::new(name, ToolKind::Plugin).with_path(path));
@@ -186,8 +199,17 @@ impl<'a> Tool<'a> {
                     "install",
                     self.install_args.as_deref().unwrap_or(&[self.name()]),
                     config.cargo_home(),
+                    config.progress_indicator.as_ref(),
                 )?;
-                return Ok(ToolRecord::cargo_tool().with_version(info.version()));
+                // record the concrete version that cargo resolved a version requirement to
+                let version = match info.version() {
+                    Some(req) if is_version_requirement(req) => {
+                        installed_crate_version(self.name(), config.cargo_home())
+                            .or_else(|| Some(req.to_string()))
+                    }
+                    ver => ver.map(ToString::to_string),
+                };
+                return Ok(ToolRecord::cargo_tool().with_version(version));
             }
             ToolKind::Executables => {
                 let mut res = vec![];
@@ -199,21 +221,26 @@ impl<'a> Tool<'a> {
                 }
                 res
             }
+            ToolKind::AppImage => vec![install_appimage(config, self.name(), self.path.single()?)?],
+            ToolKind::DistroPackage => {
+                vec![distro_package::install(
+                    config,
+                    self.name(),
+                    self.path.single()?,
+                )?]
+            }
             ToolKind::Custom => {
                 custom_instructions::install(self.name(), self.path.single()?, config)?
             }
+            ToolKind::NpmPackage | ToolKind::PipPackage => bail!(
+                "'{}' can only be installed from its package registry",
+                self.name()
+            ),
             ToolKind::DirWithBin => {
                 let tool_dir = install_dir_with_bin_(config, self.name(), self.path.single()?)?;
                 vec![tool_dir]
             }
-            ToolKind::Plugin => {
-                let path = self.path.single()?;
-                // run the installation command.
-                Plugin::install(path)?;
-                // we need to "cache" to installer, so that we could uninstall with it.
-                let plugin_backup = utils::copy_into(path, config.tools_dir())?;
-                vec![plugin_backup]
-            }
+            ToolKind::Plugin => vec![Plugin::install(self.path.single()?, config.tools_dir())?],
             ToolKind::Installer => {
                 let path = self.path.single()?;
                 // Just run the installer and wait for finish.
@@ -226,6 +253,12 @@ impl<'a> Tool<'a> {
                 let backup = utils::copy_into(path, config.tools_dir())?;
                 vec![backup]
             }
+            ToolKind::Script => vec![script::install(
+                self.name(),
+                self.path.single()?,
+                config,
+                config.hooks_allowed(),
+            )?],
             ToolKind::RuleSet => install_rule_set(&self.path, config)?,
             ToolKind::Crate => install_crate(self.name(), &self.path, config)?,
             // Just throw it under `tools` dir
@@ -237,17 +270,23 @@ impl<'a> Tool<'a> {
         Ok(ToolRecord::new(self.kind)
             .with_paths(paths)
             .with_version(info.version())
-            .with_dependencies(info.dependencies().to_vec()))
+            .with_dependencies(info.dependencies().to_vec())
+            .with_uninstall_script(
+                info.uninstall_script()
+                    .filter(|_| self.kind == ToolKind::Script),
+            ))
     }
 
     /// Remove a tool from user's machine.
     pub(crate) fn uninstall<T: RimDir>(&self, config: T) -> Result<()> {
+        let tools_dir = config.tools_dir().to_path_buf();
         match self.kind {
             ToolKind::CargoTool => {
                 cargo_install_or_uninstall(
                     "uninstall",
                     self.install_args.as_deref().unwrap_or(&[self.name()]),
                     config.cargo_home(),
+                    None,
                 )?;
             }
             ToolKind::Executables => {
@@ -255,24 +294,208 @@ impl<'a> Tool<'a> {
                     fs::remove_file(binary)?;
                 }
             }
+            ToolKind::AppImage => uninstall_appimage(self.name(), self.path.single()?)?,
+            ToolKind::DistroPackage => distro_package::uninstall(self.name(), self.path.single()?)?,
             ToolKind::Custom => custom_instructions::uninstall(self.name(), config)?,
+            ToolKind::NpmPackage => PackageManager::Npm.uninstall(self.path.single()?)?,
+            ToolKind::PipPackage => PackageManager::Pip.uninstall(self.path.single()?)?,
             ToolKind::DirWithBin => uninstall_dir_with_bin_(self.path.single()?)?,
-            ToolKind::Plugin => Plugin::uninstall(self.path.single()?)?,
+            ToolKind::Plugin => Plugin::uninstall(self.path.single()?, config.tools_dir())?,
             ToolKind::Installer => {
                 // TODO: some installer have uninstall functionality but some may not,
                 // make a list of those and only execute it if it can be used for uninstallation
                 utils::remove(self.path.single()?)?;
             }
             ToolKind::Crate => uninstall_crate(self.name(), &self.path, config)?,
-            ToolKind::RuleSet | ToolKind::Unknown => {
+            // the uninstall script (if any) was already run before this
+            ToolKind::Script | ToolKind::RuleSet | ToolKind::Unknown => {
                 utils::remove(self.path.single()?)?;
             }
         }
+
+        // tools might be placed in a shared directory, which should be kept if other tools
+        // are still in there, so only the directories that became empty are removed.
+        for path in self.path.iter() {
+            utils::remove_empty_parents(path, &tools_dir)?;
+        }
         Ok(())
     }
+
+    /// Get what [`uninstall`](Self::uninstall) will remove for this tool,
+    /// without actually removing anything.
+    pub(crate) fn removals<T: RimDir>(&self, config: T) -> Result<Vec<Removal>> {
+        let tools_dir = config.tools_dir().to_path_buf();
+        let mut removals = match self.kind {
+            ToolKind::CargoTool => {
+                let name = [self.name()];
+                let args = self.install_args.as_deref().unwrap_or(&name);
+                vec![Removal::Command(format!(
+                    "cargo uninstall {}",
+                    args.join(" ")
+                ))]
+            }
+            ToolKind::Executables => self
+                .path
+                .iter()
+                .map(|binary| Removal::Path(binary.to_path_buf()))
+                .collect(),
+            ToolKind::AppImage => appimage_removals(self.name(), self.path.single()?)?,
+            ToolKind::DistroPackage => distro_package::removals(self.name(), self.path.single()?)?,
+            ToolKind::Custom => custom_instructions::removals(self.name(), config)?,
+            ToolKind::NpmPackage => PackageManager::Npm.removals(self.path.single()?),
+            ToolKind::PipPackage => PackageManager::Pip.removals(self.path.single()?),
+            ToolKind::DirWithBin => {
+                let tool_path = self.path.single()?;
+                vec![
+                    Removal::PathEntry(tool_path.join("bin")),
+                    Removal::Path(tool_path.to_path_buf()),
+                ]
+            }
+            ToolKind::Plugin => Plugin::removals(self.path.single()?, config.tools_dir())?,
+            ToolKind::Crate => vec![
+                Removal::Path(self.path.single()?.to_path_buf()),
+                Removal::ConfigSection {
+                    file: config.cargo_home().join(CargoConfig::FILENAME),
+                    section: format!("patch.crates-io.{}", self.name()),
+                },
+            ],
+            ToolKind::Installer | ToolKind::Script | ToolKind::RuleSet | ToolKind::Unknown => {
+                vec![Removal::Path(self.path.single()?.to_path_buf())]
+            }
+        };
+
+        removals.extend(
+            emptied_parents(self.path.iter(), &tools_dir)
+                .into_iter()
+                .map(Removal::Path),
+        );
+        Ok(removals)
+    }
 }
 
-fn cargo_install_or_uninstall(op: &str, args: &[&str], cargo_home: &Path) -> Result<()> {
+/// Get the parent directories of `paths` that will become empty once `paths` are removed,
+/// which are the ones to be removed by [`utils::remove_empty_parents`].
+fn emptied_parents<'p, I>(paths: I, root: &Path) -> Vec<PathBuf>
+where
+    I: IntoIterator<Item = &'p Path>,
+{
+    let mut removed = paths.into_iter().map(Path::to_path_buf).collect::<Vec<_>>();
+    let mut emptied = vec![];
+    for path in removed.clone() {
+        let mut current = path.parent();
+        while let Some(dir) = current {
+            if dir == root || !dir.starts_with(root) || removed.iter().any(|p| p.as_path() == dir) {
+                break;
+            }
+            let becomes_empty = fs::read_dir(dir).is_ok_and(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .all(|entry| removed.contains(&entry.path()))
+            });
+            if !becomes_empty {
+                break;
+            }
+            removed.push(dir.to_path_buf());
+            emptied.push(dir.to_path_buf());
+            current = dir.parent();
+        }
+    }
+    emptied
+}
+
+/// Get the value of `--version` to pass to `cargo install` for tool `name`.
+///
+/// `version` could either be an exact version such as `1.2.3`, or a version requirement
+/// such as `^1.2`, which is validated here. Note that a partial version such as `1.2`
+/// is converted to the requirement it represents (`^1.2`), because `cargo` rejects it.
+pub(crate) fn cargo_version_arg(name: &str, version: &str) -> Result<String> {
+    let version = version.trim();
+    if !is_version_requirement(version) {
+        return Ok(version.to_string());
+    }
+    let req = semver::VersionReq::parse(version).with_context(|| {
+        t!(
+            "invalid_version_requirement",
+            name = name,
+            version = version
+        )
+    })?;
+    Ok(req.to_string())
+}
+
+/// Check whether `version` is a version requirement rather than an exact version.
+fn is_version_requirement(version: &str) -> bool {
+    semver::Version::parse(version.trim()).is_err()
+}
+
+/// Get the version of crate `name` that was installed using `cargo install`.
+fn installed_crate_version(name: &str, cargo_home: &Path) -> Option<String> {
+    let cargo = cargo_home.join("bin").join(exe!("cargo"));
+    let output = cmd!([CARGO_HOME = cargo_home] cargo, "install", "--list")
+        .output()
+        .ok()?;
+    find_installed_version(&String::from_utf8_lossy(&output.stdout), name)
+}
+
+/// Find the version of crate `name` in the output of `cargo install --list`,
+/// where each crate is listed as `<name> v<version>[ (<source>)]:` followed by its binaries.
+fn find_installed_version(list: &str, name: &str) -> Option<String> {
+    list.lines()
+        .filter(|line| !line.starts_with(char::is_whitespace))
+        .find_map(|line| {
+            let mut parts = line.trim_end_matches(':').split_whitespace();
+            if parts.next()? != name {
+                return None;
+            }
+            parts.next()?.strip_prefix('v').map(ToString::to_string)
+        })
+}
+
+/// The API of crates.io to get the information of a crate, as in `<API>/<crate name>`.
+const CRATES_IO_API: &str = "https://crates.io/api/v1/crates/";
+
+/// Ask crates.io about the latest stable version of crate `name`.
+pub(crate) async fn latest_crate_version(
+    name: &str,
+    proxy: Option<Proxy>,
+    insecure: bool,
+) -> Result<Option<String>> {
+    let url = utils::url_join(&Url::parse(CRATES_IO_API)?, name)?;
+    let raw = utils::DownloadOpt::new(name, true)
+        .insecure(insecure)
+        .with_proxy(proxy)
+        .read(&url)
+        .await?;
+    parse_latest_crate_version(&raw)
+}
+
+/// Get the latest version from the crate information responded by crates.io,
+/// prefer the latest stable version, as it is what `cargo install` installs by default.
+fn parse_latest_crate_version(raw: &str) -> Result<Option<String>> {
+    let info: serde_json::Value = serde_json::from_str(raw)?;
+    let krate = &info["crate"];
+    Ok(krate["max_stable_version"]
+        .as_str()
+        .or_else(|| krate["max_version"].as_str())
+        .map(ToString::to_string))
+}
+
+/// The minimum interval between the "still compiling" messages logged for a progress indicator.
+const COMPILING_MESSAGE_INTERVAL: Duration = Duration::from_secs(10);
+/// The portion of the remaining progress (of a tool) to advance by when a crate was compiled,
+/// which keeps the progress moving without ever reaching the end before `cargo` finishes.
+const COMPILED_CRATE_PROGRESS_RATIO: f32 = 0.02;
+
+/// Run `cargo install` or `cargo uninstall` with `args`.
+///
+/// When installing with a `progress` indicator, such as the one of GUI, the compiling progress
+/// is reported through it within its `len`, and the compiling crates are logged periodically.
+fn cargo_install_or_uninstall(
+    op: &str,
+    args: &[&str],
+    cargo_home: &Path,
+    progress: Option<&utils::Progress>,
+) -> Result<()> {
     let mut cargo_bin = cargo_home.to_path_buf();
     cargo_bin.push("bin");
     cargo_bin.push(exe!("cargo"));
@@ -287,10 +510,117 @@ fn cargo_install_or_uninstall(op: &str, args: &[&str], cargo_home: &Path) -> Res
     }
     full_args.extend_from_slice(args);
     cmd.args(full_args);
-    utils::execute(cmd)?;
+    if op != "install" {
+        return utils::execute(cmd);
+    }
+
+    // `cargo install` might take a long time to compile, show a spinner with the
+    // crate currently being compiled so that it won't look like it got stuck.
+    // The spinner is hidden if there's a progress indicator to report to instead.
+    let cli_progress = utils::CliProgress::new(GlobalOpts::get().quiet || progress.is_some());
+    let spinner = (cli_progress.start)(
+        t!("cargo_install_running", args = args.join(" ")).to_string(),
+        utils::Style::Spinner {
+            auto_tick_duration: Some(Duration::from_millis(100)),
+        },
+    )?;
+    let mut reported = 0.0;
+    let mut last_message: Option<Instant> = None;
+    utils::execute_with_output_handler(cmd, |line| {
+        let Some(krate) = compiling_crate(line) else {
+            spinner.suspend(|| utils::log_output_line(line));
+            return;
+        };
+        debug!("{}", line.trim());
+        let msg = t!("cargo_compiling_crate", name = krate);
+        spinner.set_message(msg.to_string());
+
+        let Some(prog) = progress else {
+            return;
+        };
+        let step = (prog.len - reported) * COMPILED_CRATE_PROGRESS_RATIO;
+        reported += step;
+        if let Err(e) = prog.inc(Some(step)) {
+            debug!("unable to update the progress of 'cargo install': {e}");
+        }
+        if last_message.map_or(true, |t| t.elapsed() >= COMPILING_MESSAGE_INTERVAL) {
+            info!("{msg}");
+            last_message = Some(Instant::now());
+        }
+    })
+    .inspect_err(|_| spinner.abandon())?;
+    (cli_progress.stop)(
+        &spinner,
+        t!("cargo_install_finished", args = args.join(" ")).to_string(),
+    );
     Ok(())
 }
 
+/// Get the name of the crate from a `Compiling <crate> v<version>` line of cargo's output.
+fn compiling_crate(line: &str) -> Option<&str> {
+    line.trim_start()
+        .strip_prefix("Compiling ")?
+        .split_whitespace()
+        .next()
+}
+
+fn is_appimage(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("appimage"))
+}
+
+/// The desktop shortcut of an AppImage tool, which points to the AppImage file.
+fn appimage_shortcut(name: &str, path: PathBuf) -> utils::ApplicationShortcut<'_> {
+    utils::ApplicationShortcut {
+        name,
+        path,
+        icon: None,
+        desktop: true,
+    }
+}
+
+/// Install [`ToolKind::AppImage`] by copying the `.AppImage` file into
+/// a folder with `name` under tools dir, then create a desktop shortcut for it.
+///
+/// Return the path to the folder.
+#[cfg(target_os = "linux")]
+fn install_appimage(config: &InstallConfiguration, name: &str, path: &Path) -> Result<PathBuf> {
+    let dir = config.tools_dir().join(name);
+    let appimage = utils::copy_into(path, &dir)?;
+    utils::set_exec_permission(&appimage)?;
+
+    // Shortcuts are not important, make sure it won't throw error even if it fails.
+    if config.create_shortcuts() {
+        if let Err(e) = appimage_shortcut(name, appimage).create() {
+            warn!("{}", t!("create_shortcut_failed", name = name, reason = e));
+        }
+    }
+    Ok(dir)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn install_appimage(_config: &InstallConfiguration, name: &str, _path: &Path) -> Result<PathBuf> {
+    bail!(t!("appimage_unsupported_platform", name = name));
+}
+
+/// Remove the shortcut and the folder of a [`ToolKind::AppImage`] tool.
+fn uninstall_appimage(name: &str, dir: &Path) -> Result<()> {
+    appimage_shortcut(name, dir.to_path_buf()).remove()?;
+    utils::remove(dir)
+}
+
+fn appimage_removals(name: &str, dir: &Path) -> Result<Vec<Removal>> {
+    let mut removals = appimage_shortcut(name, dir.to_path_buf())
+        .created()?
+        .into_iter()
+        .map(Removal::Shortcut)
+        .collect::<Vec<_>>();
+    removals.push(Removal::Path(dir.to_path_buf()));
+    Ok(removals)
+}
+
 /// Move one path (file/dir) to a new folder with `name` under tools dir.
 fn move_to_tools(config: &InstallConfiguration, name: &str, path: &Path) -> Result<PathBuf> {
     let dir = config.tools_dir().join(name);
@@ -336,7 +666,7 @@ fn install_rule_set(path: &PathExt<'_>, config: &InstallConfiguration) -> Result
     // we don't need additional rule set files. If we use dylint, make sure to
     // create another folder called `lints` to store custom lints)
     let runner_dir = ruleset_dir.join("runner");
-    utils::copy_as(src_dir, &runner_dir)?;
+    utils::copy_as_parallel(src_dir, &runner_dir)?;
 
     // Step 3: the binaries in runner toolchain sometimes missing
     // the execution permission, and we have to fix that
@@ -458,63 +788,153 @@ impl Plugin {
         matches!(ext, "vsix")
     }
 
-    fn install(plugin_path: &Path) -> Result<()> {
-        Self::install_or_uninstall_(plugin_path, false)
-    }
+    /// Install a plugin using every compatible editor that could be found,
+    /// and return the path to a backup of it, which is needed for uninstallation.
+    ///
+    /// If no editor could be found, the plugin will be copied into the
+    /// [`EXTENSIONS_STAGING_DIR`] under `tools_dir` instead, so that the user
+    /// can install it manually later.
+    fn install(plugin_path: &Path, tools_dir: &Path) -> Result<PathBuf> {
+        match Self::from_path(plugin_path)? {
+            Plugin::Vsix => {
+                let editors = vscode_editors(tools_dir);
+                if editors.is_empty() {
+                    let staging_dir = tools_dir.join(EXTENSIONS_STAGING_DIR);
+                    utils::ensure_dir(&staging_dir)?;
+                    let staged = utils::copy_into(plugin_path, &staging_dir)?;
+                    warn!(
+                        "{}",
+                        t!(
+                            "no_editor_for_extension",
+                            ext = plugin_path.display(),
+                            path = staged.display()
+                        )
+                    );
+                    return Ok(staged);
+                }
 
-    fn uninstall(plugin_path: &Path) -> Result<()> {
-        Self::install_or_uninstall_(plugin_path, true)
+                for editor in &editors {
+                    info!(
+                        "{}",
+                        t!(
+                            "handling_extension_info",
+                            op = t!("install"),
+                            ext = plugin_path.display(),
+                            program = editor.display()
+                        )
+                    );
+                    run!(editor, "--install-extension", plugin_path)?;
+                }
+                // we need to "cache" the plugin, so that we could uninstall with it.
+                utils::copy_into(plugin_path, tools_dir)
+            }
+        }
     }
 
-    fn install_or_uninstall_(plugin_path: &Path, uninstall: bool) -> Result<()> {
-        let ty = utils::extension_str(plugin_path)
-            .and_then(|ext| Self::from_str(ext).ok())
-            .ok_or_else(|| anyhow!("unsupported plugin file '{}'", plugin_path.display()))?;
-
-        match ty {
+    /// Uninstall a plugin from every compatible editor that could be found,
+    /// then remove the plugin file that was kept during installation.
+    fn uninstall(plugin_path: &Path, tools_dir: &Path) -> Result<()> {
+        match Self::from_path(plugin_path)? {
             Plugin::Vsix => {
-                for program in VSCODE_FAMILY.as_slice() {
-                    if !utils::cmd_exist(program) {
-                        continue;
-                    }
-
-                    let op = if uninstall { "uninstall" } else { "install" };
-                    let arg_opt = format!("--{op}-extension");
+                // editors only accept extension ids when uninstalling,
+                // fallback to the file path if the id could not be determined.
+                let ext = vsix_extension_id(plugin_path)
+                    .unwrap_or_else(|| plugin_path.to_string_lossy().to_string());
+                for editor in vscode_editors(tools_dir) {
                     info!(
                         "{}",
                         t!(
                             "handling_extension_info",
-                            op = t!(op),
-                            ext = plugin_path.display(),
-                            program = program
+                            op = t!("uninstall"),
+                            ext = ext,
+                            program = editor.display()
                         )
                     );
-                    match run!(program, arg_opt, plugin_path) {
-                        Ok(_) => continue,
-                        // Ignore error when uninstalling.
-                        Err(_) if uninstall => {
-                            info!(
-                                "{}",
-                                t!(
-                                    "skip_extension_uninstall_warn",
-                                    ext = plugin_path.display(),
-                                    program = program
-                                )
-                            );
-                            continue;
-                        }
-                        Err(e) => return Err(e),
+                    // Ignore error when uninstalling.
+                    if run!(&editor, "--uninstall-extension", &ext).is_err() {
+                        info!(
+                            "{}",
+                            t!(
+                                "skip_extension_uninstall_warn",
+                                ext = ext,
+                                program = editor.display()
+                            )
+                        );
                     }
                 }
+                utils::remove(plugin_path)?;
+            }
+        }
+        Ok(())
+    }
 
-                // Remove the plugin file if uninstalling
-                if uninstall {
-                    utils::remove(plugin_path)?;
+    /// Get what [`uninstall`](Self::uninstall) will do, without actually doing it.
+    fn removals(plugin_path: &Path, tools_dir: &Path) -> Result<Vec<Removal>> {
+        let mut removals = vec![];
+        match Self::from_path(plugin_path)? {
+            Plugin::Vsix => {
+                let ext = vsix_extension_id(plugin_path)
+                    .unwrap_or_else(|| plugin_path.to_string_lossy().to_string());
+                for editor in vscode_editors(tools_dir) {
+                    removals.push(Removal::Command(format!(
+                        "{} --uninstall-extension {ext}",
+                        editor.display()
+                    )));
                 }
+                removals.push(Removal::Path(plugin_path.to_path_buf()));
             }
         }
-        Ok(())
+        Ok(removals)
+    }
+
+    fn from_path(plugin_path: &Path) -> Result<Self> {
+        utils::extension_str(plugin_path)
+            .and_then(|ext| Self::from_str(ext).ok())
+            .ok_or_else(|| anyhow!("unsupported plugin file '{}'", plugin_path.display()))
+    }
+}
+
+/// The directory under `tools` to put the plugins that could not be installed
+/// because no compatible editor was found.
+const EXTENSIONS_STAGING_DIR: &str = "extensions";
+
+/// Find the programs of VS Code variants, including the ones installed as
+/// components under `tools_dir` (which might not be in `PATH` yet),
+/// and the ones in `PATH`.
+fn vscode_editors(tools_dir: &Path) -> Vec<PathBuf> {
+    let installed_dirs = fs::read_dir(tools_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok().map(|e| e.path().join("bin")))
+                .filter(|bin_dir| bin_dir.is_dir())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    let mut editors = vec![];
+    for program in VSCODE_FAMILY.as_slice() {
+        if let Some(installed) = installed_dirs
+            .iter()
+            .map(|dir| dir.join(program))
+            .find(|path| path.is_file())
+        {
+            editors.push(installed);
+        } else if utils::cmd_exist(program) {
+            editors.push(PathBuf::from(program));
+        }
     }
+    editors
+}
+
+/// Read the id (`publisher.name`) of a VS Code extension from its `.vsix` file.
+fn vsix_extension_id(path: &Path) -> Option<String> {
+    let file = fs::File::open(path).ok()?;
+    let mut archive = zip::ZipArchive::new(file).ok()?;
+    let package = archive.by_name("extension/package.json").ok()?;
+    let value: serde_json::Value = serde_json::from_reader(package).ok()?;
+    let publisher = value.get("publisher")?.as_str()?;
+    let name = value.get("name")?.as_str()?;
+    Some(format!("{publisher}.{name}"))
 }
 
 #[cfg(test)]
@@ -566,4 +986,152 @@ mod tests {
         assert!(matches!(tools_iter.next(), Some(ToolKind::DirWithBin)));
         assert!(matches!(tools_iter.next(), None));
     }
+
+    #[test]
+    fn parse_compiling_crate() {
+        assert_eq!(
+            compiling_crate("   Compiling serde v1.0.210"),
+            Some("serde")
+        );
+        assert_eq!(
+            compiling_crate("Compiling cargo-foo v0.1.0 (registry `crates-io`)"),
+            Some("cargo-foo")
+        );
+        assert_eq!(compiling_crate("  Downloaded serde v1.0.210"), None);
+        assert_eq!(compiling_crate("    Finished `release` profile"), None);
+    }
+
+    #[test]
+    fn cargo_install_version() {
+        assert_eq!(cargo_version_arg("a", "1.2.3").unwrap(), "1.2.3");
+        assert_eq!(cargo_version_arg("a", "^1.2").unwrap(), "^1.2");
+        assert_eq!(cargo_version_arg("a", "1.2").unwrap(), "^1.2");
+        assert_eq!(cargo_version_arg("a", ">=1.0, <2").unwrap(), ">=1.0, <2");
+        assert!(cargo_version_arg("a", "latest").is_err());
+    }
+
+    #[test]
+    fn find_version_from_install_list() {
+        let list = "\
+cargo-a v1.2.5:
+    cargo-a
+b v0.3.0 (https://github.com/x/b#0123abcd):
+    b
+    b-cli
+";
+        assert_eq!(
+            find_installed_version(list, "cargo-a").as_deref(),
+            Some("1.2.5")
+        );
+        assert_eq!(find_installed_version(list, "b").as_deref(), Some("0.3.0"));
+        assert_eq!(find_installed_version(list, "b-cli"), None);
+        assert_eq!(find_installed_version(list, "c"), None);
+    }
+
+    #[test]
+    fn latest_version_from_crates_io() {
+        let raw =
+            r#"{"crate":{"name":"a","max_version":"2.0.0-rc.1","max_stable_version":"1.9.0"}}"#;
+        assert_eq!(
+            parse_latest_crate_version(raw).unwrap().as_deref(),
+            Some("1.9.0")
+        );
+        let raw = r#"{"crate":{"name":"a","max_version":"0.1.0-alpha","max_stable_version":null}}"#;
+        assert_eq!(
+            parse_latest_crate_version(raw).unwrap().as_deref(),
+            Some("0.1.0-alpha")
+        );
+        assert_eq!(
+            parse_latest_crate_version(r#"{"errors":[]}"#).unwrap(),
+            None
+        );
+        assert!(parse_latest_crate_version("not json").is_err());
+    }
+
+    #[test]
+    fn detect_appimage() {
+        let dir = tempfile::tempdir().unwrap();
+        let appimage = dir.path().join("Some_Tool-x86_64.AppImage");
+        fs::write(&appimage, "").unwrap();
+
+        let tool = Tool::from_path("some-tool", &appimage).unwrap();
+        assert_eq!(tool.kind, ToolKind::AppImage);
+    }
+
+    #[test]
+    fn detect_distro_packages() {
+        let dir = tempfile::tempdir().unwrap();
+        for filename in ["some-tool_1.0.0_amd64.deb", "some-tool-1.0.0-1.x86_64.rpm"] {
+            let package = dir.path().join(filename);
+            fs::write(&package, "").unwrap();
+
+            let tool = Tool::from_path("some-tool", &package).unwrap();
+            assert_eq!(tool.kind, ToolKind::DistroPackage);
+        }
+
+        // a directory is never a package, whatever it's named
+        let not_package = dir.path().join("some-tool.deb.d");
+        utils::ensure_dir(not_package.join("bin")).unwrap();
+        let tool = Tool::from_path("some-tool", &not_package).unwrap();
+        assert_eq!(tool.kind, ToolKind::DirWithBin);
+    }
+
+    #[test]
+    fn read_vsix_extension_id() {
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let vsix = dir.path().join("ext.vsix");
+        let mut writer = zip::ZipWriter::new(fs::File::create(&vsix).unwrap());
+        writer
+            .start_file(
+                "extension/package.json",
+                zip::write::SimpleFileOptions::default(),
+            )
+            .unwrap();
+        writer
+            .write_all(br#"{ "name": "rust-analyzer", "publisher": "rust-lang" }"#)
+            .unwrap();
+        writer.finish().unwrap();
+
+        assert_eq!(
+            vsix_extension_id(&vsix).as_deref(),
+            Some("rust-lang.rust-analyzer")
+        );
+        assert!(vsix_extension_id(&dir.path().join("missing.vsix")).is_none());
+    }
+
+    #[test]
+    fn find_vscode_installed_as_component() {
+        let tools_dir = tempfile::tempdir().unwrap();
+        let bin_dir = tools_dir.path().join("vscodium").join("bin");
+        utils::ensure_dir(&bin_dir).unwrap();
+        let codium = bin_dir.join(&VSCODE_FAMILY[1]);
+        utils::write_file(&codium, "", false).unwrap();
+
+        assert!(vscode_editors(tools_dir.path()).contains(&codium));
+    }
+
+    #[test]
+    fn parents_emptied_by_removal() {
+        let root = tempfile::tempdir().unwrap();
+        let shared = root.path().join("shared");
+        let solo = root.path().join("a").join("b");
+        for dir in [shared.join("x"), shared.join("y"), solo.join("tool")] {
+            utils::ensure_dir(&dir).unwrap();
+        }
+
+        let x = shared.join("x");
+        assert!(emptied_parents([x.as_path()], root.path()).is_empty());
+        let y = shared.join("y");
+        assert_eq!(
+            emptied_parents([x.as_path(), y.as_path()], root.path()),
+            vec![shared.clone()]
+        );
+        let tool = solo.join("tool");
+        assert_eq!(
+            emptied_parents([tool.as_path()], root.path()),
+            vec![solo.clone(), root.path().join("a")]
+        );
+    }
 }
@@ -1,13 +1,20 @@
 use anyhow::Result;
+use rim_common::types::TomlParser;
 use rim_common::utils::Progress;
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+};
 
 use super::{
     components::ToolchainComponent,
     dependency_handler::DependencyHandler,
     directories::RimDir,
+    hooks::{self, HookStage},
     parser::fingerprint::{installed_tools, InstallationRecord, ToolRecord},
     rustup::ToolchainInstaller,
+    script,
     tools::ToolWithDeps,
 };
 use crate::core::tools::Tool;
@@ -22,6 +29,62 @@ pub(crate) trait Uninstallation {
     /// The last step of uninstallation, this will remove the binary itself, along with
     /// the folder it's in.
     fn remove_self(&self) -> Result<()>;
+    /// Get the things that [`remove_rustup_env_vars`](Self::remove_rustup_env_vars)
+    /// will remove, without actually removing them.
+    fn rustup_env_vars_removals(&self) -> Result<Vec<Removal>>;
+    /// Get the things that [`remove_self`](Self::remove_self) will remove,
+    /// without actually removing them.
+    fn self_removals(&self) -> Result<Vec<Removal>>;
+}
+
+/// Something that will be removed during uninstallation,
+/// check [`UninstallConfiguration::dry_run`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Removal {
+    /// A file, a directory (including everything in it) or a symlink.
+    Path(PathBuf),
+    /// A directory that will be removed from the `PATH` variable.
+    PathEntry(PathBuf),
+    /// A persistent environment variable, which will be restored
+    /// to its original value if there is one, or be unset otherwise.
+    EnvVar {
+        name: String,
+        restore: Option<String>,
+    },
+    /// A section of configuration written in a file, such as shell profiles.
+    ConfigSection { file: PathBuf, section: String },
+    /// A registry key under `HKEY_CURRENT_USER`.
+    Registry(String),
+    /// An application shortcut.
+    Shortcut(PathBuf),
+    /// A command that does the removal by itself, such as `cargo uninstall`.
+    Command(String),
+}
+
+impl fmt::Display for Removal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let desc = match self {
+            Self::Path(path) => t!("removal_path", path = path.display()),
+            Self::PathEntry(path) => t!("removal_path_entry", path = path.display()),
+            Self::EnvVar {
+                name,
+                restore: Some(val),
+            } => t!("removal_env_var_restore", name = name, value = val),
+            Self::EnvVar {
+                name,
+                restore: None,
+            } => t!("removal_env_var", name = name),
+            Self::ConfigSection { file, section } => t!(
+                "removal_config_section",
+                section = section,
+                file = file.display()
+            ),
+            Self::Registry(key) => t!("removal_registry", key = key),
+            Self::Shortcut(path) => t!("removal_shortcut", path = path.display()),
+            Self::Command(cmd) => t!("removal_command", command = cmd),
+        };
+        write!(f, "{desc}")
+    }
 }
 
 /// Contains every information that the uninstallation process needs.
@@ -31,6 +94,8 @@ pub struct UninstallConfiguration<'a> {
     pub(crate) install_dir: PathBuf,
     pub(crate) install_record: InstallationRecord,
     pub(crate) progress_indicator: Option<Progress<'a>>,
+    /// Run the `pre-uninstall` hooks of tools, which are skipped by default.
+    pub(crate) allow_hooks: bool,
 }
 
 impl RimDir for UninstallConfiguration<'_> {
@@ -48,13 +113,27 @@ impl RimDir for &UninstallConfiguration<'_> {
 impl<'a> UninstallConfiguration<'a> {
     pub fn init(progress: Option<Progress<'a>>) -> Result<Self> {
         let install_record = InstallationRecord::load_from_install_dir()?;
-        Ok(Self {
+        Ok(Self::with_record(install_record, progress))
+    }
+
+    /// Same as [`init`](Self::init), but for the installation under `install_dir`
+    /// instead of the one this binary belongs to.
+    pub fn init_in(install_dir: &Path, progress: Option<Progress<'a>>) -> Result<Self> {
+        let install_record = InstallationRecord::load_from_dir(install_dir)?;
+        Ok(Self::with_record(install_record, progress))
+    }
+
+    fn with_record(install_record: InstallationRecord, progress: Option<Progress<'a>>) -> Self {
+        Self {
             install_dir: install_record.root.clone(),
             install_record,
             progress_indicator: progress,
-        })
+            allow_hooks: false,
+        }
     }
 
+    setter!(allow_hooks(self.allow_hooks, bool));
+
     pub(crate) fn inc_progress(&self, val: f32) -> Result<()> {
         if let Some(prog) = &self.progress_indicator {
             prog.inc(Some(val))?;
@@ -90,7 +169,9 @@ impl<'a> UninstallConfiguration<'a> {
             info!("{}", t!("uninstall_self"));
             self.remove_self()?;
         } else {
-            self.install_record.remove_toolkit_meta();
+            // clear whatever is left of the toolkit, so that another toolkit
+            // can be installed with this manager afterward.
+            self.install_record.clear_toolkit();
             self.install_record.write()?;
         }
         self.inc_progress(10.0)?;
@@ -116,39 +197,55 @@ impl<'a> UninstallConfiguration<'a> {
         Ok(())
     }
 
-    /// Uninstall a selection of tools
-    pub fn remove_tools(&mut self, tools: HashMap<String, ToolRecord>, weight: f32) -> Result<()> {
-        let mut tools_to_uninstall = vec![];
-        for (name, tool_detail) in &tools {
-            let Some(tool) = Tool::from_installed(name, tool_detail) else {
-                continue;
-            };
-            tools_to_uninstall.push(ToolWithDeps {
-                tool,
-                dependencies: &tool_detail.dependencies,
-            });
+    /// Get everything that [`uninstall`](Self::uninstall) would remove, in the order
+    /// of them being removed, without touching anything.
+    pub fn dry_run(&self, remove_self: bool) -> Result<Vec<Removal>> {
+        let mut removals = vec![];
+
+        let tools = installed_tools(&self.install_dir)?;
+        for tool in uninstall_order(&tools) {
+            if let Some(command) = tools
+                .get(tool.name())
+                .and_then(|record| record.pre_uninstall.as_ref())
+                .filter(|_| self.allow_hooks)
+            {
+                removals.push(Removal::Command(command.clone()));
+            }
+            if let Some(script) = tools
+                .get(tool.name())
+                .and_then(|record| record.uninstall_script.as_ref())
+                .filter(|_| self.allow_hooks)
+            {
+                removals.push(Removal::Command(script.clone()));
+            }
+            // tools that cannot be uninstalled are skipped, just like in `remove_tools`
+            removals.extend(tool.removals(self).unwrap_or_default());
         }
 
-        if tools_to_uninstall.is_empty() {
+        if self.install_record.rust.is_some() {
+            removals.extend(ToolchainInstaller::self_removals(self));
+        }
+        removals.extend(self.rustup_env_vars_removals()?);
+        if remove_self {
+            removals.extend(self.self_removals()?);
+        }
+        Ok(removals)
+    }
+
+    /// Uninstall a selection of tools
+    pub fn remove_tools(&mut self, tools: HashMap<String, ToolRecord>, weight: f32) -> Result<()> {
+        let sorted = uninstall_order(&tools);
+        if sorted.is_empty() {
             return self.inc_progress(weight);
         }
-        let progress_dt = weight / tools_to_uninstall.len() as f32;
-
-        // in previous builds (< 0.6.0), we didn't support dependencies handling,
-        // instead, we sorted the tools by its kind. Therefore we use a fallback
-        // method to sort the tools here if there's no dependencies info to be found,
-        // making sure the tools are always sorted to prevent uninstallation failure.
-        let have_deps = tools_to_uninstall
-            .iter()
-            .any(|t| !t.dependencies.is_empty());
-
-        let sorted = if have_deps {
-            tools_to_uninstall.topological_sorted()
-        } else {
-            tools_to_uninstall.sorted()
-        };
+        let progress_dt = weight / sorted.len() as f32;
+
         for tool in sorted {
             info!("{}", t!("uninstalling_for", name = tool.name()));
+            if let Some(record) = tools.get(tool.name()) {
+                self.run_pre_uninstall_hook(tool.name(), record);
+                self.run_uninstall_script(tool.name(), record);
+            }
             if tool.uninstall(&*self).is_err() {
                 warn!(
                     "{}",
@@ -165,4 +262,61 @@ impl<'a> UninstallConfiguration<'a> {
 
         Ok(())
     }
+
+    fn run_pre_uninstall_hook(&self, name: &str, record: &ToolRecord) {
+        let Some(command) = &record.pre_uninstall else {
+            return;
+        };
+        // failing hooks should not block the uninstallation
+        if let Err(e) = hooks::run(
+            name,
+            HookStage::PreUninstall,
+            command,
+            &record.paths,
+            self,
+            self.allow_hooks,
+        ) {
+            warn!("{e:#}");
+        }
+    }
+
+    fn run_uninstall_script(&self, name: &str, record: &ToolRecord) {
+        let Some(script) = &record.uninstall_script else {
+            return;
+        };
+        // same as hooks, failing scripts should not block the uninstallation
+        if let Err(e) =
+            script::run_uninstall_script(name, script, &record.paths, self, self.allow_hooks)
+        {
+            warn!("{e:#}");
+        }
+    }
+}
+
+/// Sort the installed `tools` in the order that they should be uninstalled.
+fn uninstall_order(tools: &HashMap<String, ToolRecord>) -> Vec<Tool<'_>> {
+    let mut tools_to_uninstall = vec![];
+    for (name, tool_detail) in tools {
+        let Some(tool) = Tool::from_installed(name, tool_detail) else {
+            continue;
+        };
+        tools_to_uninstall.push(ToolWithDeps {
+            tool,
+            dependencies: &tool_detail.dependencies,
+        });
+    }
+
+    // in previous builds (< 0.6.0), we didn't support dependencies handling,
+    // instead, we sorted the tools by its kind. Therefore we use a fallback
+    // method to sort the tools here if there's no dependencies info to be found,
+    // making sure the tools are always sorted to prevent uninstallation failure.
+    let have_deps = tools_to_uninstall
+        .iter()
+        .any(|t| !t.dependencies.is_empty());
+
+    if have_deps {
+        tools_to_uninstall.topological_sorted()
+    } else {
+        tools_to_uninstall.sorted()
+    }
 }
@@ -207,6 +207,20 @@ pub async fn check_self_update(insecure: bool) -> Result<UpdateKind<Version>> {
     updates_checker.update.mark_checked(UpdateTarget::Manager);
     updates_checker.write_to_install_dir()?;
 
+    let res = available_self_update(insecure).await?;
+    if let UpdateKind::Newer { latest, .. } = &res {
+        if updates_checker.update_skipped(UpdateTarget::Manager, latest.to_string()) {
+            return Ok(UpdateKind::UnNeeded);
+        }
+    }
+    Ok(res)
+}
+
+/// Compare the version of this manager with the latest release on server.
+///
+/// Unlike [`check_self_update`], this does not touch the updates checker configuration,
+/// thus the result will not be affected by skipped versions either.
+pub async fn available_self_update(insecure: bool) -> Result<UpdateKind<Version>> {
     let latest_version = match latest_manager_release(insecure).await {
         Ok(release) => release.version.clone(),
         Err(e) => {
@@ -214,9 +228,6 @@ pub async fn check_self_update(insecure: bool) -> Result<UpdateKind<Version>> {
             return Ok(UpdateKind::Uncertain);
         }
     };
-    if updates_checker.update_skipped(UpdateTarget::Manager, latest_version.to_string()) {
-        return Ok(UpdateKind::UnNeeded);
-    }
 
     // safe to unwrap, otherwise cargo would fails the build
     let cur_version = Version::parse(env!("CARGO_PKG_VERSION")).unwrap();
@@ -0,0 +1,244 @@
+//! Module for verifying the integrity of an existing installation.
+
+use super::directories::RimDir;
+use crate::fingerprint::{InstallationRecord, ToolRecord};
+use anyhow::{Context, Result};
+use rim_common::{types::ToolKind, utils};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Proxies that `rustup` always creates under `cargo/bin`.
+const RUSTUP_PROXIES: &[&str] = &["cargo", "rustc", "rustdoc"];
+/// Proxies that only exist if the corresponding toolchain component was installed.
+const COMPONENT_PROXIES: &[(&str, &[&str])] = &[
+    ("rustfmt", &["rustfmt", "cargo-fmt"]),
+    ("clippy", &["cargo-clippy", "clippy-driver"]),
+    ("rust-analyzer", &["rust-analyzer"]),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum VerifyStatus {
+    Ok,
+    Missing,
+    /// The component was broken but has been fixed.
+    Fixed,
+}
+
+/// The verification result of a single component.
+#[derive(Debug)]
+pub(crate) struct VerifyEntry {
+    pub(crate) name: String,
+    pub(crate) status: VerifyStatus,
+    /// Paths that are expected to exist but are missing or broken.
+    pub(crate) broken: Vec<PathBuf>,
+}
+
+impl VerifyEntry {
+    fn new(name: &str, broken: Vec<PathBuf>) -> Self {
+        let status = if broken.is_empty() {
+            VerifyStatus::Ok
+        } else {
+            VerifyStatus::Missing
+        };
+        Self {
+            name: name.to_string(),
+            status,
+            broken,
+        }
+    }
+}
+
+/// The content of `.crates.toml` under `CARGO_HOME`,
+/// which keeps track of the tools installed by `cargo install`.
+#[derive(Debug, Default, Deserialize)]
+struct CratesToml {
+    /// A map of `"<name> <version> (<source>)"` to the list of installed binaries.
+    #[serde(default)]
+    v1: HashMap<String, Vec<String>>,
+}
+
+struct Verifier {
+    install_dir: PathBuf,
+}
+
+impl RimDir for Verifier {
+    fn install_dir(&self) -> &Path {
+        &self.install_dir
+    }
+}
+
+/// Verify the components listed in the installation record, and return the
+/// result of each one of them.
+///
+/// If `fix` is `true`, this will try re-creating missing `rustup` proxies
+/// as long as the `rustup` binary still exists.
+pub(crate) fn verify_installation(fix: bool) -> Result<Vec<VerifyEntry>> {
+    let record = InstallationRecord::load_from_install_dir()?;
+    let verifier = Verifier {
+        install_dir: record.root.clone(),
+    };
+
+    let mut entries = vec![];
+    if let Some((version, components)) = record.installed_toolchain() {
+        entries.push(verifier.verify_toolchain(version, components, fix)?);
+    }
+
+    let mut tools = record.tools.iter().collect::<Vec<_>>();
+    tools.sort_by_key(|(name, _)| *name);
+    for (name, tool) in tools {
+        entries.push(verifier.verify_tool(name, tool)?);
+    }
+
+    Ok(entries)
+}
+
+impl Verifier {
+    fn verify_toolchain(
+        &self,
+        version: &str,
+        components: &[String],
+        fix: bool,
+    ) -> Result<VerifyEntry> {
+        let rustup = self.cargo_bin().join(exe!("rustup"));
+        let name = format!("rust {version}");
+        if !rustup.is_file() {
+            return Ok(VerifyEntry::new(&name, vec![rustup]));
+        }
+
+        let optional = COMPONENT_PROXIES
+            .iter()
+            .filter(|(comp, _)| components.iter().any(|c| c == comp))
+            .flat_map(|(_, proxies)| proxies.iter());
+        let mut broken = vec![];
+        for proxy in RUSTUP_PROXIES.iter().chain(optional) {
+            let proxy_path = self.cargo_bin().join(exe!(proxy));
+            if utils::is_link_of(&proxy_path, &rustup) != utils::LinkStatus::Linked {
+                broken.push(proxy_path);
+            }
+        }
+
+        if fix && !broken.is_empty() {
+            for proxy_path in &broken {
+                relink(&rustup, proxy_path)?;
+            }
+            return Ok(VerifyEntry {
+                name,
+                status: VerifyStatus::Fixed,
+                broken,
+            });
+        }
+        Ok(VerifyEntry::new(&name, broken))
+    }
+
+    fn verify_tool(&self, name: &str, tool: &ToolRecord) -> Result<VerifyEntry> {
+        let broken = match tool.tool_kind() {
+            ToolKind::CargoTool => self.verify_cargo_tool(name)?,
+            ToolKind::Executables => tool
+                .paths
+                .iter()
+                .filter(|p| !p.is_file())
+                .cloned()
+                .collect(),
+            ToolKind::DirWithBin => tool
+                .paths
+                .iter()
+                .flat_map(|p| [p.clone(), p.join("bin")])
+                .filter(|p| !p.is_dir())
+                .collect(),
+            _ => tool.paths.iter().filter(|p| !p.exists()).cloned().collect(),
+        };
+        Ok(VerifyEntry::new(name, broken))
+    }
+
+    /// Tools installed using `cargo install` do not have their paths recorded,
+    /// therefore we look into cargo's own installation record instead.
+    fn verify_cargo_tool(&self, name: &str) -> Result<Vec<PathBuf>> {
+        let crates_toml = self.cargo_home().join(".crates.toml");
+        let installed = if crates_toml.is_file() {
+            let content = utils::read_to_string("crates.toml", &crates_toml)?;
+            toml::from_str::<CratesToml>(&content)
+                .with_context(|| format!("unable to parse '{}'", crates_toml.display()))?
+        } else {
+            CratesToml::default()
+        };
+
+        let prefix = format!("{name} ");
+        let Some(bins) = installed
+            .v1
+            .iter()
+            .find_map(|(key, bins)| key.starts_with(&prefix).then_some(bins))
+        else {
+            return Ok(vec![crates_toml]);
+        };
+        Ok(bins
+            .iter()
+            .map(|bin| self.cargo_bin().join(bin))
+            .filter(|p| !p.is_file())
+            .collect())
+    }
+}
+
+/// Re-create a `rustup` proxy at `proxy_path` as a link of `rustup`,
+/// falling back to a plain copy if no link can be created.
+fn relink(rustup: &Path, proxy_path: &Path) -> Result<()> {
+    if fs::symlink_metadata(proxy_path).is_ok() {
+        fs::remove_file(proxy_path)
+            .with_context(|| format!("unable to remove '{}'", proxy_path.display()))?;
+    }
+    if let Err(e) = utils::create_link(rustup, proxy_path) {
+        debug!("{e:#}, copying '{}' instead", rustup.display());
+        utils::copy_file(rustup, proxy_path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_tools_from_record() {
+        let root = tempfile::tempdir().unwrap();
+        let verifier = Verifier {
+            install_dir: root.path().to_path_buf(),
+        };
+
+        let exe_path = root.path().join("tool_a");
+        utils::write_file(&exe_path, "", false).unwrap();
+        let mut exe_tool = ToolRecord::new(ToolKind::Executables);
+        exe_tool.paths = vec![exe_path, root.path().join("tool_b")];
+        let entry = verifier.verify_tool("exe", &exe_tool).unwrap();
+        assert_eq!(entry.status, VerifyStatus::Missing);
+        assert_eq!(entry.broken, vec![root.path().join("tool_b")]);
+
+        let tool_dir = root.path().join("tool_c");
+        utils::ensure_dir(tool_dir.join("bin")).unwrap();
+        let mut dir_tool = ToolRecord::new(ToolKind::DirWithBin);
+        dir_tool.paths = vec![tool_dir];
+        let entry = verifier.verify_tool("dir", &dir_tool).unwrap();
+        assert_eq!(entry.status, VerifyStatus::Ok);
+    }
+
+    #[test]
+    fn relink_broken_proxy() {
+        let root = tempfile::tempdir().unwrap();
+        let rustup = root.path().join("rustup");
+        let proxy = root.path().join("cargo");
+        utils::write_file(&rustup, "rustup", false).unwrap();
+        utils::write_file(&proxy, "not a proxy", false).unwrap();
+        assert_eq!(
+            utils::is_link_of(&proxy, &rustup),
+            utils::LinkStatus::Unlinked
+        );
+
+        relink(&rustup, &proxy).unwrap();
+        assert_eq!(
+            utils::is_link_of(&proxy, &rustup),
+            utils::LinkStatus::Linked
+        );
+    }
+}
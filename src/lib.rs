@@ -12,7 +12,10 @@ pub mod cli;
 mod core;
 
 // Exports
-pub use core::install::{default_install_dir, EnvConfig, InstallConfiguration};
+pub use core::install::{
+    default_install_dir, resolve_install_dir, EnvConfig, InstallConfiguration, ToolFailureAction,
+    ToolFailureHandler,
+};
 pub use core::parser::{configuration, fingerprint};
 pub use core::try_it::try_it;
 pub use core::uninstall::UninstallConfiguration;
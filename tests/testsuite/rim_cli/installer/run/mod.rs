@@ -62,6 +62,131 @@ fn insecure_installation() {
     check_installation(root, true);
 }
 
+#[rim_test]
+fn relative_prefix() {
+    let root = INSTALLER_PROCESS.root();
+    let expected = root.join("relative").join("rust");
+    INSTALLER_PROCESS
+        .command()
+        .current_dir(root)
+        .arg("-y")
+        .arg("--no-modify-env")
+        .arg("--dry-run")
+        .arg("--no-color")
+        .args(["--locale", "en-US"])
+        .args(["--prefix", "relative/../relative/./rust"])
+        .assert()
+        .success()
+        .stdout_eq(format!(
+            "...\ninfo: [dry run] would create installation directory '{}'\n...\n",
+            expected.display()
+        ));
+}
+
+#[rim_test]
+fn rollback_keeps_existing_files() {
+    let root = INSTALLER_PROCESS.root().join("existing");
+    let manifest = root.join("toolset-manifest.toml");
+    let manager = root.join(format!("xuanwu-rust-manager{EXE_SUFFIX}"));
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(&manifest, "").unwrap();
+    std::fs::write(&manager, "").unwrap();
+
+    let unreachable = url::Url::from_directory_path(root.join("unreachable"))
+        .unwrap()
+        .to_string();
+    INSTALLER_PROCESS
+        .command()
+        .arg("-y")
+        .arg("--no-modify-env")
+        .arg("--rollback-on-failure")
+        .arg("--prefix")
+        .arg(&root)
+        .args(["--rustup-dist-server", &unreachable])
+        .args(["--rustup-update-root", &unreachable])
+        .assert()
+        .failure();
+
+    assert!(manifest.is_file());
+    assert!(manager.is_file());
+}
+
+/// Prepare an installation directory with a toolkit manifest that installs `tool`,
+/// along with a stub `rustup` and a local dist server, so that the toolchain
+/// installation does not need network.
+#[cfg(unix)]
+fn offline_toolkit(root: &Path, tool: &str) -> PathBuf {
+    let rustup = root.join("cargo").join("bin").join("rustup");
+    std::fs::create_dir_all(rustup.parent().unwrap()).unwrap();
+    std::fs::write(&rustup, "#!/bin/sh\nexit 0\n").unwrap();
+    rim_common::utils::set_exec_permission(&rustup).unwrap();
+
+    let manifest = root.with_extension("toml");
+    let raw = format!(
+        "[rust]\nversion = \"1.0.0\"\noffline-dist-server = '{}'\n\n[tools.target.{}]\n{tool}\n",
+        root.join("dist").display(),
+        env!("TARGET")
+    );
+    std::fs::write(&manifest, raw).unwrap();
+    manifest
+}
+
+#[cfg(unix)]
+#[rim_test]
+fn offline_installation() {
+    let root = INSTALLER_PROCESS.root().join("offline");
+    let vendored = INSTALLER_PROCESS.root().join("vendored");
+    std::fs::create_dir_all(vendored.join("bin")).unwrap();
+    std::fs::write(vendored.join("bin").join("hello"), "").unwrap();
+    let manifest = offline_toolkit(
+        &root,
+        &format!("hello = {{ path = '{}' }}", vendored.display()),
+    );
+
+    INSTALLER_PROCESS
+        .command()
+        .arg("-y")
+        .arg("--offline")
+        .arg("--no-modify-env")
+        .arg("--prefix")
+        .arg(&root)
+        .arg("--manifest")
+        .arg(&manifest)
+        .assert()
+        .success();
+
+    let record = std::fs::read_to_string(root.join(".fingerprint.toml")).unwrap();
+    assert!(record.contains("hello"));
+}
+
+#[cfg(unix)]
+#[rim_test]
+fn offline_installation_refuses_url() {
+    let root = INSTALLER_PROCESS.root().join("offline-url");
+    let manifest = offline_toolkit(
+        &root,
+        "hello = { url = 'https://example.com/hello.tar.gz' }",
+    );
+
+    INSTALLER_PROCESS
+        .command()
+        .arg("-y")
+        .arg("--offline")
+        .arg("--no-modify-env")
+        .arg("--no-color")
+        .args(["--locale", "en-US"])
+        .arg("--prefix")
+        .arg(&root)
+        .arg("--manifest")
+        .arg(&manifest)
+        .assert()
+        .failure()
+        .stderr_eq("Error: offline mode: network access disabled, unable to access 'https://example.com/hello.tar.gz'\n");
+
+    let record = std::fs::read_to_string(root.join(".fingerprint.toml")).unwrap_or_default();
+    assert!(!record.contains("hello"));
+}
+
 fn check_installation(root: &Path, expect_rust_success: bool) {
     let cargo_home = root.join("cargo");
     let rustup_home = root.join("rustup");
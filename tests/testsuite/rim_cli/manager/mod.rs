@@ -2,6 +2,7 @@ use rim_test_support::project::ProjectBuilder;
 use std::sync::LazyLock;
 
 mod help;
+mod search;
 mod try_it_help;
 mod uninstall_help;
 mod update_help;
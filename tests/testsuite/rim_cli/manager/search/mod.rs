@@ -0,0 +1,72 @@
+use rim_test_support::prelude::*;
+use rim_test_support::project::ProjectBuilder;
+use rim_test_support::str;
+
+const MANIFEST: &str = r#"
+name = "Test Toolkit"
+version = "1.0.0"
+
+[rust]
+version = "stable"
+profile = { name = "minimal" }
+optional-components = ["clippy", "rustfmt"]
+"#;
+
+/// Generate a manager process along with an installation that has `clippy` installed.
+fn manager_with_installation() -> ProjectBuilder {
+    let process = ProjectBuilder::manager_process();
+    let root = process.root();
+    let record = format!(
+        "root = '{}'\n\n[rust]\nversion = \"stable\"\ncomponents = [\"minimal\", \"clippy\"]\n",
+        root.display()
+    );
+    std::fs::write(root.join(".fingerprint.toml"), record).unwrap();
+    std::fs::write(root.join("toolset-manifest.toml"), MANIFEST).unwrap();
+    process
+}
+
+#[rim_test]
+fn search_components() {
+    let process = manager_with_installation();
+    process
+        .command()
+        .arg("--no-color")
+        .args(["--locale", "en-US"])
+        .args(["search", "FMT"])
+        .assert()
+        .success()
+        .stdout_eq(str![[r#"
+
+rustfmt [Other] stable
+
+"#]]);
+}
+
+#[rim_test]
+fn search_installed_components() {
+    let process = manager_with_installation();
+    process
+        .command()
+        .arg("--no-color")
+        .args(["--locale", "en-US"])
+        .args(["search", "--installed", "clippy"])
+        .assert()
+        .success()
+        .stdout_eq(str![[r#"
+
+clippy [Other] stable (installed)
+
+"#]]);
+    process
+        .command()
+        .arg("--no-color")
+        .args(["--locale", "en-US"])
+        .args(["search", "--installed", "rustfmt"])
+        .assert()
+        .success()
+        .stdout_eq(str![[r#"
+
+no component matches 'rustfmt'
+
+"#]]);
+}